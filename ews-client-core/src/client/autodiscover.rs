@@ -0,0 +1,928 @@
+//! Autodiscover: finding the EWS endpoint of a mailbox from its email address.
+//!
+//! Endpoints are probed in the order recommended by Microsoft:
+//! 1. `https://<domain>/autodiscover/autodiscover.svc` (SOAP), then
+//!    `https://<domain>/autodiscover/autodiscover.xml` (POX)
+//! 2. The same two endpoints on `autodiscover.<domain>`
+//! 3. An unauthenticated `GET` to
+//!    `http://autodiscover.<domain>/autodiscover/autodiscover.xml`, which may
+//!    redirect to the actual (HTTPS) endpoint
+//! 4. The targets of the `_autodiscover._tcp.<domain>` SRV records
+//!
+//! Address and URL redirections returned by the endpoints are followed, up to
+//! a limit. Active Directory SCP lookups are not supported.
+//!
+//! Steps 3 and 4 rely on unauthenticated answers (an HTTP redirection, DNS
+//! records), so credentials are only sent to the endpoints they point to if
+//! these are trusted: by default, if their host is the email's domain or one
+//! of its subdomains.
+//!
+//! See <https://learn.microsoft.com/en-us/exchange/client-developer/exchange-web-services/autodiscover-for-exchange>
+
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc};
+
+use ews::server_version::ExchangeServerVersion;
+use hickory_resolver::TokioAsyncResolver;
+use reqwest::{Client, StatusCode, redirect::Policy};
+use url::Url;
+
+use crate::client::{
+    Credentials, EwsError, SOAP_NS_URI, VersionStore, server_version,
+    xml::{XmlElement, escape_text, text_element},
+};
+
+/// The path of SOAP Autodiscover endpoints.
+const SOAP_AUTODISCOVER_PATH: &str = "/autodiscover/autodiscover.svc";
+
+/// The path of POX (plain old XML) Autodiscover endpoints.
+const POX_AUTODISCOVER_PATH: &str = "/autodiscover/autodiscover.xml";
+
+/// The maximum number of address and URL redirections to follow.
+const MAX_REDIRECTS: usize = 10;
+
+/// The namespace URI of SOAP Autodiscover messages.
+const AUTODISCOVER_NS_URI: &str = "http://schemas.microsoft.com/exchange/2010/Autodiscover";
+
+/// The namespace URI of WS-Addressing headers, which SOAP Autodiscover
+/// requests must include.
+const WS_ADDRESSING_NS_URI: &str = "http://www.w3.org/2005/08/addressing";
+
+/// The namespace URI of POX Autodiscover requests.
+const POX_REQUEST_NS_URI: &str = "http://schemas.microsoft.com/exchange/autodiscover/outlook/requestschema/2006";
+
+/// The response schema requested in POX Autodiscover requests.
+const POX_RESPONSE_SCHEMA: &str = "http://schemas.microsoft.com/exchange/autodiscover/outlook/responseschema/2006a";
+
+/// Decides whether credentials may be sent to an Autodiscover endpoint found
+/// through an unauthenticated HTTP redirection or SRV record.
+type RedirectValidator = dyn Fn(&Url) -> bool + Send + Sync;
+
+/// A boxed future, as returned by [`SrvResolver::lookup_srv`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The result of a successful Autodiscover lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutodiscoverResult {
+    /// The EWS endpoint of the mailbox
+    pub ews_url: Url,
+    /// The server version, if the endpoint reported one
+    pub server_version: Option<ExchangeServerVersion>,
+    /// The email address the settings were found for, which differs from the
+    /// requested one if the server redirected to another address
+    pub email_address: String,
+    /// The Autodiscover endpoint which provided the settings
+    pub autodiscover_url: Url,
+}
+
+/// A DNS SRV record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    /// The priority of the target; lower values are tried first
+    pub priority: u16,
+    /// The relative weight of targets with the same priority
+    pub weight: u16,
+    /// The port of the service on the target
+    pub port: u16,
+    /// The host name of the target
+    pub target: String,
+}
+
+/// Looks up DNS SRV records.
+///
+/// The default implementation is [`DnsSrvResolver`]; a custom implementation
+/// can be provided with [`AutodiscoverClient::with_srv_resolver`], e.g. for
+/// tests.
+pub trait SrvResolver: Send + Sync {
+    /// Looks up the SRV records with the given name (e.g.
+    /// `_autodiscover._tcp.example.com`).
+    fn lookup_srv<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<SrvRecord>, EwsError>>;
+}
+
+/// Looks up SRV records using the system's DNS configuration.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DnsSrvResolver;
+
+impl SrvResolver for DnsSrvResolver {
+    fn lookup_srv<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<SrvRecord>, EwsError>> {
+        Box::pin(async move {
+            let resolver = TokioAsyncResolver::tokio_from_system_conf().map_err(dns_error)?;
+            let lookup = resolver.srv_lookup(name).await.map_err(dns_error)?;
+
+            Ok(lookup
+                .iter()
+                .map(|srv| SrvRecord {
+                    priority: srv.priority(),
+                    weight: srv.weight(),
+                    port: srv.port(),
+                    target: srv.target().to_utf8(),
+                })
+                .collect())
+        })
+    }
+}
+
+/// Finds the EWS endpoint of a mailbox using Autodiscover.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ews_client_core::client::{AutodiscoverClient, Credentials, EwsClient};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let credentials = Credentials::basic("user@example.com", "pass");
+/// let result = AutodiscoverClient::new(credentials.clone())?
+///     .discover("user@example.com")
+///     .await?;
+///
+/// // The discovered server version is already stored for the endpoint.
+/// let client = EwsClient::new(result.ews_url, credentials)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AutodiscoverClient {
+    client: Client,
+    credentials: Credentials,
+    srv_resolver: Option<Arc<dyn SrvResolver>>,
+    host_overrides: HashMap<String, Url>,
+    require_https: bool,
+    version_store: Arc<dyn VersionStore>,
+    /// Replaces the check that endpoints found through unauthenticated
+    /// answers are in the email's domain.
+    redirect_validator: Option<Arc<RedirectValidator>>,
+}
+
+/// The settings of a mailbox provided by an Autodiscover endpoint.
+#[derive(Debug)]
+struct MailboxSettings {
+    ews_url: Url,
+    server_version: Option<ExchangeServerVersion>,
+}
+
+/// The outcome of querying a single Autodiscover endpoint.
+#[derive(Debug)]
+enum AutodiscoverOutcome {
+    /// The endpoint provided the settings of the mailbox.
+    Settings(MailboxSettings),
+    /// The mailbox is known under another address, for which Autodiscover
+    /// needs to start over.
+    RedirectAddress(String),
+    /// The settings need to be requested from another endpoint.
+    RedirectUrl(Url),
+}
+
+/// The outcome of probing the endpoints for an address, once URL
+/// redirections have been followed.
+enum ProbeOutcome {
+    /// An endpoint, whose URL is included, provided the settings of the
+    /// mailbox.
+    Settings(MailboxSettings, Url),
+    /// The mailbox is known under another address.
+    RedirectAddress(String),
+}
+
+/// Failures encountered while probing endpoints, used to build the error
+/// returned when no endpoint provided the settings.
+#[derive(Default)]
+struct ProbeFailures {
+    authentication_failed: bool,
+    last_error: Option<String>,
+}
+
+impl ProbeFailures {
+    fn record(&mut self, url: &Url, err: &EwsError) {
+        log::debug!("Autodiscover request to {url} failed: {err}");
+
+        if matches!(err, EwsError::Authentication) {
+            self.authentication_failed = true;
+        }
+        self.last_error = Some(format!("{url}: {err}"));
+    }
+
+    fn into_error(self, email: &str) -> EwsError {
+        // An endpoint rejecting the credentials is the most useful thing to
+        // report, since any other failure may just be a probe which was
+        // expected to fail.
+        if self.authentication_failed {
+            return EwsError::Authentication;
+        }
+
+        EwsError::Processing {
+            message: format!(
+                "Autodiscover could not find the EWS endpoint for {email} (last error: {})",
+                self.last_error.as_deref().unwrap_or("no endpoint to try")
+            ),
+        }
+    }
+}
+
+impl AutodiscoverClient {
+    /// Creates an Autodiscover client authenticating with the given
+    /// credentials.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn new(credentials: Credentials) -> Result<Self, EwsError> {
+        // Redirects are followed by hand, so that they can be validated and
+        // so that POST requests aren't turned into GET requests.
+        let client = Client::builder().redirect(Policy::none()).build()?;
+
+        Ok(Self {
+            client,
+            credentials,
+            srv_resolver: Some(Arc::new(DnsSrvResolver)),
+            host_overrides: HashMap::new(),
+            require_https: true,
+            version_store: server_version::default_version_store(),
+            redirect_validator: None,
+        })
+    }
+
+    /// Uses the given resolver for SRV lookups instead of the system's DNS.
+    pub fn with_srv_resolver(mut self, resolver: impl SrvResolver + 'static) -> Self {
+        self.srv_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Skips the SRV lookup step.
+    pub fn without_srv_lookup(mut self) -> Self {
+        self.srv_resolver = None;
+        self
+    }
+
+    /// Sends requests meant for the given host to another base URL instead.
+    ///
+    /// The path of each request is resolved against the base URL. This is
+    /// mostly useful to run Autodiscover against local test servers.
+    pub fn with_host_override(mut self, host: impl Into<String>, base_url: Url) -> Self {
+        self.host_overrides.insert(host.into().to_ascii_lowercase(), base_url);
+        self
+    }
+
+    /// Sets whether redirections to plain HTTP URLs are rejected, which is the
+    /// default.
+    pub fn require_https(mut self, require_https: bool) -> Self {
+        self.require_https = require_https;
+        self
+    }
+
+    /// Decides which endpoints found through the unauthenticated HTTP
+    /// redirection step or SRV records credentials may be sent to.
+    ///
+    /// By default, only endpoints whose host is the email's domain or one of
+    /// its subdomains are trusted, as an attacker able to answer DNS or plain
+    /// HTTP requests could otherwise collect the credentials. The validator
+    /// is given the URL of the endpoint, e.g. to check it against a list of
+    /// known servers or to ask the user, as Microsoft recommends.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{AutodiscoverClient, Credentials};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = AutodiscoverClient::new(Credentials::basic("user@contoso.com", "pass"))?
+    ///     .with_redirect_validator(|url| url.host_str() == Some("autodiscover.contoso-hosting.com"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_redirect_validator(mut self, validator: impl Fn(&Url) -> bool + Send + Sync + 'static) -> Self {
+        self.redirect_validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Stores the discovered server versions in the given store, rather than
+    /// in the store shared by clients which are not given one.
+    pub fn with_version_store(mut self, store: Arc<dyn VersionStore>) -> Self {
+        self.version_store = store;
+        self
+    }
+
+    /// Finds the EWS endpoint and server version for the given email address.
+    ///
+    /// If the server version is known, it is stored and persisted in the
+    /// client's [`VersionStore`], so that [`crate::client::EwsClient`]s
+    /// created for the endpoint with the same store use it from their first
+    /// request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The email address is invalid
+    /// - An endpoint rejected the credentials and no other endpoint provided
+    ///   the settings
+    /// - No endpoint provided the settings, or too many redirections happened
+    pub async fn discover(&self, email: &str) -> Result<AutodiscoverResult, EwsError> {
+        let mut email = email.to_string();
+        let mut redirects = 0;
+
+        loop {
+            match self.discover_for_address(&email, &mut redirects).await? {
+                ProbeOutcome::Settings(settings, autodiscover_url) => {
+                    if let Some(version) = settings.server_version {
+                        server_version::store_server_version(&*self.version_store, &settings.ews_url, version);
+                        server_version::persist_server_versions(Arc::clone(&self.version_store)).await;
+                    }
+
+                    return Ok(AutodiscoverResult {
+                        ews_url: settings.ews_url,
+                        server_version: settings.server_version,
+                        email_address: email,
+                        autodiscover_url,
+                    });
+                }
+                ProbeOutcome::RedirectAddress(address) => {
+                    log::debug!("Autodiscover redirected from {email} to {address}");
+                    count_redirect(&mut redirects)?;
+                    email = address;
+                }
+            }
+        }
+    }
+
+    /// Probes the endpoints for the domain of the given address until one of
+    /// them provides settings or redirects to another address.
+    async fn discover_for_address(&self, email: &str, redirects: &mut usize) -> Result<ProbeOutcome, EwsError> {
+        let domain = email_domain(email)?;
+        let autodiscover_host = format!("autodiscover.{domain}");
+        let mut failures = ProbeFailures::default();
+
+        // Steps 1 and 2: SOAP and POX endpoints on the domain itself, then on
+        // its `autodiscover` subdomain.
+        for host in [domain, autodiscover_host.as_str()] {
+            for path in [SOAP_AUTODISCOVER_PATH, POX_AUTODISCOVER_PATH] {
+                let url = self.endpoint_url("https", host, None, path)?;
+                match self.follow_redirects(url.clone(), email, redirects).await {
+                    Ok(result) => return Ok(result),
+                    Err(err) => failures.record(&url, &err),
+                }
+            }
+        }
+
+        // Step 3: an unauthenticated HTTP request, which may be redirected to
+        // the actual endpoint.
+        let url = self.endpoint_url("http", &autodiscover_host, None, POX_AUTODISCOVER_PATH)?;
+        match self.http_redirect_target(&url).await {
+            Ok(Some(target)) => {
+                let result = match self.trusted_endpoint_url(target.clone(), domain) {
+                    Ok(target) => self.follow_redirects(target, email, redirects).await,
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(result) => return Ok(result),
+                    Err(err) => failures.record(&target, &err),
+                }
+            }
+            Ok(None) => {}
+            Err(err) => failures.record(&url, &err),
+        }
+
+        // Step 4: the targets of the domain's SRV records.
+        if let Some(resolver) = &self.srv_resolver {
+            let name = format!("_autodiscover._tcp.{domain}");
+            match resolver.lookup_srv(&name).await {
+                Ok(mut records) => {
+                    // Lower priorities first, then higher weights.
+                    records.sort_by_key(|record| (record.priority, std::cmp::Reverse(record.weight)));
+
+                    for record in records {
+                        let host = record.target.trim_end_matches('.');
+                        let port = (record.port != 443).then_some(record.port);
+                        let url = endpoint_url("https", host, port, POX_AUTODISCOVER_PATH)?;
+                        let result = match self.trusted_endpoint_url(url.clone(), domain) {
+                            Ok(url) => self.follow_redirects(url, email, redirects).await,
+                            Err(err) => Err(err),
+                        };
+                        match result {
+                            Ok(result) => return Ok(result),
+                            Err(err) => failures.record(&url, &err),
+                        }
+                    }
+                }
+                Err(err) => log::debug!("SRV lookup for {name} failed: {err}"),
+            }
+        }
+
+        Err(failures.into_error(email))
+    }
+
+    /// Queries an endpoint, following URL redirections until an endpoint
+    /// provides settings or an address redirection.
+    async fn follow_redirects(
+        &self,
+        mut url: Url,
+        email: &str,
+        redirects: &mut usize,
+    ) -> Result<ProbeOutcome, EwsError> {
+        loop {
+            match self.query_endpoint(&url, email).await? {
+                AutodiscoverOutcome::Settings(settings) => return Ok(ProbeOutcome::Settings(settings, url)),
+                AutodiscoverOutcome::RedirectAddress(address) => return Ok(ProbeOutcome::RedirectAddress(address)),
+                AutodiscoverOutcome::RedirectUrl(target) => {
+                    log::debug!("Autodiscover redirected from {url} to {target}");
+                    count_redirect(redirects)?;
+                    url = target;
+                }
+            }
+        }
+    }
+
+    /// Queries a single endpoint, using the SOAP or POX protocol depending on
+    /// its path.
+    async fn query_endpoint(&self, url: &Url, email: &str) -> Result<AutodiscoverOutcome, EwsError> {
+        let is_soap = url.path().to_ascii_lowercase().ends_with(".svc");
+        let body = if is_soap {
+            soap_request(url, email)
+        } else {
+            pox_request(email)
+        };
+
+        let response = self
+            .client
+            .post(url.clone())
+            .header("Authorization", self.credentials.to_auth_header())
+            .header("Content-Type", "text/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED {
+            return Err(EwsError::Authentication);
+        }
+        if status.is_redirection() {
+            return Ok(AutodiscoverOutcome::RedirectUrl(
+                self.redirect_location(url, &response)?,
+            ));
+        }
+        if !status.is_success() {
+            return Err(EwsError::Processing {
+                message: format!("HTTP request failed with status: {status}"),
+            });
+        }
+
+        let body = response.bytes().await?;
+        if is_soap {
+            self.parse_soap_response(url, &body)
+        } else {
+            self.parse_pox_response(url, &body)
+        }
+    }
+
+    /// Sends an unauthenticated `GET` request to the given URL, and returns
+    /// where it redirects to, if anywhere.
+    async fn http_redirect_target(&self, url: &Url) -> Result<Option<Url>, EwsError> {
+        let response = self.client.get(url.clone()).send().await?;
+
+        if response.status().is_redirection() {
+            Ok(Some(self.redirect_location(url, &response)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads and validates the target of an HTTP redirection.
+    fn redirect_location(&self, url: &Url, response: &reqwest::Response) -> Result<Url, EwsError> {
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .ok_or_else(|| EwsError::Processing {
+                message: "redirection without a Location header".to_string(),
+            })?;
+
+        self.validate_redirect_url(url, location)
+    }
+
+    /// Resolves a redirection target against the URL which returned it, and
+    /// checks that it is safe to send credentials to.
+    fn validate_redirect_url(&self, url: &Url, target: &str) -> Result<Url, EwsError> {
+        let target = url.join(target)?;
+
+        if self.require_https && target.scheme() != "https" {
+            return Err(EwsError::Processing {
+                message: format!("refusing to follow Autodiscover redirection to non-HTTPS URL {target}"),
+            });
+        }
+
+        Ok(target)
+    }
+
+    /// Builds the URL of an endpoint, taking host overrides into account.
+    fn endpoint_url(&self, scheme: &str, host: &str, port: Option<u16>, path: &str) -> Result<Url, EwsError> {
+        self.apply_host_override(endpoint_url(scheme, host, port, path)?)
+    }
+
+    /// Sends a URL whose host is overridden to the override's base URL.
+    fn apply_host_override(&self, url: Url) -> Result<Url, EwsError> {
+        let base_url = url
+            .host_str()
+            .and_then(|host| self.host_overrides.get(&host.to_ascii_lowercase()));
+
+        match base_url {
+            Some(base_url) => Ok(base_url.join(url.path())?),
+            None => Ok(url),
+        }
+    }
+
+    /// Checks that credentials may be sent to an endpoint found through an
+    /// unauthenticated answer while probing the endpoints of `domain`, and
+    /// returns the URL to send requests to.
+    fn trusted_endpoint_url(&self, url: Url, domain: &str) -> Result<Url, EwsError> {
+        let trusted = match &self.redirect_validator {
+            Some(validator) => validator(&url),
+            None => url.host_str().is_some_and(|host| is_in_domain(host, domain)),
+        };
+
+        if !trusted {
+            return Err(EwsError::Processing {
+                message: format!("refusing to send credentials to untrusted Autodiscover endpoint {url}"),
+            });
+        }
+
+        self.apply_host_override(url)
+    }
+
+    /// Reads the response to a SOAP `GetUserSettings` request.
+    fn parse_soap_response(&self, url: &Url, body: &[u8]) -> Result<AutodiscoverOutcome, EwsError> {
+        let envelope = XmlElement::parse(body)?;
+
+        let response = envelope
+            .find("GetUserSettingsResponseMessage")
+            .and_then(|message| message.child("Response"))
+            .ok_or_else(|| missing_element("GetUserSettingsResponseMessage"))?;
+        check_soap_error_code(response)?;
+
+        let user_response = response
+            .descendant(&["UserResponses", "UserResponse"])
+            .ok_or_else(|| missing_element("UserResponse"))?;
+
+        match user_response.child_text("ErrorCode") {
+            Some("NoError") | None => {}
+            Some("RedirectAddress") => {
+                let target = user_response
+                    .child_text("RedirectTarget")
+                    .ok_or_else(|| missing_element("RedirectTarget"))?;
+                return Ok(AutodiscoverOutcome::RedirectAddress(target.trim().to_string()));
+            }
+            Some("RedirectUrl") => {
+                let target = user_response
+                    .child_text("RedirectTarget")
+                    .ok_or_else(|| missing_element("RedirectTarget"))?;
+                return Ok(AutodiscoverOutcome::RedirectUrl(
+                    self.validate_redirect_url(url, target.trim())?,
+                ));
+            }
+            Some(_) => check_soap_error_code(user_response)?,
+        }
+
+        let settings: HashMap<&str, &str> = user_response
+            .child("UserSettings")
+            .map(|settings| {
+                settings
+                    .children_named("UserSetting")
+                    .filter_map(|setting| Some((setting.child_text("Name")?, setting.child_text("Value")?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let ews_url = ["ExternalEwsUrl", "InternalEwsUrl"]
+            .iter()
+            .find_map(|name| settings.get(name).filter(|value| !value.is_empty()))
+            .ok_or_else(|| EwsError::Processing {
+                message: "Autodiscover response does not contain an EWS URL".to_string(),
+            })?;
+
+        // The CAS version is a dotted build number (e.g. `15.01.2507.006`).
+        // Fall back to the version the Autodiscover server reports for itself.
+        let server_version = settings
+            .get("CasVersion")
+            .and_then(|version| version_from_dotted_build(version))
+            .or_else(|| {
+                let info = envelope.find("ServerVersionInfo")?;
+                server_version::version_from_build(
+                    info.child_parse("MajorVersion")?,
+                    info.child_parse("MinorVersion")?,
+                    info.child_parse("MajorBuildNumber")?,
+                )
+            });
+
+        Ok(AutodiscoverOutcome::Settings(MailboxSettings {
+            ews_url: ews_url.trim().parse()?,
+            server_version,
+        }))
+    }
+
+    /// Reads the response to a POX Autodiscover request.
+    fn parse_pox_response(&self, url: &Url, body: &[u8]) -> Result<AutodiscoverOutcome, EwsError> {
+        let root = XmlElement::parse(body)?;
+        let response = root.child("Response").ok_or_else(|| missing_element("Response"))?;
+
+        if let Some(error) = response.child("Error") {
+            return Err(EwsError::RawResponseError {
+                response_code: error.child_text("ErrorCode").unwrap_or("Unknown").to_string(),
+                message_text: error.child_text("Message").unwrap_or_default().to_string(),
+            });
+        }
+
+        let account = response.child("Account").ok_or_else(|| missing_element("Account"))?;
+
+        match account.child_text("Action") {
+            Some("redirectAddr") => {
+                let target = account
+                    .child_text("RedirectAddr")
+                    .ok_or_else(|| missing_element("RedirectAddr"))?;
+                return Ok(AutodiscoverOutcome::RedirectAddress(target.trim().to_string()));
+            }
+            Some("redirectUrl") => {
+                let target = account
+                    .child_text("RedirectUrl")
+                    .ok_or_else(|| missing_element("RedirectUrl"))?;
+                return Ok(AutodiscoverOutcome::RedirectUrl(
+                    self.validate_redirect_url(url, target.trim())?,
+                ));
+            }
+            _ => {}
+        }
+
+        let protocols: Vec<&XmlElement> = account.children_named("Protocol").collect();
+        let protocol_of_type = |protocol_type: &str| {
+            protocols
+                .iter()
+                .find(|protocol| protocol.child_text("Type") == Some(protocol_type))
+        };
+
+        // Prefer the settings for clients outside of the organization's
+        // network (`EXPR`) over the internal ones (`EXCH`).
+        let ews_url = ["EXPR", "EXCH", "WEB"]
+            .into_iter()
+            .filter_map(protocol_of_type)
+            .chain(protocols.iter())
+            .find_map(|protocol| protocol.child_text("EwsUrl").filter(|value| !value.is_empty()))
+            .ok_or_else(|| EwsError::Processing {
+                message: "Autodiscover response does not contain an EWS URL".to_string(),
+            })?;
+
+        let server_version = protocols
+            .iter()
+            .find_map(|protocol| protocol.child_text("ServerVersion"))
+            .and_then(version_from_pox_server_version);
+
+        Ok(AutodiscoverOutcome::Settings(MailboxSettings {
+            ews_url: ews_url.trim().parse()?,
+            server_version,
+        }))
+    }
+}
+
+/// Writes a SOAP `GetUserSettings` request for the given address.
+fn soap_request(url: &Url, email: &str) -> String {
+    let settings: String = ["ExternalEwsUrl", "InternalEwsUrl", "CasVersion"]
+        .iter()
+        .map(|setting| text_element("a:Setting", setting))
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="{SOAP_NS_URI}" xmlns:a="{AUTODISCOVER_NS_URI}" xmlns:wsa="{WS_ADDRESSING_NS_URI}"><soap:Header><a:RequestedServerVersion>Exchange2010</a:RequestedServerVersion><wsa:Action>{AUTODISCOVER_NS_URI}/Autodiscover/GetUserSettings</wsa:Action>{}</soap:Header><soap:Body><a:GetUserSettingsRequestMessage><a:Request><a:Users><a:User>{}</a:User></a:Users><a:RequestedSettings>{settings}</a:RequestedSettings></a:Request></a:GetUserSettingsRequestMessage></soap:Body></soap:Envelope>"#,
+        text_element("wsa:To", url.as_str()),
+        text_element("a:Mailbox", email),
+    )
+}
+
+/// Writes a POX Autodiscover request for the given address.
+fn pox_request(email: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><Autodiscover xmlns="{POX_REQUEST_NS_URI}"><Request><EMailAddress>{}</EMailAddress><AcceptableResponseSchema>{POX_RESPONSE_SCHEMA}</AcceptableResponseSchema></Request></Autodiscover>"#,
+        escape_text(email)
+    )
+}
+
+/// Returns an error if the `ErrorCode` of a SOAP Autodiscover response (or
+/// user response) is not `NoError`.
+fn check_soap_error_code(element: &XmlElement) -> Result<(), EwsError> {
+    match element.child_text("ErrorCode") {
+        Some("NoError") | None => Ok(()),
+        Some(code) => Err(EwsError::RawResponseError {
+            response_code: code.to_string(),
+            message_text: element.child_text("ErrorMessage").unwrap_or_default().to_string(),
+        }),
+    }
+}
+
+/// Builds the URL of an endpoint.
+fn endpoint_url(scheme: &str, host: &str, port: Option<u16>, path: &str) -> Result<Url, EwsError> {
+    let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+    Ok(format!("{scheme}://{host}{port}{path}").parse()?)
+}
+
+/// Whether `host` is `domain` or one of its subdomains.
+fn is_in_domain(host: &str, domain: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+
+    host == domain
+        || host
+            .strip_suffix(&domain)
+            .is_some_and(|subdomain| subdomain.ends_with('.'))
+}
+
+/// Gets the domain part of an email address.
+fn email_domain(email: &str) -> Result<&str, EwsError> {
+    email
+        .rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .filter(|domain| !domain.is_empty())
+        .ok_or_else(|| EwsError::Processing {
+            message: format!("invalid email address for Autodiscover: {email}"),
+        })
+}
+
+/// Maps a dotted build number (e.g. `15.01.2507.006`) to a server version.
+fn version_from_dotted_build(version: &str) -> Option<ExchangeServerVersion> {
+    let mut parts = version.trim().split('.').map(|part| part.parse::<u32>().ok());
+
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let build = parts.next().flatten().unwrap_or(0);
+
+    server_version::version_from_build(major, minor, build)
+}
+
+/// Maps the hexadecimal `ServerVersion` of a POX response to a server version.
+///
+/// The value packs the major version in bits 22 to 27, the minor version in
+/// bits 16 to 21 and the build number in bits 0 to 14.
+fn version_from_pox_server_version(version: &str) -> Option<ExchangeServerVersion> {
+    let version = u32::from_str_radix(version.trim(), 16).ok()?;
+
+    server_version::version_from_build((version >> 22) & 0x3F, (version >> 16) & 0x3F, version & 0x7FFF)
+}
+
+/// Counts a redirection, failing once too many of them happened.
+fn count_redirect(redirects: &mut usize) -> Result<(), EwsError> {
+    *redirects += 1;
+    if *redirects > MAX_REDIRECTS {
+        return Err(EwsError::Processing {
+            message: format!("Autodiscover gave up after {MAX_REDIRECTS} redirections"),
+        });
+    }
+
+    Ok(())
+}
+
+/// Creates the error for an Autodiscover response missing a required element.
+fn missing_element(name: &str) -> EwsError {
+    EwsError::Processing {
+        message: format!("Autodiscover response is missing the {name} element"),
+    }
+}
+
+/// Converts a DNS resolution error into an `EwsError`.
+fn dns_error(err: impl std::fmt::Display) -> EwsError {
+    EwsError::Processing {
+        message: format!("DNS lookup failed: {err}"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn client() -> AutodiscoverClient {
+        AutodiscoverClient::new(Credentials::basic("user", "pass")).unwrap()
+    }
+
+    fn url() -> Url {
+        "https://example.com/autodiscover/autodiscover.xml".parse().unwrap()
+    }
+
+    #[test]
+    fn test_email_domain() {
+        assert_eq!(email_domain("user@example.com").unwrap(), "example.com");
+        assert!(email_domain("user").is_err());
+        assert!(email_domain("user@").is_err());
+    }
+
+    #[test]
+    fn test_version_from_dotted_build() {
+        assert_eq!(
+            version_from_dotted_build("15.01.2507.006"),
+            Some(ExchangeServerVersion::Exchange2013_SP1)
+        );
+        assert_eq!(
+            version_from_dotted_build("14.03.0123.000"),
+            Some(ExchangeServerVersion::Exchange2010)
+        );
+        assert_eq!(version_from_dotted_build("garbage"), None);
+    }
+
+    #[test]
+    fn test_version_from_pox_server_version() {
+        // 14.1.218 (Exchange 2010 SP1)
+        assert_eq!(
+            version_from_pox_server_version("738180DA"),
+            Some(ExchangeServerVersion::Exchange2010)
+        );
+        assert_eq!(version_from_pox_server_version("not hex"), None);
+    }
+
+    #[test]
+    fn test_parse_pox_settings_prefers_external_url() {
+        let body = br#"<Autodiscover xmlns="http://schemas.microsoft.com/exchange/autodiscover/responseschema/2006">
+  <Response xmlns="http://schemas.microsoft.com/exchange/autodiscover/outlook/responseschema/2006a">
+    <Account>
+      <Action>settings</Action>
+      <Protocol>
+        <Type>EXCH</Type>
+        <ServerVersion>738180DA</ServerVersion>
+        <EwsUrl>https://internal.example.com/EWS/Exchange.asmx</EwsUrl>
+      </Protocol>
+      <Protocol>
+        <Type>EXPR</Type>
+        <EwsUrl>https://mail.example.com/EWS/Exchange.asmx</EwsUrl>
+      </Protocol>
+    </Account>
+  </Response>
+</Autodiscover>"#;
+
+        let outcome = client().parse_pox_response(&url(), body).unwrap();
+        assert!(matches!(
+            outcome,
+            AutodiscoverOutcome::Settings(MailboxSettings { ref ews_url, server_version })
+                if ews_url.as_str() == "https://mail.example.com/EWS/Exchange.asmx"
+                    && server_version == Some(ExchangeServerVersion::Exchange2010)
+        ));
+    }
+
+    #[test]
+    fn test_parse_pox_redirect_url_requires_https() {
+        let body = br#"<Autodiscover><Response><Account>
+  <Action>redirectUrl</Action>
+  <RedirectUrl>http://other.example.com/autodiscover/autodiscover.xml</RedirectUrl>
+</Account></Response></Autodiscover>"#;
+
+        assert!(client().parse_pox_response(&url(), body).is_err());
+        assert!(matches!(
+            client().require_https(false).parse_pox_response(&url(), body),
+            Ok(AutodiscoverOutcome::RedirectUrl(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_soap_redirect_address() {
+        let body = br#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/"><s:Body>
+  <GetUserSettingsResponseMessage xmlns="http://schemas.microsoft.com/exchange/2010/Autodiscover">
+    <Response>
+      <ErrorCode>NoError</ErrorCode>
+      <UserResponses><UserResponse>
+        <ErrorCode>RedirectAddress</ErrorCode>
+        <RedirectTarget>other@example.org</RedirectTarget>
+      </UserResponse></UserResponses>
+    </Response>
+  </GetUserSettingsResponseMessage>
+</s:Body></s:Envelope>"#;
+
+        assert!(matches!(
+            client().parse_soap_response(&url(), body),
+            Ok(AutodiscoverOutcome::RedirectAddress(address)) if address == "other@example.org"
+        ));
+    }
+
+    #[test]
+    fn test_is_in_domain() {
+        assert!(is_in_domain("contoso.com", "contoso.com"));
+        assert!(is_in_domain("Mail.Contoso.com.", "contoso.com"));
+        assert!(!is_in_domain("evilcontoso.com", "contoso.com"));
+        assert!(!is_in_domain("contoso.com.evil.net", "contoso.com"));
+    }
+
+    #[test]
+    fn test_trusted_endpoint_url() {
+        let url: Url = "https://autodiscover.hosting.net/autodiscover/autodiscover.xml"
+            .parse()
+            .unwrap();
+        assert!(client().trusted_endpoint_url(url.clone(), "contoso.com").is_err());
+
+        let client = client().with_redirect_validator(|url| url.host_str() == Some("autodiscover.hosting.net"));
+        assert_eq!(client.trusted_endpoint_url(url.clone(), "contoso.com").unwrap(), url);
+    }
+
+    #[test]
+    fn test_endpoint_url_uses_overrides() {
+        let client = client().with_host_override("Example.com", "http://127.0.0.1:8080/".parse().unwrap());
+
+        assert_eq!(
+            client
+                .endpoint_url("https", "example.com", None, SOAP_AUTODISCOVER_PATH)
+                .unwrap()
+                .as_str(),
+            "http://127.0.0.1:8080/autodiscover/autodiscover.svc"
+        );
+        assert_eq!(
+            client
+                .endpoint_url("https", "mail.example.com", Some(8443), POX_AUTODISCOVER_PATH)
+                .unwrap()
+                .as_str(),
+            "https://mail.example.com:8443/autodiscover/autodiscover.xml"
+        );
+    }
+}
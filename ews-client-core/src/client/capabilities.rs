@@ -0,0 +1,135 @@
+//! The features available on a server, from its version.
+//!
+//! Operations which only exist on recent versions of Exchange check the
+//! client's capabilities before sending any request, and either take a
+//! fallback path or return [`EwsError::Unsupported`].
+
+use ews::server_version::ExchangeServerVersion;
+
+use crate::client::EwsError;
+
+/// The features of a server, computed from its version.
+///
+/// Servers newer than the most recent version known to the `ews` crate (e.g.
+/// Exchange 2016, 2019 and Office 365) are treated as that version, and so
+/// support every feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // One flag per feature
+pub struct ServerCapabilities {
+    /// The version the capabilities were computed from.
+    pub version: ExchangeServerVersion,
+    /// The `MarkAsJunk` operation (Exchange 2013).
+    pub mark_as_junk: bool,
+    /// The `MarkAllItemsAsRead` operation (Exchange 2013).
+    pub mark_all_items_as_read: bool,
+    /// The `EmptyFolder` operation (Exchange 2010 SP1).
+    pub empty_folder: bool,
+    /// Searching items with an AQS `QueryString` (Exchange 2010).
+    pub query_string: bool,
+    /// The `FindPeople` operation (Exchange 2013).
+    pub find_people: bool,
+    /// The `FindConversation` and `ApplyConversationAction` operations
+    /// (Exchange 2010 SP1).
+    pub conversations: bool,
+    /// The distinguished folders of the archive mailbox, e.g.
+    /// `archivemsgfolderroot` (Exchange 2010 SP1).
+    pub archive_mailbox: bool,
+    /// The `GetInboxRules` and `UpdateInboxRules` operations (Exchange 2010).
+    pub inbox_rules: bool,
+    /// Flagging messages for follow-up (Exchange 2013).
+    pub flag_for_follow_up: bool,
+    /// The `ReturnNewItemIds` option of `CopyItem` and `MoveItem` (Exchange
+    /// 2010 SP1).
+    pub return_new_item_ids: bool,
+    /// Separate start and end time zones on calendar items, instead of a
+    /// single meeting time zone (Exchange 2010).
+    pub time_zone_definitions: bool,
+    /// The `FilterHtmlContent` option of item shapes (Exchange 2010).
+    pub filter_html_content: bool,
+}
+
+impl ServerCapabilities {
+    /// Computes the capabilities of a server from its version.
+    pub fn from_version(version: ExchangeServerVersion) -> Self {
+        let exchange_2010 = version >= ExchangeServerVersion::Exchange2010;
+        // Service packs of Exchange 2010 sort between Exchange 2010 and
+        // Exchange 2013.
+        let exchange_2010_sp1 = version > ExchangeServerVersion::Exchange2010;
+        let exchange_2013 = version >= ExchangeServerVersion::Exchange2013;
+
+        Self {
+            version,
+            mark_as_junk: exchange_2013,
+            mark_all_items_as_read: exchange_2013,
+            empty_folder: exchange_2010_sp1,
+            query_string: exchange_2010,
+            find_people: exchange_2013,
+            conversations: exchange_2010_sp1,
+            archive_mailbox: exchange_2010_sp1,
+            inbox_rules: exchange_2010,
+            flag_for_follow_up: exchange_2013,
+            return_new_item_ids: exchange_2010_sp1,
+            time_zone_definitions: exchange_2010,
+            filter_html_content: exchange_2010,
+        }
+    }
+
+    /// Returns an [`EwsError::Unsupported`] error for `feature` if the
+    /// server does not support it.
+    pub(crate) fn require(self, supported: bool, feature: &str) -> Result<(), EwsError> {
+        if supported {
+            Ok(())
+        } else {
+            Err(self.unsupported(feature))
+        }
+    }
+
+    /// Returns an [`EwsError::Unsupported`] error for `feature`.
+    pub(crate) fn unsupported(self, feature: &str) -> EwsError {
+        EwsError::Unsupported {
+            feature: feature.to_string(),
+            server_version: self.version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_from_version() {
+        let exchange_2007 = ServerCapabilities::from_version(ExchangeServerVersion::Exchange2007_SP1);
+        assert!(!exchange_2007.inbox_rules);
+        assert!(!exchange_2007.time_zone_definitions);
+        assert!(!exchange_2007.return_new_item_ids);
+
+        let exchange_2010 = ServerCapabilities::from_version(ExchangeServerVersion::Exchange2010);
+        assert!(exchange_2010.inbox_rules);
+        assert!(exchange_2010.query_string);
+        assert!(!exchange_2010.return_new_item_ids);
+        assert!(!exchange_2010.mark_as_junk);
+
+        let exchange_2013 = ServerCapabilities::from_version(ExchangeServerVersion::Exchange2013_SP1);
+        assert!(exchange_2013.mark_as_junk);
+        assert!(exchange_2013.find_people);
+        assert!(exchange_2013.empty_folder);
+        assert!(exchange_2013.archive_mailbox);
+    }
+
+    #[test]
+    fn test_require() {
+        let capabilities = ServerCapabilities::from_version(ExchangeServerVersion::Exchange2010);
+        assert!(capabilities.require(capabilities.inbox_rules, "Inbox rules").is_ok());
+
+        let result = capabilities.require(capabilities.find_people, "FindPeople");
+        assert!(
+            matches!(
+                &result,
+                Err(EwsError::Unsupported { feature, server_version: ExchangeServerVersion::Exchange2010 })
+                    if feature == "FindPeople"
+            ),
+            "{result:?}"
+        );
+    }
+}
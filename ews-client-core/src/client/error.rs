@@ -9,6 +9,10 @@ pub enum EwsError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
+    /// I/O error outside of HTTP requests (e.g. binding a local listener)
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// EWS protocol error (SOAP parsing, XML issues, etc.)
     #[error("EWS protocol error: {0}")]
     Protocol(#[from] ews::Error),
@@ -21,6 +25,16 @@ pub enum EwsError {
     #[error("Response error: {0:?}")]
     ResponseError(ews::response::ResponseError),
 
+    /// EWS response contained an error code, for an operation which the `ews`
+    /// crate does not model
+    #[error("Response error: {response_code}: {message_text}")]
+    RawResponseError {
+        /// The `ResponseCode` reported by the server (e.g. `ErrorItemNotFound`)
+        response_code: String,
+        /// The `MessageText` describing the error, empty if none was provided
+        message_text: String,
+    },
+
     /// Error processing response data (validation, unexpected format, etc.)
     #[error("Processing error: {message}")]
     Processing {
@@ -41,6 +55,15 @@ pub enum EwsError {
         actual: usize,
     },
 
+    /// The server's version does not support the requested feature
+    #[error("{feature} is not supported by the server ({server_version:?})")]
+    Unsupported {
+        /// The feature which was requested (e.g. `FindPeople`)
+        feature: String,
+        /// The version of the server, as known to the client
+        server_version: ews::server_version::ExchangeServerVersion,
+    },
+
     /// Invalid URL format
     #[error("Invalid URL: {0}")]
     InvalidUrl(#[from] url::ParseError),
@@ -320,6 +320,42 @@ impl std::fmt::Display for Mailbox<'_> {
     }
 }
 
+/// An owned [`Mailbox`], e.g. a mailbox read from a directory lookup which
+/// outlives the response it was read from.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OwnedMailbox {
+    /// The display name for this mailbox (e.g., "John Doe")
+    pub name: Option<String>,
+    /// The email address (e.g., "john@example.com")
+    pub email_address: Option<String>,
+}
+
+impl OwnedMailbox {
+    /// Borrows this mailbox as a [`Mailbox`].
+    pub fn as_mailbox(&self) -> Mailbox<'_> {
+        Mailbox {
+            name: self.name.as_deref(),
+            email_address: self.email_address.as_deref(),
+        }
+    }
+}
+
+impl From<Mailbox<'_>> for OwnedMailbox {
+    fn from(value: Mailbox<'_>) -> Self {
+        OwnedMailbox {
+            name: value.name.map(ToString::to_string),
+            email_address: value.email_address.map(ToString::to_string),
+        }
+    }
+}
+
+impl std::fmt::Display for OwnedMailbox {
+    /// Writes the mailbox in the same format as [`Mailbox`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_mailbox().fmt(f)
+    }
+}
+
 /// Message priority levels.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MessagePriority {
@@ -396,4 +432,16 @@ mod tests {
             "\"John Doe\" <john@example.com>, jane@example.com"
         );
     }
+
+    #[test]
+    fn test_owned_mailbox_round_trip() {
+        let mailbox = Mailbox {
+            name: Some("John Doe"),
+            email_address: Some("john@example.com"),
+        };
+        let owned = OwnedMailbox::from(mailbox);
+
+        assert_eq!(owned.as_mailbox(), mailbox);
+        assert_eq!(owned.to_string(), mailbox.to_string());
+    }
 }
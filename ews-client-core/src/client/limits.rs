@@ -0,0 +1,468 @@
+//! Client-side limits on the requests sent to the server.
+//!
+//! Exchange throttles clients by their number of concurrent requests and by
+//! the server time they use. Rather than only waiting after the server reports
+//! `ErrorServerBusy`, clients can limit their concurrency and their request
+//! rate, and slow down as soon as the server asks them to back off.
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+
+/// The default maximum number of concurrent connections Exchange allows per
+/// user, from the `EWSMaxConcurrency` throttling policy of Exchange 2013 and
+/// later, and of Office 365.
+pub const EWS_MAX_CONCURRENCY: usize = 27;
+
+/// How long the rate stays reduced after the server last asked the client to
+/// back off, counted from the end of the backoff.
+const RECOVERY_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// How often the rate rises back towards its maximum after the cooldown.
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The number of intervals it takes to recover the maximum rate from no
+/// rate at all, i.e. each interval raises the rate by this fraction of the
+/// maximum.
+const RECOVERY_STEPS: f64 = 10.0;
+
+/// How operations fetching many items or folders (e.g. when syncing the
+/// folder hierarchy) split them into requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// The number of items or folders fetched by each request.
+    pub batch_size: usize,
+    /// The number of requests in flight at once.
+    pub parallelism: usize,
+}
+
+impl Default for BatchConfig {
+    /// Sequential batches of 10, per Microsoft's recommendation.
+    fn default() -> Self {
+        Self {
+            batch_size: 10,
+            parallelism: 1,
+        }
+    }
+}
+
+/// The limits a client waits for before sending each request.
+///
+/// Limits are shared between the clients created from the same
+/// [`EwsClientPool`](crate::client::EwsClientPool).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestLimits {
+    /// Limits the requests of all the clients sharing it.
+    pub global: Option<Arc<Semaphore>>,
+    /// Limits the requests made for a single mailbox.
+    pub mailbox: Option<Arc<Semaphore>>,
+    /// Limits the requests of a client configured with
+    /// [`EwsClient::with_throttling`](crate::client::EwsClient::with_throttling).
+    pub concurrency: Option<Arc<Semaphore>>,
+    /// Limits the rate of the requests of a client configured with
+    /// [`EwsClient::with_throttling`](crate::client::EwsClient::with_throttling).
+    pub rate: Option<Arc<RateLimiter>>,
+}
+
+/// Permits to send a request, released when dropped.
+#[derive(Debug)]
+pub(crate) struct RequestPermits {
+    _global: Option<OwnedSemaphorePermit>,
+    _mailbox: Option<OwnedSemaphorePermit>,
+    _concurrency: Option<OwnedSemaphorePermit>,
+}
+
+impl RequestLimits {
+    /// Waits until a request is allowed by all the limits.
+    ///
+    /// The request rate is checked first, so that a client waiting for its
+    /// rate (e.g. backing off after the server throttled it) does not hold
+    /// permits shared with other clients. The global permit is taken after
+    /// the mailbox one, so that requests waiting on a busy mailbox do not
+    /// hold permits other mailboxes could use.
+    pub(crate) async fn acquire(&self) -> RequestPermits {
+        if let Some(rate) = &self.rate {
+            rate.acquire().await;
+        }
+
+        let mailbox = acquire_permit(self.mailbox.as_ref()).await;
+        let concurrency = acquire_permit(self.concurrency.as_ref()).await;
+        let global = acquire_permit(self.global.as_ref()).await;
+
+        RequestPermits {
+            _global: global,
+            _mailbox: mailbox,
+            _concurrency: concurrency,
+        }
+    }
+
+    /// Slows down the client's requests after the server asked it to back off
+    /// for the given time.
+    pub(crate) fn report_backoff(&self, delay_ms: u32) {
+        if let Some(rate) = &self.rate {
+            rate.report_backoff(Duration::from_millis(u64::from(delay_ms)), Instant::now());
+        }
+    }
+
+    /// Speeds up the client's requests again after a request succeeded
+    /// without being throttled.
+    pub(crate) fn report_success(&self) {
+        if let Some(rate) = &self.rate {
+            rate.report_success(Instant::now());
+        }
+    }
+}
+
+/// Client-side throttling of the requests of a client.
+///
+/// Requests are limited by a token bucket which refills at
+/// `requests_per_second` and holds up to `burst` tokens. When the server asks
+/// the client to back off, all its requests wait for the given time and the
+/// rate is halved, down to `min_requests_per_second`. Once the client has not
+/// been throttled for a minute, successful requests raise the rate back by a
+/// tenth of `requests_per_second` every ten seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThrottlingConfig {
+    /// The sustained number of requests per second.
+    pub requests_per_second: f64,
+    /// The number of requests which can be sent at once after a quiet period.
+    pub burst: u32,
+    /// The lowest rate backing off can bring the client down to.
+    pub min_requests_per_second: f64,
+    /// The maximum number of requests in flight.
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for ThrottlingConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 10.0,
+            burst: 20,
+            min_requests_per_second: 0.5,
+            max_concurrent_requests: EWS_MAX_CONCURRENCY,
+        }
+    }
+}
+
+/// An adaptive token bucket limiting the rate of requests.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// The highest rate, in requests per second.
+    max_rate: f64,
+    /// The lowest rate, in requests per second.
+    min_rate: f64,
+    /// The number of tokens the bucket holds when full.
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+/// The mutable state of a [`RateLimiter`].
+#[derive(Debug)]
+struct BucketState {
+    /// The current rate, in requests per second.
+    rate: f64,
+    /// The tokens left in the bucket.
+    tokens: f64,
+    /// When the tokens were last refilled.
+    refilled_at: Instant,
+    /// When the server allows requests again after asking to back off.
+    paused_until: Option<Instant>,
+    /// When the rate may next be raised, if it is below its maximum.
+    raise_at: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// Creates a full bucket from a configuration, or returns `None` if the
+    /// configuration does not allow any request.
+    pub(crate) fn new(config: &ThrottlingConfig) -> Option<Self> {
+        let max_rate = config.requests_per_second;
+        if !max_rate.is_finite() || max_rate <= 0.0 {
+            log::warn!("Ignoring invalid throttling rate of {max_rate} requests per second");
+            return None;
+        }
+
+        let min_rate = if config.min_requests_per_second > 0.0 {
+            config.min_requests_per_second.min(max_rate)
+        } else {
+            max_rate
+        };
+        let capacity = f64::from(config.burst.max(1));
+
+        Some(Self {
+            max_rate,
+            min_rate,
+            capacity,
+            state: Mutex::new(BucketState {
+                rate: max_rate,
+                tokens: capacity,
+                refilled_at: Instant::now(),
+                paused_until: None,
+                raise_at: None,
+            }),
+        })
+    }
+
+    /// Waits until the bucket has a token, and takes it.
+    async fn acquire(&self) {
+        while let Some(wait) = self.try_take(Instant::now()) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token if one is available at `now`, or returns how long to
+    /// wait before trying again.
+    fn try_take(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(paused_until) = state.paused_until {
+            if paused_until > now {
+                return Some(paused_until - now);
+            }
+            state.paused_until = None;
+            state.refilled_at = paused_until;
+        }
+
+        let elapsed = now.saturating_duration_since(state.refilled_at).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * state.rate).min(self.capacity);
+        state.refilled_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return None;
+        }
+
+        let wait = (1.0 - state.tokens) / state.rate;
+        Some(Duration::try_from_secs_f64(wait).unwrap_or(Duration::from_secs(1)))
+    }
+
+    /// Pauses all requests for `delay` from `now` and halves the rate, which
+    /// then stays reduced for [`RECOVERY_COOLDOWN`] after the pause.
+    fn report_backoff(&self, delay: Duration, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let resume_at = now + delay;
+        state.paused_until = Some(
+            state
+                .paused_until
+                .map_or(resume_at, |paused_until| paused_until.max(resume_at)),
+        );
+        state.tokens = 0.0;
+        state.rate = (state.rate / 2.0).max(self.min_rate);
+        state.raise_at = state.paused_until.map(|paused_until| paused_until + RECOVERY_COOLDOWN);
+
+        log::debug!("Throttling requests to {:.2} per second after backing off", state.rate);
+    }
+
+    /// Raises the rate back towards its maximum by a step for each
+    /// [`RECOVERY_INTERVAL`] elapsed since it was last raised, once the
+    /// cooldown after the last backoff is over.
+    fn report_success(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let Some(raise_at) = state.raise_at else {
+            return;
+        };
+        if now < raise_at {
+            return;
+        }
+
+        let intervals = (now - raise_at)
+            .as_secs_f64()
+            .div_euclid(RECOVERY_INTERVAL.as_secs_f64())
+            + 1.0;
+        state.rate = (state.rate + intervals * self.max_rate / RECOVERY_STEPS).min(self.max_rate);
+        state.raise_at = (state.rate < self.max_rate).then(|| raise_at + RECOVERY_INTERVAL.mul_f64(intervals));
+
+        log::debug!("Throttling requests to {:.2} per second while recovering", state.rate);
+    }
+
+    /// The current rate, in requests per second.
+    #[cfg(test)]
+    fn rate(&self) -> f64 {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).rate
+    }
+}
+
+/// Waits for a permit of a semaphore, if there is one.
+///
+/// The semaphores of the client are never closed, so failing to acquire a
+/// permit cannot happen in practice and is treated as having no limit.
+async fn acquire_permit(semaphore: Option<&Arc<Semaphore>>) -> Option<OwnedSemaphorePermit> {
+    Arc::clone(semaphore?).acquire_owned().await.ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_limits() {
+        let global = Arc::new(Semaphore::new(2));
+        let limits = RequestLimits {
+            global: Some(Arc::clone(&global)),
+            mailbox: Some(Arc::new(Semaphore::new(1))),
+            ..RequestLimits::default()
+        };
+        let other_mailbox = RequestLimits {
+            global: Some(Arc::clone(&global)),
+            mailbox: Some(Arc::new(Semaphore::new(1))),
+            ..RequestLimits::default()
+        };
+
+        let permits = limits.acquire().await;
+        assert_eq!(global.available_permits(), 1);
+
+        // The mailbox is busy, but other mailboxes can still send requests
+        let waiting = tokio::time::timeout(std::time::Duration::from_millis(20), limits.acquire()).await;
+        assert!(waiting.is_err());
+        let _other_permits = other_mailbox.acquire().await;
+        assert_eq!(global.available_permits(), 0);
+
+        drop(permits);
+        assert_eq!(global.available_permits(), 1);
+
+        // Without limits, requests never wait
+        let _unlimited = RequestLimits::default().acquire().await;
+    }
+
+    #[tokio::test]
+    async fn test_request_limits_backoff_holds_no_permit() {
+        let global = Arc::new(Semaphore::new(1));
+        let rate = Arc::new(RateLimiter::new(&ThrottlingConfig::default()).unwrap());
+        rate.report_backoff(Duration::from_secs(60), Instant::now());
+
+        let throttled = RequestLimits {
+            global: Some(Arc::clone(&global)),
+            mailbox: Some(Arc::new(Semaphore::new(1))),
+            rate: Some(rate),
+            ..RequestLimits::default()
+        };
+        let other_mailbox = RequestLimits {
+            global: Some(Arc::clone(&global)),
+            mailbox: Some(Arc::new(Semaphore::new(1))),
+            ..RequestLimits::default()
+        };
+
+        // The throttled client waits without holding the shared permit
+        let waiting = tokio::time::timeout(Duration::from_millis(20), throttled.acquire()).await;
+        assert!(waiting.is_err());
+        assert_eq!(global.available_permits(), 1);
+
+        let other = tokio::time::timeout(Duration::from_millis(20), other_mailbox.acquire()).await;
+        assert!(other.is_ok());
+    }
+
+    #[test]
+    fn test_rate_limiter_bucket() {
+        let limiter = RateLimiter::new(&ThrottlingConfig {
+            requests_per_second: 4.0,
+            burst: 2,
+            ..ThrottlingConfig::default()
+        })
+        .unwrap();
+        let start = limiter.state.lock().unwrap().refilled_at;
+
+        // A full bucket allows a burst, then one request every quarter second
+        assert_eq!(limiter.try_take(start), None);
+        assert_eq!(limiter.try_take(start), None);
+        assert_eq!(limiter.try_take(start), Some(Duration::from_millis(250)));
+        assert_eq!(limiter.try_take(start + Duration::from_millis(250)), None);
+
+        // The bucket never holds more than the burst
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.try_take(later), None);
+        assert_eq!(limiter.try_take(later), None);
+        assert!(limiter.try_take(later).is_some());
+    }
+
+    #[test]
+    fn test_rate_limiter_backoff() {
+        let limiter = RateLimiter::new(&ThrottlingConfig {
+            requests_per_second: 4.0,
+            burst: 10,
+            min_requests_per_second: 1.5,
+            ..ThrottlingConfig::default()
+        })
+        .unwrap();
+
+        let now = Instant::now();
+        limiter.report_backoff(Duration::from_secs(30), now);
+        assert!((limiter.rate() - 2.0).abs() < f64::EPSILON);
+
+        // Requests wait for the end of the backoff, with an empty bucket
+        let wait = limiter.try_take(now).unwrap();
+        assert!(
+            wait > Duration::from_secs(29) && wait <= Duration::from_secs(30),
+            "{wait:?}"
+        );
+        let resume_at = now + wait;
+        assert_eq!(limiter.try_take(resume_at), Some(Duration::from_millis(500)));
+
+        // The rate does not go under the minimum
+        limiter.report_backoff(Duration::ZERO, resume_at);
+        assert!((limiter.rate() - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_rate_limiter_recovery() {
+        let limiter = RateLimiter::new(&ThrottlingConfig {
+            requests_per_second: 4.0,
+            min_requests_per_second: 0.5,
+            ..ThrottlingConfig::default()
+        })
+        .unwrap();
+        let rate_is = |expected: f64| (limiter.rate() - expected).abs() < 1e-9;
+
+        // Successes without a backoff leave the rate at its maximum
+        let now = Instant::now();
+        limiter.report_success(now);
+        assert!(rate_is(4.0));
+
+        // The rate stays reduced for the cooldown after the end of the pause,
+        // however many requests succeed
+        limiter.report_backoff(Duration::from_secs(5), now);
+        assert!(rate_is(2.0));
+        let resume_at = now + Duration::from_secs(5);
+        for seconds in [0, 1, 30, 59] {
+            for _ in 0..100 {
+                limiter.report_success(resume_at + Duration::from_secs(seconds));
+            }
+        }
+        assert!(rate_is(2.0), "{}", limiter.rate());
+
+        // Then it rises by a tenth of the maximum every interval
+        let cooled_down = resume_at + RECOVERY_COOLDOWN;
+        for _ in 0..100 {
+            limiter.report_success(cooled_down);
+        }
+        assert!(rate_is(2.4), "{}", limiter.rate());
+        limiter.report_success(cooled_down + Duration::from_secs(9));
+        assert!(rate_is(2.4), "{}", limiter.rate());
+        limiter.report_success(cooled_down + Duration::from_secs(30));
+        assert!(rate_is(3.6), "{}", limiter.rate());
+        limiter.report_success(cooled_down + Duration::from_secs(300));
+        assert!(rate_is(4.0), "{}", limiter.rate());
+
+        // Another backoff while recovering starts a new cooldown
+        let later = cooled_down + Duration::from_secs(300);
+        limiter.report_backoff(Duration::ZERO, later);
+        limiter.report_success(later + Duration::from_secs(30));
+        assert!(rate_is(2.0), "{}", limiter.rate());
+    }
+
+    #[test]
+    fn test_invalid_throttling_config() {
+        let config = ThrottlingConfig {
+            requests_per_second: 0.0,
+            ..ThrottlingConfig::default()
+        };
+        assert!(RateLimiter::new(&config).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! Access to mailboxes other than the authenticated user's, through Exchange
+//! impersonation or through the distinguished folders of a shared mailbox.
+//!
+//! The `ExchangeImpersonation` header is written with the rest of the SOAP
+//! header when the request envelope is built, and the `Mailbox` of
+//! distinguished folder IDs is written with the folder ID itself. The folder
+//! IDs of the `ews` crate cannot carry a mailbox, so operations addressing the
+//! target mailbox's distinguished folders build their request by hand.
+
+use crate::client::xml::text_element;
+
+/// Identifies the user a service account acts as with Exchange
+/// impersonation.
+///
+/// The account needs the `ApplicationImpersonation` role on the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImpersonatedUserId {
+    /// The primary SMTP address of the user.
+    SmtpAddress(String),
+    /// The security identifier of the user, e.g.
+    /// `S-1-5-21-1333220396-2200287332-232816053-1117`.
+    Sid(String),
+    /// The user principal name of the user, e.g. `user@corp.example.com`.
+    PrincipalName(String),
+}
+
+impl ImpersonatedUserId {
+    /// Writes the `ExchangeImpersonation` SOAP header for the user, with the
+    /// `t:` prefix of the request envelope.
+    pub(crate) fn header_xml(&self) -> String {
+        let connecting_sid = match self {
+            Self::SmtpAddress(address) => text_element("t:PrimarySmtpAddress", address),
+            Self::Sid(sid) => text_element("t:SID", sid),
+            Self::PrincipalName(name) => text_element("t:PrincipalName", name),
+        };
+        format!(
+            "<t:ExchangeImpersonation><t:ConnectingSID>{connecting_sid}</t:ConnectingSID></t:ExchangeImpersonation>"
+        )
+    }
+}
+
+/// The mailbox a client's requests act on, when it is not the mailbox of the
+/// authenticated user.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MailboxAccess {
+    /// The user to impersonate.
+    pub impersonation: Option<ImpersonatedUserId>,
+    /// The address to send in the `X-AnchorMailbox` header.
+    pub anchor_mailbox: Option<String>,
+    /// The SMTP address of the mailbox distinguished folder IDs refer to.
+    pub target_mailbox: Option<String>,
+}
+
+impl MailboxAccess {
+    /// The address to send in the `X-AnchorMailbox` header, which Office 365
+    /// uses to route the request to the server holding the mailbox.
+    ///
+    /// Defaults to the impersonated user's SMTP address, then to the target
+    /// mailbox.
+    pub(crate) fn anchor_mailbox(&self) -> Option<&str> {
+        self.anchor_mailbox
+            .as_deref()
+            .or(match &self.impersonation {
+                Some(ImpersonatedUserId::SmtpAddress(address)) => Some(address.as_str()),
+                _ => None,
+            })
+            .or(self.target_mailbox.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impersonation_header() {
+        assert_eq!(
+            ImpersonatedUserId::Sid("S-1-5-21-1117".to_string()).header_xml(),
+            "<t:ExchangeImpersonation><t:ConnectingSID><t:SID>S-1-5-21-1117</t:SID></t:ConnectingSID></t:ExchangeImpersonation>"
+        );
+        assert_eq!(
+            ImpersonatedUserId::PrincipalName("user@corp.example.com".to_string()).header_xml(),
+            "<t:ExchangeImpersonation><t:ConnectingSID><t:PrincipalName>user@corp.example.com</t:PrincipalName></t:ConnectingSID></t:ExchangeImpersonation>"
+        );
+    }
+
+    #[test]
+    fn test_anchor_mailbox() {
+        let access = MailboxAccess {
+            target_mailbox: Some("shared@example.com".to_string()),
+            ..MailboxAccess::default()
+        };
+        assert_eq!(access.anchor_mailbox(), Some("shared@example.com"));
+
+        let mut access = MailboxAccess {
+            impersonation: Some(ImpersonatedUserId::SmtpAddress("user@example.com".to_string())),
+            target_mailbox: Some("shared@example.com".to_string()),
+            ..MailboxAccess::default()
+        };
+        assert_eq!(access.anchor_mailbox(), Some("user@example.com"));
+
+        access.anchor_mailbox = Some("anchor@example.com".to_string());
+        assert_eq!(access.anchor_mailbox(), Some("anchor@example.com"));
+    }
+}
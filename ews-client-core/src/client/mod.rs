@@ -1,32 +1,86 @@
 //! EWS client implementation and related types
 
+mod autodiscover;
+mod capabilities;
 mod credentials;
 mod error;
 mod headers;
+mod limits;
+mod mailbox_access;
 
 pub mod operations;
+mod pool;
+mod raw_requests;
 mod server_version;
 mod types;
+mod xml;
 
+pub use autodiscover::{AutodiscoverClient, AutodiscoverResult, BoxFuture, DnsSrvResolver, SrvRecord, SrvResolver};
+pub use capabilities::ServerCapabilities;
 pub use credentials::Credentials;
 pub use error::EwsError;
-pub use headers::{Mailbox, MessageHeaders, MessagePriority, make_header_string_for_mailbox_list};
-pub use operations::{CreateMessageResult, FolderHierarchySyncResult, FolderInfo, SyncMessageInfo, SyncMessagesResult};
+pub use headers::{Mailbox, MessageHeaders, MessagePriority, OwnedMailbox, make_header_string_for_mailbox_list};
+pub use limits::{BatchConfig, EWS_MAX_CONCURRENCY, ThrottlingConfig};
+pub use mailbox_access::ImpersonatedUserId;
+pub use operations::{
+    Attendee, AttendeeAvailability, AttendeeType, AvailabilityEvent, BodyType, CalendarItem, CalendarItemDraft,
+    CalendarItemType, CalendarItemUpdate, CalendarViewResult, Contact, ContactEmailAddress, ContactPhoneNumber,
+    ContactPhysicalAddress, ContactUpdate, ContainmentMode, CreateCalendarItemResult, CreateContactResult,
+    CreateMessageResult, CreateTaskResult, DayOfWeek, DayOfWeekIndex, DaylightSavingTime,
+    DelegateFolderPermissionLevel, DelegatePermissions, DelegateSettings, DelegateUser, DeliverMeetingRequests,
+    EmailAddressKey, ExtendedPropertyKey, ExternalAudience, FindContactsResult, FindPeopleResult, FindTasksResult,
+    FolderExtendedProperty, FolderHierarchySyncResult, FolderInfo, FolderKind, FolderPermission, FolderUpdate,
+    Importance, InboxRule, InboxRulesResult, ItemAccess, ItemBaseShape, ItemBody, ItemExtendedProperty, ItemShape,
+    LegacyFreeBusyStatus, MailboxType, MapiPropertyType, MeetingMessageInfo, MeetingMessageKind, MeetingResponse,
+    MeetingSuggestion, Message, Notification, NotificationEvent, NotificationEventType, OccurrenceInfo, OofSettings,
+    OofState, Paging, PermissionLevel, PermissionSet, PermissionUser, Persona, PhoneNumberKey, PhysicalAddressKey,
+    PullSubscriptionPollResult, PullSubscriptionState, ReadAccess, Recurrence, RecurrencePattern, RecurrenceRange,
+    RegenerationFrequency, ResolveNamesSearchScope, ResolvedName, ResponseType, RuleActions, RuleOperation,
+    RulePredicates, SearchExpression, SearchFolderDraft, SearchFolderTraversal, SearchProperty, SendMeetingInvitations,
+    SendMeetingUpdates, SuggestionQuality, SyncMessageInfo, SyncMessagesResult, Task, TaskDraft, TaskStatus,
+    TaskUpdate, TimeWindow, TimeZoneDefinition, TimeZoneTransition, UserAvailabilityResult, parse_icalendar,
+    parse_vcards, write_icalendar,
+};
+#[cfg(feature = "push-notifications")]
+pub use operations::{PushNotificationListener, PushNotificationResult, PushSubscription};
+pub use pool::{EwsClientPool, EwsClientPoolConfig};
+pub use server_version::{InMemoryVersionStore, JsonFileVersionStore, VersionStore};
 pub use types::*;
 
-use std::collections::VecDeque;
+use std::sync::Arc;
 
 use crossbeam::atomic::AtomicCell;
 use ews::{
-    BaseFolderId, BaseItemId, BaseShape, Folder, FolderId, FolderShape, ItemShape, Operation, OperationResponse,
-    PathToElement, RealItem, response::ResponseClass, server_version::ExchangeServerVersion,
+    BaseFolderId, BaseShape, Folder, FolderId, FolderShape, Operation, OperationResponse, response::ResponseClass,
+    server_version::ExchangeServerVersion,
 };
+use futures::{StreamExt, TryStreamExt, stream};
 use reqwest::Client;
+use tokio::sync::Semaphore;
 use url::Url;
+use xml_struct::XmlSerialize;
+
+use limits::{RateLimiter, RequestLimits};
+use mailbox_access::MailboxAccess;
+use xml::XmlElement;
 
 /// The root folder ID for EWS mailbox
 pub(crate) const EWS_ROOT_FOLDER: &str = "msgfolderroot";
 
+/// The classes of the folders the client works with. Folders with a class
+/// derived from one of these (e.g. `IPF.Note.OutlookHomepage`) are supported
+/// as well.
+pub(crate) const SUPPORTED_FOLDER_CLASSES: &[&str] = &["IPF.Note", "IPF.Appointment", "IPF.Contact", "IPF.Task"];
+
+/// The namespace URI of SOAP 1.1 envelopes.
+pub(crate) const SOAP_NS_URI: &str = "http://schemas.xmlsoap.org/soap/envelope/";
+
+/// The namespace URI of EWS types, bound to the `t:` prefix in raw requests.
+pub(crate) const TYPES_NS_URI: &str = "http://schemas.microsoft.com/exchange/services/2006/types";
+
+/// The namespace URI of EWS messages, bound to the `m:` prefix in raw requests.
+pub(crate) const MESSAGES_NS_URI: &str = "http://schemas.microsoft.com/exchange/services/2006/messages";
+
 /// The base domains for Office365-hosted accounts. At the time of writing, the
 /// only valid domain for Office365 EWS URLs should be `outlook.office365.com`,
 /// but we'll throw a few additional Microsoft-owned ones in there in case it
@@ -100,6 +154,18 @@ pub struct EwsClient {
     ///
     /// Uses `AtomicCell` for lock-free access in hot paths like `make_operation_request`.
     pub(crate) server_version: AtomicCell<ExchangeServerVersion>,
+    /// Whether the server version was set with
+    /// [`EwsClient::with_server_version`], rather than detected.
+    server_version_pinned: bool,
+    /// Where the server version is read from when the client is created, and
+    /// stored whenever the server reports it.
+    version_store: Arc<dyn VersionStore>,
+    /// The mailbox requests act on, when it is not the authenticated user's.
+    mailbox_access: MailboxAccess,
+    /// The limits to wait for before sending each request.
+    limits: RequestLimits,
+    /// How operations fetching many items or folders batch their requests.
+    batch_config: BatchConfig,
 }
 
 impl EwsClient {
@@ -109,21 +175,202 @@ impl EwsClient {
     ///
     /// Returns an error if the endpoint URL is invalid or other initialization errors occur.
     pub fn new(endpoint: Url, credentials: Credentials) -> Result<Self, EwsError> {
-        let server_version = server_version::read_server_version(&endpoint);
+        let version_store = server_version::default_version_store();
+        let server_version = server_version::read_server_version(&*version_store, &endpoint);
 
         Ok(Self {
             endpoint,
             credentials,
             client: Client::new(),
             server_version: AtomicCell::new(server_version),
+            server_version_pinned: false,
+            version_store,
+            mailbox_access: MailboxAccess::default(),
+            limits: RequestLimits::default(),
+            batch_config: BatchConfig::default(),
         })
     }
 
+    /// Throttles the client's requests on its side, to stay within the
+    /// server's throttling budget instead of waiting for it to report
+    /// `ErrorServerBusy`.
+    ///
+    /// The request rate adapts to the `BackOffMilliseconds` the server asks
+    /// for (see [`ThrottlingConfig`]). Clients created from this one with
+    /// [`EwsClient::impersonating`] or [`EwsClient::for_mailbox`] share its
+    /// throttling.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials, ThrottlingConfig};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?
+    ///     .with_throttling(ThrottlingConfig {
+    ///         requests_per_second: 5.0,
+    ///         ..ThrottlingConfig::default()
+    ///     });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_throttling(mut self, config: ThrottlingConfig) -> Self {
+        self.limits.rate = RateLimiter::new(&config).map(Arc::new);
+        self.limits.concurrency = Some(Arc::new(Semaphore::new(config.max_concurrent_requests.max(1))));
+        self
+    }
+
+    /// Replaces the store the server version is read from and stored to, e.g.
+    /// with a [`JsonFileVersionStore`] so that the version survives restarts.
+    ///
+    /// The version stored for the endpoint, if any, is used from the client's
+    /// next request. By default, clients share a store kept in memory for the
+    /// lifetime of the process.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use ews_client_core::client::{EwsClient, Credentials, JsonFileVersionStore};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let store = Arc::new(JsonFileVersionStore::open("ews-versions.json")?);
+    /// let client = EwsClient::new("https://mail.example.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?
+    ///     .with_version_store(store);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_version_store(mut self, store: Arc<dyn VersionStore>) -> Self {
+        if !self.server_version_pinned
+            && let Some(version) = store.load(&self.endpoint)
+        {
+            self.server_version.store(version);
+        }
+        self.version_store = store;
+        self
+    }
+
+    /// Pins the version sent in the `RequestServerVersion` header of every
+    /// request, for servers whose reported version does not match their
+    /// behaviour, or which reject newer schema versions.
+    ///
+    /// The client then ignores the versions reported by the server and in
+    /// its version store, and neither stores them nor computes its
+    /// [`EwsClient::capabilities`] from them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials};
+    /// # use ews_client_core::ews::server_version::ExchangeServerVersion;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://mail.example.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?
+    ///     .with_server_version(ExchangeServerVersion::Exchange2010);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_server_version(mut self, version: ExchangeServerVersion) -> Self {
+        self.server_version.store(version);
+        self.server_version_pinned = true;
+        self
+    }
+
+    /// Replaces the credentials the client authenticates with.
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Makes every request of the client act as the given user, with
+    /// Exchange impersonation.
+    ///
+    /// When impersonating by SMTP address, the address is also sent in the
+    /// `X-AnchorMailbox` header unless another anchor mailbox is set, which
+    /// Office 365 requires to route the request.
+    pub fn with_impersonation(mut self, user: ImpersonatedUserId) -> Self {
+        self.mailbox_access.impersonation = Some(user);
+        self
+    }
+
+    /// Sets the address sent in the `X-AnchorMailbox` header of every
+    /// request, which Office 365 uses to route the request to the server
+    /// holding the mailbox.
+    pub fn with_anchor_mailbox(mut self, address: impl Into<String>) -> Self {
+        self.mailbox_access.anchor_mailbox = Some(address.into());
+        self
+    }
+
+    /// Makes distinguished folder names (e.g. `inbox` or `calendar`) refer to
+    /// the folders of another mailbox, such as a shared mailbox the
+    /// authenticated user has been granted access to.
+    pub fn with_target_mailbox(mut self, address: impl Into<String>) -> Self {
+        self.mailbox_access.target_mailbox = Some(address.into());
+        self
+    }
+
+    /// Returns a client acting as the given user with Exchange
+    /// impersonation, for the requests made through it.
+    ///
+    /// The returned client shares the connection pool and credentials of this
+    /// one, and keeps its other settings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials, ImpersonatedUserId};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("service", "pass"))?;
+    /// let result = client
+    ///     .impersonating(ImpersonatedUserId::SmtpAddress("user@example.com".to_string()))
+    ///     .sync_folder_hierarchy(None)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn impersonating(&self, user: ImpersonatedUserId) -> Self {
+        self.derived_client().with_impersonation(user)
+    }
+
+    /// Returns a client whose distinguished folder names refer to the folders
+    /// of another mailbox, for the requests made through it.
+    ///
+    /// The returned client shares the connection pool and credentials of this
+    /// one, and keeps its other settings.
+    pub fn for_mailbox(&self, address: impl Into<String>) -> Self {
+        self.derived_client().with_target_mailbox(address)
+    }
+
+    /// Creates a client with the same endpoint, credentials, connection pool,
+    /// server version and store, mailbox access and limits as this one.
+    fn derived_client(&self) -> Self {
+        Self {
+            endpoint: self.endpoint.clone(),
+            credentials: self.credentials.clone(),
+            client: self.client.clone(),
+            server_version: AtomicCell::new(self.server_version.load()),
+            server_version_pinned: self.server_version_pinned,
+            version_store: Arc::clone(&self.version_store),
+            mailbox_access: self.mailbox_access.clone(),
+            limits: self.limits.clone(),
+            batch_config: self.batch_config,
+        }
+    }
+
     /// Get the endpoint URL
     pub fn endpoint(&self) -> &Url {
         &self.endpoint
     }
 
+    /// Gets the version sent in the `RequestServerVersion` header of the
+    /// client's requests: the pinned version, or the one last detected.
+    pub fn server_version(&self) -> ExchangeServerVersion {
+        self.server_version.load()
+    }
+
+    /// Whether the server version was pinned with
+    /// [`EwsClient::with_server_version`].
+    pub fn is_server_version_pinned(&self) -> bool {
+        self.server_version_pinned
+    }
+
     /// Get a reference to the HTTP client
     #[allow(dead_code)]
     pub(crate) fn http_client(&self) -> &Client {
@@ -136,14 +383,35 @@ impl EwsClient {
         &self.credentials
     }
 
-    /// Updates the server version from a `ServerVersionInfo` header
+    /// The SMTP address of the mailbox distinguished folder IDs refer to,
+    /// when it is not the authenticated user's.
+    pub(crate) fn target_mailbox(&self) -> Option<&str> {
+        self.mailbox_access.target_mailbox.as_deref()
+    }
+
+    /// Updates the server version from a `ServerVersionInfo` header, unless
+    /// it is pinned.
     pub(crate) fn update_server_version(&self, header: ews::server_version::ServerVersionInfo) {
-        let version = server_version::update_server_version_from_header(&self.endpoint, header);
+        if self.server_version_pinned {
+            return;
+        }
+
+        let version = server_version::update_server_version_from_header(&*self.version_store, &self.endpoint, header);
 
         // Update the in-memory representation (lock-free atomic operation)
         self.server_version.store(version);
     }
 
+    /// Returns the features supported by the server, from the version the
+    /// client currently knows.
+    ///
+    /// Until the server has reported its version (e.g. through
+    /// [`Self::detect_server_version`] or a stored version), this reflects
+    /// the conservative default of Exchange 2007 SP1.
+    pub fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities::from_version(self.server_version.load())
+    }
+
     /// Check if the endpoint is an Office365 server
     pub fn is_office365(&self) -> bool {
         self.endpoint.host_str().is_some_and(|domain| {
@@ -178,78 +446,33 @@ impl EwsClient {
         // Get the current server version (lock-free atomic read)
         let version = self.server_version.load();
 
-        // Create SOAP envelope with the operation
-        let envelope = ews::soap::Envelope {
-            headers: vec![ews::soap::Header::RequestServerVersion { version }],
-            body: op,
-        };
-
-        // Serialize to XML
-        let request_body = envelope.as_xml_document()?;
+        // Serialize the operation on its own, and send it in the same
+        // envelope as raw operations, which carries the SOAP headers the
+        // `ews` crate does not model.
+        let request_body = self.request_envelope(version, &serialize_operation(&op)?);
 
         // Loop in case we need to retry the request after a delay
         loop {
-            // Send the authenticated request
-            let response = match self.send_authenticated_request(&request_body, op_name).await {
-                Ok(response) => response,
-                Err(err) => {
-                    // Handle authentication, network and transport security
-                    // failures early because we know how to process them
-                    // without requiring more data from the response body.
-                    match err {
-                        // If the error is an authentication failure, check if we should
-                        // retry based on the options. In a pure library context (without UI),
-                        // we just propagate the error.
-                        EwsError::Authentication
-                            if matches!(options.auth_failure_behavior, AuthFailureBehavior::ReAuth) =>
-                        {
-                            // In ews_xpcom, this would prompt the user for new credentials
-                            // and retry. In a pure library, we just log and return the error.
-                            log::error!("Authentication failed for operation {op_name}");
-                            return Err(err);
-                        }
+            // Wait for the client's limits, and hold them until the response
+            // has been read
+            let permits = self.limits.acquire().await;
 
-                        // If auth_failure_behavior is Silent, fail immediately
-                        EwsError::Authentication => {
-                            log::debug!("Authentication failed for operation {op_name} (silent mode)");
-                            return Err(err);
-                        }
-
-                        // For HTTP errors, check if it's a transport security failure
-                        // and handle according to options
-                        EwsError::Http(ref http_err) => {
-                            // In ews_xpcom, this would check for TransportSecurityFailure
-                            // and potentially show a certificate error dialog.
-                            // In a pure library, we just log based on the behavior setting.
-                            match options.transport_sec_failure_behavior {
-                                TransportSecFailureBehavior::Alert => {
-                                    log::error!("HTTP/Transport error during operation {op_name}: {http_err:?}");
-                                }
-                                TransportSecFailureBehavior::Silent => {
-                                    log::debug!(
-                                        "HTTP/Transport error during operation {op_name} (silent mode): {http_err:?}"
-                                    );
-                                }
-                            }
-                            return Err(err);
-                        }
-
-                        _ => return Err(err),
-                    }
-                }
-            };
+            // Send the authenticated request
+            let response = self
+                .send_authenticated_request(request_body.as_bytes(), op_name)
+                .await
+                .map_err(|err| report_request_failure(err, op_name, options))?;
 
-            // Check HTTP status
+            // Check HTTP status. Exchange reports SOAP faults, including
+            // throttling, with a 500 status, so their body is read as well.
             let status = response.status();
-            if !status.is_success() {
-                log::error!("Request FAILED with status {status} for operation {op_name}");
-                return Err(EwsError::Processing {
-                    message: format!("HTTP request failed with status: {status}"),
-                });
+            if !status.is_success() && !may_carry_soap_fault(status) {
+                return Err(http_status_error(op_name, status));
             }
 
             // Get response body
             let response_body = response.bytes().await?;
+            drop(permits);
 
             // Try to deserialize the response
             let op_result: Result<ews::soap::Envelope<Op::Response>, _> =
@@ -272,10 +495,12 @@ impl EwsClient {
                     {
                         let delay_ms = server_busy.back_off_milliseconds;
                         log::debug!("{op_name} returned busy message, will retry after {delay_ms} milliseconds");
+                        self.limits.report_backoff(delay_ms);
                         tokio::time::sleep(tokio::time::Duration::from_millis(u64::from(delay_ms))).await;
                         continue;
                     }
 
+                    self.limits.report_success();
                     Ok(envelope.body)
                 }
                 Err(err) => {
@@ -283,17 +508,133 @@ impl EwsClient {
                     let backoff_delay_ms = maybe_get_backoff_delay_ms(&err);
                     if let Some(backoff_delay_ms) = backoff_delay_ms {
                         log::debug!("{op_name} request throttled, will retry after {backoff_delay_ms} milliseconds");
+                        self.limits.report_backoff(backoff_delay_ms);
                         tokio::time::sleep(tokio::time::Duration::from_millis(u64::from(backoff_delay_ms))).await;
                         continue;
                     }
 
-                    // If not, propagate the error
+                    // If not, propagate the error, which is the HTTP status
+                    // unless the server answered with a SOAP fault
+                    if !status.is_success() && !matches!(err, ews::Error::RequestFault(_)) {
+                        return Err(http_status_error(op_name, status));
+                    }
                     Err(err.into())
                 }
             };
         }
     }
 
+    /// Makes a request to the EWS endpoint to perform an operation which the
+    /// `ews` crate does not model.
+    ///
+    /// `body` is the XML of the operation element (e.g. `<m:Subscribe>...`),
+    /// written with the `m:` and `t:` prefixes for the EWS messages and types
+    /// namespaces. Authentication failures, server version headers and
+    /// throttling are handled the same way as in
+    /// [`EwsClient::make_operation_request`].
+    ///
+    /// Returns the operation's response element, i.e. the first child of the
+    /// SOAP body.
+    pub(crate) async fn make_raw_operation_request(
+        &self,
+        op_name: &str,
+        body: &str,
+        options: OperationRequestOptions,
+    ) -> Result<XmlElement, EwsError> {
+        // Get the current server version (lock-free atomic read)
+        let version = self.server_version.load();
+        let request_body = self.request_envelope(version, body);
+
+        // Loop in case we need to retry the request after a delay
+        loop {
+            // Wait for the client's limits, and hold them until the response
+            // has been read
+            let permits = self.limits.acquire().await;
+
+            // Send the authenticated request
+            let response = self
+                .send_authenticated_request(request_body.as_bytes(), op_name)
+                .await
+                .map_err(|err| report_request_failure(err, op_name, options))?;
+
+            // Check HTTP status. Exchange reports SOAP faults, including
+            // throttling, with a 500 status, so their body is read as well.
+            let status = response.status();
+            if !status.is_success() && !may_carry_soap_fault(status) {
+                return Err(http_status_error(op_name, status));
+            }
+
+            let response_body = response.bytes().await?;
+            drop(permits);
+            let envelope = match XmlElement::parse(&response_body) {
+                Ok(envelope) => envelope,
+                Err(_) if !status.is_success() => return Err(http_status_error(op_name, status)),
+                Err(err) => return Err(err),
+            };
+
+            // If the server responded with a version identifier, store it
+            if let Some(version) = envelope
+                .descendant(&["Header", "ServerVersionInfo"])
+                .and_then(|info| info.attribute("Version"))
+            {
+                self.update_server_version(ews::server_version::ServerVersionInfo {
+                    major_version: None,
+                    minor_version: None,
+                    major_build_number: None,
+                    minor_build_number: None,
+                    version: Some(version.to_string()),
+                });
+            }
+
+            let response_element = envelope
+                .children
+                .into_iter()
+                .find(|child| child.name == "Body")
+                .and_then(|body| body.children.into_iter().next())
+                .ok_or_else(|| EwsError::Processing {
+                    message: format!("{op_name} response has an empty SOAP body"),
+                })?;
+            if !status.is_success() && response_element.name != "Fault" {
+                return Err(http_status_error(op_name, status));
+            }
+
+            // Check if the response is a back off message, and retry if so
+            if let Some(delay_ms) = maybe_get_raw_backoff_delay_ms(&response_element) {
+                log::debug!("{op_name} returned busy message, will retry after {delay_ms} milliseconds");
+                self.limits.report_backoff(delay_ms);
+                tokio::time::sleep(tokio::time::Duration::from_millis(u64::from(delay_ms))).await;
+                continue;
+            }
+
+            if response_element.name == "Fault" {
+                return Err(EwsError::RawResponseError {
+                    response_code: response_element
+                        .find("ResponseCode")
+                        .map_or_else(|| "Fault".to_string(), |code| code.text.clone()),
+                    message_text: response_element
+                        .child_text("faultstring")
+                        .unwrap_or_default()
+                        .to_string(),
+                });
+            }
+
+            self.limits.report_success();
+            break Ok(response_element);
+        }
+    }
+
+    /// Writes the SOAP envelope of a request for the given operation element,
+    /// with the client's impersonation header if any.
+    fn request_envelope(&self, version: ExchangeServerVersion, operation: &str) -> String {
+        let headers = self
+            .mailbox_access
+            .impersonation
+            .as_ref()
+            .map(ImpersonatedUserId::header_xml)
+            .unwrap_or_default();
+        raw_envelope(&server_version::version_header_value(version), &headers, operation)
+    }
+
     /// Send an authenticated EWS operation request with the given body.
     ///
     /// This method:
@@ -312,14 +653,15 @@ impl EwsClient {
         log::info!("Making operation request: {op_name}");
 
         // Send the request
-        let response = self
+        let mut request = self
             .client
             .post(self.endpoint.clone())
             .header("Authorization", auth_header_value)
-            .header("Content-Type", "text/xml; charset=utf-8")
-            .body(request_body.to_vec())
-            .send()
-            .await?;
+            .header("Content-Type", "text/xml; charset=utf-8");
+        if let Some(anchor_mailbox) = self.mailbox_access.anchor_mailbox() {
+            request = request.header("X-AnchorMailbox", anchor_mailbox);
+        }
+        let response = request.body(request_body.to_vec()).send().await?;
 
         let response_status = response.status();
         log::info!("Response received for operation {op_name} (status {response_status})");
@@ -332,159 +674,152 @@ impl EwsClient {
         Ok(response)
     }
 
-    /// Fetches items from the remote Exchange server in batches.
+    /// Sets how operations fetching many items or folders split them into
+    /// requests, and how many of these requests are in flight at once.
     ///
-    /// This method handles batching of `GetItem` requests (max 10 items per request)
-    /// to avoid throttling.
-    pub(crate) async fn get_items<IdColl>(
-        &self,
-        ids: IdColl,
-        fields: &[&str],
-        include_mime_content: bool,
-    ) -> Result<Vec<RealItem>, EwsError>
-    where
-        IdColl: IntoIterator<Item = String>,
-    {
-        let mut ids: VecDeque<_> = ids.into_iter().collect();
-        let mut items = Vec::with_capacity(ids.len());
-
-        while !ids.is_empty() {
-            // Batch items in chunks of 10 per Microsoft's recommendation
-            let batch_ids: Vec<_> = {
-                let range_end = usize::min(10, ids.len());
-                ids.drain(0..range_end)
-            }
-            .map(|id| BaseItemId::ItemId { id, change_key: None })
-            .collect();
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{BatchConfig, EwsClient, Credentials};
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?
+    ///     .with_batch_config(BatchConfig {
+    ///         batch_size: 50,
+    ///         parallelism: 4,
+    ///     });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_batch_config(mut self, config: BatchConfig) -> Self {
+        self.batch_config = BatchConfig {
+            batch_size: config.batch_size.max(1),
+            parallelism: config.parallelism.max(1),
+        };
+        self
+    }
 
-            let additional_properties: Vec<_> = fields
-                .iter()
-                .map(|&field| PathToElement::FieldURI {
-                    field_URI: String::from(field),
-                })
-                .collect();
-
-            let additional_properties = if additional_properties.is_empty() {
-                None
-            } else {
-                Some(additional_properties)
-            };
+    /// Get how operations fetching many items or folders batch their
+    /// requests
+    pub fn batch_config(&self) -> BatchConfig {
+        self.batch_config
+    }
 
-            let op = ews::get_item::GetItem {
-                item_shape: ItemShape {
-                    base_shape: BaseShape::IdOnly,
-                    additional_properties,
-                    include_mime_content: Some(include_mime_content),
-                },
-                item_ids: batch_ids,
-            };
+    /// Splits IDs into batches of the configured size and fetches them with
+    /// `fetch_batch`, with up to the configured number of batches in flight.
+    ///
+    /// The results are returned in the order of the IDs. The first error
+    /// cancels the batches in flight and those not started yet.
+    async fn fetch_in_batches<T, F, Fut>(&self, ids: Vec<String>, fetch_batch: F) -> Result<Vec<T>, EwsError>
+    where
+        F: FnMut(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, EwsError>>,
+    {
+        let BatchConfig {
+            batch_size,
+            parallelism,
+        } = self.batch_config;
 
-            let response = self
-                .make_operation_request(op, OperationRequestOptions::default())
-                .await?;
-            for response_message in response.into_response_messages() {
-                let message = process_response_message_class("GetItem", response_message)?;
-
-                let items_len = message.items.inner.len();
-                if items_len != 1 {
-                    log::warn!("GetItemResponseMessage contained {items_len} items, only 1 expected");
-                }
+        let mut ids = ids.into_iter().peekable();
+        let batches = std::iter::from_fn(move || {
+            ids.peek()?;
+            Some(ids.by_ref().take(batch_size).collect::<Vec<_>>())
+        });
 
-                items.extend(message.items.inner.into_iter());
-            }
-        }
+        let results: Vec<Vec<T>> = stream::iter(batches.map(fetch_batch))
+            .buffered(parallelism)
+            .try_collect()
+            .await?;
 
-        Ok(items)
+        Ok(results.into_iter().flatten().collect())
     }
 
     /// Fetches folders from the remote Exchange server in batches.
     ///
-    /// This method handles batching of `GetFolder` requests and filters out
-    /// non-mail folders.
+    /// `GetFolder` requests are batched according to the client's
+    /// [`BatchConfig`], and folders whose class is not one of
+    /// [`SUPPORTED_FOLDER_CLASSES`] are filtered out. The other folders are
+    /// returned in the order of the IDs.
     pub(crate) async fn batch_get_folders(&self, ids: Vec<String>) -> Result<Vec<Folder>, EwsError> {
-        let mut folders = Vec::with_capacity(ids.len());
-        let mut ids = ids.into_iter().peekable();
-        let mut buf = Vec::with_capacity(10);
-
-        loop {
-            // Batch folders in chunks of 10 per Microsoft's recommendation
-            for _ in 0..10 {
-                match ids.next() {
-                    Some(value) => buf.push(value),
-                    None => break,
-                }
-            }
+        self.fetch_in_batches(ids, |batch_ids| self.get_folders_batch(batch_ids))
+            .await
+    }
 
-            let to_fetch = buf
-                .drain(..)
+    /// Fetches a single batch of folders with one `GetFolder` request.
+    async fn get_folders_batch(&self, ids: Vec<String>) -> Result<Vec<Folder>, EwsError> {
+        let op = ews::get_folder::GetFolder {
+            folder_shape: FolderShape {
+                base_shape: BaseShape::AllProperties,
+            },
+            folder_ids: ids
+                .into_iter()
                 .map(|id| BaseFolderId::FolderId { id, change_key: None })
-                .collect();
-
-            let op = ews::get_folder::GetFolder {
-                folder_shape: FolderShape {
-                    base_shape: BaseShape::AllProperties,
-                },
-                folder_ids: to_fetch,
-            };
-
-            let response = self
-                .make_operation_request(op, OperationRequestOptions::default())
-                .await?;
-            let messages = response.into_response_messages();
+                .collect(),
+        };
 
-            let mut fetched = messages
-                .into_iter()
-                .filter_map(|response_class| {
-                    let message = match process_response_message_class("GetFolder", response_class) {
-                        Ok(message) => message,
-                        Err(err) => return Some(Err(err)),
-                    };
-                    if let Err(err) = validate_get_folder_response_message(&message) {
-                        return Some(Err(err));
-                    }
+        let response = self
+            .make_operation_request(op, OperationRequestOptions::default())
+            .await?;
+        let messages = response.into_response_messages();
+
+        messages
+            .into_iter()
+            .filter_map(|response_class| {
+                let message = match process_response_message_class("GetFolder", response_class) {
+                    Ok(message) => message,
+                    Err(err) => return Some(Err(err)),
+                };
+                if let Err(err) = validate_get_folder_response_message(&message) {
+                    return Some(Err(err));
+                }
 
-                    message
-                        .folders
-                        .inner
-                        .into_iter()
-                        .next()
-                        .and_then(|folder| match &folder {
-                            Folder::Folder {
-                                folder_class,
-                                display_name,
-                                ..
-                            } => {
-                                let folder_class = folder_class.as_ref().map(std::string::String::as_str);
-
-                                // Filter out non-mail folders
-                                if let Some(folder_class) = folder_class {
-                                    if folder_class == "IPF.Note" || folder_class.starts_with("IPF.Note.") {
-                                        Some(Ok(folder))
-                                    } else {
-                                        log::debug!("Skipping folder with unsupported class: {folder_class}");
-                                        None
-                                    }
+                message
+                    .folders
+                    .inner
+                    .into_iter()
+                    .next()
+                    .and_then(|folder| match &folder {
+                        Folder::Folder {
+                            folder_class,
+                            display_name,
+                            ..
+                        }
+                        | Folder::CalendarFolder {
+                            folder_class,
+                            display_name,
+                            ..
+                        }
+                        | Folder::ContactsFolder {
+                            folder_class,
+                            display_name,
+                            ..
+                        }
+                        | Folder::TasksFolder {
+                            folder_class,
+                            display_name,
+                            ..
+                        } => {
+                            let folder_class = folder_class.as_ref().map(std::string::String::as_str);
+
+                            // Filter out folders we don't support
+                            if let Some(folder_class) = folder_class {
+                                if is_supported_folder_class(folder_class) {
+                                    Some(Ok(folder))
                                 } else {
-                                    log::warn!(
-                                        "Skipping folder without a class: {}",
-                                        display_name.clone().unwrap_or("unknown".to_string())
-                                    );
+                                    log::debug!("Skipping folder with unsupported class: {folder_class}");
                                     None
                                 }
+                            } else {
+                                log::warn!(
+                                    "Skipping folder without a class: {}",
+                                    display_name.clone().unwrap_or("unknown".to_string())
+                                );
+                                None
                             }
-                            _ => None,
-                        })
-                })
-                .collect::<Result<_, _>>()?;
-
-            folders.append(&mut fetched);
-
-            if ids.peek().is_none() {
-                break;
-            }
-        }
-
-        Ok(folders)
+                        }
+                        _ => None,
+                    })
+            })
+            .collect()
     }
 
     /// Performs a `CreateItem` operation and processes its response.
@@ -539,6 +874,64 @@ impl EwsClient {
     }
 }
 
+/// Handles authentication, network and transport security failures which
+/// happened while sending a request.
+///
+/// We know how to process these early, without requiring more data from the
+/// response body. The error is logged according to the request options and
+/// returned so it can be propagated.
+fn report_request_failure(err: EwsError, op_name: &str, options: OperationRequestOptions) -> EwsError {
+    match err {
+        // If the error is an authentication failure, check if we should
+        // retry based on the options. In a pure library context (without UI),
+        // we just propagate the error.
+        EwsError::Authentication if matches!(options.auth_failure_behavior, AuthFailureBehavior::ReAuth) => {
+            // In ews_xpcom, this would prompt the user for new credentials
+            // and retry. In a pure library, we just log and return the error.
+            log::error!("Authentication failed for operation {op_name}");
+        }
+
+        // If auth_failure_behavior is Silent, fail immediately
+        EwsError::Authentication => {
+            log::debug!("Authentication failed for operation {op_name} (silent mode)");
+        }
+
+        // For HTTP errors, check if it's a transport security failure
+        // and handle according to options
+        EwsError::Http(ref http_err) => {
+            // In ews_xpcom, this would check for TransportSecurityFailure
+            // and potentially show a certificate error dialog.
+            // In a pure library, we just log based on the behavior setting.
+            match options.transport_sec_failure_behavior {
+                TransportSecFailureBehavior::Alert => {
+                    log::error!("HTTP/Transport error during operation {op_name}: {http_err:?}");
+                }
+                TransportSecFailureBehavior::Silent => {
+                    log::debug!("HTTP/Transport error during operation {op_name} (silent mode): {http_err:?}");
+                }
+            }
+        }
+
+        _ => {}
+    }
+
+    err
+}
+
+/// Whether a response with a failure status may still hold a SOAP fault,
+/// which Exchange sends with `500 Internal Server Error`.
+fn may_carry_soap_fault(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Creates the error for a response with a failure status and no SOAP fault.
+fn http_status_error(op_name: &str, status: reqwest::StatusCode) -> EwsError {
+    log::error!("Request FAILED with status {status} for operation {op_name}");
+    EwsError::Processing {
+        message: format!("HTTP request failed with status: {status}"),
+    }
+}
+
 /// Gets the time to wait before retrying a throttled request, if any.
 ///
 /// When an Exchange server throttles a request, the response will specify a
@@ -556,6 +949,117 @@ fn maybe_get_backoff_delay_ms(err: &ews::Error) -> Option<u32> {
     }
 }
 
+/// Whether a folder class is one of [`SUPPORTED_FOLDER_CLASSES`], or derived
+/// from one of them.
+pub(crate) fn is_supported_folder_class(folder_class: &str) -> bool {
+    SUPPORTED_FOLDER_CLASSES.iter().any(|supported| {
+        folder_class == *supported
+            || folder_class
+                .strip_prefix(supported)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Serializes an operation from the `ews` crate as the element of a SOAP body
+/// (e.g. `<GetFolder xmlns="...">`), to be written into [`raw_envelope`].
+///
+/// The operation's child elements use the `t:` prefix, which the envelope
+/// binds to the types namespace.
+fn serialize_operation<Op>(op: &Op) -> Result<String, EwsError>
+where
+    Op: Operation,
+{
+    let op_name = <Op as Operation>::NAME;
+
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    op.serialize_as_element(&mut writer, op_name)
+        .map_err(|err| EwsError::Processing {
+            message: format!("failed to serialize {op_name} request: {err}"),
+        })?;
+
+    String::from_utf8(writer.into_inner()).map_err(|err| EwsError::Processing {
+        message: format!("{op_name} request is not valid UTF-8: {err}"),
+    })
+}
+
+/// Writes the SOAP envelope of a request, with the given SOAP headers after
+/// the requested server version.
+fn raw_envelope(version: &str, headers: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="{SOAP_NS_URI}" xmlns:t="{TYPES_NS_URI}" xmlns:m="{MESSAGES_NS_URI}"><soap:Header><t:RequestServerVersion Version="{version}"/>{headers}</soap:Header><soap:Body>{body}</soap:Body></soap:Envelope>"#
+    )
+}
+
+/// Gets the time to wait before retrying a throttled raw request, if any.
+///
+/// Throttling is reported either in the detail of a SOAP fault, or as an
+/// `ErrorServerBusy` code in the first response message of the operation.
+fn maybe_get_raw_backoff_delay_ms(response: &XmlElement) -> Option<u32> {
+    let error = if response.name == "Fault" {
+        response.child("detail")?
+    } else {
+        *raw_response_messages(response).first()?
+    };
+
+    if error.child_text("ResponseCode") != Some("ErrorServerBusy") {
+        return None;
+    }
+
+    error
+        .child("MessageXml")?
+        .children_named("Value")
+        .find(|value| value.attribute("Name") == Some("BackOffMilliseconds"))?
+        .text
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Gets the response messages of a raw operation response.
+///
+/// Most operations wrap their messages in a `ResponseMessages` element, but a
+/// few (e.g. `GetUserOofSettings`) return a single `ResponseMessage` element
+/// alongside the operation's data.
+pub(crate) fn raw_response_messages(response: &XmlElement) -> Vec<&XmlElement> {
+    if let Some(messages) = response.child("ResponseMessages") {
+        messages.children.iter().collect()
+    } else {
+        response.child("ResponseMessage").into_iter().collect()
+    }
+}
+
+/// Raw counterpart of [`process_response_message_class`]: look at the
+/// `ResponseClass` attribute of a response message, and do nothing, warn or
+/// return an error accordingly.
+pub(crate) fn process_raw_response_message<'a>(
+    op_name: &str,
+    message: &'a XmlElement,
+) -> Result<&'a XmlElement, EwsError> {
+    match message.attribute("ResponseClass") {
+        Some("Success") => Ok(message),
+
+        Some("Warning") => {
+            log::warn!("{op_name} operation encountered unknown warning");
+            Ok(message)
+        }
+
+        _ => Err(EwsError::RawResponseError {
+            response_code: message.child_text("ResponseCode").unwrap_or("Unknown").to_string(),
+            message_text: message.child_text("MessageText").unwrap_or_default().to_string(),
+        }),
+    }
+}
+
+/// For raw responses where we expect a single response message, extract and
+/// process that message.
+pub(crate) fn single_raw_response_or_error<'a>(
+    op_name: &str,
+    response: &'a XmlElement,
+) -> Result<&'a XmlElement, EwsError> {
+    let message = single_response_or_error(raw_response_messages(response))?;
+    process_raw_response_message(op_name, message)
+}
+
 /// Look at the response class of a response message, and do nothing, warn or
 /// return an error accordingly.
 pub(crate) fn process_response_message_class<T>(
@@ -592,9 +1096,16 @@ pub(crate) fn validate_get_folder_response_message(
 
     // We've verified the length is 1, so first() should always return Some
     match message.folders.inner.first() {
-        Some(Folder::Folder { folder_id, .. }) => folder_id.clone().ok_or(EwsError::MissingIdInResponse),
+        Some(
+            Folder::Folder { folder_id, .. }
+            | Folder::CalendarFolder { folder_id, .. }
+            | Folder::ContactsFolder { folder_id, .. }
+            | Folder::TasksFolder { folder_id, .. },
+        ) => folder_id.clone().ok_or(EwsError::MissingIdInResponse),
         Some(_) => Err(EwsError::Processing {
-            message: String::from("expected folder to be of type Folder"),
+            message: String::from(
+                "expected folder to be of type Folder, CalendarFolder, ContactsFolder or TasksFolder",
+            ),
         }),
         None => Err(EwsError::Processing {
             message: String::from("no folder in response"),
@@ -636,3 +1147,91 @@ pub(crate) fn single_response_or_error<T>(responses: Vec<T>) -> Result<T, EwsErr
     }
     Ok(message)
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+
+    fn batching_client(batch_size: usize, parallelism: usize) -> EwsClient {
+        EwsClient::new(
+            "https://batch.example.com/EWS/Exchange.asmx".parse().unwrap(),
+            Credentials::basic("user", "pass"),
+        )
+        .unwrap()
+        .with_batch_config(BatchConfig {
+            batch_size,
+            parallelism,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_batches_keeps_order() {
+        let client = batching_client(3, 2);
+        let ids: Vec<String> = (0..10).map(|id| id.to_string()).collect();
+
+        let in_flight = &AtomicUsize::new(0);
+        let max_in_flight = &AtomicUsize::new(0);
+        let batch_sizes = &std::sync::Mutex::new(Vec::new());
+
+        let result = client
+            .fetch_in_batches(ids.clone(), |batch| async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                batch_sizes.lock().unwrap().push(batch.len());
+
+                // Later batches complete first
+                let first: u64 = batch[0].parse().unwrap();
+                tokio::time::sleep(Duration::from_millis(50 - first * 5)).await;
+
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(batch)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result, ids);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(*batch_sizes.lock().unwrap(), vec![3, 3, 3, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_batches_cancels_on_error() {
+        let client = batching_client(3, 2);
+        let ids: Vec<String> = (0..10).map(|id| id.to_string()).collect();
+        let started = &AtomicUsize::new(0);
+
+        let result = client
+            .fetch_in_batches(ids, |batch| async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                if batch[0] == "3" {
+                    return Err(EwsError::Processing {
+                        message: "batch failed".to_string(),
+                    });
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(batch)
+            })
+            .await;
+
+        assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+        assert!(started.load(Ordering::SeqCst) < 4);
+    }
+
+    #[test]
+    fn test_batch_config_is_never_zero() {
+        let client = batching_client(0, 0);
+        assert_eq!(
+            client.batch_config(),
+            BatchConfig {
+                batch_size: 1,
+                parallelism: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,338 @@
+//! Free/busy and scheduling operations.
+//!
+//! This module contains the `GetUserAvailability` operation, which reads the
+//! free/busy information of several attendees at once and suggests times at
+//! which they can all meet.
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    operations::calendar::{Attendee, LegacyFreeBusyStatus},
+    process_raw_response_message,
+    raw_requests::availability::user_availability_request,
+    xml::XmlElement,
+};
+
+/// The smallest interval, in minutes, Exchange accepts for merged free/busy
+/// information.
+const MIN_MERGED_INTERVAL_MINUTES: u32 = 5;
+
+/// The largest interval, in minutes, Exchange accepts for merged free/busy
+/// information.
+const MAX_MERGED_INTERVAL_MINUTES: u32 = 1440;
+
+/// The maximum number of attendees in a single `GetUserAvailability` request.
+const MAX_AVAILABILITY_ATTENDEES: usize = 100;
+
+/// A range of time, as Unix timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    /// The start of the range, as a Unix timestamp.
+    pub start: i64,
+    /// The end of the range, as a Unix timestamp.
+    pub end: i64,
+}
+
+/// A busy period in an attendee's calendar.
+#[derive(Debug, Clone)]
+pub struct AvailabilityEvent {
+    /// The start of the period, as a Unix timestamp.
+    pub start: i64,
+    /// The end of the period, as a Unix timestamp.
+    pub end: i64,
+    /// How the period is shown in free/busy information.
+    pub busy_type: LegacyFreeBusyStatus,
+    /// The subject of the calendar item, if the caller may see it.
+    pub subject: Option<String>,
+    /// The location of the calendar item, if the caller may see it.
+    pub location: Option<String>,
+    /// Whether the calendar item is a meeting.
+    pub is_meeting: bool,
+    /// Whether the calendar item is an occurrence of a recurring meeting.
+    pub is_recurring: bool,
+    /// Whether the calendar item is private.
+    pub is_private: bool,
+}
+
+impl AvailabilityEvent {
+    /// Reads a busy period from a `CalendarEvent` element.
+    fn from_xml(element: &XmlElement) -> Option<Self> {
+        let details = element.child("CalendarEventDetails");
+        let detail_text = |name: &str| {
+            details
+                .and_then(|details| details.child_text(name))
+                .map(ToString::to_string)
+        };
+        let detail_bool = |name: &str| details.and_then(|details| details.child_bool(name)).unwrap_or(false);
+
+        Some(Self {
+            start: element.child_date_time("StartTime")?,
+            end: element.child_date_time("EndTime")?,
+            busy_type: element
+                .child_text("BusyType")
+                .and_then(LegacyFreeBusyStatus::parse)
+                .unwrap_or(LegacyFreeBusyStatus::NoData),
+            subject: detail_text("Subject"),
+            location: detail_text("Location"),
+            is_meeting: detail_bool("IsMeeting"),
+            is_recurring: detail_bool("IsRecurring"),
+            is_private: detail_bool("IsPrivate"),
+        })
+    }
+}
+
+/// The availability of a single attendee.
+#[derive(Debug, Clone)]
+pub struct AttendeeAvailability {
+    /// The email address of the attendee.
+    pub email: String,
+    /// The merged free/busy information of the attendee, as one digit per
+    /// interval from the start of the window (`0` free, `1` tentative, `2`
+    /// busy, `3` out of office, `4` working elsewhere, `5` no data).
+    pub merged_free_busy: Option<String>,
+    /// The busy periods of the attendee in the window.
+    pub events: Vec<AvailabilityEvent>,
+    /// The response code if the availability of this attendee could not be
+    /// read (e.g. `ErrorMailRecipientNotFound`).
+    pub error: Option<String>,
+}
+
+impl AttendeeAvailability {
+    /// The merged free/busy information, decoded into one status per
+    /// interval.
+    pub fn merged_statuses(&self) -> Vec<LegacyFreeBusyStatus> {
+        self.merged_free_busy
+            .as_deref()
+            .unwrap_or_default()
+            .chars()
+            .map(|status| match status {
+                '0' => LegacyFreeBusyStatus::Free,
+                '1' => LegacyFreeBusyStatus::Tentative,
+                '2' => LegacyFreeBusyStatus::Busy,
+                '3' => LegacyFreeBusyStatus::OutOfOffice,
+                '4' => LegacyFreeBusyStatus::WorkingElsewhere,
+                _ => LegacyFreeBusyStatus::NoData,
+            })
+            .collect()
+    }
+}
+
+/// How good a suggested meeting time is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SuggestionQuality {
+    /// Most attendees are unavailable.
+    Poor,
+    /// Some attendees are unavailable.
+    Fair,
+    /// Few attendees are unavailable.
+    Good,
+    /// Every attendee is available.
+    Excellent,
+}
+
+impl SuggestionQuality {
+    /// Parses a suggestion quality from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "Poor" => Some(Self::Poor),
+            "Fair" => Some(Self::Fair),
+            "Good" => Some(Self::Good),
+            "Excellent" => Some(Self::Excellent),
+            _ => None,
+        }
+    }
+}
+
+/// A suggested meeting time.
+#[derive(Debug, Clone)]
+pub struct MeetingSuggestion {
+    /// The start of the suggested meeting, as a Unix timestamp.
+    pub start: i64,
+    /// Whether the suggested time is within the attendees' working hours.
+    pub is_work_time: bool,
+    /// How good the suggested time is.
+    pub quality: SuggestionQuality,
+}
+
+/// Result of reading the availability of attendees.
+#[derive(Debug, Clone)]
+pub struct UserAvailabilityResult {
+    /// The availability of each attendee, in the order of the request.
+    pub attendees: Vec<AttendeeAvailability>,
+    /// Suggested meeting times, from the best to the worst, if a meeting
+    /// duration was provided.
+    pub suggestions: Vec<MeetingSuggestion>,
+}
+
+impl EwsClient {
+    /// Reads the free/busy information of attendees over a time window, and
+    /// optionally suggests times at which they can meet.
+    ///
+    /// All times, including those in the result, are Unix timestamps; the
+    /// request is made in UTC so that no conversion between time zones is
+    /// needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `attendees` - The attendees, with their role in the meeting
+    /// * `window` - The time window to read
+    /// * `interval_minutes` - The length of each interval of the merged
+    ///   free/busy information, between 5 and 1440 minutes
+    /// * `meeting_duration_minutes` - The duration of the meeting to suggest
+    ///   times for, or `None` for no suggestions
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - There are no attendees, or more than 100
+    /// - The window or the interval is invalid
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Attendee, AttendeeType, Credentials, EwsClient, TimeWindow};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let attendees = [
+    ///     Attendee::new("alice@example.com", AttendeeType::Required),
+    ///     Attendee::new("bob@example.com", AttendeeType::Optional),
+    /// ];
+    /// let window = TimeWindow { start: 1_705_276_800, end: 1_705_363_200 };
+    ///
+    /// let availability = client.get_user_availability(&attendees, window, 30, Some(60)).await?;
+    /// for attendee in &availability.attendees {
+    ///     println!("{}: {:?}", attendee.email, attendee.merged_free_busy);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_user_availability(
+        &self,
+        attendees: &[Attendee],
+        window: TimeWindow,
+        interval_minutes: u32,
+        meeting_duration_minutes: Option<u32>,
+    ) -> Result<UserAvailabilityResult, EwsError> {
+        if attendees.is_empty() || attendees.len() > MAX_AVAILABILITY_ATTENDEES {
+            return Err(EwsError::Processing {
+                message: format!(
+                    "availability can be requested for 1 to {MAX_AVAILABILITY_ATTENDEES} attendees, got {}",
+                    attendees.len()
+                ),
+            });
+        }
+        if window.end <= window.start {
+            return Err(EwsError::Processing {
+                message: "the end of an availability window must be after its start".to_string(),
+            });
+        }
+        if !(MIN_MERGED_INTERVAL_MINUTES..=MAX_MERGED_INTERVAL_MINUTES).contains(&interval_minutes) {
+            return Err(EwsError::Processing {
+                message: format!(
+                    "merged free/busy interval must be between {MIN_MERGED_INTERVAL_MINUTES} and {MAX_MERGED_INTERVAL_MINUTES} minutes, got {interval_minutes}"
+                ),
+            });
+        }
+
+        let body = user_availability_request(attendees, window, interval_minutes, meeting_duration_minutes)?;
+
+        let response = self
+            .make_raw_operation_request("GetUserAvailability", &body, OperationRequestOptions::default())
+            .await?;
+
+        let free_busy_responses: Vec<&XmlElement> = response
+            .child("FreeBusyResponseArray")
+            .map(|array| array.children_named("FreeBusyResponse").collect())
+            .unwrap_or_default();
+
+        if free_busy_responses.len() != attendees.len() {
+            return Err(EwsError::UnexpectedResponseMessageCount {
+                expected: attendees.len(),
+                actual: free_busy_responses.len(),
+            });
+        }
+
+        let attendees = attendees
+            .iter()
+            .zip(free_busy_responses)
+            .map(|(attendee, free_busy_response)| read_attendee_availability(attendee, free_busy_response))
+            .collect();
+
+        let suggestions = match response.child("SuggestionsResponse") {
+            Some(suggestions_response) if meeting_duration_minutes.is_some() => read_suggestions(suggestions_response)?,
+            _ => Vec::new(),
+        };
+
+        Ok(UserAvailabilityResult { attendees, suggestions })
+    }
+}
+
+/// Reads the availability of an attendee from a `FreeBusyResponse` element.
+fn read_attendee_availability(attendee: &Attendee, free_busy_response: &XmlElement) -> AttendeeAvailability {
+    let mut availability = AttendeeAvailability {
+        email: attendee.email.clone(),
+        merged_free_busy: None,
+        events: Vec::new(),
+        error: None,
+    };
+
+    if let Some(message) = free_busy_response.child("ResponseMessage")
+        && let Err(err) = process_raw_response_message("GetUserAvailability", message)
+    {
+        log::warn!("could not read availability of {}: {err}", attendee.email);
+        availability.error = Some(message.child_text("ResponseCode").unwrap_or("Unknown").to_string());
+        return availability;
+    }
+
+    if let Some(view) = free_busy_response.child("FreeBusyView") {
+        availability.merged_free_busy = view
+            .child_text("MergedFreeBusy")
+            .map(|merged| merged.trim().to_string());
+        availability.events = view
+            .child("CalendarEventArray")
+            .map(|events| {
+                events
+                    .children_named("CalendarEvent")
+                    .filter_map(AvailabilityEvent::from_xml)
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    availability
+}
+
+/// Reads the suggested meeting times from a `SuggestionsResponse` element.
+fn read_suggestions(suggestions_response: &XmlElement) -> Result<Vec<MeetingSuggestion>, EwsError> {
+    if let Some(message) = suggestions_response.child("ResponseMessage") {
+        process_raw_response_message("GetUserAvailability", message)?;
+    }
+
+    let mut suggestions: Vec<MeetingSuggestion> = suggestions_response
+        .child("SuggestionDayResultArray")
+        .into_iter()
+        .flat_map(|days| days.children_named("SuggestionDayResult"))
+        .filter_map(|day| day.child("SuggestionArray"))
+        .flat_map(|suggestions| suggestions.children_named("Suggestion"))
+        .filter_map(|suggestion| {
+            Some(MeetingSuggestion {
+                start: suggestion.child_date_time("MeetingTime")?,
+                is_work_time: suggestion.child_bool("IsWorkTime").unwrap_or(false),
+                quality: suggestion
+                    .child_text("SuggestionQuality")
+                    .and_then(SuggestionQuality::parse)?,
+            })
+        })
+        .collect();
+
+    // Best suggestions first, earliest first among equally good ones.
+    suggestions.sort_by(|a, b| b.quality.cmp(&a.quality).then(a.start.cmp(&b.start)));
+
+    Ok(suggestions)
+}
@@ -0,0 +1,1517 @@
+//! Conversion of calendar items to and from iCalendar.
+//!
+//! Calendar items are written as the `VEVENT`s of an iCalendar object (RFC
+//! 5545), with the definitions of their time zones as `VTIMEZONE`s. A
+//! recurring item is written as a master event with its recurrence rule and
+//! deleted occurrences, followed by an event for each modified occurrence,
+//! linked to the master by its UID and `RECURRENCE-ID`.
+
+use std::collections::HashMap;
+
+use time::{Date, Month, OffsetDateTime, Time, Weekday};
+
+use crate::client::{
+    EwsError,
+    operations::{
+        calendar::{
+            Attendee, AttendeeType, CalendarItem, CalendarItemType, LegacyFreeBusyStatus, OccurrenceInfo, ResponseType,
+            time_zones::{DaylightSavingTime, TimeZoneDefinition, TimeZoneTransition},
+        },
+        content_lines::{
+            ContentLine, escape_value, join_lines, parse_content_line, quote_parameter_value, unescape_value,
+            unfold_lines,
+        },
+        recurrence::{DayOfWeek, DayOfWeekIndex, Recurrence, RecurrencePattern, RecurrenceRange},
+    },
+};
+
+/// The number of seconds in a day.
+const SECONDS_PER_DAY: i64 = 24 * 3600;
+
+/// A component of an iCalendar object (e.g. a `VEVENT`), with its properties
+/// and the components it contains.
+struct Component {
+    /// The name of the component, in upper case.
+    name: String,
+    /// The properties of the component.
+    properties: Vec<ContentLine>,
+    /// The components nested in this one (e.g. the `VEVENT`s of a
+    /// `VCALENDAR`).
+    components: Vec<Component>,
+}
+
+impl Component {
+    /// The first property with the given name, if any.
+    fn property(&self, name: &str) -> Option<&ContentLine> {
+        self.properties.iter().find(|property| property.name == name)
+    }
+
+    /// The nested components with the given name.
+    fn components_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Component> + 'a {
+        self.components.iter().filter(move |component| component.name == name)
+    }
+}
+
+/// The value of a date or date-time property, e.g. `20240115`,
+/// `20240115T103000` or `20240115T103000Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateTimeValue {
+    /// A date, as the Unix timestamp of its midnight UTC.
+    Date(i64),
+    /// A local date-time, as a Unix timestamp as if it were UTC.
+    Local(i64),
+    /// A UTC date-time, as a Unix timestamp.
+    Utc(i64),
+}
+
+/// The parts of an iCalendar recurrence rule (`RRULE`) which EWS can
+/// represent.
+struct RecurrenceRule {
+    /// The frequency, e.g. `WEEKLY`.
+    frequency: String,
+    /// The number of periods between occurrences.
+    interval: u32,
+    /// The days of the `BYDAY` part, with their position in the month if any
+    /// (e.g. `-1SU` for the last Sunday).
+    by_day: Vec<(Option<i8>, Weekday)>,
+    /// The days of the `BYMONTHDAY` part.
+    by_month_day: Vec<i8>,
+    /// The months of the `BYMONTH` part.
+    by_month: Vec<u8>,
+    /// The positions of the `BYSETPOS` part.
+    by_set_position: Vec<i8>,
+    /// The number of occurrences, if limited.
+    count: Option<u32>,
+    /// The raw value of the last occurrence, if limited.
+    until: Option<String>,
+}
+
+impl CalendarItem {
+    /// Writes the calendar item as an iCalendar object.
+    ///
+    /// A recurring master is written with its recurrence rule, its deleted
+    /// occurrences, and an event for each of its modified occurrences. The
+    /// master only knows the times of its modified occurrences, so the other
+    /// properties of these events are those of the master; use
+    /// [`write_icalendar`] with the modified occurrences read by
+    /// [`EwsClient::get_calendar_items`](crate::client::EwsClient::get_calendar_items)
+    /// to export all their changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recurrence or the time zone of the item cannot
+    /// be represented, or if one of its times is out of range.
+    pub fn to_icalendar(&self) -> Result<String, EwsError> {
+        write_icalendar(std::slice::from_ref(self))
+    }
+}
+
+/// Writes calendar items as a single iCalendar object (e.g. to publish a
+/// calendar as an `.ics` file).
+///
+/// The time zones of the items are written as `VTIMEZONE`s, and their times
+/// in these time zones. Times of items without a time zone definition are
+/// written in UTC.
+///
+/// # Errors
+///
+/// Returns an error if the recurrence or the time zone of an item cannot be
+/// represented, or if one of its times is out of range.
+pub fn write_icalendar(items: &[CalendarItem]) -> Result<String, EwsError> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//ews-client-core//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    let mut time_zones: Vec<&TimeZoneDefinition> = Vec::new();
+    for time_zone in items.iter().filter_map(|item| item.start_time_zone_definition.as_ref()) {
+        if time_zones.iter().all(|known| known.id != time_zone.id) {
+            time_zones.push(time_zone);
+        }
+    }
+    for time_zone in time_zones {
+        lines.extend(time_zone_lines(time_zone)?);
+    }
+
+    let timestamp = format!(
+        "{}Z",
+        format_date_time_value(OffsetDateTime::now_utc().unix_timestamp())?
+    );
+    for item in items {
+        lines.extend(event_lines(item, &timestamp)?);
+
+        for occurrence in &item.modified_occurrences {
+            let exported = items.iter().any(|other| {
+                other.uid.is_some() && other.uid == item.uid && other.original_start == Some(occurrence.original_start)
+            });
+            if !exported {
+                lines.extend(event_lines(&occurrence_of(item, occurrence), &timestamp)?);
+            }
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(join_lines(&lines))
+}
+
+/// Reads the events of the iCalendar objects in the given text (e.g. an
+/// `.ics` invite or a published calendar) into calendar items.
+///
+/// The items have no EWS ID. A modified occurrence of a recurring event is
+/// returned as an item of type [`CalendarItemType::Exception`], and is also
+/// listed in the modified occurrences of its master. Times in a time zone
+/// which is not defined by a `VTIMEZONE` of the object are read as UTC.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - The text contains no `VCALENDAR`, or a component is not terminated
+/// - A date or time cannot be parsed
+/// - A recurrence rule cannot be represented in EWS
+pub fn parse_icalendar(text: &str) -> Result<Vec<CalendarItem>, EwsError> {
+    let calendars: Vec<Component> = parse_components(text)?
+        .into_iter()
+        .filter(|component| component.name == "VCALENDAR")
+        .collect();
+    if calendars.is_empty() {
+        return Err(EwsError::Processing {
+            message: "no VCALENDAR found".to_string(),
+        });
+    }
+
+    let mut items = Vec::new();
+    for calendar in &calendars {
+        let time_zones: HashMap<String, TimeZoneDefinition> = calendar
+            .components_named("VTIMEZONE")
+            .filter_map(time_zone_from_component)
+            .map(|time_zone| (time_zone.id.clone(), time_zone))
+            .collect();
+
+        for event in calendar.components_named("VEVENT") {
+            items.push(item_from_event(event, &time_zones)?);
+        }
+    }
+
+    link_exceptions(&mut items);
+
+    Ok(items)
+}
+
+/// Splits the text of iCalendar objects into their components.
+fn parse_components(text: &str) -> Result<Vec<Component>, EwsError> {
+    let mut components = Vec::new();
+    let mut open: Vec<Component> = Vec::new();
+
+    for line in unfold_lines(text) {
+        let Some(line) = parse_content_line(&line) else {
+            continue;
+        };
+
+        match line.name.as_str() {
+            "BEGIN" => open.push(Component {
+                name: line.value.trim().to_ascii_uppercase(),
+                properties: Vec::new(),
+                components: Vec::new(),
+            }),
+            "END" => {
+                let name = line.value.trim().to_ascii_uppercase();
+                let component =
+                    open.pop()
+                        .filter(|component| component.name == name)
+                        .ok_or_else(|| EwsError::Processing {
+                            message: format!("unexpected END:{name}"),
+                        })?;
+                match open.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => components.push(component),
+                }
+            }
+            _ => {
+                if let Some(component) = open.last_mut() {
+                    component.properties.push(line);
+                }
+            }
+        }
+    }
+
+    if let Some(component) = open.last() {
+        return Err(EwsError::Processing {
+            message: format!("{0} is not terminated by END:{0}", component.name),
+        });
+    }
+
+    Ok(components)
+}
+
+/// Writes the `VEVENT` of a calendar item.
+fn event_lines(item: &CalendarItem, timestamp: &str) -> Result<Vec<String>, EwsError> {
+    let time_zone = item.start_time_zone_definition.as_ref();
+    // The end is only written in the time zone of the start if both agree.
+    let end_time_zone =
+        time_zone.filter(|_| item.end_time_zone.is_none() || item.end_time_zone == item.start_time_zone);
+    let uid = item.uid.as_deref().unwrap_or(&item.item_id);
+
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", escape_value(uid)),
+        format!("DTSTAMP:{timestamp}"),
+    ];
+
+    if item.calendar_item_type == Some(CalendarItemType::Exception)
+        && let Some(original_start) = item.original_start
+    {
+        lines.push(date_time_line(
+            "RECURRENCE-ID",
+            original_start,
+            time_zone,
+            item.is_all_day_event,
+        )?);
+    }
+    if let Some(start) = item.start {
+        lines.push(date_time_line("DTSTART", start, time_zone, item.is_all_day_event)?);
+    }
+    if let Some(end) = item.end {
+        lines.push(date_time_line("DTEND", end, end_time_zone, item.is_all_day_event)?);
+    }
+    if let Some(recurrence) = &item.recurrence {
+        lines.push(rrule_line(recurrence, time_zone, item.is_all_day_event)?);
+    }
+    for deleted in &item.deleted_occurrences {
+        lines.push(date_time_line("EXDATE", *deleted, time_zone, item.is_all_day_event)?);
+    }
+
+    for (name, value) in [
+        ("SUMMARY", &item.subject),
+        ("DESCRIPTION", &item.body),
+        ("LOCATION", &item.location),
+    ] {
+        if let Some(value) = value {
+            lines.push(format!("{name}:{}", escape_value(value)));
+        }
+    }
+
+    if let Some(organizer) = &item.organizer {
+        lines.push(address_line("ORGANIZER", organizer, false));
+    }
+    for attendee in &item.attendees {
+        lines.push(address_line("ATTENDEE", attendee, true));
+    }
+
+    if let Some(status) = item
+        .legacy_free_busy_status
+        .filter(|status| *status != LegacyFreeBusyStatus::NoData)
+    {
+        let transparency = if status == LegacyFreeBusyStatus::Free {
+            "TRANSPARENT"
+        } else {
+            "OPAQUE"
+        };
+        lines.push(format!("TRANSP:{transparency}"));
+        lines.push(format!(
+            "X-MICROSOFT-CDO-BUSYSTATUS:{}",
+            status.as_str().to_ascii_uppercase()
+        ));
+    }
+    if item.is_cancelled {
+        lines.push("STATUS:CANCELLED".to_string());
+    }
+
+    lines.push("END:VEVENT".to_string());
+
+    Ok(lines)
+}
+
+/// A modified occurrence of a recurring master, with the properties of the
+/// master.
+fn occurrence_of(master: &CalendarItem, occurrence: &OccurrenceInfo) -> CalendarItem {
+    CalendarItem {
+        item_id: occurrence.item_id.clone(),
+        start: Some(occurrence.start),
+        end: Some(occurrence.end),
+        original_start: Some(occurrence.original_start),
+        calendar_item_type: Some(CalendarItemType::Exception),
+        uid: Some(master.uid.clone().unwrap_or_else(|| master.item_id.clone())),
+        recurrence: None,
+        modified_occurrences: Vec::new(),
+        deleted_occurrences: Vec::new(),
+        ..master.clone()
+    }
+}
+
+/// Reads a calendar item from a `VEVENT`.
+fn item_from_event(
+    event: &Component,
+    time_zones: &HashMap<String, TimeZoneDefinition>,
+) -> Result<CalendarItem, EwsError> {
+    let mut item = CalendarItem::default();
+    let mut start = None;
+    let mut duration = None;
+    let mut rule = None;
+    let mut transparent = None;
+
+    for property in &event.properties {
+        let time_zone = property_time_zone(property, time_zones);
+
+        match property.name.as_str() {
+            "UID" => item.uid = text_value(property),
+            "SUMMARY" => item.subject = text_value(property),
+            "DESCRIPTION" => item.body = text_value(property),
+            "LOCATION" => item.location = text_value(property),
+            "DTSTART" => {
+                let value = date_time_value(property)?;
+                item.is_all_day_event = matches!(value, DateTimeValue::Date(_));
+                item.start = Some(to_timestamp(value, time_zone));
+                item.start_time_zone = time_zone.map(|time_zone| time_zone.id.clone());
+                item.start_time_zone_definition = time_zone.cloned();
+                start = Some(value);
+            }
+            "DTEND" => {
+                item.end = Some(to_timestamp(date_time_value(property)?, time_zone));
+                item.end_time_zone = time_zone.map(|time_zone| time_zone.id.clone());
+            }
+            "DURATION" => duration = Some(parse_duration(&property.value).ok_or_else(|| invalid_value(property))?),
+            "RECURRENCE-ID" => item.original_start = Some(to_timestamp(date_time_value(property)?, time_zone)),
+            "RRULE" => rule = Some(property.value.as_str()),
+            "EXDATE" => {
+                for value in property.value.split(',') {
+                    let value = parse_date_time_value(value).ok_or_else(|| invalid_value(property))?;
+                    item.deleted_occurrences.push(to_timestamp(value, time_zone));
+                }
+            }
+            "ORGANIZER" => {
+                item.organizer = attendee_from_property(property).map(|organizer| Attendee {
+                    attendee_type: AttendeeType::Required,
+                    response_type: Some(ResponseType::Organizer),
+                    ..organizer
+                });
+            }
+            "ATTENDEE" => item.attendees.extend(attendee_from_property(property)),
+            "TRANSP" => transparent = Some(property.value.trim().eq_ignore_ascii_case("TRANSPARENT")),
+            "X-MICROSOFT-CDO-BUSYSTATUS" => item.legacy_free_busy_status = busy_status(&property.value),
+            "STATUS" => item.is_cancelled = property.value.trim().eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    // Without an end, an event lasts for its duration, or the whole day of
+    // its start for a date.
+    if item.end.is_none()
+        && let Some(start) = item.start
+    {
+        let default_duration = if item.is_all_day_event { SECONDS_PER_DAY } else { 0 };
+        item.end = Some(start + duration.unwrap_or(default_duration));
+    }
+
+    if let (Some(rule), Some(start)) = (rule, start) {
+        let time_zone = item.start_time_zone_definition.as_ref();
+        let local_start = match start {
+            DateTimeValue::Date(value) | DateTimeValue::Local(value) => value,
+            DateTimeValue::Utc(value) => time_zone.map_or(value, |time_zone| time_zone.utc_to_local(value)),
+        };
+        item.recurrence = Some(recurrence_from_rule(rule, local_start, time_zone)?);
+    }
+
+    item.calendar_item_type = Some(if item.original_start.is_some() {
+        CalendarItemType::Exception
+    } else if item.recurrence.is_some() {
+        CalendarItemType::RecurringMaster
+    } else {
+        CalendarItemType::Single
+    });
+    item.is_meeting = !item.attendees.is_empty();
+    if item.legacy_free_busy_status.is_none() {
+        item.legacy_free_busy_status = transparent.map(|transparent| {
+            if transparent {
+                LegacyFreeBusyStatus::Free
+            } else {
+                LegacyFreeBusyStatus::Busy
+            }
+        });
+    }
+
+    Ok(item)
+}
+
+/// Lists the exceptions read from an iCalendar object in the modified
+/// occurrences of their masters.
+fn link_exceptions(items: &mut [CalendarItem]) {
+    let exceptions: Vec<(String, OccurrenceInfo)> = items
+        .iter()
+        .filter_map(|item| {
+            let original_start = item.original_start?;
+            Some((
+                item.uid.clone()?,
+                OccurrenceInfo {
+                    item_id: item.item_id.clone(),
+                    start: item.start.unwrap_or(original_start),
+                    end: item.end.unwrap_or(original_start),
+                    original_start,
+                },
+            ))
+        })
+        .collect();
+
+    for (uid, occurrence) in exceptions {
+        if let Some(master) = items
+            .iter_mut()
+            .find(|item| item.recurrence.is_some() && item.uid.as_deref() == Some(uid.as_str()))
+        {
+            master.modified_occurrences.push(occurrence);
+        }
+    }
+}
+
+/// Writes the `ORGANIZER` or an `ATTENDEE` property of an event.
+fn address_line(name: &str, attendee: &Attendee, with_participation: bool) -> String {
+    let mut line = name.to_string();
+
+    if let Some(common_name) = &attendee.name {
+        line.push_str(&format!(";CN={}", quote_parameter_value(common_name)));
+    }
+    if with_participation {
+        let (user_type, role) = match attendee.attendee_type {
+            AttendeeType::Required => ("INDIVIDUAL", "REQ-PARTICIPANT"),
+            AttendeeType::Optional => ("INDIVIDUAL", "OPT-PARTICIPANT"),
+            AttendeeType::Resource => ("RESOURCE", "NON-PARTICIPANT"),
+        };
+        line.push_str(&format!(";CUTYPE={user_type};ROLE={role}"));
+
+        let status = match attendee.response_type {
+            Some(ResponseType::Accept) => Some("ACCEPTED"),
+            Some(ResponseType::Decline) => Some("DECLINED"),
+            Some(ResponseType::Tentative) => Some("TENTATIVE"),
+            Some(ResponseType::NoResponseReceived) => Some("NEEDS-ACTION"),
+            Some(ResponseType::Organizer | ResponseType::Unknown) | None => None,
+        };
+        if let Some(status) = status {
+            line.push_str(&format!(";PARTSTAT={status}"));
+        }
+    }
+
+    line.push_str(":mailto:");
+    line.push_str(&attendee.email);
+    line
+}
+
+/// Reads an attendee from an `ORGANIZER` or `ATTENDEE` property.
+fn attendee_from_property(property: &ContentLine) -> Option<Attendee> {
+    let address = property.value.trim();
+    let email = address
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .and_then(|_| address.get(7..))
+        .unwrap_or(address)
+        .trim();
+    if email.is_empty() {
+        return None;
+    }
+
+    let parameter = |name: &str| property.parameter(name).map(str::to_ascii_uppercase);
+    let role = parameter("ROLE");
+    let attendee_type = if matches!(parameter("CUTYPE").as_deref(), Some("RESOURCE" | "ROOM"))
+        || role.as_deref() == Some("NON-PARTICIPANT")
+    {
+        AttendeeType::Resource
+    } else if role.as_deref() == Some("OPT-PARTICIPANT") {
+        AttendeeType::Optional
+    } else {
+        AttendeeType::Required
+    };
+    let response_type = match parameter("PARTSTAT").as_deref() {
+        Some("ACCEPTED") => Some(ResponseType::Accept),
+        Some("DECLINED") => Some(ResponseType::Decline),
+        Some("TENTATIVE") => Some(ResponseType::Tentative),
+        Some("NEEDS-ACTION") => Some(ResponseType::NoResponseReceived),
+        _ => None,
+    };
+
+    Some(Attendee {
+        email: email.to_string(),
+        name: property.parameter("CN").map(ToString::to_string),
+        attendee_type,
+        response_type,
+    })
+}
+
+/// Parses the value of an `X-MICROSOFT-CDO-BUSYSTATUS` property.
+fn busy_status(value: &str) -> Option<LegacyFreeBusyStatus> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "FREE" => Some(LegacyFreeBusyStatus::Free),
+        "TENTATIVE" => Some(LegacyFreeBusyStatus::Tentative),
+        "BUSY" => Some(LegacyFreeBusyStatus::Busy),
+        "OOF" => Some(LegacyFreeBusyStatus::OutOfOffice),
+        "WORKINGELSEWHERE" => Some(LegacyFreeBusyStatus::WorkingElsewhere),
+        _ => None,
+    }
+}
+
+/// Writes the `RRULE` property of a recurrence.
+fn rrule_line(
+    recurrence: &Recurrence,
+    time_zone: Option<&TimeZoneDefinition>,
+    is_all_day_event: bool,
+) -> Result<String, EwsError> {
+    // Reject the patterns which EWS would reject.
+    recurrence.to_xml()?;
+
+    let relative = |day_of_week: DayOfWeek, day_of_week_index: DayOfWeekIndex| {
+        format!(
+            "BYDAY={};BYSETPOS={}",
+            weekday_codes(day_of_week.weekdays()),
+            day_of_week_index.position()
+        )
+    };
+
+    let mut rule = match &recurrence.pattern {
+        RecurrencePattern::Daily { interval } => format!("FREQ=DAILY;INTERVAL={interval}"),
+        RecurrencePattern::Weekly { interval, days_of_week } => {
+            let weekdays: Vec<Weekday> = days_of_week
+                .iter()
+                .flat_map(|day| day.weekdays().iter().copied())
+                .collect();
+            format!("FREQ=WEEKLY;INTERVAL={interval};BYDAY={}", weekday_codes(&weekdays))
+        }
+        RecurrencePattern::AbsoluteMonthly { interval, day_of_month } => {
+            format!("FREQ=MONTHLY;INTERVAL={interval};BYMONTHDAY={day_of_month}")
+        }
+        RecurrencePattern::RelativeMonthly {
+            interval,
+            day_of_week,
+            day_of_week_index,
+        } => format!(
+            "FREQ=MONTHLY;INTERVAL={interval};{}",
+            relative(*day_of_week, *day_of_week_index)
+        ),
+        RecurrencePattern::AbsoluteYearly { month, day_of_month } => {
+            format!("FREQ=YEARLY;BYMONTH={month};BYMONTHDAY={day_of_month}")
+        }
+        RecurrencePattern::RelativeYearly {
+            month,
+            day_of_week,
+            day_of_week_index,
+        } => format!(
+            "FREQ=YEARLY;BYMONTH={month};{}",
+            relative(*day_of_week, *day_of_week_index)
+        ),
+        RecurrencePattern::Regenerating { .. } => {
+            return Err(EwsError::Processing {
+                message: "a regenerating recurrence cannot be written as iCalendar".to_string(),
+            });
+        }
+    };
+
+    match recurrence.range {
+        RecurrenceRange::NoEnd { .. } => {}
+        RecurrenceRange::Numbered { occurrences, .. } => rule.push_str(&format!(";COUNT={occurrences}")),
+        RecurrenceRange::EndDate { end_date, .. } => {
+            // The end date is inclusive, and must have the type of the start:
+            // a date for all-day events, and a UTC date-time otherwise.
+            let until = if is_all_day_event {
+                format_date_value(end_date)?
+            } else {
+                let end_of_day = end_date - end_date.rem_euclid(SECONDS_PER_DAY) + SECONDS_PER_DAY - 1;
+                let until = time_zone.map_or(end_of_day, |time_zone| time_zone.local_to_utc(end_of_day));
+                format!("{}Z", format_date_time_value(until)?)
+            };
+            rule.push_str(&format!(";UNTIL={until}"));
+        }
+    }
+
+    Ok(format!("RRULE:{rule}"))
+}
+
+/// Reads the recurrence of an event from its `RRULE`, given the local time of
+/// its start.
+fn recurrence_from_rule(
+    value: &str,
+    local_start: i64,
+    time_zone: Option<&TimeZoneDefinition>,
+) -> Result<Recurrence, EwsError> {
+    let unsupported = || EwsError::Processing {
+        message: format!("unsupported recurrence rule {value}"),
+    };
+    let rule = RecurrenceRule::parse(value).ok_or_else(unsupported)?;
+    let start_date = OffsetDateTime::from_unix_timestamp(local_start)
+        .map_err(|_| unsupported())?
+        .date();
+    let start_day = start_date.midnight().assume_utc().unix_timestamp();
+
+    let interval = rule.interval;
+    let day_of_month = || match rule.by_month_day.as_slice() {
+        [] => Ok(start_date.day()),
+        [day] => u8::try_from(*day).ok().filter(|day| *day >= 1).ok_or_else(unsupported),
+        _ => Err(unsupported()),
+    };
+    let relative_day = || rule.relative_day().ok_or_else(unsupported);
+    let weekly = |interval| {
+        if rule.by_day.iter().any(|(position, _)| position.is_some()) {
+            return Err(unsupported());
+        }
+        let days_of_week = if rule.by_day.is_empty() {
+            vec![DayOfWeek::from_weekday(start_date.weekday())]
+        } else {
+            rule.by_day
+                .iter()
+                .map(|(_, weekday)| DayOfWeek::from_weekday(*weekday))
+                .collect()
+        };
+        Ok(RecurrencePattern::Weekly { interval, days_of_week })
+    };
+
+    let pattern = match rule.frequency.as_str() {
+        "DAILY" if rule.by_day.is_empty() => RecurrencePattern::Daily { interval },
+        // e.g. every weekday
+        "DAILY" if interval == 1 => weekly(1)?,
+        "WEEKLY" => weekly(interval)?,
+        "MONTHLY" if rule.by_day.is_empty() => RecurrencePattern::AbsoluteMonthly {
+            interval,
+            day_of_month: day_of_month()?,
+        },
+        "MONTHLY" => {
+            let (day_of_week, day_of_week_index) = relative_day()?;
+            RecurrencePattern::RelativeMonthly {
+                interval,
+                day_of_week,
+                day_of_week_index,
+            }
+        }
+        "YEARLY" if interval == 1 => {
+            let month = match rule.by_month.as_slice() {
+                [] => u8::from(start_date.month()),
+                [month] => *month,
+                _ => return Err(unsupported()),
+            };
+            if rule.by_day.is_empty() {
+                RecurrencePattern::AbsoluteYearly {
+                    month,
+                    day_of_month: day_of_month()?,
+                }
+            } else {
+                let (day_of_week, day_of_week_index) = relative_day()?;
+                RecurrencePattern::RelativeYearly {
+                    month,
+                    day_of_week,
+                    day_of_week_index,
+                }
+            }
+        }
+        _ => return Err(unsupported()),
+    };
+
+    let range = match (rule.count, &rule.until) {
+        (Some(occurrences), _) => RecurrenceRange::Numbered {
+            start_date: start_day,
+            occurrences,
+        },
+        (None, Some(until)) => {
+            let until = match parse_date_time_value(until).ok_or_else(unsupported)? {
+                DateTimeValue::Date(value) | DateTimeValue::Local(value) => value,
+                DateTimeValue::Utc(value) => time_zone.map_or(value, |time_zone| time_zone.utc_to_local(value)),
+            };
+            RecurrenceRange::EndDate {
+                start_date: start_day,
+                end_date: until - until.rem_euclid(SECONDS_PER_DAY),
+            }
+        }
+        (None, None) => RecurrenceRange::NoEnd { start_date: start_day },
+    };
+
+    Ok(Recurrence { pattern, range })
+}
+
+impl RecurrenceRule {
+    /// Parses the value of an `RRULE` property. Returns `None` if a part is
+    /// malformed.
+    fn parse(value: &str) -> Option<Self> {
+        let mut rule = Self {
+            frequency: String::new(),
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_position: Vec::new(),
+            count: None,
+            until: None,
+        };
+
+        for part in value.trim().split(';') {
+            let (name, value) = part.split_once('=')?;
+            let values = || value.split(',').map(str::trim);
+
+            match name.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => rule.frequency = value.trim().to_ascii_uppercase(),
+                "INTERVAL" => rule.interval = value.trim().parse().ok()?,
+                "COUNT" => rule.count = Some(value.trim().parse().ok()?),
+                "UNTIL" => rule.until = Some(value.trim().to_string()),
+                "BYDAY" => rule.by_day = values().map(parse_weekday_position).collect::<Option<_>>()?,
+                "BYMONTHDAY" => rule.by_month_day = values().map(|day| day.parse().ok()).collect::<Option<_>>()?,
+                "BYMONTH" => rule.by_month = values().map(|month| month.parse().ok()).collect::<Option<_>>()?,
+                "BYSETPOS" => {
+                    rule.by_set_position = values().map(|position| position.parse().ok()).collect::<Option<_>>()?;
+                }
+                // e.g. the first day of the week, which EWS does not need.
+                _ => {}
+            }
+        }
+
+        (!rule.frequency.is_empty() && rule.interval >= 1).then_some(rule)
+    }
+
+    /// The day of a relative monthly or yearly rule, given either as a
+    /// position and a day (e.g. `BYDAY=-1SU` for the last Sunday), or as a
+    /// set of days and a position (e.g. `BYDAY=MO,TU,WE,TH,FR;BYSETPOS=1` for
+    /// the first weekday).
+    fn relative_day(&self) -> Option<(DayOfWeek, DayOfWeekIndex)> {
+        match (self.by_day.as_slice(), self.by_set_position.as_slice()) {
+            ([(Some(position), weekday)], []) => Some((
+                DayOfWeek::from_weekday(*weekday),
+                DayOfWeekIndex::from_position(*position)?,
+            )),
+            (days, [position]) if days.iter().all(|(position, _)| position.is_none()) => {
+                let weekdays: Vec<Weekday> = days.iter().map(|(_, weekday)| *weekday).collect();
+                Some((day_of_week_of(&weekdays)?, DayOfWeekIndex::from_position(*position)?))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The day of the week (or set of days) of a relative rule standing for the
+/// given days.
+fn day_of_week_of(weekdays: &[Weekday]) -> Option<DayOfWeek> {
+    if let [weekday] = weekdays {
+        return Some(DayOfWeek::from_weekday(*weekday));
+    }
+
+    [DayOfWeek::Day, DayOfWeek::Weekday, DayOfWeek::WeekendDay]
+        .into_iter()
+        .find(|day| {
+            day.weekdays().len() == weekdays.len() && weekdays.iter().all(|weekday| day.weekdays().contains(weekday))
+        })
+}
+
+/// Writes a list of days of the week, e.g. `MO,WE,FR`.
+fn weekday_codes(weekdays: &[Weekday]) -> String {
+    weekdays
+        .iter()
+        .map(|weekday| weekday_code(*weekday))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The two-letter code of a day of the week.
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Sunday => "SU",
+        Weekday::Monday => "MO",
+        Weekday::Tuesday => "TU",
+        Weekday::Wednesday => "WE",
+        Weekday::Thursday => "TH",
+        Weekday::Friday => "FR",
+        Weekday::Saturday => "SA",
+    }
+}
+
+/// Parses a day of a `BYDAY` part, with its position if any (e.g. `-1SU`).
+fn parse_weekday_position(value: &str) -> Option<(Option<i8>, Weekday)> {
+    let split = value.len().checked_sub(2)?;
+    let (position, code) = (value.get(..split)?, value.get(split..)?);
+
+    let weekday = match code.to_ascii_uppercase().as_str() {
+        "SU" => Weekday::Sunday,
+        "MO" => Weekday::Monday,
+        "TU" => Weekday::Tuesday,
+        "WE" => Weekday::Wednesday,
+        "TH" => Weekday::Thursday,
+        "FR" => Weekday::Friday,
+        "SA" => Weekday::Saturday,
+        _ => return None,
+    };
+    let position = match position.trim_start_matches('+') {
+        "" => None,
+        position => Some(position.parse().ok()?),
+    };
+
+    Some((position, weekday))
+}
+
+/// Writes the `VTIMEZONE` of a time zone.
+fn time_zone_lines(time_zone: &TimeZoneDefinition) -> Result<Vec<String>, EwsError> {
+    let mut lines = vec![
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", escape_value(&time_zone.id)),
+    ];
+
+    match &time_zone.daylight {
+        Some(daylight) => {
+            lines.extend(observance_lines(
+                "STANDARD",
+                &daylight.end,
+                daylight.offset,
+                time_zone.standard_offset,
+            )?);
+            lines.extend(observance_lines(
+                "DAYLIGHT",
+                &daylight.start,
+                time_zone.standard_offset,
+                daylight.offset,
+            )?);
+        }
+        None => {
+            let offset = format_utc_offset(time_zone.standard_offset);
+            lines.extend([
+                "BEGIN:STANDARD".to_string(),
+                "DTSTART:19700101T000000".to_string(),
+                format!("TZOFFSETFROM:{offset}"),
+                format!("TZOFFSETTO:{offset}"),
+                "END:STANDARD".to_string(),
+            ]);
+        }
+    }
+
+    lines.push("END:VTIMEZONE".to_string());
+
+    Ok(lines)
+}
+
+/// Writes a `STANDARD` or `DAYLIGHT` observance of a `VTIMEZONE`, starting at
+/// the given yearly transition.
+fn observance_lines(
+    name: &str,
+    transition: &TimeZoneTransition,
+    offset_from: i32,
+    offset_to: i32,
+) -> Result<Vec<String>, EwsError> {
+    transition.validate()?;
+    // The first onset of the observance is its transition in 1970.
+    let (Some(date), Some(weekday)) = (transition.date(1970), transition.day_of_week.weekday()) else {
+        return Err(EwsError::Processing {
+            message: format!("time zone transition {transition:?} cannot be written as iCalendar"),
+        });
+    };
+
+    Ok(vec![
+        format!("BEGIN:{name}"),
+        format!(
+            "DTSTART:{:04}{:02}{:02}T{:02}{:02}00",
+            date.year(),
+            u8::from(date.month()),
+            date.day(),
+            transition.time / 60,
+            transition.time % 60
+        ),
+        format!("TZOFFSETFROM:{}", format_utc_offset(offset_from)),
+        format!("TZOFFSETTO:{}", format_utc_offset(offset_to)),
+        format!(
+            "RRULE:FREQ=YEARLY;BYMONTH={};BYDAY={}{}",
+            transition.month,
+            transition.day_of_week_index.position(),
+            weekday_code(weekday)
+        ),
+        format!("END:{name}"),
+    ])
+}
+
+/// Reads a time zone from a `VTIMEZONE`. Returns `None` if it has no standard
+/// time observance.
+///
+/// The time zone observes daylight saving time only if its latest standard
+/// and daylight observances both recur yearly on a day of a week of a month.
+fn time_zone_from_component(component: &Component) -> Option<TimeZoneDefinition> {
+    let id = unescape_value(&component.property("TZID")?.value);
+
+    // The observances in effect today are the last ones to start.
+    let latest = |name| {
+        component.components_named(name).max_by_key(|observance| {
+            observance
+                .property("DTSTART")
+                .map(|start| start.value.clone())
+                .unwrap_or_default()
+        })
+    };
+    let offset_to = |observance: &Component| {
+        observance
+            .property("TZOFFSETTO")
+            .and_then(|offset| parse_utc_offset(&offset.value))
+    };
+
+    let standard = latest("STANDARD")?;
+    let daylight = latest("DAYLIGHT").and_then(|daylight| {
+        Some(DaylightSavingTime {
+            offset: offset_to(daylight)?,
+            start: transition_from_observance(daylight)?,
+            end: transition_from_observance(standard)?,
+        })
+    });
+
+    Some(TimeZoneDefinition {
+        name: None,
+        standard_offset: offset_to(standard)?,
+        daylight,
+        id,
+    })
+}
+
+/// Reads the yearly transition starting an observance of a `VTIMEZONE`.
+fn transition_from_observance(observance: &Component) -> Option<TimeZoneTransition> {
+    let start = match parse_date_time_value(&observance.property("DTSTART")?.value)? {
+        DateTimeValue::Date(value) | DateTimeValue::Local(value) | DateTimeValue::Utc(value) => value,
+    };
+    let rule = RecurrenceRule::parse(&observance.property("RRULE")?.value)?;
+    if rule.frequency != "YEARLY" {
+        return None;
+    }
+
+    let (day_of_week, day_of_week_index) = rule.relative_day()?;
+    day_of_week.weekday()?;
+
+    Some(TimeZoneTransition {
+        month: match rule.by_month.as_slice() {
+            [month] => *month,
+            _ => return None,
+        },
+        day_of_week,
+        day_of_week_index,
+        time: u16::try_from(start.rem_euclid(SECONDS_PER_DAY) / 60).ok()?,
+    })
+}
+
+/// The time zone of a date-time property, from its `TZID` parameter.
+fn property_time_zone<'a>(
+    property: &ContentLine,
+    time_zones: &'a HashMap<String, TimeZoneDefinition>,
+) -> Option<&'a TimeZoneDefinition> {
+    let id = property.parameter("TZID")?;
+    let time_zone = time_zones.get(id);
+    if time_zone.is_none() {
+        log::debug!("time zone {id} has no VTIMEZONE, reading its times as UTC");
+    }
+    time_zone
+}
+
+/// Writes a date-time property, in the given time zone or else in UTC, or as
+/// a date for all-day events.
+fn date_time_line(
+    name: &str,
+    timestamp: i64,
+    time_zone: Option<&TimeZoneDefinition>,
+    is_all_day_event: bool,
+) -> Result<String, EwsError> {
+    let local = time_zone.map_or(timestamp, |time_zone| time_zone.utc_to_local(timestamp));
+
+    Ok(match time_zone {
+        _ if is_all_day_event => format!("{name};VALUE=DATE:{}", format_date_value(local)?),
+        Some(time_zone) => format!(
+            "{name};TZID={}:{}",
+            quote_parameter_value(&time_zone.id),
+            format_date_time_value(local)?
+        ),
+        None => format!("{name}:{}Z", format_date_time_value(timestamp)?),
+    })
+}
+
+/// Formats the date of a timestamp, e.g. `20240115`.
+fn format_date_value(timestamp: i64) -> Result<String, EwsError> {
+    let date = date_time(timestamp)?;
+    Ok(format!(
+        "{:04}{:02}{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    ))
+}
+
+/// Formats a timestamp as a date-time without offset, e.g. `20240115T103000`.
+fn format_date_time_value(timestamp: i64) -> Result<String, EwsError> {
+    let date_time = date_time(timestamp)?;
+    Ok(format!(
+        "{}T{:02}{:02}{:02}",
+        format_date_value(timestamp)?,
+        date_time.hour(),
+        date_time.minute(),
+        date_time.second()
+    ))
+}
+
+/// Converts a timestamp to a date-time.
+fn date_time(timestamp: i64) -> Result<OffsetDateTime, EwsError> {
+    OffsetDateTime::from_unix_timestamp(timestamp).map_err(|_| EwsError::Processing {
+        message: format!("timestamp {timestamp} cannot be represented as a date"),
+    })
+}
+
+/// Parses the value of a date or date-time property.
+fn parse_date_time_value(value: &str) -> Option<DateTimeValue> {
+    let value = value.trim();
+    let (value, is_utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let (date, time) = match value.split_once(['T', 't']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let number = |text: &str, range: std::ops::Range<usize>| -> Option<u16> {
+        let digits = text.get(range)?;
+        digits
+            .bytes()
+            .all(|byte| byte.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+    if date.len() != 8 {
+        return None;
+    }
+    let date = Date::from_calendar_date(
+        i32::from(number(date, 0..4)?),
+        Month::try_from(u8::try_from(number(date, 4..6)?).ok()?).ok()?,
+        u8::try_from(number(date, 6..8)?).ok()?,
+    )
+    .ok()?;
+
+    let Some(time) = time else {
+        return Some(DateTimeValue::Date(date.midnight().assume_utc().unix_timestamp()));
+    };
+    if time.len() != 6 {
+        return None;
+    }
+    let time = Time::from_hms(
+        u8::try_from(number(time, 0..2)?).ok()?,
+        u8::try_from(number(time, 2..4)?).ok()?,
+        u8::try_from(number(time, 4..6)?).ok()?,
+    )
+    .ok()?;
+
+    let timestamp = date.with_time(time).assume_utc().unix_timestamp();
+    Some(if is_utc {
+        DateTimeValue::Utc(timestamp)
+    } else {
+        DateTimeValue::Local(timestamp)
+    })
+}
+
+/// Parses the value of a date or date-time property, or reports it as
+/// invalid.
+fn date_time_value(property: &ContentLine) -> Result<DateTimeValue, EwsError> {
+    parse_date_time_value(&property.value).ok_or_else(|| invalid_value(property))
+}
+
+/// Converts a date or date-time value to a Unix timestamp, reading local
+/// times in the given time zone, or else as UTC.
+fn to_timestamp(value: DateTimeValue, time_zone: Option<&TimeZoneDefinition>) -> i64 {
+    match value {
+        DateTimeValue::Date(timestamp) | DateTimeValue::Utc(timestamp) => timestamp,
+        DateTimeValue::Local(local) => time_zone.map_or(local, |time_zone| time_zone.local_to_utc(local)),
+    }
+}
+
+/// Parses an iCalendar duration (e.g. `PT1H30M` or `P1D`) into seconds.
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => {}
+            _ => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += amount
+                    * match c {
+                        'W' => 7 * SECONDS_PER_DAY,
+                        'D' => SECONDS_PER_DAY,
+                        'H' => 3600,
+                        'M' => 60,
+                        'S' => 1,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+
+    number.is_empty().then_some(if negative { -seconds } else { seconds })
+}
+
+/// Formats an offset from UTC in minutes, e.g. `+0530`.
+fn format_utc_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.unsigned_abs();
+    format!("{sign}{:02}{:02}", offset / 60, offset % 60)
+}
+
+/// Parses an offset from UTC (e.g. `+0530` or `-083000`) into minutes.
+fn parse_utc_offset(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, value.strip_prefix('+')?),
+    };
+    if !matches!(digits.len(), 4 | 6) || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let hours: i32 = digits.get(0..2)?.parse().ok()?;
+    let minutes: i32 = digits.get(2..4)?.parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// The unescaped value of a text property, if not empty.
+fn text_value(property: &ContentLine) -> Option<String> {
+    Some(unescape_value(&property.value)).filter(|value| !value.trim().is_empty())
+}
+
+/// The error for a property whose value cannot be parsed.
+fn invalid_value(property: &ContentLine) -> EwsError {
+    EwsError::Processing {
+        message: format!("invalid {} value {}", property.name, property.value),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::client::operations::recurrence::RegenerationFrequency;
+
+    /// 2024-01-15T10:30:00Z, 11:30 in Central European Time
+    const START: i64 = 1_705_314_600;
+    const DAY: i64 = SECONDS_PER_DAY;
+
+    fn w_europe() -> TimeZoneDefinition {
+        TimeZoneDefinition {
+            id: "W. Europe Standard Time".to_string(),
+            name: Some("(UTC+01:00) Amsterdam, Berlin".to_string()),
+            standard_offset: 60,
+            daylight: Some(DaylightSavingTime {
+                offset: 120,
+                start: TimeZoneTransition {
+                    month: 3,
+                    day_of_week: DayOfWeek::Sunday,
+                    day_of_week_index: DayOfWeekIndex::Last,
+                    time: 120,
+                },
+                end: TimeZoneTransition {
+                    month: 10,
+                    day_of_week: DayOfWeek::Sunday,
+                    day_of_week_index: DayOfWeekIndex::Last,
+                    time: 180,
+                },
+            }),
+        }
+    }
+
+    /// A weekly meeting with a deleted and a moved occurrence.
+    fn recurring_master() -> CalendarItem {
+        CalendarItem {
+            item_id: "master-1".to_string(),
+            subject: Some("Weekly sync; team".to_string()),
+            start: Some(START),
+            end: Some(START + 3600),
+            location: Some("Room 1".to_string()),
+            organizer: Some(Attendee {
+                name: Some("Doe, Jane".to_string()),
+                response_type: Some(ResponseType::Organizer),
+                ..Attendee::new("jane@example.com", AttendeeType::Required)
+            }),
+            attendees: vec![
+                Attendee {
+                    response_type: Some(ResponseType::Accept),
+                    ..Attendee::new("bob@example.com", AttendeeType::Required)
+                },
+                Attendee::new("room1@example.com", AttendeeType::Resource),
+            ],
+            calendar_item_type: Some(CalendarItemType::RecurringMaster),
+            legacy_free_busy_status: Some(LegacyFreeBusyStatus::Busy),
+            start_time_zone: Some("W. Europe Standard Time".to_string()),
+            end_time_zone: Some("W. Europe Standard Time".to_string()),
+            is_meeting: true,
+            uid: Some("040000008200E00074C5B7101A82E008".to_string()),
+            recurrence: Some(Recurrence {
+                pattern: RecurrencePattern::Weekly {
+                    interval: 1,
+                    days_of_week: vec![DayOfWeek::Monday],
+                },
+                range: RecurrenceRange::EndDate {
+                    start_date: 1_705_276_800,
+                    end_date: 1_705_276_800 + 70 * DAY,
+                },
+            }),
+            modified_occurrences: vec![OccurrenceInfo {
+                item_id: "exception-1".to_string(),
+                start: START + 14 * DAY + 9000,
+                end: START + 14 * DAY + 12_600,
+                original_start: START + 14 * DAY,
+            }],
+            deleted_occurrences: vec![START + 7 * DAY],
+            start_time_zone_definition: Some(w_europe()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_write_icalendar() {
+        let text = recurring_master().to_icalendar().unwrap();
+
+        assert!(text.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(text.ends_with("END:VCALENDAR\r\n"));
+        assert!(text.contains(
+            "BEGIN:VTIMEZONE\r\nTZID:W. Europe Standard Time\r\nBEGIN:STANDARD\r\nDTSTART:19701025T030000\r\n\
+             TZOFFSETFROM:+0200\r\nTZOFFSETTO:+0100\r\nRRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\nEND:STANDARD\r\n"
+        ));
+        assert!(text.contains("DTSTART:19700329T020000\r\nTZOFFSETFROM:+0100\r\nTZOFFSETTO:+0200\r\n"));
+        assert!(text.contains("DTSTART;TZID=W. Europe Standard Time:20240115T113000\r\n"));
+        assert!(text.contains("DTEND;TZID=W. Europe Standard Time:20240115T123000\r\n"));
+        assert!(text.contains("RRULE:FREQ=WEEKLY;INTERVAL=1;BYDAY=MO;UNTIL=20240325T225959Z\r\n"));
+        assert!(text.contains("EXDATE;TZID=W. Europe Standard Time:20240122T113000\r\n"));
+        assert!(text.contains("SUMMARY:Weekly sync\\; team\r\n"));
+        assert!(text.contains("ORGANIZER;CN=\"Doe, Jane\":mailto:jane@example.com\r\n"));
+        assert!(
+            text.contains(
+                "ATTENDEE;CUTYPE=INDIVIDUAL;ROLE=REQ-PARTICIPANT;PARTSTAT=ACCEPTED:mailto:bob@example.com\r\n"
+            )
+        );
+        assert!(text.contains("ATTENDEE;CUTYPE=RESOURCE;ROLE=NON-PARTICIPANT:mailto:room1@example.com\r\n"));
+        assert!(text.contains("TRANSP:OPAQUE\r\nX-MICROSOFT-CDO-BUSYSTATUS:BUSY\r\n"));
+
+        // The modified occurrence follows the master, with its new times
+        assert_eq!(text.matches("BEGIN:VEVENT").count(), 2);
+        assert!(text.contains("RECURRENCE-ID;TZID=W. Europe Standard Time:20240129T113000\r\n"));
+        assert!(text.contains("DTSTART;TZID=W. Europe Standard Time:20240129T140000\r\n"));
+        assert_eq!(text.matches("UID:040000008200E00074C5B7101A82E008\r\n").count(), 2);
+    }
+
+    #[test]
+    fn test_icalendar_round_trip() {
+        let master = recurring_master();
+        let items = parse_icalendar(&master.to_icalendar().unwrap()).unwrap();
+        assert_eq!(items.len(), 2);
+
+        let parsed = &items[0];
+        assert_eq!(parsed.item_id, "");
+        assert_eq!(parsed.uid, master.uid);
+        assert_eq!(parsed.subject, master.subject);
+        assert_eq!(parsed.start, master.start);
+        assert_eq!(parsed.end, master.end);
+        assert_eq!(parsed.calendar_item_type, Some(CalendarItemType::RecurringMaster));
+        assert_eq!(parsed.recurrence, master.recurrence);
+        assert_eq!(parsed.deleted_occurrences, master.deleted_occurrences);
+        assert_eq!(parsed.organizer, master.organizer);
+        assert_eq!(parsed.attendees, master.attendees);
+        assert_eq!(parsed.legacy_free_busy_status, master.legacy_free_busy_status);
+        assert_eq!(parsed.start_time_zone, master.start_time_zone);
+
+        let time_zone = parsed.start_time_zone_definition.as_ref().unwrap();
+        assert_eq!(time_zone.standard_offset, 60);
+        assert_eq!(time_zone.daylight, w_europe().daylight);
+
+        assert_eq!(parsed.modified_occurrences.len(), 1);
+        let occurrence = &parsed.modified_occurrences[0];
+        assert_eq!(occurrence.start, master.modified_occurrences[0].start);
+        assert_eq!(occurrence.end, master.modified_occurrences[0].end);
+        assert_eq!(occurrence.original_start, master.modified_occurrences[0].original_start);
+
+        let exception = &items[1];
+        assert_eq!(exception.calendar_item_type, Some(CalendarItemType::Exception));
+        assert_eq!(exception.original_start, Some(START + 14 * DAY));
+        assert_eq!(exception.recurrence, None);
+    }
+
+    #[test]
+    fn test_parse_outlook_invite() {
+        let text = "BEGIN:VCALENDAR\r\n\
+            METHOD:REQUEST\r\n\
+            PRODID:Microsoft Exchange Server 2010\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VTIMEZONE\r\n\
+            TZID:Pacific Standard Time\r\n\
+            BEGIN:STANDARD\r\n\
+            DTSTART:16010101T020000\r\n\
+            TZOFFSETFROM:-0700\r\n\
+            TZOFFSETTO:-0800\r\n\
+            RRULE:FREQ=YEARLY;INTERVAL=1;BYDAY=1SU;BYMONTH=11\r\n\
+            END:STANDARD\r\n\
+            BEGIN:DAYLIGHT\r\n\
+            DTSTART:16010101T020000\r\n\
+            TZOFFSETFROM:-0800\r\n\
+            TZOFFSETTO:-0700\r\n\
+            RRULE:FREQ=YEARLY;INTERVAL=1;BYDAY=2SU;BYMONTH=3\r\n\
+            END:DAYLIGHT\r\n\
+            END:VTIMEZONE\r\n\
+            BEGIN:VEVENT\r\n\
+            ORGANIZER;CN=\"Doe, Jane\":mailto:jane@example.com\r\n\
+            ATTENDEE;ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE;CN=Bob:mailto:bob@example.com\r\n\
+            ATTENDEE;ROLE=OPT-PARTICIPANT;PARTSTAT=TENTATIVE;CN=Carol:MAILTO:carol@example.com\r\n\
+            ATTENDEE;CUTYPE=RESOURCE;ROLE=NON-PARTICIPANT;CN=Room 1:mailto:room1@example.com\r\n\
+            DESCRIPTION;LANGUAGE=en-US:Quarterly review\\nBring numbers\r\n\
+            RRULE:FREQ=MONTHLY;INTERVAL=3;BYDAY=2TU;COUNT=4\r\n\
+            SUMMARY;LANGUAGE=en-US:Review\r\n\
+            DTSTART;TZID=Pacific Standard Time:20240709T090000\r\n\
+            DTEND;TZID=Pacific Standard Time:20240709T100000\r\n\
+            UID:040000008200E00074C5B7101A82E00800000000\r\n \
+            A1B2C3\r\n\
+            LOCATION:Room 1\r\n\
+            X-MICROSOFT-CDO-BUSYSTATUS:TENTATIVE\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let items = parse_icalendar(text).unwrap();
+        assert_eq!(items.len(), 1);
+
+        let item = &items[0];
+        assert_eq!(
+            item.uid.as_deref(),
+            Some("040000008200E00074C5B7101A82E00800000000A1B2C3")
+        );
+        assert_eq!(item.subject.as_deref(), Some("Review"));
+        assert_eq!(item.body.as_deref(), Some("Quarterly review\nBring numbers"));
+        // 09:00 PDT is 16:00Z
+        assert_eq!(item.start, Some(1_720_540_800));
+        assert_eq!(item.end, Some(1_720_544_400));
+        assert_eq!(item.legacy_free_busy_status, Some(LegacyFreeBusyStatus::Tentative));
+        assert!(item.is_meeting);
+
+        let organizer = item.organizer.as_ref().unwrap();
+        assert_eq!(organizer.email, "jane@example.com");
+        assert_eq!(organizer.name.as_deref(), Some("Doe, Jane"));
+
+        let attendees: Vec<(&str, AttendeeType, Option<ResponseType>)> = item
+            .attendees
+            .iter()
+            .map(|attendee| (attendee.email.as_str(), attendee.attendee_type, attendee.response_type))
+            .collect();
+        assert_eq!(
+            attendees,
+            vec![
+                (
+                    "bob@example.com",
+                    AttendeeType::Required,
+                    Some(ResponseType::NoResponseReceived)
+                ),
+                (
+                    "carol@example.com",
+                    AttendeeType::Optional,
+                    Some(ResponseType::Tentative)
+                ),
+                ("room1@example.com", AttendeeType::Resource, None),
+            ]
+        );
+
+        assert_eq!(
+            item.recurrence,
+            Some(Recurrence {
+                pattern: RecurrencePattern::RelativeMonthly {
+                    interval: 3,
+                    day_of_week: DayOfWeek::Tuesday,
+                    day_of_week_index: DayOfWeekIndex::Second,
+                },
+                range: RecurrenceRange::Numbered {
+                    start_date: 1_720_483_200,
+                    occurrences: 4,
+                },
+            })
+        );
+
+        let time_zone = item.start_time_zone_definition.as_ref().unwrap();
+        assert_eq!(time_zone.id, "Pacific Standard Time");
+        assert_eq!(time_zone.standard_offset, -480);
+        let daylight = time_zone.daylight.unwrap();
+        assert_eq!(daylight.offset, -420);
+        assert_eq!(daylight.start.month, 3);
+        assert_eq!(daylight.start.day_of_week_index, DayOfWeekIndex::Second);
+        assert_eq!(daylight.end.month, 11);
+        assert_eq!(daylight.end.day_of_week_index, DayOfWeekIndex::First);
+        assert_eq!(daylight.end.time, 120);
+    }
+
+    #[test]
+    fn test_parse_recurrence_rules() {
+        let event = |properties: &str| {
+            let text = format!("BEGIN:VCALENDAR\nBEGIN:VEVENT\n{properties}\nEND:VEVENT\nEND:VCALENDAR\n");
+            parse_icalendar(&text).map(|mut items| items.remove(0))
+        };
+
+        // An all-day event without an end lasts one day
+        let item = event("DTSTART;VALUE=DATE:20240101\nRRULE:FREQ=YEARLY").unwrap();
+        assert!(item.is_all_day_event);
+        assert_eq!(item.end, Some(1_704_067_200 + DAY));
+        assert_eq!(
+            item.recurrence.unwrap().pattern,
+            RecurrencePattern::AbsoluteYearly {
+                month: 1,
+                day_of_month: 1
+            }
+        );
+
+        // Every weekday
+        let item = event("DTSTART:20240101T090000Z\nRRULE:FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20240131").unwrap();
+        assert_eq!(
+            item.recurrence.unwrap(),
+            Recurrence {
+                pattern: RecurrencePattern::Weekly {
+                    interval: 1,
+                    days_of_week: vec![
+                        DayOfWeek::Monday,
+                        DayOfWeek::Tuesday,
+                        DayOfWeek::Wednesday,
+                        DayOfWeek::Thursday,
+                        DayOfWeek::Friday,
+                    ],
+                },
+                range: RecurrenceRange::EndDate {
+                    start_date: 1_704_067_200,
+                    end_date: 1_704_067_200 + 30 * DAY,
+                },
+            }
+        );
+
+        // The last weekday of every month
+        let item =
+            event("DTSTART:20240131T090000Z\nDURATION:PT30M\nRRULE:FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1")
+                .unwrap();
+        assert_eq!(item.end, Some(item.start.unwrap() + 1800));
+        assert_eq!(
+            item.recurrence.unwrap().pattern,
+            RecurrencePattern::RelativeMonthly {
+                interval: 1,
+                day_of_week: DayOfWeek::Weekday,
+                day_of_week_index: DayOfWeekIndex::Last,
+            }
+        );
+
+        assert!(event("DTSTART:20240101T090000Z\nRRULE:FREQ=HOURLY").is_err());
+        assert!(event("DTSTART:20240101T090000Z\nRRULE:FREQ=MONTHLY;BYDAY=5FR").is_err());
+        assert!(event("DTSTART:2024-01-01").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_icalendar() {
+        assert!(parse_icalendar("").is_err());
+        assert!(parse_icalendar("BEGIN:VCALENDAR\nBEGIN:VEVENT\nEND:VCALENDAR\n").is_err());
+        assert!(parse_icalendar("BEGIN:VCALENDAR\nBEGIN:VEVENT\n").is_err());
+    }
+
+    #[test]
+    fn test_regenerating_recurrence_is_rejected() {
+        let item = CalendarItem {
+            start: Some(START),
+            recurrence: Some(Recurrence {
+                pattern: RecurrencePattern::Regenerating {
+                    frequency: RegenerationFrequency::Weekly,
+                    interval: 1,
+                },
+                range: RecurrenceRange::NoEnd { start_date: START },
+            }),
+            ..Default::default()
+        };
+        assert!(item.to_icalendar().is_err());
+    }
+}
@@ -0,0 +1,857 @@
+//! Calendar operations.
+//!
+//! This module contains the operations for reading a calendar over a date
+//! range (with recurring meetings expanded into their occurrences), and for
+//! creating, updating and deleting calendar items, including meetings with
+//! attendees. Calendar items can be converted to and from iCalendar.
+
+mod icalendar;
+mod time_zones;
+
+use ews::{
+    BaseItemId, DeleteType, Operation, OperationResponse,
+    delete_item::{DeleteItem, SendMeetingCancellations},
+};
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    operations::{
+        get_items::{BodyType, ItemBaseShape, ItemShape},
+        recurrence::{Recurrence, RecurrencePattern},
+    },
+    process_response_message_class,
+    raw_requests::calendar::{calendar_view_request, create_calendar_item_request, update_calendar_item_request},
+    single_raw_response_or_error, single_response_or_error,
+    xml::XmlElement,
+};
+
+pub use icalendar::{parse_icalendar, write_icalendar};
+pub use time_zones::{DaylightSavingTime, TimeZoneDefinition, TimeZoneTransition};
+
+/// The distinguished ID of the default calendar folder.
+const DEFAULT_CALENDAR_FOLDER: &str = "calendar";
+
+/// The role of an attendee in a meeting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttendeeType {
+    /// An attendee whose presence is required.
+    Required,
+    /// An attendee whose presence is optional.
+    Optional,
+    /// A resource, such as a meeting room or equipment.
+    Resource,
+}
+
+impl AttendeeType {
+    /// The name of the element listing attendees of this type.
+    pub(crate) fn list_element_name(self) -> &'static str {
+        match self {
+            Self::Required => "RequiredAttendees",
+            Self::Optional => "OptionalAttendees",
+            Self::Resource => "Resources",
+        }
+    }
+
+    /// The `FieldURI` of the property listing attendees of this type.
+    pub(crate) fn field_uri(self) -> &'static str {
+        match self {
+            Self::Required => "calendar:RequiredAttendees",
+            Self::Optional => "calendar:OptionalAttendees",
+            Self::Resource => "calendar:Resources",
+        }
+    }
+}
+
+/// The response of an attendee to a meeting request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseType {
+    /// The response is unknown.
+    Unknown,
+    /// The attendee is the organizer of the meeting.
+    Organizer,
+    /// The attendee tentatively accepted the meeting.
+    Tentative,
+    /// The attendee accepted the meeting.
+    Accept,
+    /// The attendee declined the meeting.
+    Decline,
+    /// The attendee has not responded yet.
+    NoResponseReceived,
+}
+
+impl ResponseType {
+    /// The value used for this response type in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::Organizer => "Organizer",
+            Self::Tentative => "Tentative",
+            Self::Accept => "Accept",
+            Self::Decline => "Decline",
+            Self::NoResponseReceived => "NoResponseReceived",
+        }
+    }
+
+    /// Parses a response type from its EWS value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "Unknown" => Some(Self::Unknown),
+            "Organizer" => Some(Self::Organizer),
+            "Tentative" => Some(Self::Tentative),
+            "Accept" => Some(Self::Accept),
+            "Decline" => Some(Self::Decline),
+            "NoResponseReceived" => Some(Self::NoResponseReceived),
+            _ => None,
+        }
+    }
+}
+
+/// How the time of a calendar item is shown to others looking at the
+/// calendar's free/busy information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyFreeBusyStatus {
+    /// The time is free.
+    Free,
+    /// The time is tentatively booked.
+    Tentative,
+    /// The time is busy.
+    Busy,
+    /// The owner of the calendar is out of office.
+    OutOfOffice,
+    /// The owner of the calendar is working elsewhere.
+    WorkingElsewhere,
+    /// No information is available.
+    NoData,
+}
+
+impl LegacyFreeBusyStatus {
+    /// The value used for this status in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Free => "Free",
+            Self::Tentative => "Tentative",
+            Self::Busy => "Busy",
+            Self::OutOfOffice => "OOF",
+            Self::WorkingElsewhere => "WorkingElsewhere",
+            Self::NoData => "NoData",
+        }
+    }
+
+    /// Parses a status from its EWS value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "Free" => Some(Self::Free),
+            "Tentative" => Some(Self::Tentative),
+            "Busy" => Some(Self::Busy),
+            "OOF" => Some(Self::OutOfOffice),
+            "WorkingElsewhere" => Some(Self::WorkingElsewhere),
+            "NoData" => Some(Self::NoData),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of a calendar item with regard to recurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarItemType {
+    /// A calendar item which does not recur.
+    Single,
+    /// An occurrence of a recurring calendar item.
+    Occurrence,
+    /// An occurrence of a recurring calendar item which was modified.
+    Exception,
+    /// The master of a recurring calendar item, holding its recurrence
+    /// pattern.
+    RecurringMaster,
+}
+
+impl CalendarItemType {
+    /// Parses a calendar item type from its EWS value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "Single" => Some(Self::Single),
+            "Occurrence" => Some(Self::Occurrence),
+            "Exception" => Some(Self::Exception),
+            "RecurringMaster" => Some(Self::RecurringMaster),
+            _ => None,
+        }
+    }
+}
+
+/// Whether and to whom meeting invitations or cancellations are sent when a
+/// calendar item is created or deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SendMeetingInvitations {
+    /// Don't send anything. This is the only valid value for appointments
+    /// without attendees.
+    #[default]
+    SendToNone,
+    /// Send to every attendee, without saving a copy.
+    SendOnlyToAll,
+    /// Send to every attendee, and save a copy in the Sent Items folder.
+    SendToAllAndSaveCopy,
+}
+
+impl SendMeetingInvitations {
+    /// The value used for this option in EWS.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::SendToNone => "SendToNone",
+            Self::SendOnlyToAll => "SendOnlyToAll",
+            Self::SendToAllAndSaveCopy => "SendToAllAndSaveCopy",
+        }
+    }
+
+    /// The same option for the cancellations of a typed `DeleteItem`.
+    fn cancellations(self) -> SendMeetingCancellations {
+        match self {
+            Self::SendToNone => SendMeetingCancellations::SendToNone,
+            Self::SendOnlyToAll => SendMeetingCancellations::SendOnlyToAll,
+            Self::SendToAllAndSaveCopy => SendMeetingCancellations::SendToAllAndSaveCopy,
+        }
+    }
+}
+
+/// Whether and to whom meeting updates are sent when a calendar item is
+/// updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SendMeetingUpdates {
+    /// Don't send anything.
+    #[default]
+    SendToNone,
+    /// Send to every attendee, without saving a copy.
+    SendOnlyToAll,
+    /// Send only to attendees which were added or removed, without saving a
+    /// copy.
+    SendOnlyToChanged,
+    /// Send to every attendee, and save a copy in the Sent Items folder.
+    SendToAllAndSaveCopy,
+    /// Send only to attendees which were added or removed, and save a copy in
+    /// the Sent Items folder.
+    SendToChangedAndSaveCopy,
+}
+
+impl SendMeetingUpdates {
+    /// The value used for this option in EWS.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::SendToNone => "SendToNone",
+            Self::SendOnlyToAll => "SendOnlyToAll",
+            Self::SendOnlyToChanged => "SendOnlyToChanged",
+            Self::SendToAllAndSaveCopy => "SendToAllAndSaveCopy",
+            Self::SendToChangedAndSaveCopy => "SendToChangedAndSaveCopy",
+        }
+    }
+}
+
+/// An attendee of a meeting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attendee {
+    /// The email address of the attendee.
+    pub email: String,
+    /// The display name of the attendee, if known.
+    pub name: Option<String>,
+    /// The role of the attendee in the meeting.
+    pub attendee_type: AttendeeType,
+    /// The response of the attendee to the meeting request, if known. Ignored
+    /// when creating or updating a calendar item.
+    pub response_type: Option<ResponseType>,
+}
+
+impl Attendee {
+    /// Creates an attendee with the given email address and role.
+    pub fn new(email: impl Into<String>, attendee_type: AttendeeType) -> Self {
+        Self {
+            email: email.into(),
+            name: None,
+            attendee_type,
+            response_type: None,
+        }
+    }
+
+    /// Reads an attendee from an `Attendee` element.
+    fn from_xml(element: &XmlElement, attendee_type: AttendeeType) -> Option<Self> {
+        let mailbox = element.child("Mailbox")?;
+
+        Some(Self {
+            email: mailbox.child_text("EmailAddress")?.to_string(),
+            name: mailbox.child_text("Name").map(ToString::to_string),
+            attendee_type,
+            response_type: element.child_text("ResponseType").and_then(ResponseType::parse),
+        })
+    }
+}
+
+/// An occurrence of a recurring calendar item which was modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccurrenceInfo {
+    /// The EWS ID of the occurrence. Empty for occurrences read from
+    /// iCalendar.
+    pub item_id: String,
+    /// The start of the occurrence, as a Unix timestamp.
+    pub start: i64,
+    /// The end of the occurrence, as a Unix timestamp.
+    pub end: i64,
+    /// The start of the occurrence before it was modified, as a Unix
+    /// timestamp.
+    pub original_start: i64,
+}
+
+impl OccurrenceInfo {
+    /// Reads a modified occurrence from an `Occurrence` element.
+    fn from_xml(element: &XmlElement) -> Option<Self> {
+        Some(Self {
+            item_id: element.child_id("ItemId")?,
+            start: element.child_date_time("Start")?,
+            end: element.child_date_time("End")?,
+            original_start: element.child_date_time("OriginalStart")?,
+        })
+    }
+}
+
+/// A calendar item (an appointment or a meeting) read from the server.
+#[derive(Debug, Clone, Default)]
+pub struct CalendarItem {
+    /// The EWS ID of the item.
+    pub item_id: String,
+    /// The change key of the item, if returned.
+    pub change_key: Option<String>,
+    /// The subject of the item.
+    pub subject: Option<String>,
+    /// The text body of the item, if requested.
+    pub body: Option<String>,
+    /// The start of the item, as a Unix timestamp.
+    pub start: Option<i64>,
+    /// The end of the item, as a Unix timestamp.
+    pub end: Option<i64>,
+    /// Whether the item lasts the whole day.
+    pub is_all_day_event: bool,
+    /// The location of the item.
+    pub location: Option<String>,
+    /// The organizer of the meeting, if any.
+    pub organizer: Option<Attendee>,
+    /// The attendees of the meeting, in the order required, optional, then
+    /// resources.
+    pub attendees: Vec<Attendee>,
+    /// The kind of the item with regard to recurrence.
+    pub calendar_item_type: Option<CalendarItemType>,
+    /// How the item's time is shown in free/busy information.
+    pub legacy_free_busy_status: Option<LegacyFreeBusyStatus>,
+    /// The Windows ID of the time zone the item starts in, if known.
+    pub start_time_zone: Option<String>,
+    /// The Windows ID of the time zone the item ends in, if known.
+    pub end_time_zone: Option<String>,
+    /// Whether the item is a meeting (it has attendees).
+    pub is_meeting: bool,
+    /// Whether the meeting was cancelled by its organizer.
+    pub is_cancelled: bool,
+    /// The response of the mailbox owner to the meeting, if any.
+    pub my_response_type: Option<ResponseType>,
+    /// The iCalendar UID of the item, shared by all its copies.
+    pub uid: Option<String>,
+    /// The start of an occurrence or exception before it was modified, as a
+    /// Unix timestamp.
+    pub original_start: Option<i64>,
+    /// The recurrence of a recurring master.
+    pub recurrence: Option<Recurrence>,
+    /// The modified occurrences of a recurring master.
+    pub modified_occurrences: Vec<OccurrenceInfo>,
+    /// The original starts of the deleted occurrences of a recurring master,
+    /// as Unix timestamps.
+    pub deleted_occurrences: Vec<i64>,
+    /// The definition of the time zone the item starts in, if returned by the
+    /// server (Exchange 2010 and later).
+    pub start_time_zone_definition: Option<TimeZoneDefinition>,
+}
+
+impl CalendarItem {
+    /// Reads a calendar item from a `CalendarItem` element.
+    pub(crate) fn from_xml(element: &XmlElement) -> Result<Self, EwsError> {
+        let id = element.child("ItemId").ok_or(EwsError::MissingIdInResponse)?;
+
+        let mut attendees = Vec::new();
+        for attendee_type in [AttendeeType::Required, AttendeeType::Optional, AttendeeType::Resource] {
+            if let Some(list) = element.child(attendee_type.list_element_name()) {
+                attendees.extend(
+                    list.children_named("Attendee")
+                        .filter_map(|attendee| Attendee::from_xml(attendee, attendee_type)),
+                );
+            }
+        }
+
+        // Exchange 2007 only reports a single time zone for the whole item.
+        let legacy_time_zone = element.child_text("TimeZone").map(ToString::to_string);
+        let time_zone_id = |name: &str| {
+            element
+                .child(name)
+                .and_then(|time_zone| time_zone.attribute("Id"))
+                .map(ToString::to_string)
+                .or_else(|| legacy_time_zone.clone())
+        };
+
+        Ok(Self {
+            item_id: id.attribute("Id").ok_or(EwsError::MissingIdInResponse)?.to_string(),
+            change_key: id.attribute("ChangeKey").map(ToString::to_string),
+            subject: element.child_text("Subject").map(ToString::to_string),
+            body: element.child_text("Body").map(ToString::to_string),
+            start: element.child_date_time("Start"),
+            end: element.child_date_time("End"),
+            is_all_day_event: element.child_bool("IsAllDayEvent").unwrap_or(false),
+            location: element.child_text("Location").map(ToString::to_string),
+            organizer: element
+                .child("Organizer")
+                .and_then(|organizer| Attendee::from_xml(organizer, AttendeeType::Required))
+                .map(|organizer| Attendee {
+                    response_type: Some(ResponseType::Organizer),
+                    ..organizer
+                }),
+            attendees,
+            calendar_item_type: element.child_text("CalendarItemType").and_then(CalendarItemType::parse),
+            legacy_free_busy_status: element
+                .child_text("LegacyFreeBusyStatus")
+                .and_then(LegacyFreeBusyStatus::parse),
+            start_time_zone: time_zone_id("StartTimeZone"),
+            end_time_zone: time_zone_id("EndTimeZone"),
+            is_meeting: element.child_bool("IsMeeting").unwrap_or(false),
+            is_cancelled: element.child_bool("IsCancelled").unwrap_or(false),
+            my_response_type: element.child_text("MyResponseType").and_then(ResponseType::parse),
+            uid: element.child_text("UID").map(ToString::to_string),
+            original_start: element.child_date_time("OriginalStart"),
+            recurrence: element.child("Recurrence").and_then(Recurrence::from_xml),
+            modified_occurrences: element
+                .child("ModifiedOccurrences")
+                .map(|list| {
+                    list.children_named("Occurrence")
+                        .filter_map(OccurrenceInfo::from_xml)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            deleted_occurrences: element
+                .child("DeletedOccurrences")
+                .map(|list| {
+                    list.children_named("DeletedOccurrence")
+                        .filter_map(|occurrence| occurrence.child_date_time("Start"))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            start_time_zone_definition: element.child("StartTimeZone").and_then(TimeZoneDefinition::from_xml),
+        })
+    }
+}
+
+/// The properties of a new calendar item.
+#[derive(Debug, Clone, Default)]
+pub struct CalendarItemDraft {
+    /// The subject of the item.
+    pub subject: String,
+    /// The text body of the item.
+    pub body: Option<String>,
+    /// The start of the item, as a Unix timestamp.
+    pub start: i64,
+    /// The end of the item, as a Unix timestamp.
+    pub end: i64,
+    /// Whether the item lasts the whole day.
+    pub is_all_day_event: bool,
+    /// The location of the item.
+    pub location: Option<String>,
+    /// The Windows ID of the item's time zone (e.g. `"W. Europe Standard
+    /// Time"`). The server uses it to place all-day events and recurring
+    /// occurrences; it defaults to UTC when not set.
+    pub time_zone: Option<String>,
+    /// The full definition of the item's time zone, e.g. read from an
+    /// iCalendar `VTIMEZONE`. Takes precedence over `time_zone`, for time
+    /// zones the server may not know.
+    pub time_zone_definition: Option<TimeZoneDefinition>,
+    /// The attendees of the meeting. Leave empty for an appointment.
+    pub attendees: Vec<Attendee>,
+    /// The number of minutes before the start at which to show a reminder, or
+    /// `None` for no reminder.
+    pub reminder_minutes: Option<u32>,
+    /// How the item's time is shown in free/busy information.
+    pub legacy_free_busy_status: Option<LegacyFreeBusyStatus>,
+    /// The iCalendar UID of the item, e.g. to keep the UID of an imported
+    /// event. The server generates one when not set.
+    pub uid: Option<String>,
+    /// The recurrence of the item, making it a recurring master. Calendar
+    /// items cannot regenerate.
+    pub recurrence: Option<Recurrence>,
+}
+
+impl From<&CalendarItem> for CalendarItemDraft {
+    /// A draft recreating a calendar item, e.g. one read from iCalendar. The
+    /// modified and deleted occurrences of a recurring item are not part of
+    /// the draft.
+    fn from(item: &CalendarItem) -> Self {
+        Self {
+            subject: item.subject.clone().unwrap_or_default(),
+            body: item.body.clone(),
+            start: item.start.unwrap_or_default(),
+            end: item.end.or(item.start).unwrap_or_default(),
+            is_all_day_event: item.is_all_day_event,
+            location: item.location.clone(),
+            time_zone: item.start_time_zone.clone(),
+            time_zone_definition: item.start_time_zone_definition.clone(),
+            attendees: item.attendees.clone(),
+            reminder_minutes: None,
+            legacy_free_busy_status: item.legacy_free_busy_status,
+            uid: item.uid.clone(),
+            recurrence: item.recurrence.clone(),
+        }
+    }
+}
+
+/// Changes to apply to an existing calendar item. Fields left as `None` are
+/// not changed.
+#[derive(Debug, Clone, Default)]
+pub struct CalendarItemUpdate {
+    /// The new subject of the item.
+    pub subject: Option<String>,
+    /// The new text body of the item.
+    pub body: Option<String>,
+    /// The new start of the item, as a Unix timestamp.
+    pub start: Option<i64>,
+    /// The new end of the item, as a Unix timestamp.
+    pub end: Option<i64>,
+    /// Whether the item lasts the whole day.
+    pub is_all_day_event: Option<bool>,
+    /// The new location of the item.
+    pub location: Option<String>,
+    /// The Windows ID of the new time zone of the item.
+    pub time_zone: Option<String>,
+    /// The new attendees of the meeting, replacing all the current ones.
+    pub attendees: Option<Vec<Attendee>>,
+    /// The new free/busy status of the item.
+    pub legacy_free_busy_status: Option<LegacyFreeBusyStatus>,
+}
+
+/// Result of reading a calendar over a date range.
+#[derive(Debug, Clone)]
+pub struct CalendarViewResult {
+    /// The calendar items in the range, with recurring meetings expanded into
+    /// their occurrences.
+    pub items: Vec<CalendarItem>,
+    /// Whether the last item in the range was returned. This is `false` when
+    /// the maximum number of entries was reached before the end of the range.
+    pub includes_last_item: bool,
+}
+
+/// Result of creating a calendar item.
+#[derive(Debug, Clone)]
+pub struct CreateCalendarItemResult {
+    /// The EWS ID of the new item.
+    pub item_id: String,
+    /// The change key of the new item, if returned.
+    pub change_key: Option<String>,
+}
+
+impl EwsClient {
+    /// Reads the calendar items between two dates.
+    ///
+    /// Recurring meetings are expanded by the server into their individual
+    /// occurrences and exceptions, so each entry of the result corresponds to
+    /// a single occurrence in the range. The attendees and body of items are
+    /// not included; use [`EwsClient::get_calendar_items`] for these.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the calendar folder, or `None` for the
+    ///   default calendar
+    /// * `start` - The start of the range, as a Unix timestamp
+    /// * `end` - The end of the range, as a Unix timestamp
+    /// * `max_entries` - The maximum number of items to return, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The end of the range is before its start
+    /// - The folder does not exist or is not a calendar
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let view = client.get_calendar_view(None, 1_704_067_200, 1_704_672_000, None).await?;
+    /// for item in view.items {
+    ///     println!("{:?} at {:?}", item.subject, item.start);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_calendar_view(
+        &self,
+        folder_id: Option<&str>,
+        start: i64,
+        end: i64,
+        max_entries: Option<u32>,
+    ) -> Result<CalendarViewResult, EwsError> {
+        if end < start {
+            return Err(EwsError::Processing {
+                message: "the end of a calendar view must not be before its start".to_string(),
+            });
+        }
+
+        let body = calendar_view_request(
+            folder_id.unwrap_or(DEFAULT_CALENDAR_FOLDER),
+            start,
+            end,
+            max_entries,
+            self.target_mailbox(),
+        )?;
+
+        let response = self
+            .make_raw_operation_request("FindItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("FindItem", &response)?;
+
+        let root_folder = message.child("RootFolder").ok_or_else(|| EwsError::Processing {
+            message: "FindItem response is missing a root folder".to_string(),
+        })?;
+
+        let items = root_folder
+            .child("Items")
+            .map(|items| {
+                items
+                    .children_named("CalendarItem")
+                    .map(CalendarItem::from_xml)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(CalendarViewResult {
+            items,
+            includes_last_item: root_folder
+                .attribute("IncludesLastItemInRange")
+                .is_none_or(|value| value == "true"),
+        })
+    }
+
+    /// Reads calendar items by their EWS IDs, with all their properties
+    /// including attendees, text body, recurrence and, on Exchange 2010 and
+    /// later, time zone definitions.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The EWS IDs of the items to read
+    ///
+    /// # Returns
+    ///
+    /// The items, in the order of the IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Any of the items cannot be found or is not a calendar item
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn get_calendar_items(&self, ids: &[&str]) -> Result<Vec<CalendarItem>, EwsError> {
+        let mut item_shape = ItemShape::new(ItemBaseShape::AllProperties).with_body_type(BodyType::Text);
+        // The time zone definitions only exist in Exchange 2010 and later.
+        if self.capabilities().time_zone_definitions {
+            item_shape = item_shape
+                .with_field("calendar:StartTimeZone")
+                .with_field("calendar:EndTimeZone");
+        }
+
+        self.get_items_with(ids, &item_shape, |item| {
+            if item.name != "CalendarItem" {
+                return Err(EwsError::Processing {
+                    message: "GetItem response does not contain a calendar item".to_string(),
+                });
+            }
+            CalendarItem::from_xml(item)
+        })
+        .await
+    }
+
+    /// Creates a calendar item, optionally sending meeting invitations to its
+    /// attendees.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the calendar folder, or `None` for the
+    ///   default calendar
+    /// * `draft` - The properties of the new item
+    /// * `send_invitations` - Whether and to whom invitations are sent
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The end of the item is before its start
+    /// - The recurrence or the time zone definition is not valid
+    /// - The folder does not exist or is not a calendar
+    /// - The time zone is not known to the server
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{
+    /// #     Attendee, AttendeeType, CalendarItemDraft, Credentials, EwsClient, SendMeetingInvitations,
+    /// # };
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let draft = CalendarItemDraft {
+    ///     subject: "Planning".to_string(),
+    ///     start: 1_705_314_600,
+    ///     end: 1_705_318_200,
+    ///     time_zone: Some("W. Europe Standard Time".to_string()),
+    ///     attendees: vec![Attendee::new("alice@example.com", AttendeeType::Required)],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let result = client
+    ///     .create_calendar_item(None, &draft, SendMeetingInvitations::SendToAllAndSaveCopy)
+    ///     .await?;
+    /// println!("Created meeting: {}", result.item_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_calendar_item(
+        &self,
+        folder_id: Option<&str>,
+        draft: &CalendarItemDraft,
+        send_invitations: SendMeetingInvitations,
+    ) -> Result<CreateCalendarItemResult, EwsError> {
+        if draft.end < draft.start {
+            return Err(EwsError::Processing {
+                message: "the end of a calendar item must not be before its start".to_string(),
+            });
+        }
+
+        if let Some(recurrence) = &draft.recurrence
+            && matches!(recurrence.pattern, RecurrencePattern::Regenerating { .. })
+        {
+            return Err(EwsError::Processing {
+                message: "calendar items cannot have a regenerating recurrence".to_string(),
+            });
+        }
+
+        let body = create_calendar_item_request(
+            folder_id,
+            &draft.to_xml(self.capabilities())?,
+            send_invitations,
+            self.target_mailbox(),
+        );
+
+        let response = self
+            .make_raw_operation_request("CreateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("CreateItem", &response)?;
+
+        let id = message
+            .descendant(&["Items", "CalendarItem", "ItemId"])
+            .ok_or(EwsError::MissingIdInResponse)?;
+
+        Ok(CreateCalendarItemResult {
+            item_id: id.attribute("Id").ok_or(EwsError::MissingIdInResponse)?.to_string(),
+            change_key: id.attribute("ChangeKey").map(ToString::to_string),
+        })
+    }
+
+    /// Updates an existing calendar item, optionally sending meeting updates
+    /// to its attendees.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the item
+    /// * `change_key` - The change key of the item, if known. When provided,
+    ///   the update fails if the item was changed since it was read
+    /// * `update` - The changes to apply
+    /// * `send_updates` - Whether and to whom meeting updates are sent
+    ///
+    /// # Returns
+    ///
+    /// The new change key of the item.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The update does not change anything
+    /// - The item cannot be found, or was changed since it was read
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn update_calendar_item(
+        &self,
+        item_id: &str,
+        change_key: Option<&str>,
+        update: &CalendarItemUpdate,
+        send_updates: SendMeetingUpdates,
+    ) -> Result<String, EwsError> {
+        let updates = update.updates_xml(self.capabilities())?;
+
+        if updates.is_empty() {
+            return Err(EwsError::Processing {
+                message: "calendar item update does not change anything".to_string(),
+            });
+        }
+
+        let body = update_calendar_item_request(item_id, change_key, &updates, send_updates);
+
+        let response = self
+            .make_raw_operation_request("UpdateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("UpdateItem", &response)?;
+
+        message
+            .descendant(&["Items", "CalendarItem", "ItemId"])
+            .and_then(|id| id.attribute("ChangeKey"))
+            .map(ToString::to_string)
+            .ok_or(EwsError::MissingIdInResponse)
+    }
+
+    /// Deletes a calendar item by moving it to the Deleted Items folder,
+    /// optionally sending meeting cancellations to its attendees.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the item
+    /// * `send_cancellations` - Whether and to whom cancellations are sent
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The item cannot be found
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn delete_calendar_item(
+        &self,
+        item_id: &str,
+        send_cancellations: SendMeetingInvitations,
+    ) -> Result<(), EwsError> {
+        let delete_item = DeleteItem {
+            item_ids: vec![BaseItemId::ItemId {
+                id: item_id.to_string(),
+                change_key: None,
+            }],
+            delete_type: DeleteType::MoveToDeletedItems,
+            send_meeting_cancellations: Some(send_cancellations.cancellations()),
+            affected_task_occurrences: None,
+            suppress_read_receipts: None,
+        };
+
+        let response = self
+            .make_operation_request(delete_item, OperationRequestOptions::default())
+            .await?
+            .into_response_messages();
+        let response = single_response_or_error(response)?;
+        process_response_message_class(DeleteItem::NAME, response)?;
+
+        Ok(())
+    }
+}
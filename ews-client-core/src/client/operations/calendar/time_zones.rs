@@ -0,0 +1,341 @@
+//! Time zone definitions of calendar items.
+//!
+//! Exchange 2010 and later describe the time zone of a calendar item with a
+//! `TimeZoneDefinition`: the periods of the time zone (standard and daylight
+//! saving time, each with its offset from UTC), and the yearly transitions
+//! between them. Only the rules in effect today are kept; past changes to the
+//! rules of a time zone are ignored.
+
+use time::{Date, Month, OffsetDateTime};
+
+use crate::client::{
+    EwsError,
+    operations::recurrence::{DayOfWeek, DayOfWeekIndex},
+    xml::{XmlElement, parse_duration_minutes},
+};
+
+/// The number of minutes in a day.
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// A time zone, with its offset from UTC and its daylight saving time rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZoneDefinition {
+    /// The ID of the time zone, e.g. the Windows ID `"W. Europe Standard
+    /// Time"` or the `TZID` of an iCalendar `VTIMEZONE`.
+    pub id: String,
+    /// The display name of the time zone, if known.
+    pub name: Option<String>,
+    /// The offset from UTC of standard time, in minutes (e.g. 60 for
+    /// UTC+01:00).
+    pub standard_offset: i32,
+    /// Daylight saving time, if the time zone observes it.
+    pub daylight: Option<DaylightSavingTime>,
+}
+
+/// The daylight saving time of a time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaylightSavingTime {
+    /// The offset from UTC during daylight saving time, in minutes.
+    pub offset: i32,
+    /// When daylight saving time starts, in local standard time.
+    pub start: TimeZoneTransition,
+    /// When daylight saving time ends, in local daylight saving time.
+    pub end: TimeZoneTransition,
+}
+
+/// A yearly transition between standard and daylight saving time, e.g. "the
+/// last Sunday of March at 02:00".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZoneTransition {
+    /// The month of the transition, from 1 to 12.
+    pub month: u8,
+    /// The day of the week of the transition. Must be a single day.
+    pub day_of_week: DayOfWeek,
+    /// The week of the month of the transition.
+    pub day_of_week_index: DayOfWeekIndex,
+    /// The local time of the transition, in minutes after midnight.
+    pub time: u16,
+}
+
+impl TimeZoneDefinition {
+    /// The offset from UTC of the time zone at the given instant, in minutes.
+    pub fn utc_offset_at(&self, timestamp: i64) -> i32 {
+        let Some(daylight) = &self.daylight else {
+            return self.standard_offset;
+        };
+
+        let year = OffsetDateTime::from_unix_timestamp(timestamp + i64::from(self.standard_offset) * 60)
+            .map_or(1970, OffsetDateTime::year);
+        let (Some(start), Some(end)) = (
+            daylight.start.instant(year, self.standard_offset),
+            daylight.end.instant(year, daylight.offset),
+        ) else {
+            return self.standard_offset;
+        };
+
+        // In the southern hemisphere, daylight saving time spans the new year.
+        let in_daylight = if start < end {
+            start <= timestamp && timestamp < end
+        } else {
+            timestamp >= start || timestamp < end
+        };
+
+        if in_daylight {
+            daylight.offset
+        } else {
+            self.standard_offset
+        }
+    }
+
+    /// Converts a local time of the time zone, given as a Unix timestamp as if
+    /// it were UTC, to a Unix timestamp.
+    pub(crate) fn local_to_utc(&self, local: i64) -> i64 {
+        let offset = self.utc_offset_at(local - i64::from(self.standard_offset) * 60);
+        local - i64::from(offset) * 60
+    }
+
+    /// Converts a Unix timestamp to the local time of the time zone, given as
+    /// a Unix timestamp as if it were UTC.
+    pub(crate) fn utc_to_local(&self, timestamp: i64) -> i64 {
+        timestamp + i64::from(self.utc_offset_at(timestamp)) * 60
+    }
+
+    /// Reads a time zone from a `TimeZoneDefinition` element (e.g.
+    /// `StartTimeZone`). Returns `None` if the element only names the time
+    /// zone, as Exchange 2007 does, or if its rules are not understood.
+    pub(crate) fn from_xml(element: &XmlElement) -> Option<Self> {
+        let periods = element.child("Periods")?;
+        let period_offset = |period_id: &str| {
+            periods
+                .children_named("Period")
+                .find(|period| period.attribute("Id") == Some(period_id.trim()))
+                .and_then(|period| period.attribute("Bias"))
+                .and_then(parse_duration_minutes)
+                // The bias is what is added to local time to get UTC.
+                .map(|bias| -bias)
+        };
+
+        // The transitions group in effect today is the target of the last
+        // transition.
+        let group_id = element
+            .child("Transitions")
+            .and_then(|transitions| transitions.children.last())
+            .and_then(|transition| transition.child_text("To"))
+            .map(str::trim);
+        let group = element
+            .child("TransitionsGroups")?
+            .children_named("TransitionsGroup")
+            .find(|group| group_id.is_none_or(|id| group.attribute("Id") == Some(id)))?;
+
+        let mut transitions: Vec<(i32, TimeZoneTransition)> = group
+            .children_named("RecurringDayTransition")
+            .filter_map(|transition| {
+                Some((
+                    period_offset(transition.child_text("To")?)?,
+                    TimeZoneTransition::from_xml(transition)?,
+                ))
+            })
+            .collect();
+        transitions.sort_by_key(|(offset, _)| *offset);
+
+        let (standard_offset, daylight) = match transitions.as_slice() {
+            [(standard_offset, end), (daylight_offset, start)] if standard_offset != daylight_offset => (
+                *standard_offset,
+                Some(DaylightSavingTime {
+                    offset: *daylight_offset,
+                    start: *start,
+                    end: *end,
+                }),
+            ),
+            _ => {
+                // Without yearly transitions, the time zone stays in the
+                // period its group switches to.
+                let period_id = group.children.first()?.child_text("To")?;
+                (period_offset(period_id)?, None)
+            }
+        };
+
+        Some(Self {
+            id: element.attribute("Id")?.to_string(),
+            name: element.attribute("Name").map(ToString::to_string),
+            standard_offset,
+            daylight,
+        })
+    }
+}
+
+impl TimeZoneTransition {
+    /// The date of the transition in the given year.
+    pub(crate) fn date(&self, year: i32) -> Option<Date> {
+        let month = Month::try_from(self.month).ok()?;
+        let weekday = self.day_of_week.weekday()?;
+
+        let first_of_month = Date::from_calendar_date(year, month, 1).ok()?;
+        let first_match =
+            1 + (7 + weekday.number_days_from_monday() - first_of_month.weekday().number_days_from_monday()) % 7;
+        let day = match self.day_of_week_index {
+            DayOfWeekIndex::First => first_match,
+            DayOfWeekIndex::Second => first_match + 7,
+            DayOfWeekIndex::Third => first_match + 14,
+            DayOfWeekIndex::Fourth => first_match + 21,
+            DayOfWeekIndex::Last => {
+                let length = time::util::days_in_year_month(year, month);
+                first_match + 7 * ((length - first_match) / 7)
+            }
+        };
+
+        Date::from_calendar_date(year, month, day).ok()
+    }
+
+    /// The instant of the transition in the given year, for a time zone with
+    /// the given offset before the transition.
+    fn instant(&self, year: i32, offset: i32) -> Option<i64> {
+        let midnight = self.date(year)?.midnight().assume_utc().unix_timestamp();
+        Some(midnight + i64::from(self.time) * 60 - i64::from(offset) * 60)
+    }
+
+    /// Checks that the transition can be written, returning its month.
+    pub(crate) fn validate(&self) -> Result<Month, EwsError> {
+        if self.day_of_week.weekday().is_none() {
+            return Err(EwsError::Processing {
+                message: format!("{} is not a single day of the week", self.day_of_week.as_str()),
+            });
+        }
+        if self.time >= MINUTES_PER_DAY {
+            return Err(EwsError::Processing {
+                message: format!("{} minutes is not a valid time of day", self.time),
+            });
+        }
+        Month::try_from(self.month).map_err(|_| EwsError::Processing {
+            message: format!("{} is not a valid month", self.month),
+        })
+    }
+
+    /// Reads a transition from a `RecurringDayTransition` element.
+    fn from_xml(element: &XmlElement) -> Option<Self> {
+        Some(Self {
+            month: element.child_parse("Month")?,
+            day_of_week: element
+                .child_text("DayOfWeek")
+                .map(str::trim)
+                .and_then(DayOfWeek::parse)?,
+            day_of_week_index: element
+                .child_parse("Occurrence")
+                .and_then(DayOfWeekIndex::from_position)?,
+            time: element
+                .child_text("TimeOffset")
+                .and_then(parse_duration_minutes)
+                .and_then(|minutes| u16::try_from(minutes).ok())?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    /// Central European Time, as returned by Exchange.
+    const W_EUROPE_XML: &str = r#"<t:StartTimeZone xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types" Id="W. Europe Standard Time" Name="(UTC+01:00) Amsterdam, Berlin">
+          <t:Periods>
+            <t:Period Bias="-PT1H" Name="Standard" Id="trule:Microsoft/Registry/W. Europe Standard Time/2006-Standard"/>
+            <t:Period Bias="-PT2H" Name="Daylight" Id="trule:Microsoft/Registry/W. Europe Standard Time/2006-Daylight"/>
+          </t:Periods>
+          <t:TransitionsGroups>
+            <t:TransitionsGroup Id="0">
+              <t:RecurringDayTransition>
+                <t:To Kind="Period">trule:Microsoft/Registry/W. Europe Standard Time/2006-Daylight</t:To>
+                <t:TimeOffset>PT2H</t:TimeOffset>
+                <t:Month>3</t:Month>
+                <t:DayOfWeek>Sunday</t:DayOfWeek>
+                <t:Occurrence>-1</t:Occurrence>
+              </t:RecurringDayTransition>
+              <t:RecurringDayTransition>
+                <t:To Kind="Period">trule:Microsoft/Registry/W. Europe Standard Time/2006-Standard</t:To>
+                <t:TimeOffset>PT3H</t:TimeOffset>
+                <t:Month>10</t:Month>
+                <t:DayOfWeek>Sunday</t:DayOfWeek>
+                <t:Occurrence>-1</t:Occurrence>
+              </t:RecurringDayTransition>
+            </t:TransitionsGroup>
+          </t:TransitionsGroups>
+          <t:Transitions>
+            <t:Transition>
+              <t:To Kind="Group">0</t:To>
+            </t:Transition>
+          </t:Transitions>
+        </t:StartTimeZone>"#;
+
+    fn w_europe() -> TimeZoneDefinition {
+        TimeZoneDefinition::from_xml(&XmlElement::parse(W_EUROPE_XML.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_from_xml() {
+        let time_zone = w_europe();
+
+        assert_eq!(time_zone.id, "W. Europe Standard Time");
+        assert_eq!(time_zone.standard_offset, 60);
+        assert_eq!(
+            time_zone.daylight,
+            Some(DaylightSavingTime {
+                offset: 120,
+                start: TimeZoneTransition {
+                    month: 3,
+                    day_of_week: DayOfWeek::Sunday,
+                    day_of_week_index: DayOfWeekIndex::Last,
+                    time: 120,
+                },
+                end: TimeZoneTransition {
+                    month: 10,
+                    day_of_week: DayOfWeek::Sunday,
+                    day_of_week_index: DayOfWeekIndex::Last,
+                    time: 180,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_xml_round_trip() {
+        let time_zone = w_europe();
+        let xml = time_zone.to_xml("t:StartTimeZone").unwrap();
+        let parsed = TimeZoneDefinition::from_xml(&XmlElement::parse(xml.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, time_zone);
+
+        let fixed = TimeZoneDefinition {
+            id: "India Standard Time".to_string(),
+            name: None,
+            standard_offset: 330,
+            daylight: None,
+        };
+        let xml = fixed.to_xml("t:StartTimeZone").unwrap();
+        assert!(xml.contains(r#"Bias="-PT5H30M""#));
+        let parsed = TimeZoneDefinition::from_xml(&XmlElement::parse(xml.as_bytes()).unwrap()).unwrap();
+        assert_eq!(parsed, fixed);
+    }
+
+    #[test]
+    fn test_utc_offset_at() {
+        let time_zone = w_europe();
+
+        // Daylight saving time ran from 2024-03-31T01:00Z to 2024-10-27T01:00Z
+        assert_eq!(time_zone.utc_offset_at(1_711_846_799), 60);
+        assert_eq!(time_zone.utc_offset_at(1_711_846_800), 120);
+        assert_eq!(time_zone.utc_offset_at(1_729_990_799), 120);
+        assert_eq!(time_zone.utc_offset_at(1_729_990_800), 60);
+
+        // 2024-07-01T12:00 local is 10:00Z
+        assert_eq!(time_zone.local_to_utc(1_719_835_200), 1_719_828_000);
+        assert_eq!(time_zone.utc_to_local(1_719_828_000), 1_719_835_200);
+    }
+
+    #[test]
+    fn test_invalid_transition() {
+        let mut time_zone = w_europe();
+        if let Some(daylight) = time_zone.daylight.as_mut() {
+            daylight.start.day_of_week = DayOfWeek::Weekday;
+        }
+        assert!(time_zone.to_xml("t:StartTimeZone").is_err());
+    }
+}
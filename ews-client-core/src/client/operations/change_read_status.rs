@@ -140,6 +140,7 @@ impl EwsClient {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The server is older than Exchange 2013 ([`EwsError::Unsupported`])
     /// - Any folder does not exist
     /// - Network or authentication errors occur
     /// - The server returns an unexpected response
@@ -163,17 +164,8 @@ impl EwsClient {
         use ews::BaseFolderId;
         use ews::mark_all_read::MarkAllItemsAsRead;
 
-        // The `MarkAllItemsAsRead` operation was added in Exchange 2013
-        let server_version = Some(self.server_version.load());
-        if let Some(version) = server_version
-            && version < ews::server_version::ExchangeServerVersion::Exchange2013
-        {
-            return Err(EwsError::Processing {
-                message: format!(
-                    "MarkAllItemsAsRead operation is not supported on Exchange version {version:?}. Requires Exchange 2013 or later."
-                ),
-            });
-        }
+        let capabilities = self.capabilities();
+        capabilities.require(capabilities.mark_all_items_as_read, "MarkAllItemsAsRead")?;
 
         let folder_ids: Vec<BaseFolderId> = folder_ids
             .iter()
@@ -1,10 +1,16 @@
-//! Performs a connectivity check to the EWS server.
+//! Performs a connectivity check to the EWS server, and detects its version.
+
+use std::sync::Arc;
 
 use crate::client::{
     AuthFailureBehavior, EWS_ROOT_FOLDER, EwsClient, EwsError, OperationRequestOptions, process_response_message_class,
+    raw_requests::folders::get_folder_ids_request, server_version, single_raw_response_or_error,
     single_response_or_error, validate_get_folder_response_message,
 };
-use ews::{BaseFolderId, BaseShape, FolderShape, Operation, OperationResponse, get_folder::GetFolder};
+use ews::{
+    BaseFolderId, BaseShape, FolderShape, Operation, OperationResponse, get_folder::GetFolder,
+    server_version::ExchangeServerVersion,
+};
 
 impl EwsClient {
     /// Performs a connectivity check to the EWS server.
@@ -22,6 +28,56 @@ impl EwsClient {
     /// - The server returns an error response
     /// - The root folder cannot be found
     pub async fn check_connectivity(&self) -> Result<(), EwsError> {
+        // Make authentication failure silent, since all we want to know is
+        // whether our credentials are valid.
+        self.get_root_folder(OperationRequestOptions {
+            auth_failure_behavior: AuthFailureBehavior::Silent,
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Detects the version of the server, and stores it for the endpoint.
+    ///
+    /// Clients otherwise learn the version from the responses to their
+    /// requests, so the first request to an endpoint whose version is not
+    /// stored yet is sent for Exchange 2007 SP1, and operations which depend
+    /// on the version (e.g. [`EwsClient::mark_as_junk`]) may take their
+    /// fallback path. Detecting the version first avoids this.
+    ///
+    /// The version is detected by looking up the ID of the account's root
+    /// mail folder, as in [`EwsClient::check_connectivity`]. The client's
+    /// [`VersionStore`](crate::client::VersionStore) is then persisted.
+    ///
+    /// # Returns
+    ///
+    /// The version reported by the server, or the client's current version if
+    /// the server did not report one. Clients whose version is pinned (see
+    /// [`EwsClient::with_server_version`]) return it without sending a
+    /// request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Authentication fails
+    /// - Network connection fails
+    /// - The server returns an error response
+    pub async fn detect_server_version(&self) -> Result<ExchangeServerVersion, EwsError> {
+        if self.is_server_version_pinned() {
+            return Ok(self.server_version());
+        }
+
+        self.get_root_folder(OperationRequestOptions::default()).await?;
+        server_version::persist_server_versions(Arc::clone(&self.version_store)).await;
+        Ok(self.server_version.load())
+    }
+
+    /// Looks up the ID of the account's root mail folder.
+    async fn get_root_folder(&self, options: OperationRequestOptions) -> Result<(), EwsError> {
+        if let Some(mailbox) = self.target_mailbox() {
+            return self.get_root_folder_in_mailbox(mailbox, options).await;
+        }
+
         // Request the EWS ID of the root folder
         let get_root_folder = GetFolder {
             folder_shape: FolderShape {
@@ -34,15 +90,7 @@ impl EwsClient {
         };
 
         let response_messages = self
-            // Make authentication failure silent, since all we want to know is
-            // whether our credentials are valid.
-            .make_operation_request(
-                get_root_folder,
-                OperationRequestOptions {
-                    auth_failure_behavior: AuthFailureBehavior::Silent,
-                    ..Default::default()
-                },
-            )
+            .make_operation_request(get_root_folder, options)
             .await?
             .into_response_messages();
 
@@ -57,4 +105,32 @@ impl EwsClient {
 
         Ok(())
     }
+
+    /// Looks up the ID of the root mail folder of the client's target mailbox.
+    ///
+    /// The `ews` crate cannot name the mailbox of a distinguished folder, so
+    /// the request is built by hand.
+    async fn get_root_folder_in_mailbox(
+        &self,
+        mailbox: &str,
+        options: OperationRequestOptions,
+    ) -> Result<(), EwsError> {
+        let body = get_folder_ids_request(&[EWS_ROOT_FOLDER], Some(mailbox));
+
+        let response = self.make_raw_operation_request(GetFolder::NAME, &body, options).await?;
+        let message = single_raw_response_or_error(GetFolder::NAME, &response)?;
+
+        // As above, a missing root folder is fatal.
+        let folders = message.child("Folders").map(|folders| folders.children.len());
+        if folders != Some(1) {
+            return Err(EwsError::Processing {
+                message: format!(
+                    "expected exactly one folder per response message, got {}",
+                    folders.unwrap_or_default()
+                ),
+            });
+        }
+
+        Ok(())
+    }
 }
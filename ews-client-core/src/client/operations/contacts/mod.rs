@@ -0,0 +1,676 @@
+//! Contact operations.
+//!
+//! This module contains the operations for listing, reading, creating,
+//! updating and deleting contacts, and the conversion of contacts to and from
+//! vCard 4.0 (see the `vcard` module).
+
+mod vcard;
+
+use ews::{BaseItemId, DeleteType, Operation, OperationResponse, delete_item::DeleteItem};
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    operations::get_items::{BodyType, ItemBaseShape, ItemShape},
+    process_response_message_class,
+    raw_requests::contacts::{create_contact_request, find_contacts_request, update_contact_request},
+    single_raw_response_or_error, single_response_or_error,
+    xml::XmlElement,
+};
+
+pub use vcard::parse_vcards;
+
+/// The distinguished ID of the default contacts folder.
+const DEFAULT_CONTACTS_FOLDER: &str = "contacts";
+
+/// The slot of an email address in a contact. Exchange stores at most three
+/// email addresses per contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EmailAddressKey {
+    /// The first (primary) email address.
+    EmailAddress1,
+    /// The second email address.
+    EmailAddress2,
+    /// The third email address.
+    EmailAddress3,
+}
+
+impl EmailAddressKey {
+    /// All the slots, in order.
+    pub const ALL: [Self; 3] = [Self::EmailAddress1, Self::EmailAddress2, Self::EmailAddress3];
+
+    /// The value used for this slot in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::EmailAddress1 => "EmailAddress1",
+            Self::EmailAddress2 => "EmailAddress2",
+            Self::EmailAddress3 => "EmailAddress3",
+        }
+    }
+
+    /// Parses a slot from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+}
+
+/// The kind of a phone number in a contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneNumberKey {
+    /// The phone number of the contact's assistant.
+    AssistantPhone,
+    /// A business fax number.
+    BusinessFax,
+    /// A business phone number.
+    BusinessPhone,
+    /// A second business phone number.
+    BusinessPhone2,
+    /// A callback number.
+    Callback,
+    /// A car phone number.
+    CarPhone,
+    /// The main phone number of the contact's company.
+    CompanyMainPhone,
+    /// A home fax number.
+    HomeFax,
+    /// A home phone number.
+    HomePhone,
+    /// A second home phone number.
+    HomePhone2,
+    /// An ISDN number.
+    Isdn,
+    /// A mobile phone number.
+    MobilePhone,
+    /// Another fax number.
+    OtherFax,
+    /// Another phone number.
+    OtherTelephone,
+    /// A pager number.
+    Pager,
+    /// The primary phone number.
+    PrimaryPhone,
+    /// A radio phone number.
+    RadioPhone,
+    /// A telex number.
+    Telex,
+    /// A TTY/TDD phone number.
+    TtyTddPhone,
+}
+
+impl PhoneNumberKey {
+    /// All the kinds of phone numbers.
+    pub const ALL: [Self; 19] = [
+        Self::AssistantPhone,
+        Self::BusinessFax,
+        Self::BusinessPhone,
+        Self::BusinessPhone2,
+        Self::Callback,
+        Self::CarPhone,
+        Self::CompanyMainPhone,
+        Self::HomeFax,
+        Self::HomePhone,
+        Self::HomePhone2,
+        Self::Isdn,
+        Self::MobilePhone,
+        Self::OtherFax,
+        Self::OtherTelephone,
+        Self::Pager,
+        Self::PrimaryPhone,
+        Self::RadioPhone,
+        Self::Telex,
+        Self::TtyTddPhone,
+    ];
+
+    /// The value used for this kind of phone number in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::AssistantPhone => "AssistantPhone",
+            Self::BusinessFax => "BusinessFax",
+            Self::BusinessPhone => "BusinessPhone",
+            Self::BusinessPhone2 => "BusinessPhone2",
+            Self::Callback => "Callback",
+            Self::CarPhone => "CarPhone",
+            Self::CompanyMainPhone => "CompanyMainPhone",
+            Self::HomeFax => "HomeFax",
+            Self::HomePhone => "HomePhone",
+            Self::HomePhone2 => "HomePhone2",
+            Self::Isdn => "Isdn",
+            Self::MobilePhone => "MobilePhone",
+            Self::OtherFax => "OtherFax",
+            Self::OtherTelephone => "OtherTelephone",
+            Self::Pager => "Pager",
+            Self::PrimaryPhone => "PrimaryPhone",
+            Self::RadioPhone => "RadioPhone",
+            Self::Telex => "Telex",
+            Self::TtyTddPhone => "TtyTddPhone",
+        }
+    }
+
+    /// Parses a kind of phone number from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+}
+
+/// The kind of a physical address in a contact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PhysicalAddressKey {
+    /// A home address.
+    Home,
+    /// A business address.
+    Business,
+    /// Another address.
+    #[default]
+    Other,
+}
+
+impl PhysicalAddressKey {
+    /// All the kinds of physical addresses.
+    pub const ALL: [Self; 3] = [Self::Home, Self::Business, Self::Other];
+
+    /// The value used for this kind of address in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Home => "Home",
+            Self::Business => "Business",
+            Self::Other => "Other",
+        }
+    }
+
+    /// Parses a kind of address from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == value)
+    }
+}
+
+/// An email address of a contact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactEmailAddress {
+    /// The slot of the address.
+    pub key: EmailAddressKey,
+    /// The email address.
+    pub address: String,
+}
+
+/// A phone number of a contact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactPhoneNumber {
+    /// The kind of phone number.
+    pub key: PhoneNumberKey,
+    /// The phone number, as entered.
+    pub number: String,
+}
+
+/// A physical address of a contact.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContactPhysicalAddress {
+    /// The kind of address.
+    pub key: PhysicalAddressKey,
+    /// The street, including the house number.
+    pub street: Option<String>,
+    /// The city.
+    pub city: Option<String>,
+    /// The state or province.
+    pub state: Option<String>,
+    /// The postal code.
+    pub postal_code: Option<String>,
+    /// The country or region.
+    pub country_or_region: Option<String>,
+}
+
+impl ContactPhysicalAddress {
+    /// The parts of the address with the names of their EWS elements, in the
+    /// order of the EWS schema.
+    pub(crate) fn parts(&self) -> [(&'static str, Option<&str>); 5] {
+        [
+            ("Street", self.street.as_deref()),
+            ("City", self.city.as_deref()),
+            ("State", self.state.as_deref()),
+            ("CountryOrRegion", self.country_or_region.as_deref()),
+            ("PostalCode", self.postal_code.as_deref()),
+        ]
+    }
+}
+
+/// A contact.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Contact {
+    /// The EWS ID of the contact, if it was read from the server.
+    pub item_id: Option<String>,
+    /// The change key of the contact, if it was read from the server.
+    pub change_key: Option<String>,
+    /// The name the contact is displayed with.
+    pub display_name: Option<String>,
+    /// The given (first) name.
+    pub given_name: Option<String>,
+    /// The middle name.
+    pub middle_name: Option<String>,
+    /// The surname (last name).
+    pub surname: Option<String>,
+    /// The nickname.
+    pub nickname: Option<String>,
+    /// The name of the contact's company.
+    pub company_name: Option<String>,
+    /// The department of the contact in their company.
+    pub department: Option<String>,
+    /// The job title of the contact.
+    pub job_title: Option<String>,
+    /// The email addresses of the contact.
+    pub email_addresses: Vec<ContactEmailAddress>,
+    /// The phone numbers of the contact.
+    pub phone_numbers: Vec<ContactPhoneNumber>,
+    /// The physical addresses of the contact. Only one address of each kind
+    /// is stored by Exchange.
+    pub physical_addresses: Vec<ContactPhysicalAddress>,
+    /// The birthday of the contact, as a Unix timestamp at midnight UTC.
+    pub birthday: Option<i64>,
+    /// Free-form text notes about the contact.
+    pub notes: Option<String>,
+}
+
+impl Contact {
+    /// Reads a contact from a `Contact` element.
+    pub(crate) fn from_xml(element: &XmlElement) -> Result<Self, EwsError> {
+        let contact = Self::from_xml_without_id(element);
+        if contact.item_id.is_none() {
+            return Err(EwsError::MissingIdInResponse);
+        }
+        Ok(contact)
+    }
+
+    /// Reads a contact from a `Contact` element which may have no item ID,
+    /// e.g. a directory entry returned by `ResolveNames`.
+    pub(crate) fn from_xml_without_id(element: &XmlElement) -> Self {
+        let id = element.child("ItemId");
+        let text = |name: &str| element.child_text(name).map(ToString::to_string);
+        let entries = |name: &str| -> Vec<&XmlElement> {
+            element
+                .child(name)
+                .map(|list| list.children_named("Entry").collect())
+                .unwrap_or_default()
+        };
+
+        let email_addresses = entries("EmailAddresses")
+            .into_iter()
+            .filter_map(|entry| {
+                Some(ContactEmailAddress {
+                    key: EmailAddressKey::parse(entry.attribute("Key")?)?,
+                    address: strip_smtp_prefix(entry.text.trim()).to_string(),
+                })
+            })
+            .filter(|email| !email.address.is_empty())
+            .collect();
+
+        let phone_numbers = entries("PhoneNumbers")
+            .into_iter()
+            .filter_map(|entry| {
+                Some(ContactPhoneNumber {
+                    key: PhoneNumberKey::parse(entry.attribute("Key")?)?,
+                    number: entry.text.trim().to_string(),
+                })
+            })
+            .filter(|phone| !phone.number.is_empty())
+            .collect();
+
+        let physical_addresses = entries("PhysicalAddresses")
+            .into_iter()
+            .map(|entry| ContactPhysicalAddress {
+                key: entry
+                    .attribute("Key")
+                    .and_then(PhysicalAddressKey::parse)
+                    .unwrap_or_default(),
+                street: entry.child_text("Street").map(ToString::to_string),
+                city: entry.child_text("City").map(ToString::to_string),
+                state: entry.child_text("State").map(ToString::to_string),
+                postal_code: entry.child_text("PostalCode").map(ToString::to_string),
+                country_or_region: entry.child_text("CountryOrRegion").map(ToString::to_string),
+            })
+            .collect();
+
+        Self {
+            item_id: id.and_then(|id| id.attribute("Id")).map(ToString::to_string),
+            change_key: id.and_then(|id| id.attribute("ChangeKey")).map(ToString::to_string),
+            display_name: text("DisplayName"),
+            given_name: text("GivenName"),
+            middle_name: text("MiddleName"),
+            surname: text("Surname"),
+            nickname: text("Nickname"),
+            company_name: text("CompanyName"),
+            department: text("Department"),
+            job_title: text("JobTitle"),
+            email_addresses,
+            phone_numbers,
+            physical_addresses,
+            birthday: element.child_date_time("Birthday"),
+            notes: text("Body").filter(|notes| !notes.is_empty()),
+        }
+    }
+}
+
+/// Changes to apply to an existing contact. Fields left as `None` are not
+/// changed; text fields set to an empty string are cleared.
+#[derive(Debug, Clone, Default)]
+pub struct ContactUpdate {
+    /// The new display name.
+    pub display_name: Option<String>,
+    /// The new given (first) name.
+    pub given_name: Option<String>,
+    /// The new middle name.
+    pub middle_name: Option<String>,
+    /// The new surname (last name).
+    pub surname: Option<String>,
+    /// The new nickname.
+    pub nickname: Option<String>,
+    /// The new company name.
+    pub company_name: Option<String>,
+    /// The new department.
+    pub department: Option<String>,
+    /// The new job title.
+    pub job_title: Option<String>,
+    /// The new notes.
+    pub notes: Option<String>,
+    /// The new email addresses, replacing all the current ones.
+    pub email_addresses: Option<Vec<ContactEmailAddress>>,
+    /// The new phone numbers, replacing all the current ones.
+    pub phone_numbers: Option<Vec<ContactPhoneNumber>>,
+    /// The new physical addresses, replacing all the current ones.
+    pub physical_addresses: Option<Vec<ContactPhysicalAddress>>,
+}
+
+/// Result of listing the contacts of a folder.
+#[derive(Debug, Clone)]
+pub struct FindContactsResult {
+    /// The contacts in the page, sorted by display name.
+    pub contacts: Vec<Contact>,
+    /// The total number of contacts in the folder.
+    pub total_items: Option<u32>,
+    /// Whether the page contains the last contact of the folder.
+    pub includes_last_item: bool,
+    /// The offset to request the next page from, if there is one.
+    pub next_offset: Option<u32>,
+}
+
+/// Result of creating a contact.
+#[derive(Debug, Clone)]
+pub struct CreateContactResult {
+    /// The EWS ID of the new contact.
+    pub item_id: String,
+    /// The change key of the new contact, if returned.
+    pub change_key: Option<String>,
+}
+
+impl EwsClient {
+    /// Lists the contacts of a folder, one page at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the contacts folder, or `None` for the
+    ///   default contacts folder
+    /// * `offset` - The index of the first contact of the page
+    /// * `max_entries` - The maximum number of contacts in the page
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The folder does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let mut offset = Some(0);
+    /// while let Some(current) = offset {
+    ///     let page = client.find_contacts(None, current, 100).await?;
+    ///     for contact in &page.contacts {
+    ///         println!("{:?}", contact.display_name);
+    ///     }
+    ///     offset = page.next_offset;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_contacts(
+        &self,
+        folder_id: Option<&str>,
+        offset: u32,
+        max_entries: u32,
+    ) -> Result<FindContactsResult, EwsError> {
+        let body = find_contacts_request(
+            folder_id.unwrap_or(DEFAULT_CONTACTS_FOLDER),
+            offset,
+            max_entries,
+            self.target_mailbox(),
+        );
+
+        let response = self
+            .make_raw_operation_request("FindItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("FindItem", &response)?;
+
+        let root_folder = message.child("RootFolder").ok_or_else(|| EwsError::Processing {
+            message: "FindItem response is missing a root folder".to_string(),
+        })?;
+
+        let contacts = root_folder
+            .child("Items")
+            .map(|items| {
+                items
+                    .children_named("Contact")
+                    .map(Contact::from_xml)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let includes_last_item = root_folder
+            .attribute("IncludesLastItemInRange")
+            .is_none_or(|value| value == "true");
+        let next_offset = if includes_last_item {
+            None
+        } else {
+            root_folder
+                .attribute("IndexedPagingOffset")
+                .and_then(|offset| offset.parse().ok())
+        };
+
+        Ok(FindContactsResult {
+            contacts,
+            total_items: root_folder
+                .attribute("TotalItemsInView")
+                .and_then(|total| total.parse().ok()),
+            includes_last_item,
+            next_offset,
+        })
+    }
+
+    /// Reads contacts by their EWS IDs, with all their properties.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The EWS IDs of the contacts to read
+    ///
+    /// # Returns
+    ///
+    /// The contacts, in the order of the IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Any of the items cannot be found or is not a contact
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn get_contacts(&self, ids: &[&str]) -> Result<Vec<Contact>, EwsError> {
+        let item_shape = ItemShape::new(ItemBaseShape::AllProperties).with_body_type(BodyType::Text);
+
+        self.get_items_with(ids, &item_shape, |item| {
+            if item.name != "Contact" {
+                return Err(EwsError::Processing {
+                    message: "GetItem response does not contain a contact".to_string(),
+                });
+            }
+            Contact::from_xml(item)
+        })
+        .await
+    }
+
+    /// Creates a contact.
+    ///
+    /// The `item_id` and `change_key` of the contact are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the contacts folder, or `None` for the
+    ///   default contacts folder
+    /// * `contact` - The contact to create
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The folder does not exist or is not a contacts folder
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Contact, EwsClient, Credentials};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let vcard = std::fs::read_to_string("alice.vcf")?;
+    /// let contact = Contact::from_vcard(&vcard)?;
+    /// let result = client.create_contact(None, &contact).await?;
+    /// println!("Created contact: {}", result.item_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_contact(
+        &self,
+        folder_id: Option<&str>,
+        contact: &Contact,
+    ) -> Result<CreateContactResult, EwsError> {
+        let body = create_contact_request(
+            folder_id.unwrap_or(DEFAULT_CONTACTS_FOLDER),
+            contact,
+            self.target_mailbox(),
+        )?;
+
+        let response = self
+            .make_raw_operation_request("CreateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("CreateItem", &response)?;
+
+        let id = message
+            .descendant(&["Items", "Contact", "ItemId"])
+            .ok_or(EwsError::MissingIdInResponse)?;
+
+        Ok(CreateContactResult {
+            item_id: id.attribute("Id").ok_or(EwsError::MissingIdInResponse)?.to_string(),
+            change_key: id.attribute("ChangeKey").map(ToString::to_string),
+        })
+    }
+
+    /// Updates an existing contact.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the contact
+    /// * `change_key` - The change key of the contact, if known. When
+    ///   provided, the update fails if the contact was changed since it was
+    ///   read
+    /// * `update` - The changes to apply
+    ///
+    /// # Returns
+    ///
+    /// The new change key of the contact.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The update does not change anything
+    /// - The contact cannot be found, or was changed since it was read
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn update_contact(
+        &self,
+        item_id: &str,
+        change_key: Option<&str>,
+        update: &ContactUpdate,
+    ) -> Result<String, EwsError> {
+        let updates = update.updates_xml();
+
+        if updates.is_empty() {
+            return Err(EwsError::Processing {
+                message: "contact update does not change anything".to_string(),
+            });
+        }
+
+        let body = update_contact_request(item_id, change_key, &updates);
+
+        let response = self
+            .make_raw_operation_request("UpdateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("UpdateItem", &response)?;
+
+        message
+            .descendant(&["Items", "Contact", "ItemId"])
+            .and_then(|id| id.attribute("ChangeKey"))
+            .map(ToString::to_string)
+            .ok_or(EwsError::MissingIdInResponse)
+    }
+
+    /// Deletes a contact by moving it to the Deleted Items folder.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the contact
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The contact cannot be found
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn delete_contact(&self, item_id: &str) -> Result<(), EwsError> {
+        let delete_item = DeleteItem {
+            item_ids: vec![BaseItemId::ItemId {
+                id: item_id.to_string(),
+                change_key: None,
+            }],
+            delete_type: DeleteType::MoveToDeletedItems,
+            send_meeting_cancellations: None,
+            affected_task_occurrences: None,
+            suppress_read_receipts: None,
+        };
+
+        let response = self
+            .make_operation_request(delete_item, OperationRequestOptions::default())
+            .await?
+            .into_response_messages();
+        let response = single_response_or_error(response)?;
+        process_response_message_class(DeleteItem::NAME, response)?;
+
+        Ok(())
+    }
+}
+
+/// Removes the `SMTP:` routing prefix Exchange sometimes puts in front of
+/// email addresses.
+fn strip_smtp_prefix(address: &str) -> &str {
+    address
+        .strip_prefix("SMTP:")
+        .or_else(|| address.strip_prefix("smtp:"))
+        .unwrap_or(address)
+}
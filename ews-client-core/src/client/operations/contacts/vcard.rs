@@ -0,0 +1,566 @@
+//! Conversion of contacts to and from vCard.
+//!
+//! Contacts are written as vCard 4.0 (RFC 6350). Reading also accepts the
+//! vCard 3.0 and 2.1 forms most address books still export, e.g. bare type
+//! parameters (`TEL;WORK;VOICE:...`) and `TYPE=pref`.
+
+use time::{Date, Month, OffsetDateTime};
+
+use crate::client::{
+    EwsError,
+    operations::{
+        contacts::{
+            Contact, ContactEmailAddress, ContactPhoneNumber, ContactPhysicalAddress, EmailAddressKey, PhoneNumberKey,
+            PhysicalAddressKey,
+        },
+        content_lines::{escape_value, join_lines, parse_content_line, unescape_value, unfold_lines},
+    },
+};
+
+/// A property of a vCard, e.g. `TEL;TYPE=work:+1 555 0100`.
+struct Property {
+    /// The name of the property, in upper case and without its group.
+    name: String,
+    /// The values of the `TYPE` parameters, in lower case.
+    types: Vec<String>,
+    /// The value of the `PREF` parameter, if any.
+    pref: Option<u8>,
+    /// The raw (still escaped) value of the property.
+    value: String,
+}
+
+impl Contact {
+    /// Writes the contact as a vCard 4.0.
+    ///
+    /// Exchange-specific information which vCard cannot represent (e.g. the
+    /// slot of each email address) is mapped to the closest vCard equivalent.
+    pub fn to_vcard(&self) -> String {
+        let mut lines = vec!["BEGIN:VCARD".to_string(), "VERSION:4.0".to_string()];
+
+        // FN is the only required property.
+        let formatted_name = self
+            .display_name
+            .clone()
+            .or_else(|| {
+                let parts: Vec<&str> = [&self.given_name, &self.middle_name, &self.surname]
+                    .into_iter()
+                    .filter_map(|part| part.as_deref())
+                    .collect();
+                (!parts.is_empty()).then(|| parts.join(" "))
+            })
+            .or_else(|| self.email_addresses.first().map(|email| email.address.clone()))
+            .unwrap_or_default();
+        lines.push(format!("FN:{}", escape_value(&formatted_name)));
+
+        if self.surname.is_some() || self.given_name.is_some() || self.middle_name.is_some() {
+            lines.push(format!(
+                "N:{};{};{};;",
+                escape_value(self.surname.as_deref().unwrap_or_default()),
+                escape_value(self.given_name.as_deref().unwrap_or_default()),
+                escape_value(self.middle_name.as_deref().unwrap_or_default()),
+            ));
+        }
+        if let Some(nickname) = &self.nickname {
+            lines.push(format!("NICKNAME:{}", escape_value(nickname)));
+        }
+        if self.company_name.is_some() || self.department.is_some() {
+            let mut org = escape_value(self.company_name.as_deref().unwrap_or_default());
+            if let Some(department) = &self.department {
+                org.push(';');
+                org.push_str(&escape_value(department));
+            }
+            lines.push(format!("ORG:{org}"));
+        }
+        if let Some(job_title) = &self.job_title {
+            lines.push(format!("TITLE:{}", escape_value(job_title)));
+        }
+
+        let mut email_addresses: Vec<&ContactEmailAddress> = self.email_addresses.iter().collect();
+        email_addresses.sort_by_key(|email| email.key);
+        for email in email_addresses {
+            let pref = if email.key == EmailAddressKey::EmailAddress1 {
+                ";PREF=1"
+            } else {
+                ""
+            };
+            lines.push(format!("EMAIL{pref}:{}", escape_value(&email.address)));
+        }
+
+        for phone in &self.phone_numbers {
+            let pref = if phone.key == PhoneNumberKey::PrimaryPhone {
+                ";PREF=1"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "TEL;VALUE=text;TYPE=\"{}\"{pref}:{}",
+                phone_types(phone.key),
+                escape_value(&phone.number)
+            ));
+        }
+
+        for address in &self.physical_addresses {
+            let address_type = match address.key {
+                PhysicalAddressKey::Home => ";TYPE=home",
+                PhysicalAddressKey::Business => ";TYPE=work",
+                PhysicalAddressKey::Other => "",
+            };
+            let component = |value: &Option<String>| escape_value(value.as_deref().unwrap_or_default());
+            lines.push(format!(
+                "ADR{address_type}:;;{};{};{};{};{}",
+                component(&address.street),
+                component(&address.city),
+                component(&address.state),
+                component(&address.postal_code),
+                component(&address.country_or_region),
+            ));
+        }
+
+        if let Some(birthday) = self
+            .birthday
+            .and_then(|birthday| OffsetDateTime::from_unix_timestamp(birthday).ok())
+        {
+            lines.push(format!(
+                "BDAY:{:04}{:02}{:02}",
+                birthday.year(),
+                u8::from(birthday.month()),
+                birthday.day()
+            ));
+        }
+        if let Some(notes) = &self.notes {
+            lines.push(format!("NOTE:{}", escape_value(notes)));
+        }
+
+        lines.push("END:VCARD".to_string());
+
+        join_lines(&lines)
+    }
+
+    /// Reads a contact from the first vCard in the given text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text contains no complete vCard.
+    pub fn from_vcard(text: &str) -> Result<Self, EwsError> {
+        parse_vcards(text)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| EwsError::Processing {
+                message: "no vCard found".to_string(),
+            })
+    }
+}
+
+/// Reads every vCard in the given text (e.g. a `.vcf` file exported from an
+/// address book) into contacts.
+///
+/// # Errors
+///
+/// Returns an error if a vCard is not terminated by `END:VCARD`.
+pub fn parse_vcards(text: &str) -> Result<Vec<Contact>, EwsError> {
+    let mut contacts = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in unfold_lines(text) {
+        let Some(property) = parse_property(&line) else {
+            continue;
+        };
+
+        match property.name.as_str() {
+            "BEGIN" if property.value.eq_ignore_ascii_case("VCARD") => {
+                current = Some(Vec::new());
+            }
+            "END" if property.value.eq_ignore_ascii_case("VCARD") => {
+                if let Some(properties) = current.take() {
+                    contacts.push(contact_from_properties(properties));
+                }
+            }
+            _ => {
+                if let Some(properties) = current.as_mut() {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    if current.is_some() {
+        return Err(EwsError::Processing {
+            message: "vCard is not terminated by END:VCARD".to_string(),
+        });
+    }
+
+    Ok(contacts)
+}
+
+/// Builds a contact from the properties of a vCard.
+fn contact_from_properties(properties: Vec<Property>) -> Contact {
+    let mut contact = Contact::default();
+    let mut email_addresses: Vec<(u8, String)> = Vec::new();
+
+    for property in properties {
+        match property.name.as_str() {
+            "FN" => contact.display_name = non_empty(unescape_value(&property.value)),
+            "N" => {
+                let components = split_components(&property.value);
+                let component = |index: usize| components.get(index).cloned().and_then(non_empty);
+                contact.surname = component(0);
+                contact.given_name = component(1);
+                contact.middle_name = component(2);
+            }
+            "NICKNAME" => contact.nickname = non_empty(unescape_value(&property.value)),
+            "ORG" => {
+                let components = split_components(&property.value);
+                contact.company_name = components.first().cloned().and_then(non_empty);
+                contact.department = components.get(1).cloned().and_then(non_empty);
+            }
+            "TITLE" => contact.job_title = non_empty(unescape_value(&property.value)),
+            "EMAIL" => {
+                let address = unescape_value(&property.value);
+                let address = address.strip_prefix("mailto:").unwrap_or(&address).trim();
+                if !address.is_empty() {
+                    email_addresses.push((property.pref.unwrap_or(u8::MAX), address.to_string()));
+                }
+            }
+            "TEL" => {
+                let number = unescape_value(&property.value);
+                let number = number.strip_prefix("tel:").unwrap_or(&number).trim().to_string();
+                if number.is_empty() {
+                    continue;
+                }
+                match phone_key(&property.types, &contact.phone_numbers) {
+                    Some(key) => contact.phone_numbers.push(ContactPhoneNumber { key, number }),
+                    None => log::debug!("skipping phone number {number}, all matching slots are used"),
+                }
+            }
+            "ADR" => {
+                let key = if has_type(&property.types, "work") {
+                    PhysicalAddressKey::Business
+                } else if has_type(&property.types, "home") {
+                    PhysicalAddressKey::Home
+                } else {
+                    PhysicalAddressKey::Other
+                };
+                if contact.physical_addresses.iter().any(|address| address.key == key) {
+                    log::debug!("skipping additional {} address", key.as_str());
+                    continue;
+                }
+
+                let components = split_components(&property.value);
+                let component = |index: usize| components.get(index).cloned().and_then(non_empty);
+                contact.physical_addresses.push(ContactPhysicalAddress {
+                    key,
+                    street: component(2),
+                    city: component(3),
+                    state: component(4),
+                    postal_code: component(5),
+                    country_or_region: component(6),
+                });
+            }
+            "BDAY" => contact.birthday = parse_birthday(&property.value),
+            "NOTE" => contact.notes = non_empty(unescape_value(&property.value)),
+            _ => {}
+        }
+    }
+
+    // The preferred addresses go first; Exchange only has room for three.
+    email_addresses.sort_by_key(|(pref, _)| *pref);
+    if email_addresses.len() > EmailAddressKey::ALL.len() {
+        log::debug!("keeping only the first {} email addresses", EmailAddressKey::ALL.len());
+    }
+    contact.email_addresses = EmailAddressKey::ALL
+        .into_iter()
+        .zip(email_addresses)
+        .map(|(key, (_, address))| ContactEmailAddress { key, address })
+        .collect();
+
+    contact
+}
+
+/// The vCard `TYPE` parameter value for a kind of phone number.
+fn phone_types(key: PhoneNumberKey) -> &'static str {
+    match key {
+        PhoneNumberKey::BusinessPhone | PhoneNumberKey::BusinessPhone2 | PhoneNumberKey::CompanyMainPhone => {
+            "work,voice"
+        }
+        PhoneNumberKey::BusinessFax => "work,fax",
+        PhoneNumberKey::HomePhone | PhoneNumberKey::HomePhone2 => "home,voice",
+        PhoneNumberKey::HomeFax => "home,fax",
+        PhoneNumberKey::MobilePhone | PhoneNumberKey::CarPhone | PhoneNumberKey::RadioPhone => "cell",
+        PhoneNumberKey::OtherFax => "fax",
+        PhoneNumberKey::Pager => "pager",
+        PhoneNumberKey::TtyTddPhone => "textphone",
+        PhoneNumberKey::AssistantPhone
+        | PhoneNumberKey::Callback
+        | PhoneNumberKey::Isdn
+        | PhoneNumberKey::OtherTelephone
+        | PhoneNumberKey::PrimaryPhone
+        | PhoneNumberKey::Telex => "voice",
+    }
+}
+
+/// Picks the kind of phone number for a vCard `TEL` property with the given
+/// types, among the kinds not used yet.
+fn phone_key(types: &[String], used: &[ContactPhoneNumber]) -> Option<PhoneNumberKey> {
+    let candidates: &[PhoneNumberKey] = if has_type(types, "fax") {
+        if has_type(types, "work") {
+            &[PhoneNumberKey::BusinessFax]
+        } else if has_type(types, "home") {
+            &[PhoneNumberKey::HomeFax]
+        } else {
+            &[PhoneNumberKey::OtherFax]
+        }
+    } else if has_type(types, "cell") {
+        &[PhoneNumberKey::MobilePhone]
+    } else if has_type(types, "pager") {
+        &[PhoneNumberKey::Pager]
+    } else if has_type(types, "textphone") {
+        &[PhoneNumberKey::TtyTddPhone]
+    } else if has_type(types, "work") {
+        &[PhoneNumberKey::BusinessPhone, PhoneNumberKey::BusinessPhone2]
+    } else if has_type(types, "home") {
+        &[PhoneNumberKey::HomePhone, PhoneNumberKey::HomePhone2]
+    } else {
+        &[PhoneNumberKey::OtherTelephone, PhoneNumberKey::PrimaryPhone]
+    };
+
+    candidates
+        .iter()
+        .copied()
+        .find(|candidate| used.iter().all(|phone| phone.key != *candidate))
+}
+
+/// Whether the given type is among the types of a property.
+fn has_type(types: &[String], wanted: &str) -> bool {
+    types.iter().any(|value| value == wanted)
+}
+
+/// Parses a vCard birthday (`19850412`, `1985-04-12`, optionally followed by
+/// a time) into a Unix timestamp at midnight UTC. Dates without a year (e.g.
+/// `--0412`) cannot be represented and are ignored.
+fn parse_birthday(value: &str) -> Option<i64> {
+    let date = value.split('T').next().unwrap_or_default().replace('-', "");
+    if date.len() != 8 || !date.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let year = date.get(0..4)?.parse().ok()?;
+    let month = Month::try_from(date.get(4..6)?.parse::<u8>().ok()?).ok()?;
+    let day = date.get(6..8)?.parse().ok()?;
+
+    Date::from_calendar_date(year, month, day)
+        .ok()
+        .map(|date| date.midnight().assume_utc().unix_timestamp())
+}
+
+/// Parses a logical line of a vCard into a property.
+fn parse_property(line: &str) -> Option<Property> {
+    let line = parse_content_line(line)?;
+
+    let mut types = Vec::new();
+    let mut pref = None;
+    for (parameter_name, parameter_value) in &line.parameters {
+        match parameter_name.as_str() {
+            // vCard 2.1 allows bare types, e.g. `TEL;WORK;VOICE:...`.
+            "TYPE" | "" => {
+                for value in parameter_value.split(',') {
+                    let value = value.trim().to_ascii_lowercase();
+                    // vCard 3.0 marks preferred values with a type.
+                    if value == "pref" {
+                        pref = pref.or(Some(1));
+                    } else if !value.is_empty() {
+                        types.push(value);
+                    }
+                }
+            }
+            "PREF" => pref = parameter_value.trim().parse().ok(),
+            _ => {}
+        }
+    }
+
+    Some(Property {
+        name: line.name,
+        types,
+        pref,
+        value: line.value,
+    })
+}
+
+/// Splits a structured value (e.g. of `N` or `ADR`) on the semicolons which
+/// are not escaped, and unescapes each component.
+fn split_components(value: &str) -> Vec<String> {
+    let mut components = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            ';' => components.push(unescape_value(&std::mem::take(&mut current))),
+            _ => current.push(c),
+        }
+    }
+    components.push(unescape_value(&current));
+
+    components
+}
+
+/// Turns an empty string into `None`.
+fn non_empty(value: String) -> Option<String> {
+    (!value.trim().is_empty()).then_some(value)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    fn sample_contact() -> Contact {
+        Contact {
+            display_name: Some("Alice Example".to_string()),
+            given_name: Some("Alice".to_string()),
+            surname: Some("Example".to_string()),
+            company_name: Some("Example, Inc.".to_string()),
+            department: Some("R&D".to_string()),
+            job_title: Some("Engineer".to_string()),
+            email_addresses: vec![
+                ContactEmailAddress {
+                    key: EmailAddressKey::EmailAddress2,
+                    address: "alice@home.example".to_string(),
+                },
+                ContactEmailAddress {
+                    key: EmailAddressKey::EmailAddress1,
+                    address: "alice@example.com".to_string(),
+                },
+            ],
+            phone_numbers: vec![
+                ContactPhoneNumber {
+                    key: PhoneNumberKey::MobilePhone,
+                    number: "+1 555 0100".to_string(),
+                },
+                ContactPhoneNumber {
+                    key: PhoneNumberKey::BusinessPhone,
+                    number: "+1 555 0101".to_string(),
+                },
+            ],
+            physical_addresses: vec![ContactPhysicalAddress {
+                key: PhysicalAddressKey::Business,
+                street: Some("1 Main St; Suite 2".to_string()),
+                city: Some("Springfield".to_string()),
+                postal_code: Some("12345".to_string()),
+                country_or_region: Some("USA".to_string()),
+                ..Default::default()
+            }],
+            birthday: Some(482_112_000),
+            notes: Some("Met at the conference.\nLikes tea.".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_to_vcard() {
+        let vcard = sample_contact().to_vcard();
+
+        assert!(vcard.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+        assert!(vcard.ends_with("END:VCARD\r\n"));
+        assert!(vcard.contains("FN:Alice Example\r\n"));
+        assert!(vcard.contains("N:Example;Alice;;;\r\n"));
+        assert!(vcard.contains("ORG:Example\\, Inc.;R&D\r\n"));
+        assert!(vcard.contains("EMAIL;PREF=1:alice@example.com\r\n"));
+        assert!(vcard.contains("TEL;VALUE=text;TYPE=\"cell\":+1 555 0100\r\n"));
+        assert!(vcard.contains("ADR;TYPE=work:;;1 Main St\\; Suite 2;Springfield;;12345;USA\r\n"));
+        assert!(vcard.contains("BDAY:19850412\r\n"));
+        assert!(vcard.contains("NOTE:Met at the conference.\\nLikes tea.\r\n"));
+    }
+
+    #[test]
+    fn test_vcard_round_trip() {
+        let contact = sample_contact();
+        let parsed = Contact::from_vcard(&contact.to_vcard()).unwrap();
+
+        assert_eq!(parsed.display_name, contact.display_name);
+        assert_eq!(parsed.given_name, contact.given_name);
+        assert_eq!(parsed.surname, contact.surname);
+        assert_eq!(parsed.company_name, contact.company_name);
+        assert_eq!(parsed.department, contact.department);
+        assert_eq!(parsed.job_title, contact.job_title);
+        assert_eq!(parsed.physical_addresses, contact.physical_addresses);
+        assert_eq!(parsed.birthday, contact.birthday);
+        assert_eq!(parsed.notes, contact.notes);
+
+        // The primary address keeps its slot
+        assert_eq!(parsed.email_addresses.len(), 2);
+        assert_eq!(parsed.email_addresses[0].key, EmailAddressKey::EmailAddress1);
+        assert_eq!(parsed.email_addresses[0].address, "alice@example.com");
+
+        assert!(parsed.phone_numbers.contains(&ContactPhoneNumber {
+            key: PhoneNumberKey::MobilePhone,
+            number: "+1 555 0100".to_string(),
+        }));
+        assert!(parsed.phone_numbers.contains(&ContactPhoneNumber {
+            key: PhoneNumberKey::BusinessPhone,
+            number: "+1 555 0101".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_parse_vcard_3_with_folding() {
+        let text = "BEGIN:VCARD\r\n\
+            VERSION:3.0\r\n\
+            FN:Bob\r\n \
+             Builder\r\n\
+            item1.EMAIL;TYPE=INTERNET:bob@example.com\r\n\
+            EMAIL;TYPE=INTERNET,pref:bob@work.example\r\n\
+            TEL;TYPE=WORK,VOICE:555-0100\r\n\
+            TEL;TYPE=WORK,VOICE:555-0101\r\n\
+            TEL;WORK;FAX:555-0102\r\n\
+            TEL;TYPE=HOME:tel:555-0103\r\n\
+            BDAY:1990-02-03\r\n\
+            END:VCARD\r\n";
+
+        let contacts = parse_vcards(text).unwrap();
+        assert_eq!(contacts.len(), 1);
+
+        let contact = &contacts[0];
+        assert_eq!(contact.display_name.as_deref(), Some("BobBuilder"));
+        assert_eq!(contact.email_addresses[0].address, "bob@work.example");
+        assert_eq!(contact.email_addresses[1].address, "bob@example.com");
+
+        let keys: Vec<PhoneNumberKey> = contact.phone_numbers.iter().map(|phone| phone.key).collect();
+        assert_eq!(
+            keys,
+            vec![
+                PhoneNumberKey::BusinessPhone,
+                PhoneNumberKey::BusinessPhone2,
+                PhoneNumberKey::BusinessFax,
+                PhoneNumberKey::HomePhone,
+            ]
+        );
+        assert_eq!(contact.phone_numbers[3].number, "555-0103");
+        assert_eq!(contact.birthday, Some(634_003_200));
+    }
+
+    #[test]
+    fn test_parse_multiple_vcards() {
+        let text = "BEGIN:VCARD\nVERSION:4.0\nFN:One\nEND:VCARD\nBEGIN:VCARD\nVERSION:4.0\nFN:Two\nEND:VCARD\n";
+        let contacts = parse_vcards(text).unwrap();
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(contacts[1].display_name.as_deref(), Some("Two"));
+    }
+
+    #[test]
+    fn test_parse_invalid_vcards() {
+        assert!(Contact::from_vcard("").is_err());
+        assert!(parse_vcards("BEGIN:VCARD\nFN:Unterminated\n").is_err());
+    }
+
+    #[test]
+    fn test_birthday_without_year_is_ignored() {
+        assert_eq!(parse_birthday("--0412"), None);
+        assert_eq!(parse_birthday("19850412T000000Z"), Some(482_112_000));
+    }
+}
@@ -0,0 +1,207 @@
+//! Content lines of vCard (RFC 6350) and iCalendar (RFC 5545) data.
+//!
+//! Both formats are made of lines of the form
+//! `NAME;PARAM=value;PARAM="quoted value":value`, folded at 75 octets, with
+//! the same escaping of text values.
+
+/// The maximum length of a line, in octets, before it is folded.
+pub(crate) const MAX_LINE_LENGTH: usize = 75;
+
+/// A logical (unfolded) line, e.g. `DTSTART;TZID=Europe/Berlin:20240115T113000`.
+pub(crate) struct ContentLine {
+    /// The name of the property, in upper case and without its group (e.g.
+    /// `EMAIL` for `item1.EMAIL`).
+    pub name: String,
+    /// The parameters of the property, with their names in upper case and
+    /// quotes removed from their values. Parameters without a name (e.g. the
+    /// bare types of vCard 2.1, `TEL;WORK:...`) have an empty name.
+    pub parameters: Vec<(String, String)>,
+    /// The raw (still escaped) value of the property.
+    pub value: String,
+}
+
+impl ContentLine {
+    /// The value of the first parameter with the given (upper case) name.
+    pub(crate) fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(parameter_name, _)| parameter_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Splits a text into logical lines, joining folded lines.
+pub(crate) fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        if let Some(continuation) = line.strip_prefix([' ', '\t'])
+            && let Some(previous) = lines.last_mut()
+        {
+            previous.push_str(continuation);
+        } else if !line.trim().is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines
+}
+
+/// Parses a logical line into its name, parameters and value.
+pub(crate) fn parse_content_line(line: &str) -> Option<ContentLine> {
+    // The value starts after the first colon which is not in a quoted
+    // parameter value.
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = line.split_at(colon);
+    let value = value.get(1..).unwrap_or_default();
+
+    let mut parts = head.split(';');
+    let name = parts.next()?;
+    let name = name.rsplit('.').next().unwrap_or(name).to_ascii_uppercase();
+
+    let parameters = parts
+        .map(|parameter| match parameter.split_once('=') {
+            Some((parameter_name, parameter_value)) => (
+                parameter_name.to_ascii_uppercase(),
+                parameter_value.trim_matches('"').to_string(),
+            ),
+            None => (String::new(), parameter.to_string()),
+        })
+        .collect();
+
+    Some(ContentLine {
+        name,
+        parameters,
+        value: value.to_string(),
+    })
+}
+
+/// Escapes a text value.
+pub(crate) fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unescapes a text value.
+pub(crate) fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => unescaped.push(c),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    unescaped
+}
+
+/// Writes a parameter value, quoting it if it contains a separator. Double
+/// quotes and control characters cannot appear in parameter values and are
+/// removed.
+pub(crate) fn quote_parameter_value(value: &str) -> String {
+    let value: String = value.chars().filter(|c| *c != '"' && !c.is_control()).collect();
+    if value.contains([':', ';', ',']) {
+        format!("\"{value}\"")
+    } else {
+        value
+    }
+}
+
+/// Folds a line longer than 75 octets, without splitting a character.
+pub(crate) fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_length = 0;
+
+    for c in line.chars() {
+        if line_length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the length of the new line.
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+
+    folded
+}
+
+/// Joins lines into a text, folding them and ending each with CRLF.
+pub(crate) fn join_lines(lines: &[String]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        text.push_str(&fold_line(line));
+        text.push_str("\r\n");
+    }
+    text
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("NOTE:{}", "é".repeat(60));
+        let folded = fold_line(&line);
+
+        for physical_line in folded.split("\r\n") {
+            assert!(physical_line.len() <= MAX_LINE_LENGTH);
+        }
+        assert_eq!(unfold_lines(&folded), vec![line]);
+    }
+
+    #[test]
+    fn test_parse_content_line() {
+        let line =
+            parse_content_line(r#"attendee;CN="Doe: Jane";ROLE=REQ-PARTICIPANT:mailto:jane@example.com"#).unwrap();
+        assert_eq!(line.name, "ATTENDEE");
+        assert_eq!(line.parameter("CN"), Some("Doe: Jane"));
+        assert_eq!(line.parameter("ROLE"), Some("REQ-PARTICIPANT"));
+        assert_eq!(line.value, "mailto:jane@example.com");
+
+        let line = parse_content_line("item1.TEL;WORK:555-0100").unwrap();
+        assert_eq!(line.name, "TEL");
+        assert_eq!(line.parameters, vec![(String::new(), "WORK".to_string())]);
+
+        assert!(parse_content_line("no value").is_none());
+    }
+
+    #[test]
+    fn test_quote_parameter_value() {
+        assert_eq!(quote_parameter_value("Alice"), "Alice");
+        assert_eq!(quote_parameter_value("Doe, \"Jane\""), "\"Doe, Jane\"");
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let value = "One, two; three\\four\nfive";
+        assert_eq!(escape_value(value), "One\\, two\\; three\\\\four\\nfive");
+        assert_eq!(unescape_value(&escape_value(value)), value);
+    }
+}
@@ -6,7 +6,11 @@ pub mod item;
 
 use ews::{BaseFolderId, Operation, OperationResponse};
 
-use crate::client::{EwsClient, EwsError, OperationRequestOptions, process_response_message_class};
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions, process_raw_response_message, process_response_message_class,
+    raw_requests::folders::{copy_move_request, get_distinguished_folder_ids_request},
+    raw_response_messages,
+};
 
 /// Trait for EWS operations that copy or move folders or items.
 pub trait CopyMoveOperation: Operation + Clone {
@@ -25,6 +29,18 @@ impl EwsClient {
     where
         Op: CopyMoveOperation + CopyMoveOperationBuilder,
     {
+        // The `ews` crate's distinguished folder IDs cannot name a mailbox, so
+        // the distinguished folders of a target mailbox are only reachable
+        // with a raw request.
+        let shared_mailbox = self
+            .target_mailbox()
+            .filter(|_| is_distinguished_folder_id(destination_id));
+        if let Some(mailbox) = shared_mailbox {
+            return self
+                .copy_move_raw(<Op as Operation>::NAME, destination_id, mailbox, ids)
+                .await;
+        }
+
         let response = self
             .make_operation_request(
                 Op::operation_builder(self, destination_id.to_string(), ids),
@@ -49,6 +65,58 @@ impl EwsClient {
 
         Ok(Op::response_to_ids(messages))
     }
+
+    /// Performs a `CopyFolder`, `MoveFolder`, `CopyItem` or `MoveItem`
+    /// operation into a distinguished folder of another mailbox, with a raw
+    /// request.
+    async fn copy_move_raw(
+        &self,
+        op_name: &str,
+        destination_id: &str,
+        mailbox: &str,
+        ids: &[&str],
+    ) -> Result<Vec<String>, EwsError> {
+        let (id_element, results_element) = if op_name.ends_with("Folder") {
+            ("FolderId", "Folders")
+        } else {
+            ("ItemId", "Items")
+        };
+        let body = copy_move_request(
+            op_name,
+            destination_id,
+            ids,
+            self.capabilities().return_new_item_ids,
+            Some(mailbox),
+        );
+
+        let response = self
+            .make_raw_operation_request(op_name, &body, OperationRequestOptions::default())
+            .await?;
+        let messages = raw_response_messages(&response);
+        if messages.len() != ids.len() {
+            return Err(EwsError::UnexpectedResponseMessageCount {
+                expected: ids.len(),
+                actual: messages.len(),
+            });
+        }
+
+        let messages = messages
+            .into_iter()
+            .map(|message| process_raw_response_message(op_name, message))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // As with the `ews` crate's operations, results without an ID (e.g.
+        // items moved to another mailbox) are skipped.
+        Ok(messages
+            .into_iter()
+            .filter_map(|message| {
+                message
+                    .child(results_element)
+                    .and_then(|results| results.children.first())
+                    .and_then(|result| result.child_id(id_element))
+            })
+            .collect())
+    }
 }
 
 /// Extension trait for building copy/move operations.
@@ -65,7 +133,43 @@ pub(crate) fn create_base_folder_id(id: String) -> BaseFolderId {
     }
 }
 
-fn is_distinguished_folder_id(id: &str) -> bool {
+impl EwsClient {
+    /// Looks up the EWS IDs of distinguished folders, in the client's target
+    /// mailbox if any, with a single `GetFolder` request.
+    ///
+    /// Returns one result per requested folder, in the same order, so that
+    /// callers can decide which errors (e.g. missing folders) to skip.
+    pub(crate) async fn get_distinguished_folder_ids(
+        &self,
+        ids: &[&str],
+    ) -> Result<Vec<Result<String, EwsError>>, EwsError> {
+        let body = get_distinguished_folder_ids_request(ids, self.target_mailbox());
+
+        let response = self
+            .make_raw_operation_request("GetFolder", &body, OperationRequestOptions::default())
+            .await?;
+        let messages = raw_response_messages(&response);
+        if messages.len() != ids.len() {
+            return Err(EwsError::UnexpectedResponseMessageCount {
+                expected: ids.len(),
+                actual: messages.len(),
+            });
+        }
+
+        Ok(messages
+            .into_iter()
+            .map(|message| {
+                process_raw_response_message("GetFolder", message)?
+                    .child("Folders")
+                    .and_then(|folders| folders.children.first())
+                    .and_then(|folder| folder.child_id("FolderId"))
+                    .ok_or(EwsError::MissingIdInResponse)
+            })
+            .collect())
+    }
+}
+
+pub(crate) fn is_distinguished_folder_id(id: &str) -> bool {
     matches!(
         id,
         "calendar"
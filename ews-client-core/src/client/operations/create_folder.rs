@@ -1,10 +1,49 @@
 //! Create a folder via EWS.
 
 use crate::client::{
-    EwsClient, EwsError, OperationRequestOptions, process_response_message_class, single_response_or_error,
+    EwsClient, EwsError, OperationRequestOptions, process_response_message_class,
+    raw_requests::folders::{create_folder_request, folder_xml},
+    single_raw_response_or_error, single_response_or_error,
+    xml::XmlElement,
 };
 use ews::{BaseFolderId, Folder, Operation, OperationResponse, create_folder::CreateFolder};
 
+/// The kind of items a folder holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FolderKind {
+    /// Messages.
+    #[default]
+    Mail,
+    /// Calendar items.
+    Calendar,
+    /// Contacts and distribution lists.
+    Contacts,
+    /// Tasks.
+    Tasks,
+}
+
+impl FolderKind {
+    /// The element of the folder in EWS requests.
+    pub(crate) fn element_name(self) -> &'static str {
+        match self {
+            Self::Mail => "t:Folder",
+            Self::Calendar => "t:CalendarFolder",
+            Self::Contacts => "t:ContactsFolder",
+            Self::Tasks => "t:TasksFolder",
+        }
+    }
+
+    /// The folder class given to new folders of this kind.
+    pub fn default_folder_class(self) -> &'static str {
+        match self {
+            Self::Mail => "IPF.Note",
+            Self::Calendar => "IPF.Appointment",
+            Self::Contacts => "IPF.Contact",
+            Self::Tasks => "IPF.Task",
+        }
+    }
+}
+
 impl EwsClient {
     /// Creates a new folder in the specified parent folder.
     ///
@@ -87,6 +126,76 @@ impl EwsClient {
 
         Ok(folder_id)
     }
+
+    /// Creates a new folder of the given kind in the specified parent folder.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_id` - The EWS ID of the parent folder, or a distinguished
+    ///   folder name
+    /// * `name` - The display name for the new folder
+    /// * `kind` - The kind of items the folder holds
+    /// * `folder_class` - The folder class, derived from the kind's default
+    ///   class (e.g. `IPF.Note.Archive`), or `None` for the default class
+    ///
+    /// # Returns
+    ///
+    /// The EWS ID of the newly created folder
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The parent folder does not exist, or already has a folder with the
+    ///   same name
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials, FolderKind};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?;
+    /// let folder_id = client
+    ///     .create_folder_of_kind("calendar", "Team Events", FolderKind::Calendar, None)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_folder_of_kind(
+        &self,
+        parent_id: &str,
+        name: &str,
+        kind: FolderKind,
+        folder_class: Option<&str>,
+    ) -> Result<String, EwsError> {
+        let folder = folder_xml(kind, name, folder_class.unwrap_or(kind.default_folder_class()));
+
+        self.create_raw_folder(parent_id, &folder).await
+    }
+
+    /// Sends a `CreateFolder` request for a single folder, given as the XML
+    /// of its folder element, and returns the ID of the created folder.
+    pub(crate) async fn create_raw_folder(&self, parent_id: &str, folder: &str) -> Result<String, EwsError> {
+        let body = create_folder_request(parent_id, folder, self.target_mailbox());
+
+        let response = self
+            .make_raw_operation_request("CreateFolder", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("CreateFolder", &response)?;
+
+        created_folder_id(message)
+    }
+}
+
+/// Reads the ID of the folder in a `CreateFolder` response message, whatever
+/// the kind of the folder.
+fn created_folder_id(message: &XmlElement) -> Result<String, EwsError> {
+    message
+        .child("Folders")
+        .and_then(|folders| folders.children.first())
+        .and_then(|folder| folder.child_id("FolderId"))
+        .ok_or(EwsError::MissingIdInResponse)
 }
 
 // Note: Integration tests for this module should be placed in the tests/ directory
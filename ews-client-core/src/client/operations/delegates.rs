@@ -0,0 +1,451 @@
+//! Delegate access operations.
+//!
+//! This module contains the `GetDelegate`, `AddDelegate`, `UpdateDelegate`
+//! and `RemoveDelegate` operations, which manage the users who may act on
+//! behalf of a mailbox owner, their access to the owner's default folders,
+//! and where meeting requests for the owner are delivered.
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions, process_raw_response_message,
+    raw_requests::mailbox_settings::{
+        add_delegate_request, get_delegate_request, remove_delegate_request, update_delegate_request,
+    },
+    xml::XmlElement,
+};
+
+/// The access a delegate has to one of the owner's default folders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelegateFolderPermissionLevel {
+    /// No access.
+    None,
+    /// Read, create and change any item.
+    Editor,
+    /// Read items.
+    Reviewer,
+    /// Read and create items, and change the items the delegate created.
+    Author,
+    /// A combination of rights set outside of the delegate model (e.g.
+    /// through folder permissions). Cannot be set through the delegate
+    /// operations.
+    Custom,
+}
+
+impl DelegateFolderPermissionLevel {
+    /// The EWS value of the permission level.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Editor => "Editor",
+            Self::Reviewer => "Reviewer",
+            Self::Author => "Author",
+            Self::Custom => "Custom",
+        }
+    }
+
+    /// Parses a permission level from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "None" => Some(Self::None),
+            "Editor" => Some(Self::Editor),
+            "Reviewer" => Some(Self::Reviewer),
+            "Author" => Some(Self::Author),
+            "Custom" => Some(Self::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// The access a delegate has to each of the owner's default folders.
+///
+/// When adding or updating a delegate, folders left to `None` keep their
+/// current permissions (no access for a new delegate).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DelegatePermissions {
+    /// Access to the Calendar folder.
+    pub calendar: Option<DelegateFolderPermissionLevel>,
+    /// Access to the Tasks folder.
+    pub tasks: Option<DelegateFolderPermissionLevel>,
+    /// Access to the Inbox folder.
+    pub inbox: Option<DelegateFolderPermissionLevel>,
+    /// Access to the Contacts folder.
+    pub contacts: Option<DelegateFolderPermissionLevel>,
+    /// Access to the Notes folder.
+    pub notes: Option<DelegateFolderPermissionLevel>,
+    /// Access to the Journal folder.
+    pub journal: Option<DelegateFolderPermissionLevel>,
+}
+
+impl DelegatePermissions {
+    /// The permission elements, in schema order, with the value of each.
+    pub(crate) fn levels(self) -> [(&'static str, Option<DelegateFolderPermissionLevel>); 6] {
+        [
+            ("CalendarFolderPermissionLevel", self.calendar),
+            ("TasksFolderPermissionLevel", self.tasks),
+            ("InboxFolderPermissionLevel", self.inbox),
+            ("ContactsFolderPermissionLevel", self.contacts),
+            ("NotesFolderPermissionLevel", self.notes),
+            ("JournalFolderPermissionLevel", self.journal),
+        ]
+    }
+
+    /// Reads the permissions from a `DelegatePermissions` element.
+    fn from_xml(element: &XmlElement) -> Self {
+        let level = |name: &str| element.child_text(name).and_then(DelegateFolderPermissionLevel::parse);
+
+        Self {
+            calendar: level("CalendarFolderPermissionLevel"),
+            tasks: level("TasksFolderPermissionLevel"),
+            inbox: level("InboxFolderPermissionLevel"),
+            contacts: level("ContactsFolderPermissionLevel"),
+            notes: level("NotesFolderPermissionLevel"),
+            journal: level("JournalFolderPermissionLevel"),
+        }
+    }
+}
+
+/// Where meeting requests for the mailbox owner are delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliverMeetingRequests {
+    /// To the delegates only, with a copy in the owner's Deleted Items
+    /// folder.
+    DelegatesOnly,
+    /// To the delegates and the owner.
+    DelegatesAndMe,
+    /// To the delegates, with a notification to the owner which cannot be
+    /// answered.
+    DelegatesAndSendInformationToMe,
+    /// To the owner only.
+    NoForward,
+}
+
+impl DeliverMeetingRequests {
+    /// The EWS value of the delivery scope.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DelegatesOnly => "DelegatesOnly",
+            Self::DelegatesAndMe => "DelegatesAndMe",
+            Self::DelegatesAndSendInformationToMe => "DelegatesAndSendInformationToMe",
+            Self::NoForward => "NoForward",
+        }
+    }
+
+    /// Parses a delivery scope from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "DelegatesOnly" => Some(Self::DelegatesOnly),
+            "DelegatesAndMe" => Some(Self::DelegatesAndMe),
+            "DelegatesAndSendInformationToMe" => Some(Self::DelegatesAndSendInformationToMe),
+            "NoForward" => Some(Self::NoForward),
+            _ => None,
+        }
+    }
+}
+
+/// A delegate of a mailbox.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DelegateUser {
+    /// The primary SMTP address of the delegate, which identifies them in
+    /// requests.
+    pub primary_smtp_address: String,
+    /// The display name of the delegate, as returned by the server.
+    pub display_name: Option<String>,
+    /// The security identifier of the delegate, as returned by the server.
+    pub sid: Option<String>,
+    /// The access of the delegate to the owner's default folders. Only
+    /// returned when requested.
+    pub permissions: DelegatePermissions,
+    /// Whether the delegate receives copies of the meeting messages sent to
+    /// the owner.
+    pub receive_copies_of_meeting_messages: bool,
+    /// Whether the delegate can see the owner's private items.
+    pub view_private_items: bool,
+}
+
+impl DelegateUser {
+    /// Creates a delegate without any permission, for use with
+    /// [`EwsClient::add_delegates`].
+    pub fn new(primary_smtp_address: impl Into<String>) -> Self {
+        Self {
+            primary_smtp_address: primary_smtp_address.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Reads a delegate from a `DelegateUser` element.
+    fn from_xml(element: &XmlElement) -> Self {
+        let user_id = element.child("UserId");
+        let text = |name: &str| {
+            user_id
+                .and_then(|user_id| user_id.child_text(name))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)
+        };
+
+        Self {
+            primary_smtp_address: text("PrimarySmtpAddress").unwrap_or_default(),
+            display_name: text("DisplayName"),
+            sid: text("SID"),
+            permissions: element
+                .child("DelegatePermissions")
+                .map(DelegatePermissions::from_xml)
+                .unwrap_or_default(),
+            receive_copies_of_meeting_messages: element.child_bool("ReceiveCopiesOfMeetingMessages").unwrap_or(false),
+            view_private_items: element.child_bool("ViewPrivateItems").unwrap_or(false),
+        }
+    }
+}
+
+/// The delegates of a mailbox and the delivery of its meeting requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegateSettings {
+    /// The delegates of the mailbox.
+    pub delegates: Vec<DelegateUser>,
+    /// Where meeting requests for the owner are delivered, if returned.
+    pub deliver_meeting_requests: Option<DeliverMeetingRequests>,
+}
+
+impl EwsClient {
+    /// Lists the delegates of a mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox owner
+    /// * `include_permissions` - Whether to return the folder permissions of
+    ///   each delegate
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The user may not read the delegates of the mailbox
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Credentials, EwsClient};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let settings = client.get_delegates("boss@example.com", true).await?;
+    /// for delegate in settings.delegates {
+    ///     println!("{}: {:?}", delegate.primary_smtp_address, delegate.permissions.calendar);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_delegates(&self, mailbox: &str, include_permissions: bool) -> Result<DelegateSettings, EwsError> {
+        let body = get_delegate_request(mailbox, include_permissions)?;
+
+        let response = self
+            .make_raw_operation_request("GetDelegate", &body, OperationRequestOptions::default())
+            .await?;
+
+        delegate_settings_from_response("GetDelegate", &response)
+    }
+
+    /// Adds delegates to a mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox owner
+    /// * `delegates` - The delegates to add, with their permissions
+    /// * `deliver_meeting_requests` - Where meeting requests for the owner
+    ///   are delivered from now on, or `None` to keep the current setting
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No delegate is given, or a delegate has no address or custom
+    ///   permissions
+    /// - A delegate cannot be added (e.g. because they already are one)
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn add_delegates(
+        &self,
+        mailbox: &str,
+        delegates: &[DelegateUser],
+        deliver_meeting_requests: Option<DeliverMeetingRequests>,
+    ) -> Result<DelegateSettings, EwsError> {
+        if delegates.is_empty() {
+            return Err(EwsError::Processing {
+                message: "at least one delegate is required".to_string(),
+            });
+        }
+
+        let body = add_delegate_request(mailbox, delegates, deliver_meeting_requests)?;
+
+        let response = self
+            .make_raw_operation_request("AddDelegate", &body, OperationRequestOptions::default())
+            .await?;
+
+        delegate_settings_from_response("AddDelegate", &response)
+    }
+
+    /// Changes the permissions of delegates of a mailbox, and where meeting
+    /// requests for the owner are delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox owner
+    /// * `delegates` - The delegates to change, with their new permissions
+    /// * `deliver_meeting_requests` - Where meeting requests for the owner
+    ///   are delivered from now on, or `None` to keep the current setting
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - There is nothing to change, or a delegate has no address or custom
+    ///   permissions
+    /// - A user is not a delegate of the mailbox
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn update_delegates(
+        &self,
+        mailbox: &str,
+        delegates: &[DelegateUser],
+        deliver_meeting_requests: Option<DeliverMeetingRequests>,
+    ) -> Result<DelegateSettings, EwsError> {
+        if delegates.is_empty() && deliver_meeting_requests.is_none() {
+            return Err(EwsError::Processing {
+                message: "a delegate or a meeting request delivery scope is required".to_string(),
+            });
+        }
+
+        let body = update_delegate_request(mailbox, delegates, deliver_meeting_requests)?;
+
+        let response = self
+            .make_raw_operation_request("UpdateDelegate", &body, OperationRequestOptions::default())
+            .await?;
+
+        delegate_settings_from_response("UpdateDelegate", &response)
+    }
+
+    /// Removes delegates from a mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox owner
+    /// * `delegates` - The primary SMTP addresses of the delegates to remove
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No delegate is given
+    /// - A user is not a delegate of the mailbox
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn remove_delegates(&self, mailbox: &str, delegates: &[&str]) -> Result<(), EwsError> {
+        if delegates.is_empty() {
+            return Err(EwsError::Processing {
+                message: "at least one delegate is required".to_string(),
+            });
+        }
+
+        let body = remove_delegate_request(mailbox, delegates)?;
+
+        let response = self
+            .make_raw_operation_request("RemoveDelegate", &body, OperationRequestOptions::default())
+            .await?;
+
+        delegate_settings_from_response("RemoveDelegate", &response).map(|_| ())
+    }
+}
+
+/// Reads the delegates of a delegate operation response.
+///
+/// The response carries the result of the operation as a whole, and a
+/// response message per delegate which fails on its own (e.g. when adding a
+/// user who already is a delegate).
+fn delegate_settings_from_response(op_name: &str, response: &XmlElement) -> Result<DelegateSettings, EwsError> {
+    let response = process_raw_response_message(op_name, response)?;
+
+    let delegates = response
+        .child("ResponseMessages")
+        .map(|messages| messages.children.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|message| {
+            let message = process_raw_response_message(op_name, message)?;
+            Ok(message.child("DelegateUser").map(DelegateUser::from_xml))
+        })
+        .filter_map(Result::transpose)
+        .collect::<Result<Vec<_>, EwsError>>()?;
+
+    Ok(DelegateSettings {
+        delegates,
+        deliver_meeting_requests: response
+            .child_text("DeliverMeetingRequests")
+            .and_then(DeliverMeetingRequests::parse),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delegate_user_round_trip() {
+        let delegate = DelegateUser {
+            permissions: DelegatePermissions {
+                calendar: Some(DelegateFolderPermissionLevel::Editor),
+                inbox: Some(DelegateFolderPermissionLevel::Reviewer),
+                ..DelegatePermissions::default()
+            },
+            receive_copies_of_meeting_messages: true,
+            ..DelegateUser::new("assistant@example.com")
+        };
+        let xml = format!(
+            r#"<Root xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">{}</Root>"#,
+            delegate.to_xml().unwrap()
+        );
+        let root = XmlElement::parse(xml.as_bytes()).unwrap();
+
+        assert_eq!(root.child("DelegateUser").map(DelegateUser::from_xml), Some(delegate));
+
+        // Folders without a level are left out, so that they keep their
+        // current permissions
+        assert!(!xml.contains("TasksFolderPermissionLevel"));
+    }
+
+    #[test]
+    fn test_invalid_delegates_are_rejected() {
+        let custom = DelegateUser {
+            permissions: DelegatePermissions {
+                notes: Some(DelegateFolderPermissionLevel::Custom),
+                ..DelegatePermissions::default()
+            },
+            ..DelegateUser::new("assistant@example.com")
+        };
+        assert!(custom.to_xml().is_err());
+        assert!(DelegateUser::new(" ").to_xml().is_err());
+    }
+
+    #[test]
+    fn test_delegate_response_errors() {
+        let response = XmlElement::parse(
+            br#"<AddDelegateResponse ResponseClass="Success">
+              <ResponseCode>NoError</ResponseCode>
+              <ResponseMessages>
+                <DelegateUserResponseMessageType ResponseClass="Error">
+                  <MessageText>The delegate already exists.</MessageText>
+                  <ResponseCode>ErrorDelegateAlreadyExists</ResponseCode>
+                </DelegateUserResponseMessageType>
+              </ResponseMessages>
+            </AddDelegateResponse>"#,
+        )
+        .unwrap();
+
+        let result = delegate_settings_from_response("AddDelegate", &response);
+        assert!(matches!(
+            result,
+            Err(EwsError::RawResponseError { response_code, .. }) if response_code == "ErrorDelegateAlreadyExists"
+        ));
+    }
+}
@@ -0,0 +1,573 @@
+//! Address lookup operations.
+//!
+//! This module contains the `ResolveNames` operation, which matches a partial
+//! name or address against the Global Address List and the user's contacts
+//! (e.g. for autocompletion while composing a message), the `ExpandDL`
+//! operation, which lists the members of a distribution list, and the
+//! `FindPeople` operation of Exchange 2013 and later, which searches the
+//! user's contacts, the directory and recent correspondents at once.
+
+use std::collections::HashSet;
+
+use futures::future::try_join_all;
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions, OwnedMailbox,
+    operations::contacts::Contact,
+    process_raw_response_message,
+    raw_requests::directory::{expand_dl_request, find_people_request, resolve_names_request},
+    single_raw_response_or_error,
+    xml::XmlElement,
+};
+
+/// The maximum number of people in a single `FindPeople` page.
+const MAX_PEOPLE_PER_PAGE: u32 = 1000;
+
+/// The folders of personas searched by [`EwsClient::find_people`]: the
+/// user's contacts, which include the recipient cache of the people the user
+/// recently corresponded with, and the directory (Global Address List).
+///
+/// A single `FindPeople` request only searches one of them.
+const PEOPLE_FOLDERS: [&str; 2] = ["mycontacts", "directory"];
+
+/// Where `ResolveNames` looks for matching names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResolveNamesSearchScope {
+    /// Only the directory (Global Address List).
+    ActiveDirectory,
+    /// The directory first, then the user's contacts folders.
+    #[default]
+    ActiveDirectoryContacts,
+    /// Only the user's contacts folders.
+    Contacts,
+    /// The user's contacts folders first, then the directory.
+    ContactsActiveDirectory,
+}
+
+impl ResolveNamesSearchScope {
+    /// The EWS value of the search scope.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ActiveDirectory => "ActiveDirectory",
+            Self::ActiveDirectoryContacts => "ActiveDirectoryContacts",
+            Self::Contacts => "Contacts",
+            Self::ContactsActiveDirectory => "ContactsActiveDirectory",
+        }
+    }
+}
+
+/// The kind of recipient a mailbox stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MailboxType {
+    /// A mail-enabled user or resource.
+    Mailbox,
+    /// A distribution list of the directory.
+    PublicDistributionList,
+    /// A distribution list in the user's contacts.
+    PrivateDistributionList,
+    /// A contact in the user's contacts or in the directory.
+    Contact,
+    /// A mail-enabled public folder.
+    PublicFolder,
+    /// An address which is not in the directory nor in the contacts.
+    OneOff,
+    /// A Microsoft 365 group.
+    GroupMailbox,
+    /// A recipient of unknown type.
+    Unknown,
+}
+
+impl MailboxType {
+    /// Parses a mailbox type from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "Mailbox" => Some(Self::Mailbox),
+            "PublicDL" => Some(Self::PublicDistributionList),
+            "PrivateDL" => Some(Self::PrivateDistributionList),
+            "Contact" => Some(Self::Contact),
+            "PublicFolder" => Some(Self::PublicFolder),
+            "OneOff" => Some(Self::OneOff),
+            "GroupMailbox" => Some(Self::GroupMailbox),
+            "Unknown" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+
+    /// Whether the mailbox is a distribution list, which can be expanded with
+    /// [`EwsClient::expand_distribution_list`].
+    pub fn is_distribution_list(self) -> bool {
+        matches!(self, Self::PublicDistributionList | Self::PrivateDistributionList)
+    }
+}
+
+/// A name matched by [`EwsClient::resolve_names`].
+#[derive(Debug, Clone)]
+pub struct ResolvedName {
+    /// The display name and email address of the match.
+    pub mailbox: OwnedMailbox,
+    /// The routing type of the address, usually `SMTP`.
+    pub routing_type: Option<String>,
+    /// The kind of recipient matched.
+    pub mailbox_type: Option<MailboxType>,
+    /// The contact details of the match, if returned by the server. Entries
+    /// of the directory have no item ID.
+    pub contact: Option<Contact>,
+}
+
+impl ResolvedName {
+    /// Reads a match from a `Resolution` element.
+    fn from_xml(element: &XmlElement) -> Option<Self> {
+        let mailbox = element.child("Mailbox")?;
+
+        Some(Self {
+            mailbox: mailbox_from_xml(mailbox),
+            routing_type: mailbox.child_text("RoutingType").map(ToString::to_string),
+            mailbox_type: mailbox.child_text("MailboxType").and_then(MailboxType::parse),
+            contact: element.child("Contact").map(Contact::from_xml_without_id),
+        })
+    }
+}
+
+/// A page of results to request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paging {
+    /// The index of the first result of the page.
+    pub offset: u32,
+    /// The maximum number of results in the page.
+    pub max_entries: u32,
+}
+
+impl Default for Paging {
+    /// The first 100 results.
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            max_entries: 100,
+        }
+    }
+}
+
+/// A person found by [`EwsClient::find_people`], aggregating the contacts,
+/// directory entries and correspondents which stand for the same person.
+#[derive(Debug, Clone)]
+pub struct Persona {
+    /// The EWS ID of the persona.
+    pub persona_id: String,
+    /// The display name of the person.
+    pub display_name: Option<String>,
+    /// The given name of the person.
+    pub given_name: Option<String>,
+    /// The surname of the person.
+    pub surname: Option<String>,
+    /// The company of the person.
+    pub company_name: Option<String>,
+    /// The preferred email address of the person.
+    pub email_address: Option<OwnedMailbox>,
+    /// All the email addresses of the person.
+    pub email_addresses: Vec<OwnedMailbox>,
+    /// Whether the persona is a distribution list rather than a person.
+    pub is_distribution_list: bool,
+    /// How relevant the person is to the query, higher being more relevant.
+    pub relevance_score: Option<i32>,
+}
+
+impl Persona {
+    /// Reads a persona from a `Persona` element.
+    fn from_xml(element: &XmlElement) -> Option<Self> {
+        let text = |name: &str| {
+            element
+                .child_text(name)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)
+        };
+
+        Some(Self {
+            persona_id: element.child_id("PersonaId")?,
+            display_name: text("DisplayName"),
+            given_name: text("GivenName"),
+            surname: text("Surname"),
+            company_name: text("CompanyName"),
+            email_address: element.child("EmailAddress").map(mailbox_from_xml),
+            email_addresses: element
+                .child("EmailAddresses")
+                .map(|addresses| addresses.children_named("Address").map(mailbox_from_xml).collect())
+                .unwrap_or_default(),
+            is_distribution_list: element
+                .child_text("PersonaType")
+                .is_some_and(|persona_type| persona_type.trim() == "DistributionList"),
+            relevance_score: element.child_parse("RelevanceScore"),
+        })
+    }
+}
+
+/// Result of searching for people.
+#[derive(Debug, Clone)]
+pub struct FindPeopleResult {
+    /// The people in the page, from the most to the least relevant.
+    pub people: Vec<Persona>,
+    /// The total number of people matching the query. It is approximate: a
+    /// person found both in the user's contacts and in the directory may be
+    /// counted twice.
+    pub total_items: Option<u32>,
+    /// The offset to request the next page from, if there is one.
+    pub next_offset: Option<u32>,
+}
+
+impl EwsClient {
+    /// Matches a partial name or email address against the directory and the
+    /// user's contacts, e.g. to autocomplete recipients.
+    ///
+    /// The server returns at most 100 matches. A query without any match
+    /// returns an empty list rather than an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The beginning of a name or address to look for
+    /// * `search_scope` - Where to look for matches
+    /// * `return_full_contact_data` - Whether to return the contact details
+    ///   (phone numbers, job title, ...) of each match
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The query is empty
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Credentials, EwsClient, ResolveNamesSearchScope};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let names = client
+    ///     .resolve_names("ali", ResolveNamesSearchScope::ActiveDirectoryContacts, false)
+    ///     .await?;
+    /// for name in &names {
+    ///     println!("{}", name.mailbox);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_names(
+        &self,
+        query: &str,
+        search_scope: ResolveNamesSearchScope,
+        return_full_contact_data: bool,
+    ) -> Result<Vec<ResolvedName>, EwsError> {
+        if query.trim().is_empty() {
+            return Err(EwsError::Processing {
+                message: "cannot resolve an empty name".to_string(),
+            });
+        }
+
+        let body = resolve_names_request(query.trim(), search_scope, return_full_contact_data);
+
+        let response = self
+            .make_raw_operation_request("ResolveNames", &body, OperationRequestOptions::default())
+            .await?;
+
+        // Several matches are reported as a warning, and no match at all as
+        // an error.
+        let message = match single_raw_response_or_error("ResolveNames", &response) {
+            Ok(message) => message,
+            Err(EwsError::RawResponseError { response_code, .. })
+                if response_code == "ErrorNameResolutionNoResults" =>
+            {
+                return Ok(Vec::new());
+            }
+            Err(err) => return Err(err),
+        };
+
+        Ok(message
+            .child("ResolutionSet")
+            .map(|set| {
+                set.children_named("Resolution")
+                    .filter_map(ResolvedName::from_xml)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Lists the members of a distribution list of the directory.
+    ///
+    /// Only direct members are returned; members which are distribution lists
+    /// themselves can be expanded with further calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The email address of the distribution list
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The address is empty
+    /// - The address is not a distribution list the user may expand
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn expand_distribution_list(&self, address: &str) -> Result<Vec<OwnedMailbox>, EwsError> {
+        if address.trim().is_empty() {
+            return Err(EwsError::Processing {
+                message: "cannot expand a distribution list without an address".to_string(),
+            });
+        }
+
+        let body = expand_dl_request(address.trim());
+
+        let response = self
+            .make_raw_operation_request("ExpandDL", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("ExpandDL", &response)?;
+
+        Ok(message
+            .child("DLExpansion")
+            .map(|expansion| expansion.children_named("Mailbox").map(mailbox_from_xml).collect())
+            .unwrap_or_default())
+    }
+
+    /// Searches for people matching a query among the user's contacts, the
+    /// directory and the people the user recently corresponded with, ordered
+    /// by relevance. Requires Exchange 2013 or later.
+    ///
+    /// The user's contacts and the directory are searched concurrently, and
+    /// their results merged by relevance. A person found in both is returned
+    /// once, as the more relevant of the two.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The name or address to look for
+    /// * `paging` - The page of results to return, of at most 1000 people.
+    ///   Only the first 1000 results of each source can be paged through
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The server is not known to run Exchange 2013 or later
+    ///   ([`EwsError::Unsupported`])
+    /// - The query is empty, or the page size is 0 or larger than 1000
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Credentials, EwsClient, Paging};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let page = client.find_people("alice", Paging::default()).await?;
+    /// for person in &page.people {
+    ///     println!("{:?} {:?}", person.display_name, person.email_address);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_people(&self, query: &str, paging: Paging) -> Result<FindPeopleResult, EwsError> {
+        let capabilities = self.capabilities();
+        capabilities.require(capabilities.find_people, "FindPeople")?;
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(EwsError::Processing {
+                message: "cannot search for people without a query".to_string(),
+            });
+        }
+        if !(1..=MAX_PEOPLE_PER_PAGE).contains(&paging.max_entries) {
+            return Err(EwsError::Processing {
+                message: format!(
+                    "a page of people must hold 1 to {MAX_PEOPLE_PER_PAGE} entries, got {}",
+                    paging.max_entries
+                ),
+            });
+        }
+
+        // The sources are ranked separately, so the page can only be cut once
+        // the results of both up to its end are merged.
+        let end = paging
+            .offset
+            .saturating_add(paging.max_entries)
+            .min(MAX_PEOPLE_PER_PAGE);
+        let sources = try_join_all(
+            PEOPLE_FOLDERS
+                .iter()
+                .map(|folder| self.find_people_in_folder(folder, query, end)),
+        )
+        .await?;
+
+        // More people match than were fetched from a source, so there are
+        // more pages after the merged results.
+        let truncated = sources.iter().any(|(people, total)| {
+            total.is_some_and(|total| usize::try_from(total).is_ok_and(|total| total > people.len()))
+        });
+        let total_items = sources.iter().map(|(_, total)| *total).sum::<Option<u32>>();
+
+        // Sort by decreasing relevance, keeping the order of the sources for
+        // people as relevant, then drop the less relevant duplicates.
+        let mut people: Vec<Persona> = sources.into_iter().flat_map(|(people, _)| people).collect();
+        people.sort_by_key(|person| std::cmp::Reverse(person.relevance_score));
+        let mut seen_addresses = HashSet::new();
+        let fetched = people.len();
+        people.retain(|person| {
+            person
+                .email_address
+                .as_ref()
+                .and_then(|mailbox| mailbox.email_address.as_deref())
+                .is_none_or(|address| seen_addresses.insert(address.to_lowercase()))
+        });
+        let duplicates = u32::try_from(fetched - people.len()).unwrap_or(u32::MAX);
+
+        let available = people.len();
+        let people: Vec<Persona> = people
+            .into_iter()
+            .skip(usize::try_from(paging.offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(paging.max_entries).unwrap_or(usize::MAX))
+            .collect();
+        let page_end = paging
+            .offset
+            .saturating_add(u32::try_from(people.len()).unwrap_or(u32::MAX));
+        let has_more = truncated || usize::try_from(page_end).is_ok_and(|page_end| page_end < available);
+        let next_offset = (has_more && !people.is_empty() && page_end < MAX_PEOPLE_PER_PAGE).then_some(page_end);
+
+        Ok(FindPeopleResult {
+            people,
+            total_items: total_items.map(|total| total.saturating_sub(duplicates)),
+            next_offset,
+        })
+    }
+
+    /// Searches a single folder of personas with a `FindPeople` request.
+    ///
+    /// Returns the first `max_entries` people matching the query, from the
+    /// most to the least relevant, and the number of people matching it in
+    /// the folder.
+    async fn find_people_in_folder(
+        &self,
+        folder: &str,
+        query: &str,
+        max_entries: u32,
+    ) -> Result<(Vec<Persona>, Option<u32>), EwsError> {
+        let body = find_people_request(folder, query, max_entries);
+
+        let response = self
+            .make_raw_operation_request("FindPeople", &body, OperationRequestOptions::default())
+            .await?;
+        // Unlike most operations, the response is not wrapped in response
+        // messages.
+        let response = process_raw_response_message("FindPeople", &response)?;
+
+        let people = response
+            .child("People")
+            .map(|people| people.children_named("Persona").filter_map(Persona::from_xml).collect())
+            .unwrap_or_default();
+
+        Ok((people, response.child_parse("TotalNumberOfPeopleInView")))
+    }
+}
+
+/// Reads the display name and email address of a `Mailbox` element.
+pub(crate) fn mailbox_from_xml(element: &XmlElement) -> OwnedMailbox {
+    let text = |name: &str| {
+        element
+            .child_text(name)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToString::to_string)
+    };
+
+    OwnedMailbox {
+        name: text("Name"),
+        email_address: text("EmailAddress"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_name_from_xml() {
+        let xml = br#"<t:Resolution xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+              <t:Mailbox>
+                <t:Name>Alice Smith</t:Name>
+                <t:EmailAddress>alice@example.com</t:EmailAddress>
+                <t:RoutingType>SMTP</t:RoutingType>
+                <t:MailboxType>Mailbox</t:MailboxType>
+              </t:Mailbox>
+              <t:Contact>
+                <t:DisplayName>Alice Smith</t:DisplayName>
+                <t:GivenName>Alice</t:GivenName>
+                <t:JobTitle>Engineer</t:JobTitle>
+                <t:ContactSource>ActiveDirectory</t:ContactSource>
+              </t:Contact>
+            </t:Resolution>"#;
+        let element = XmlElement::parse(xml).unwrap();
+        let name = ResolvedName::from_xml(&element).unwrap();
+
+        assert_eq!(name.mailbox.name.as_deref(), Some("Alice Smith"));
+        assert_eq!(name.mailbox.email_address.as_deref(), Some("alice@example.com"));
+        assert_eq!(name.routing_type.as_deref(), Some("SMTP"));
+        assert_eq!(name.mailbox_type, Some(MailboxType::Mailbox));
+
+        let contact = name.contact.unwrap();
+        assert_eq!(contact.item_id, None);
+        assert_eq!(contact.job_title.as_deref(), Some("Engineer"));
+    }
+
+    #[test]
+    fn test_persona_from_xml() {
+        let xml = br#"<t:Persona xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+              <t:PersonaId Id="AAUQAA=="/>
+              <t:PersonaType>Person</t:PersonaType>
+              <t:DisplayName>Alice Smith</t:DisplayName>
+              <t:EmailAddress>
+                <t:Name>Alice Smith</t:Name>
+                <t:EmailAddress>alice@example.com</t:EmailAddress>
+                <t:RoutingType>SMTP</t:RoutingType>
+              </t:EmailAddress>
+              <t:EmailAddresses>
+                <t:Address>
+                  <t:Name>Alice Smith</t:Name>
+                  <t:EmailAddress>alice@example.com</t:EmailAddress>
+                </t:Address>
+                <t:Address>
+                  <t:EmailAddress>alice@home.example</t:EmailAddress>
+                </t:Address>
+              </t:EmailAddresses>
+              <t:RelevanceScore>2147483647</t:RelevanceScore>
+            </t:Persona>"#;
+        let element = XmlElement::parse(xml).unwrap();
+        let persona = Persona::from_xml(&element).unwrap();
+
+        assert_eq!(persona.persona_id, "AAUQAA==");
+        assert_eq!(persona.display_name.as_deref(), Some("Alice Smith"));
+        assert_eq!(
+            persona.email_address.unwrap().email_address.as_deref(),
+            Some("alice@example.com")
+        );
+        assert_eq!(persona.email_addresses.len(), 2);
+        assert_eq!(persona.email_addresses[1].name, None);
+        assert!(!persona.is_distribution_list);
+        assert_eq!(persona.relevance_score, Some(i32::MAX));
+    }
+
+    #[test]
+    fn test_mailbox_type() {
+        assert_eq!(
+            MailboxType::parse("PublicDL"),
+            Some(MailboxType::PublicDistributionList)
+        );
+        assert!(MailboxType::PrivateDistributionList.is_distribution_list());
+        assert!(!MailboxType::Mailbox.is_distribution_list());
+        assert_eq!(MailboxType::parse("Other"), None);
+    }
+}
@@ -0,0 +1,489 @@
+//! Folder permission operations.
+//!
+//! This module reads the `PermissionSet` of a folder through `GetFolder`, and
+//! replaces it through `UpdateFolder`, to share folders with other users.
+//! Calendar folders have a permission set of their own, with levels which
+//! give access to free/busy information only.
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions, OwnedMailbox, operations::update_folder::FolderUpdate,
+    raw_requests::folders::get_folder_permissions_request, single_raw_response_or_error, xml::XmlElement,
+};
+
+/// The user a folder permission applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionUser {
+    /// Every authenticated user without a permission of their own.
+    Default,
+    /// Unauthenticated users.
+    Anonymous,
+    /// A user of the organization, identified by their primary SMTP address.
+    User(OwnedMailbox),
+}
+
+impl PermissionUser {
+    /// Reads the user from a `UserId` element.
+    fn from_xml(element: &XmlElement) -> Self {
+        match element.child_text("DistinguishedUser").map(str::trim) {
+            Some("Default") => Self::Default,
+            Some("Anonymous") => Self::Anonymous,
+            _ => {
+                let text = |name: &str| {
+                    element
+                        .child_text(name)
+                        .map(str::trim)
+                        .filter(|value| !value.is_empty())
+                        .map(ToString::to_string)
+                };
+                Self::User(OwnedMailbox {
+                    name: text("DisplayName"),
+                    email_address: text("PrimarySmtpAddress"),
+                })
+            }
+        }
+    }
+}
+
+/// A predefined set of folder rights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionLevel {
+    /// No access.
+    None,
+    /// All rights, including changing the permissions of the folder.
+    Owner,
+    /// Create, read, change and delete items, and create subfolders.
+    PublishingEditor,
+    /// Create, read, change and delete items.
+    Editor,
+    /// Create and read items, change and delete owned items, and create
+    /// subfolders.
+    PublishingAuthor,
+    /// Create and read items, and change and delete owned items.
+    Author,
+    /// Create and read items, and delete owned items.
+    NoneditingAuthor,
+    /// Read items.
+    Reviewer,
+    /// Create items.
+    Contributor,
+    /// See free/busy times only. Calendar folders only.
+    FreeBusyTimeOnly,
+    /// See free/busy times, subjects and locations. Calendar folders only.
+    FreeBusyTimeAndSubjectAndLocation,
+    /// The individual rights of the permission.
+    Custom,
+}
+
+impl PermissionLevel {
+    /// The EWS value of the permission level.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Owner => "Owner",
+            Self::PublishingEditor => "PublishingEditor",
+            Self::Editor => "Editor",
+            Self::PublishingAuthor => "PublishingAuthor",
+            Self::Author => "Author",
+            Self::NoneditingAuthor => "NoneditingAuthor",
+            Self::Reviewer => "Reviewer",
+            Self::Contributor => "Contributor",
+            Self::FreeBusyTimeOnly => "FreeBusyTimeOnly",
+            Self::FreeBusyTimeAndSubjectAndLocation => "FreeBusyTimeAndSubjectAndLocation",
+            Self::Custom => "Custom",
+        }
+    }
+
+    /// Parses a permission level from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "None" => Some(Self::None),
+            "Owner" => Some(Self::Owner),
+            "PublishingEditor" => Some(Self::PublishingEditor),
+            "Editor" => Some(Self::Editor),
+            "PublishingAuthor" => Some(Self::PublishingAuthor),
+            "Author" => Some(Self::Author),
+            "NoneditingAuthor" => Some(Self::NoneditingAuthor),
+            "Reviewer" => Some(Self::Reviewer),
+            "Contributor" => Some(Self::Contributor),
+            "FreeBusyTimeOnly" => Some(Self::FreeBusyTimeOnly),
+            "FreeBusyTimeAndSubjectAndLocation" => Some(Self::FreeBusyTimeAndSubjectAndLocation),
+            "Custom" => Some(Self::Custom),
+            _ => None,
+        }
+    }
+
+    /// Whether the level can only be given on calendar folders.
+    pub(crate) fn is_calendar_only(self) -> bool {
+        matches!(self, Self::FreeBusyTimeOnly | Self::FreeBusyTimeAndSubjectAndLocation)
+    }
+}
+
+/// Which items a permission allows changing or deleting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ItemAccess {
+    /// No item.
+    #[default]
+    None,
+    /// The items the user created.
+    Owned,
+    /// All items.
+    All,
+}
+
+impl ItemAccess {
+    /// The EWS value of the access.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Owned => "Owned",
+            Self::All => "All",
+        }
+    }
+
+    /// Parses an access from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "None" => Some(Self::None),
+            "Owned" => Some(Self::Owned),
+            "All" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// How much of the items a permission allows reading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadAccess {
+    /// Nothing.
+    #[default]
+    None,
+    /// The free/busy times of calendar items. Calendar folders only.
+    TimeOnly,
+    /// The free/busy times, subjects and locations of calendar items.
+    /// Calendar folders only.
+    TimeAndSubjectAndLocation,
+    /// The whole items.
+    FullDetails,
+}
+
+impl ReadAccess {
+    /// The EWS value of the access.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::TimeOnly => "TimeOnly",
+            Self::TimeAndSubjectAndLocation => "TimeAndSubjectAndLocation",
+            Self::FullDetails => "FullDetails",
+        }
+    }
+
+    /// Parses an access from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "None" => Some(Self::None),
+            "TimeOnly" => Some(Self::TimeOnly),
+            "TimeAndSubjectAndLocation" => Some(Self::TimeAndSubjectAndLocation),
+            "FullDetails" => Some(Self::FullDetails),
+            _ => None,
+        }
+    }
+}
+
+/// The rights of a user on a folder.
+///
+/// The individual rights are returned for every level, but only sent to the
+/// server for [`PermissionLevel::Custom`]: other levels imply their rights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderPermission {
+    /// The user the permission applies to.
+    pub user: PermissionUser,
+    /// The level of the permission.
+    pub level: PermissionLevel,
+    /// Whether the user can create items.
+    pub can_create_items: bool,
+    /// Whether the user can create subfolders.
+    pub can_create_subfolders: bool,
+    /// Whether the user owns the folder.
+    pub is_folder_owner: bool,
+    /// Whether the user can see the folder.
+    pub is_folder_visible: bool,
+    /// Whether the user is a contact of the folder (for public folders).
+    pub is_folder_contact: bool,
+    /// Which items the user can change.
+    pub edit_items: ItemAccess,
+    /// Which items the user can delete.
+    pub delete_items: ItemAccess,
+    /// How much of the items the user can read.
+    pub read_items: ReadAccess,
+}
+
+impl FolderPermission {
+    /// Creates a permission with a predefined level.
+    pub fn new(user: PermissionUser, level: PermissionLevel) -> Self {
+        Self {
+            user,
+            level,
+            can_create_items: false,
+            can_create_subfolders: false,
+            is_folder_owner: false,
+            is_folder_visible: false,
+            is_folder_contact: false,
+            edit_items: ItemAccess::None,
+            delete_items: ItemAccess::None,
+            read_items: ReadAccess::None,
+        }
+    }
+
+    /// Reads a permission from a `Permission` or `CalendarPermission`
+    /// element.
+    fn from_xml(element: &XmlElement) -> Result<Self, EwsError> {
+        let flag = |name: &str| element.child_bool(name).unwrap_or(false);
+        let level = element
+            .child_text("PermissionLevel")
+            .or_else(|| element.child_text("CalendarPermissionLevel"))
+            .and_then(PermissionLevel::parse)
+            .ok_or_else(|| EwsError::Processing {
+                message: "folder permission is missing its level".to_string(),
+            })?;
+
+        Ok(Self {
+            user: element
+                .child("UserId")
+                .map_or(PermissionUser::Default, PermissionUser::from_xml),
+            level,
+            can_create_items: flag("CanCreateItems"),
+            can_create_subfolders: flag("CanCreateSubFolders"),
+            is_folder_owner: flag("IsFolderOwner"),
+            is_folder_visible: flag("IsFolderVisible"),
+            is_folder_contact: flag("IsFolderContact"),
+            edit_items: element
+                .child_text("EditItems")
+                .and_then(ItemAccess::parse)
+                .unwrap_or_default(),
+            delete_items: element
+                .child_text("DeleteItems")
+                .and_then(ItemAccess::parse)
+                .unwrap_or_default(),
+            read_items: element
+                .child_text("ReadItems")
+                .and_then(ReadAccess::parse)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// The permissions of a folder.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionSet {
+    /// Whether the set belongs to a calendar folder, which allows the
+    /// free/busy levels.
+    pub is_calendar: bool,
+    /// The permissions of the folder, including those of the `Default` and
+    /// `Anonymous` users.
+    pub permissions: Vec<FolderPermission>,
+    /// Entries for users which no longer exist, as returned by the server.
+    /// They are dropped when the set is written back.
+    pub unknown_entries: Vec<String>,
+}
+
+impl PermissionSet {
+    /// Reads the permission set of a folder element (`Folder`,
+    /// `CalendarFolder`, etc.).
+    pub(crate) fn from_folder_xml(folder: &XmlElement) -> Result<Self, EwsError> {
+        let is_calendar = folder.name == "CalendarFolder";
+        let Some(set) = folder.child("PermissionSet") else {
+            return Ok(Self {
+                is_calendar,
+                ..Self::default()
+            });
+        };
+
+        let (list, entry) = if is_calendar {
+            ("CalendarPermissions", "CalendarPermission")
+        } else {
+            ("Permissions", "Permission")
+        };
+        let permissions = set
+            .child(list)
+            .map(|permissions| {
+                permissions
+                    .children_named(entry)
+                    .map(FolderPermission::from_xml)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let unknown_entries = set
+            .child("UnknownEntries")
+            .map(|entries| {
+                entries
+                    .children_named("UnknownEntry")
+                    .map(|entry| entry.text.trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            is_calendar,
+            permissions,
+            unknown_entries,
+        })
+    }
+}
+
+impl EwsClient {
+    /// Reads the permissions of a folder.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the folder, or a distinguished folder
+    ///   name (e.g. `"calendar"`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The folder does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Credentials, EwsClient};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let permission_set = client.get_folder_permissions("calendar").await?;
+    /// for permission in permission_set.permissions {
+    ///     println!("{:?}: {:?}", permission.user, permission.level);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_folder_permissions(&self, folder_id: &str) -> Result<PermissionSet, EwsError> {
+        let body = get_folder_permissions_request(folder_id, self.target_mailbox());
+
+        let response = self
+            .make_raw_operation_request("GetFolder", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("GetFolder", &response)?;
+
+        let folder = message
+            .child("Folders")
+            .and_then(|folders| folders.children.first())
+            .ok_or_else(|| EwsError::Processing {
+                message: "GetFolder response is missing the folder".to_string(),
+            })?;
+
+        PermissionSet::from_folder_xml(folder)
+    }
+
+    /// Replaces the permissions of a folder.
+    ///
+    /// The permission set is usually read with
+    /// [`get_folder_permissions`](Self::get_folder_permissions) and changed
+    /// before being written back, so that it keeps the permissions of the
+    /// `Default` and `Anonymous` users and is of the right kind for the
+    /// folder.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the folder, or a distinguished folder
+    ///   name
+    /// * `permission_set` - The new permissions of the folder
+    ///
+    /// # Returns
+    ///
+    /// The new change key of the folder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A user has no address, or a calendar-only level is given on another
+    ///   kind of folder
+    /// - The folder does not exist, or the user may not change its
+    ///   permissions
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn set_folder_permissions(
+        &self,
+        folder_id: &str,
+        permission_set: &PermissionSet,
+    ) -> Result<String, EwsError> {
+        let update = FolderUpdate {
+            permission_set: Some(permission_set.clone()),
+            ..FolderUpdate::default()
+        };
+
+        self.update_folder(folder_id, None, &update).await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn reviewer() -> FolderPermission {
+        FolderPermission::new(
+            PermissionUser::User(OwnedMailbox {
+                name: None,
+                email_address: Some("colleague@example.com".to_string()),
+            }),
+            PermissionLevel::Reviewer,
+        )
+    }
+
+    #[test]
+    fn test_permission_set_round_trip() {
+        let permission_set = PermissionSet {
+            is_calendar: true,
+            permissions: vec![
+                FolderPermission::new(PermissionUser::Default, PermissionLevel::FreeBusyTimeOnly),
+                FolderPermission {
+                    can_create_items: true,
+                    edit_items: ItemAccess::Owned,
+                    read_items: ReadAccess::FullDetails,
+                    is_folder_visible: true,
+                    ..FolderPermission::new(PermissionUser::Anonymous, PermissionLevel::Custom)
+                },
+                reviewer(),
+            ],
+            unknown_entries: Vec::new(),
+        };
+        let xml = format!(
+            r#"<Root xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">{}</Root>"#,
+            permission_set.to_folder_xml().unwrap()
+        );
+        let root = XmlElement::parse(xml.as_bytes()).unwrap();
+        let folder = root.child("CalendarFolder").unwrap();
+
+        assert_eq!(PermissionSet::from_folder_xml(folder).unwrap(), permission_set);
+
+        // The rights of predefined levels are implied
+        assert_eq!(xml.matches("<t:CanCreateItems>").count(), 1);
+    }
+
+    #[test]
+    fn test_calendar_levels_are_rejected_on_other_folders() {
+        let mut permission_set = PermissionSet {
+            is_calendar: false,
+            permissions: vec![reviewer()],
+            unknown_entries: Vec::new(),
+        };
+        assert!(permission_set.to_folder_xml().unwrap().starts_with("<t:Folder>"));
+
+        permission_set.permissions.push(FolderPermission::new(
+            PermissionUser::Default,
+            PermissionLevel::FreeBusyTimeOnly,
+        ));
+        assert!(permission_set.to_folder_xml().is_err());
+
+        let nobody = FolderPermission::new(PermissionUser::User(OwnedMailbox::default()), PermissionLevel::Editor);
+        assert!(nobody.to_xml(false).is_err());
+    }
+}
@@ -0,0 +1,568 @@
+//! Get items with a chosen set of properties.
+//!
+//! The `ews` crate's item shape has no body type nor HTML filtering, and its
+//! messages do not carry extended properties, so items are requested and read
+//! as raw XML.
+
+use base64::prelude::{BASE64_STANDARD, Engine};
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions, OwnedMailbox,
+    operations::{
+        directory::mailbox_from_xml,
+        inbox_rules::Importance,
+        meetings::MeetingMessageInfo,
+        update_folder::{ExtendedPropertyKey, MapiPropertyType},
+    },
+    process_raw_response_message,
+    raw_requests::items::get_item_request,
+    raw_response_messages,
+    xml::XmlElement,
+};
+
+/// The set of properties a shape starts from, before any additional property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemBaseShape {
+    /// Only the ID and change key of the items.
+    #[default]
+    IdOnly,
+    /// The properties the server returns by default, e.g. the subject, the
+    /// sender and the body.
+    Default,
+    /// All the properties of the item type, except those which must be
+    /// requested explicitly (e.g. the Internet headers).
+    AllProperties,
+}
+
+impl ItemBaseShape {
+    /// The EWS value of the base shape.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::IdOnly => "IdOnly",
+            Self::Default => "Default",
+            Self::AllProperties => "AllProperties",
+        }
+    }
+}
+
+/// The format of an item body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyType {
+    /// The format the body is stored in. Only valid in requests.
+    Best,
+    /// HTML.
+    Html,
+    /// Plain text.
+    Text,
+}
+
+impl BodyType {
+    /// The EWS value of the body type.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Best => "Best",
+            Self::Html => "HTML",
+            Self::Text => "Text",
+        }
+    }
+
+    /// Parses the EWS value of a body type.
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "HTML" => Some(Self::Html),
+            "Text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// A property requested in addition to those of the base shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ItemProperty {
+    /// A property identified by its field URI, e.g. `item:Categories`.
+    Field(String),
+    /// An entry of an indexed property, e.g. the `Subject` entry of
+    /// `message:InternetMessageHeader`.
+    Indexed { field_uri: String, index: String },
+    /// An extended MAPI property.
+    Extended {
+        key: ExtendedPropertyKey,
+        property_type: MapiPropertyType,
+    },
+}
+
+/// The properties to return for each item fetched by [`EwsClient::get_items`].
+///
+/// # Example
+///
+/// ```
+/// # use ews_client_core::client::{BodyType, ExtendedPropertyKey, ItemBaseShape, ItemShape, MapiPropertyType};
+/// let shape = ItemShape::new(ItemBaseShape::IdOnly)
+///     .with_field("item:Subject")
+///     .with_field("item:Body")
+///     .with_field("item:Categories")
+///     .with_indexed_field("message:InternetMessageHeader", "List-Id")
+///     .with_extended_property(ExtendedPropertyKey::Tag(0x1081), MapiPropertyType::Integer)
+///     .with_body_type(BodyType::Html)
+///     .with_filter_html_content(true);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemShape {
+    pub(crate) base_shape: ItemBaseShape,
+    pub(crate) properties: Vec<ItemProperty>,
+    pub(crate) body_type: Option<BodyType>,
+    pub(crate) filter_html_content: bool,
+    pub(crate) include_mime_content: bool,
+}
+
+impl ItemShape {
+    /// Creates a shape returning the properties of a base shape.
+    pub fn new(base_shape: ItemBaseShape) -> Self {
+        Self {
+            base_shape,
+            ..Self::default()
+        }
+    }
+
+    /// Also returns the property with the given field URI, e.g.
+    /// `item:Categories` or `message:InternetMessageHeaders`.
+    pub fn with_field(mut self, field_uri: impl Into<String>) -> Self {
+        self.properties.push(ItemProperty::Field(field_uri.into()));
+        self
+    }
+
+    /// Also returns an entry of an indexed property, e.g. the `List-Id`
+    /// entry of `message:InternetMessageHeader`.
+    pub fn with_indexed_field(mut self, field_uri: impl Into<String>, index: impl Into<String>) -> Self {
+        self.properties.push(ItemProperty::Indexed {
+            field_uri: field_uri.into(),
+            index: index.into(),
+        });
+        self
+    }
+
+    /// Also returns an extended MAPI property, in
+    /// [`Message::extended_properties`].
+    pub fn with_extended_property(mut self, key: ExtendedPropertyKey, property_type: MapiPropertyType) -> Self {
+        self.properties.push(ItemProperty::Extended { key, property_type });
+        self
+    }
+
+    /// Sets the format of the returned bodies. The server returns HTML bodies
+    /// by default.
+    pub fn with_body_type(mut self, body_type: BodyType) -> Self {
+        self.body_type = Some(body_type);
+        self
+    }
+
+    /// Sets whether the server removes potentially unsafe content (e.g.
+    /// scripts) from HTML bodies. Ignored by servers older than Exchange
+    /// 2010.
+    pub fn with_filter_html_content(mut self, filter_html_content: bool) -> Self {
+        self.filter_html_content = filter_html_content;
+        self
+    }
+
+    /// Sets whether to return the MIME content of the items, in
+    /// [`Message::mime_content`].
+    pub fn with_mime_content(mut self, include_mime_content: bool) -> Self {
+        self.include_mime_content = include_mime_content;
+        self
+    }
+}
+
+/// The body of an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemBody {
+    /// The format of the body.
+    pub body_type: BodyType,
+    /// The content of the body.
+    pub content: String,
+    /// Whether the server truncated the body.
+    pub is_truncated: bool,
+}
+
+/// The value of an extended MAPI property of an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemExtendedProperty {
+    /// The property.
+    pub key: ExtendedPropertyKey,
+    /// The type of the value of the property.
+    pub property_type: MapiPropertyType,
+    /// The value of the property, as EWS writes it (e.g. `"true"`, `"42"` or
+    /// base64 data).
+    pub value: String,
+}
+
+/// An item fetched with [`EwsClient::get_items`].
+///
+/// Only the properties requested by the [`ItemShape`] are set. Items which
+/// are not messages (e.g. calendar items) are read the same way, with only
+/// their item properties.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    /// The EWS ID of the item.
+    pub item_id: String,
+    /// The change key of the item, if returned.
+    pub change_key: Option<String>,
+    /// The item class, e.g. `IPM.Note`.
+    pub item_class: Option<String>,
+    /// The subject.
+    pub subject: Option<String>,
+    /// The body, in the format requested by the shape.
+    pub body: Option<ItemBody>,
+    /// The sender, on behalf of whom the message was sent.
+    pub from: Option<OwnedMailbox>,
+    /// The account which actually sent the message.
+    pub sender: Option<OwnedMailbox>,
+    /// The To recipients.
+    pub to_recipients: Vec<OwnedMailbox>,
+    /// The Cc recipients.
+    pub cc_recipients: Vec<OwnedMailbox>,
+    /// The Bcc recipients, only known for messages sent from the mailbox.
+    pub bcc_recipients: Vec<OwnedMailbox>,
+    /// The `Message-ID` of the message.
+    pub internet_message_id: Option<String>,
+    /// The Internet headers of the message, as name and value pairs in the
+    /// order the server returned them.
+    pub internet_message_headers: Vec<(String, String)>,
+    /// The categories of the item.
+    pub categories: Vec<String>,
+    /// The importance of the item.
+    pub importance: Option<Importance>,
+    /// Whether the message has been read.
+    pub is_read: Option<bool>,
+    /// Whether the item has attachments.
+    pub has_attachments: Option<bool>,
+    /// When the message was sent, as a Unix timestamp.
+    pub date_time_sent: Option<i64>,
+    /// When the item was received, as a Unix timestamp.
+    pub date_time_received: Option<i64>,
+    /// The size of the item in bytes.
+    pub size: Option<u64>,
+    /// The requested extended properties which the item has.
+    pub extended_properties: Vec<ItemExtendedProperty>,
+    /// The decoded MIME content of the item.
+    pub mime_content: Option<Vec<u8>>,
+    /// The meeting properties of meeting requests, responses and
+    /// cancellations, and `None` for any other item.
+    pub meeting: Option<MeetingMessageInfo>,
+}
+
+impl Message {
+    /// Reads an item from a `Message` element, or from the element of any
+    /// other item type.
+    pub(crate) fn from_xml(element: &XmlElement) -> Result<Self, EwsError> {
+        let id = element.child("ItemId").ok_or(EwsError::MissingIdInResponse)?;
+        let text = |name: &str| element.child_text(name).map(ToString::to_string);
+        let mailbox = |name: &str| element.descendant(&[name, "Mailbox"]).map(mailbox_from_xml);
+        let mailboxes = |name: &str| {
+            element
+                .child(name)
+                .map(|list| list.children_named("Mailbox").map(mailbox_from_xml).collect())
+                .unwrap_or_default()
+        };
+
+        let mime_content = element
+            .child_text("MimeContent")
+            .map(|content| {
+                // EWS returns the content of the email base64-encoded on top
+                // of any encoding within the message
+                BASE64_STANDARD
+                    .decode(content.trim())
+                    .map_err(|_| EwsError::Processing {
+                        message: "MIME content for item is not validly base64 encoded".to_string(),
+                    })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            item_id: id.attribute("Id").ok_or(EwsError::MissingIdInResponse)?.to_string(),
+            change_key: id.attribute("ChangeKey").map(ToString::to_string),
+            item_class: text("ItemClass"),
+            subject: text("Subject"),
+            body: element.child("Body").and_then(body_from_xml),
+            from: mailbox("From"),
+            sender: mailbox("Sender"),
+            to_recipients: mailboxes("ToRecipients"),
+            cc_recipients: mailboxes("CcRecipients"),
+            bcc_recipients: mailboxes("BccRecipients"),
+            internet_message_id: text("InternetMessageId"),
+            internet_message_headers: element
+                .child("InternetMessageHeaders")
+                .map(|headers| {
+                    headers
+                        .children_named("InternetMessageHeader")
+                        .filter_map(|header| {
+                            let name = header.attribute("HeaderName")?;
+                            Some((name.to_string(), header.text.clone()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            categories: element
+                .child("Categories")
+                .map(|categories| {
+                    categories
+                        .children_named("String")
+                        .map(|category| category.text.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            importance: element.child_text("Importance").and_then(Importance::parse),
+            is_read: element.child_bool("IsRead"),
+            has_attachments: element.child_bool("HasAttachments"),
+            date_time_sent: element.child_date_time("DateTimeSent"),
+            date_time_received: element.child_date_time("DateTimeReceived"),
+            size: element.child_parse("Size"),
+            extended_properties: element
+                .children_named("ExtendedProperty")
+                .filter_map(extended_property_from_xml)
+                .collect(),
+            mime_content,
+            meeting: MeetingMessageInfo::from_xml(element),
+        })
+    }
+}
+
+/// Reads a `Body` element.
+fn body_from_xml(element: &XmlElement) -> Option<ItemBody> {
+    Some(ItemBody {
+        body_type: element.attribute("BodyType").and_then(BodyType::parse)?,
+        content: element.text.clone(),
+        is_truncated: element.attribute("IsTruncated") == Some("true"),
+    })
+}
+
+/// Reads a single-valued `ExtendedProperty` element, skipping properties the
+/// client cannot have requested.
+fn extended_property_from_xml(element: &XmlElement) -> Option<ItemExtendedProperty> {
+    let uri = element.child("ExtendedFieldURI")?;
+    let property_type = uri.attribute("PropertyType").and_then(MapiPropertyType::parse)?;
+
+    let key = if let Some(tag) = uri.attribute("PropertyTag") {
+        let tag = match tag.strip_prefix("0x").or_else(|| tag.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16).ok()?,
+            None => tag.parse().ok()?,
+        };
+        ExtendedPropertyKey::Tag(tag)
+    } else {
+        let property_set_id = uri.attribute("PropertySetId")?.to_string();
+        match (uri.attribute("PropertyName"), uri.attribute("PropertyId")) {
+            (Some(name), _) => ExtendedPropertyKey::Named {
+                property_set_id,
+                name: name.to_string(),
+            },
+            (None, Some(id)) => ExtendedPropertyKey::Id {
+                property_set_id,
+                id: id.parse().ok()?,
+            },
+            (None, None) => return None,
+        }
+    };
+
+    Some(ItemExtendedProperty {
+        key,
+        property_type,
+        value: element.child_text("Value")?.to_string(),
+    })
+}
+
+impl EwsClient {
+    /// Fetches items by ID with the properties of the given shape.
+    ///
+    /// `GetItem` requests are batched according to the client's
+    /// [`BatchConfig`](crate::client::BatchConfig).
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The EWS IDs of the items to fetch
+    /// * `shape` - The properties to return for each item
+    ///
+    /// # Returns
+    ///
+    /// The items, in the order of the IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Any of the items cannot be found
+    /// - The shape requests a property the server does not know
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{BodyType, EwsClient, Credentials, ItemBaseShape, ItemShape};
+    /// # async fn example(client: &EwsClient) -> Result<(), Box<dyn std::error::Error>> {
+    /// let shape = ItemShape::new(ItemBaseShape::IdOnly)
+    ///     .with_field("item:Body")
+    ///     .with_field("message:InternetMessageHeaders")
+    ///     .with_body_type(BodyType::Text);
+    /// for message in client.get_items(&["AAMkAD..."], &shape).await? {
+    ///     println!("{} headers", message.internet_message_headers.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_items(&self, ids: &[&str], shape: &ItemShape) -> Result<Vec<Message>, EwsError> {
+        self.get_items_with(ids, shape, Message::from_xml).await
+    }
+
+    /// Reads items with raw `GetItem` requests, batched according to the
+    /// client's [`BatchConfig`](crate::client::BatchConfig).
+    ///
+    /// `shape` gives the properties returned for each item, and `parse`
+    /// reads the item of each response message. The parsed items are
+    /// returned in the order of the IDs.
+    pub(crate) async fn get_items_with<T>(
+        &self,
+        ids: &[&str],
+        shape: &ItemShape,
+        parse: impl Fn(&XmlElement) -> Result<T, EwsError>,
+    ) -> Result<Vec<T>, EwsError> {
+        let item_shape = shape.to_xml(self.capabilities());
+        let ids = ids.iter().map(ToString::to_string).collect();
+        self.fetch_in_batches(ids, |batch_ids| self.get_items_batch(batch_ids, &item_shape, &parse))
+            .await
+    }
+
+    /// Fetches a single batch of items with one `GetItem` request.
+    async fn get_items_batch<T>(
+        &self,
+        ids: Vec<String>,
+        item_shape: &str,
+        parse: &impl Fn(&XmlElement) -> Result<T, EwsError>,
+    ) -> Result<Vec<T>, EwsError> {
+        let expected = ids.len();
+        let body = get_item_request(item_shape, &ids);
+
+        let response = self
+            .make_raw_operation_request("GetItem", &body, OperationRequestOptions::default())
+            .await?;
+        let messages = raw_response_messages(&response);
+
+        if messages.len() != expected {
+            return Err(EwsError::UnexpectedResponseMessageCount {
+                expected,
+                actual: messages.len(),
+            });
+        }
+
+        messages
+            .into_iter()
+            .map(|message| {
+                let message = process_raw_response_message("GetItem", message)?;
+                let item = message
+                    .child("Items")
+                    .and_then(|items| items.children.first())
+                    .ok_or_else(|| EwsError::Processing {
+                        message: "GetItem response does not contain an item".to_string(),
+                    })?;
+                parse(item)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+    use crate::client::MeetingMessageKind;
+
+    #[test]
+    fn test_message_from_xml() {
+        let element = XmlElement::parse(
+            br#"<t:Message xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+                <t:ItemId Id="item-1" ChangeKey="CK"/>
+                <t:Subject>Weekly report</t:Subject>
+                <t:Body BodyType="HTML" IsTruncated="false">&lt;p&gt;Hi&lt;/p&gt;</t:Body>
+                <t:Categories><t:String>Blue</t:String><t:String>Work</t:String></t:Categories>
+                <t:Importance>High</t:Importance>
+                <t:ExtendedProperty>
+                    <t:ExtendedFieldURI PropertyTag="0x1081" PropertyType="Integer"/>
+                    <t:Value>261</t:Value>
+                </t:ExtendedProperty>
+                <t:ExtendedProperty>
+                    <t:ExtendedFieldURI PropertySetId="00020386-0000-0000-C000-000000000046" PropertyName="X-Tag" PropertyType="String"/>
+                    <t:Value>tagged</t:Value>
+                </t:ExtendedProperty>
+                <t:ToRecipients>
+                    <t:Mailbox><t:Name>Alice</t:Name><t:EmailAddress>alice@example.com</t:EmailAddress></t:Mailbox>
+                    <t:Mailbox><t:EmailAddress>bob@example.com</t:EmailAddress></t:Mailbox>
+                </t:ToRecipients>
+                <t:InternetMessageHeaders>
+                    <t:InternetMessageHeader HeaderName="List-Id">team.example.com</t:InternetMessageHeader>
+                </t:InternetMessageHeaders>
+                <t:IsRead>true</t:IsRead>
+                <t:From><t:Mailbox><t:EmailAddress>carol@example.com</t:EmailAddress></t:Mailbox></t:From>
+            </t:Message>"#,
+        )
+        .unwrap();
+        let message = Message::from_xml(&element).unwrap();
+
+        assert_eq!(message.item_id, "item-1");
+        assert_eq!(message.change_key.as_deref(), Some("CK"));
+        assert_eq!(message.subject.as_deref(), Some("Weekly report"));
+        assert_eq!(
+            message.body,
+            Some(ItemBody {
+                body_type: BodyType::Html,
+                content: "<p>Hi</p>".to_string(),
+                is_truncated: false,
+            })
+        );
+        assert_eq!(message.categories, vec!["Blue", "Work"]);
+        assert_eq!(message.importance, Some(Importance::High));
+        assert_eq!(message.extended_properties.len(), 2);
+        assert_eq!(message.extended_properties[0].key, ExtendedPropertyKey::Tag(0x1081));
+        assert_eq!(message.extended_properties[0].value, "261");
+        assert_eq!(
+            message.extended_properties[1].key,
+            ExtendedPropertyKey::Named {
+                property_set_id: "00020386-0000-0000-C000-000000000046".to_string(),
+                name: "X-Tag".to_string(),
+            }
+        );
+        assert_eq!(message.to_recipients.len(), 2);
+        assert_eq!(message.to_recipients[0].name.as_deref(), Some("Alice"));
+        assert_eq!(
+            message.internet_message_headers,
+            vec![("List-Id".to_string(), "team.example.com".to_string())]
+        );
+        assert_eq!(message.is_read, Some(true));
+        assert_eq!(
+            message.from.unwrap().email_address.as_deref(),
+            Some("carol@example.com")
+        );
+        assert_eq!(message.mime_content, None);
+        assert_eq!(message.meeting, None);
+    }
+
+    #[test]
+    fn test_meeting_message_from_xml() {
+        let element = XmlElement::parse(
+            br#"<t:MeetingCancellation xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+                <t:ItemId Id="cancellation-1"/>
+                <t:Subject>Canceled: Planning</t:Subject>
+                <t:AssociatedCalendarItemId Id="meeting-1"/>
+                <t:IsOutOfDate>true</t:IsOutOfDate>
+                <t:Start>2024-01-15T10:30:00Z</t:Start>
+            </t:MeetingCancellation>"#,
+        )
+        .unwrap();
+        let message = Message::from_xml(&element).unwrap();
+
+        assert_eq!(message.item_id, "cancellation-1");
+        assert_eq!(message.subject.as_deref(), Some("Canceled: Planning"));
+        let meeting = message.meeting.unwrap();
+        assert_eq!(meeting.kind, MeetingMessageKind::Cancellation);
+        assert_eq!(meeting.associated_calendar_item_id.as_deref(), Some("meeting-1"));
+        assert_eq!(meeting.start, Some(1_705_314_600));
+        assert_eq!(meeting.end, None);
+        assert!(meeting.is_out_of_date);
+        assert!(!meeting.has_been_processed);
+    }
+}
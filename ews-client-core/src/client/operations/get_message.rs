@@ -1,7 +1,6 @@
 //! Get a message via EWS.
 
-use crate::client::{EwsClient, EwsError};
-use base64::prelude::{BASE64_STANDARD, Engine};
+use crate::client::{EwsClient, EwsError, ItemBaseShape, ItemShape};
 
 impl EwsClient {
     /// Fetches a single message by its EWS ID and returns its MIME content.
@@ -43,7 +42,8 @@ impl EwsClient {
         let id = id.into();
 
         // Fetch the item with MIME content
-        let items = self.get_items([id.clone()], &[], true).await?;
+        let shape = ItemShape::new(ItemBaseShape::IdOnly).with_mime_content(true);
+        let items = self.get_items(&[id.as_str()], &shape).await?;
 
         if items.len() != 1 {
             return Err(EwsError::Processing {
@@ -55,20 +55,11 @@ impl EwsClient {
         let item = items.into_iter().next().ok_or_else(|| EwsError::Processing {
             message: "no item in response".to_string(),
         })?;
-        let message = item.inner_message();
 
-        let raw_mime = message.mime_content.as_ref().ok_or_else(|| EwsError::Processing {
+        let mime_content = item.mime_content.ok_or_else(|| EwsError::Processing {
             message: "item has no content".to_string(),
         })?;
 
-        // EWS returns the content of the email base64-encoded on top of any
-        // encoding within the message
-        let mime_content = BASE64_STANDARD
-            .decode(&raw_mime.content)
-            .map_err(|_| EwsError::Processing {
-                message: "MIME content for item is not validly base64 encoded".to_string(),
-            })?;
-
         Ok(mime_content)
     }
 }
@@ -0,0 +1,514 @@
+//! Inbox rule operations.
+//!
+//! This module contains the `GetInboxRules` and `UpdateInboxRules` operations
+//! of Exchange 2010 and later, which read and change the server-side rules
+//! applied to messages arriving in a mailbox's Inbox.
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions, OwnedMailbox,
+    operations::directory::mailbox_from_xml,
+    process_raw_response_message,
+    raw_requests::mailbox_settings::{get_inbox_rules_request, update_inbox_rules_request},
+    xml::XmlElement,
+};
+
+/// The importance of a message, as matched or set by a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Importance {
+    /// Low importance.
+    Low,
+    /// Normal importance.
+    Normal,
+    /// High importance.
+    High,
+}
+
+impl Importance {
+    /// The EWS value of the importance.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Normal => "Normal",
+            Self::High => "High",
+        }
+    }
+
+    /// Parses an importance from its EWS value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "Low" => Some(Self::Low),
+            "Normal" => Some(Self::Normal),
+            "High" => Some(Self::High),
+            _ => None,
+        }
+    }
+}
+
+/// The conditions under which a rule applies, or the exceptions to it.
+///
+/// All the predicates which are set must match. Text predicates match if any
+/// of their strings is found, and address predicates if any of their
+/// addresses matches. Predicates the model does not cover are left out when
+/// reading rules; see [`InboxRule::is_not_supported`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RulePredicates {
+    /// Categories of the message.
+    pub categories: Vec<String>,
+    /// Strings to look for in the body.
+    pub contains_body_strings: Vec<String>,
+    /// Strings to look for in the Internet headers.
+    pub contains_header_strings: Vec<String>,
+    /// Strings to look for in the recipient addresses.
+    pub contains_recipient_strings: Vec<String>,
+    /// Strings to look for in the sender address.
+    pub contains_sender_strings: Vec<String>,
+    /// Strings to look for in the subject or the body.
+    pub contains_subject_or_body_strings: Vec<String>,
+    /// Strings to look for in the subject.
+    pub contains_subject_strings: Vec<String>,
+    /// Senders of the message.
+    pub from_addresses: Vec<OwnedMailbox>,
+    /// The message has attachments.
+    pub has_attachments: bool,
+    /// The importance of the message.
+    pub importance: Option<Importance>,
+    /// The message is an automatic reply.
+    pub is_automatic_reply: bool,
+    /// The message is a meeting request.
+    pub is_meeting_request: bool,
+    /// The user is not a recipient of the message.
+    pub not_sent_to_me: bool,
+    /// The user is on the Cc line of the message.
+    pub sent_cc_me: bool,
+    /// The user is the only recipient of the message.
+    pub sent_only_to_me: bool,
+    /// Recipients of the message.
+    pub sent_to_addresses: Vec<OwnedMailbox>,
+    /// The user is on the To line of the message.
+    pub sent_to_me: bool,
+    /// The user is on the To or Cc line of the message.
+    pub sent_to_or_cc_me: bool,
+}
+
+impl RulePredicates {
+    /// Reads the predicates from a `Conditions` or `Exceptions` element.
+    fn from_xml(element: &XmlElement) -> Self {
+        let flag = |name: &str| element.child_bool(name).unwrap_or(false);
+
+        Self {
+            categories: strings_from_xml(element, "Categories"),
+            contains_body_strings: strings_from_xml(element, "ContainsBodyStrings"),
+            contains_header_strings: strings_from_xml(element, "ContainsHeaderStrings"),
+            contains_recipient_strings: strings_from_xml(element, "ContainsRecipientStrings"),
+            contains_sender_strings: strings_from_xml(element, "ContainsSenderStrings"),
+            contains_subject_or_body_strings: strings_from_xml(element, "ContainsSubjectOrBodyStrings"),
+            contains_subject_strings: strings_from_xml(element, "ContainsSubjectStrings"),
+            from_addresses: addresses_from_xml(element, "FromAddresses"),
+            has_attachments: flag("HasAttachments"),
+            importance: element.child_text("Importance").and_then(Importance::parse),
+            is_automatic_reply: flag("IsAutomaticReply"),
+            is_meeting_request: flag("IsMeetingRequest"),
+            not_sent_to_me: flag("NotSentToMe"),
+            sent_cc_me: flag("SentCcMe"),
+            sent_only_to_me: flag("SentOnlyToMe"),
+            sent_to_addresses: addresses_from_xml(element, "SentToAddresses"),
+            sent_to_me: flag("SentToMe"),
+            sent_to_or_cc_me: flag("SentToOrCcMe"),
+        }
+    }
+}
+
+/// What a rule does with the messages it applies to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleActions {
+    /// Categories to add to the message.
+    pub assign_categories: Vec<String>,
+    /// The folder to copy the message to, as a folder ID or a distinguished
+    /// folder name (e.g. `"junkemail"`).
+    pub copy_to_folder: Option<String>,
+    /// Move the message to the Deleted Items folder.
+    pub delete: bool,
+    /// Recipients to forward the message to as an attachment.
+    pub forward_as_attachment_to_recipients: Vec<OwnedMailbox>,
+    /// Recipients to forward the message to.
+    pub forward_to_recipients: Vec<OwnedMailbox>,
+    /// The importance to give the message.
+    pub mark_importance: Option<Importance>,
+    /// Mark the message as read.
+    pub mark_as_read: bool,
+    /// The folder to move the message to, as a folder ID or a distinguished
+    /// folder name.
+    pub move_to_folder: Option<String>,
+    /// Delete the message permanently, without moving it to Deleted Items.
+    pub permanent_delete: bool,
+    /// Recipients to redirect the message to, keeping its original sender.
+    pub redirect_to_recipients: Vec<OwnedMailbox>,
+    /// Do not apply any rule with a larger priority number to the message.
+    pub stop_processing_rules: bool,
+}
+
+impl RuleActions {
+    /// Reads the actions from an `Actions` element.
+    fn from_xml(element: &XmlElement) -> Self {
+        let flag = |name: &str| element.child_bool(name).unwrap_or(false);
+        let folder = |name: &str| {
+            element.child(name).and_then(|target| {
+                target
+                    .child_id("FolderId")
+                    .or_else(|| target.child_id("DistinguishedFolderId"))
+            })
+        };
+
+        Self {
+            assign_categories: strings_from_xml(element, "AssignCategories"),
+            copy_to_folder: folder("CopyToFolder"),
+            delete: flag("Delete"),
+            forward_as_attachment_to_recipients: addresses_from_xml(element, "ForwardAsAttachmentToRecipients"),
+            forward_to_recipients: addresses_from_xml(element, "ForwardToRecipients"),
+            mark_importance: element.child_text("MarkImportance").and_then(Importance::parse),
+            mark_as_read: flag("MarkAsRead"),
+            move_to_folder: folder("MoveToFolder"),
+            permanent_delete: flag("PermanentDelete"),
+            redirect_to_recipients: addresses_from_xml(element, "RedirectToRecipients"),
+            stop_processing_rules: flag("StopProcessingRules"),
+        }
+    }
+}
+
+/// A server-side rule of a mailbox's Inbox.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InboxRule {
+    /// The ID of the rule. Assigned by the server when the rule is created,
+    /// and required to change it.
+    pub rule_id: Option<String>,
+    /// The name of the rule.
+    pub display_name: String,
+    /// The order in which the rule is applied, starting at 1.
+    pub priority: u32,
+    /// Whether the rule is applied.
+    pub is_enabled: bool,
+    /// Whether the rule uses conditions or actions which cannot be
+    /// represented in EWS. Such rules can be deleted, but not changed.
+    pub is_not_supported: bool,
+    /// Whether the rule is in an error state, e.g. because its target folder
+    /// was deleted.
+    pub is_in_error: bool,
+    /// The conditions which messages must match.
+    pub conditions: RulePredicates,
+    /// The conditions under which the rule does not apply.
+    pub exceptions: RulePredicates,
+    /// What the rule does with matching messages.
+    pub actions: RuleActions,
+}
+
+impl InboxRule {
+    /// Reads a rule from a `Rule` element.
+    fn from_xml(element: &XmlElement) -> Self {
+        let flag = |name: &str| element.child_bool(name).unwrap_or(false);
+
+        Self {
+            rule_id: element.child_text("RuleId").map(|id| id.trim().to_string()),
+            display_name: element.child_text("DisplayName").unwrap_or_default().to_string(),
+            priority: element.child_parse("Priority").unwrap_or_default(),
+            is_enabled: flag("IsEnabled"),
+            is_not_supported: flag("IsNotSupported"),
+            is_in_error: flag("IsInError"),
+            conditions: element
+                .child("Conditions")
+                .map(RulePredicates::from_xml)
+                .unwrap_or_default(),
+            exceptions: element
+                .child("Exceptions")
+                .map(RulePredicates::from_xml)
+                .unwrap_or_default(),
+            actions: element.child("Actions").map(RuleActions::from_xml).unwrap_or_default(),
+        }
+    }
+}
+
+/// A change to the rules of a mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleOperation {
+    /// Creates a new rule. Its `rule_id` is ignored.
+    Create(InboxRule),
+    /// Replaces the rule with the same `rule_id`.
+    Set(InboxRule),
+    /// Deletes the rule with the given ID.
+    Delete(String),
+}
+
+/// The result of a `get_inbox_rules` call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboxRulesResult {
+    /// The rules of the mailbox, in the order returned by the server.
+    pub rules: Vec<InboxRule>,
+    /// Whether the mailbox has rules saved by Outlook which EWS updates
+    /// would overwrite.
+    pub outlook_rule_blob_exists: bool,
+}
+
+impl EwsClient {
+    /// Reads the Inbox rules of a mailbox.
+    ///
+    /// Requires Exchange 2010 or later.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox, or `None` for the
+    ///   user's own mailbox
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The server is older than Exchange 2010 ([`EwsError::Unsupported`])
+    /// - The user may not read the rules of the mailbox
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Credentials, EwsClient};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let result = client.get_inbox_rules(None).await?;
+    /// for rule in result.rules {
+    ///     println!("{}: {}", rule.priority, rule.display_name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_inbox_rules(&self, mailbox: Option<&str>) -> Result<InboxRulesResult, EwsError> {
+        self.check_inbox_rules_supported()?;
+
+        let body = get_inbox_rules_request(mailbox);
+
+        let response = self
+            .make_raw_operation_request("GetInboxRules", &body, OperationRequestOptions::default())
+            .await?;
+        let response = process_raw_response_message("GetInboxRules", &response)?;
+
+        Ok(InboxRulesResult {
+            rules: response
+                .child("InboxRules")
+                .map(|rules| rules.children_named("Rule").map(InboxRule::from_xml).collect())
+                .unwrap_or_default(),
+            outlook_rule_blob_exists: response.child_bool("OutlookRuleBlobExists").unwrap_or(false),
+        })
+    }
+
+    /// Creates, changes and deletes Inbox rules of a mailbox.
+    ///
+    /// The operations are applied together: if one of them is invalid, none
+    /// is applied.
+    ///
+    /// Requires Exchange 2010 or later.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox, or `None` for the
+    ///   user's own mailbox
+    /// * `operations` - The changes to apply
+    /// * `remove_outlook_rule_blob` - Whether to remove the rules saved by
+    ///   Outlook, which the server otherwise refuses to overwrite (see
+    ///   [`InboxRulesResult::outlook_rule_blob_exists`])
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The server is older than Exchange 2010 ([`EwsError::Unsupported`])
+    /// - A set or delete operation has no rule ID
+    /// - The server rejects one of the operations (the error message lists
+    ///   the failed validations)
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn update_inbox_rules(
+        &self,
+        mailbox: Option<&str>,
+        operations: &[RuleOperation],
+        remove_outlook_rule_blob: bool,
+    ) -> Result<(), EwsError> {
+        self.check_inbox_rules_supported()?;
+
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        let body = update_inbox_rules_request(mailbox, operations, remove_outlook_rule_blob, self.target_mailbox())?;
+
+        let response = self
+            .make_raw_operation_request("UpdateInboxRules", &body, OperationRequestOptions::default())
+            .await?;
+
+        match process_raw_response_message("UpdateInboxRules", &response) {
+            Ok(_) => Ok(()),
+            Err(EwsError::RawResponseError {
+                response_code,
+                message_text,
+            }) => Err(EwsError::RawResponseError {
+                message_text: with_rule_operation_errors(message_text, &response),
+                response_code,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Checks that the server supports the Inbox rule operations.
+    fn check_inbox_rules_supported(&self) -> Result<(), EwsError> {
+        let capabilities = self.capabilities();
+        capabilities.require(capabilities.inbox_rules, "Inbox rules")
+    }
+}
+
+/// Appends the validation errors of an `UpdateInboxRules` response to its
+/// error message, e.g. `"...; operation 0: ActionsMoveToFolder:
+/// FolderDoesNotExist (The folder does not exist.)"`.
+fn with_rule_operation_errors(message_text: String, response: &XmlElement) -> String {
+    let Some(errors) = response.child("RuleOperationErrors") else {
+        return message_text;
+    };
+
+    let details: Vec<String> = errors
+        .children_named("RuleOperationError")
+        .flat_map(|error| {
+            let index = error.child_text("OperationIndex").unwrap_or("?").trim().to_string();
+            error
+                .child("ValidationErrors")
+                .into_iter()
+                .flat_map(|validation_errors| validation_errors.children_named("RuleValidationError"))
+                .map(move |validation_error| {
+                    format!(
+                        "operation {index}: {}: {} ({})",
+                        validation_error.child_text("FieldURI").unwrap_or_default().trim(),
+                        validation_error.child_text("ErrorCode").unwrap_or_default().trim(),
+                        validation_error.child_text("ErrorMessage").unwrap_or_default().trim(),
+                    )
+                })
+        })
+        .collect();
+
+    if details.is_empty() {
+        message_text
+    } else {
+        format!("{message_text}; {}", details.join("; "))
+    }
+}
+
+/// Reads the `String` children of an `ArrayOfStringsType` element.
+fn strings_from_xml(element: &XmlElement, name: &str) -> Vec<String> {
+    element
+        .child(name)
+        .map(|strings| {
+            strings
+                .children_named("String")
+                .map(|string| string.text.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Reads the `Address` children of an `ArrayOfEmailAddressesType` element.
+fn addresses_from_xml(element: &XmlElement, name: &str) -> Vec<OwnedMailbox> {
+    element
+        .child(name)
+        .map(|addresses| addresses.children_named("Address").map(mailbox_from_xml).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    fn sample_rule() -> InboxRule {
+        InboxRule {
+            rule_id: Some("AQAAAJRHm8s=".to_string()),
+            display_name: "Newsletters & digests".to_string(),
+            priority: 2,
+            is_enabled: true,
+            conditions: RulePredicates {
+                contains_subject_strings: vec!["Newsletter".to_string(), "Digest".to_string()],
+                from_addresses: vec![OwnedMailbox {
+                    name: Some("News".to_string()),
+                    email_address: Some("news@example.com".to_string()),
+                }],
+                ..RulePredicates::default()
+            },
+            exceptions: RulePredicates {
+                importance: Some(Importance::High),
+                ..RulePredicates::default()
+            },
+            actions: RuleActions {
+                move_to_folder: Some("AAMkAGNewsletters".to_string()),
+                mark_as_read: true,
+                stop_processing_rules: true,
+                ..RuleActions::default()
+            },
+            ..InboxRule::default()
+        }
+    }
+
+    #[test]
+    fn test_rule_round_trip() {
+        let rule = sample_rule();
+        let xml = format!(
+            r#"<Root xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">{}</Root>"#,
+            rule.to_xml(true, None)
+        );
+        let root = XmlElement::parse(xml.as_bytes()).unwrap();
+
+        assert_eq!(InboxRule::from_xml(&root.children[0]), rule);
+
+        // Unset predicates are left out, and so is an empty exception list
+        let xml = rule.to_xml(false, None);
+        assert!(!xml.contains("RuleId"));
+        assert!(xml.contains(r#"<t:MoveToFolder><t:FolderId Id="AAMkAGNewsletters"/></t:MoveToFolder>"#));
+        assert!(!xml.contains("HasAttachments"));
+        assert!(!RulePredicates::default().to_xml("t:Exceptions").contains("Exceptions"));
+    }
+
+    #[test]
+    fn test_rule_operation_requires_rule_id() {
+        let mut rule = sample_rule();
+        assert!(RuleOperation::Set(rule.clone()).to_xml(None).is_ok());
+
+        rule.rule_id = None;
+        assert!(RuleOperation::Create(rule.clone()).to_xml(None).is_ok());
+        assert!(RuleOperation::Set(rule).to_xml(None).is_err());
+        assert!(RuleOperation::Delete(" ".to_string()).to_xml(None).is_err());
+    }
+
+    #[test]
+    fn test_with_rule_operation_errors() {
+        let response = XmlElement::parse(
+            br#"<UpdateInboxRulesResponse ResponseClass="Error">
+              <MessageText>Validation error occurred.</MessageText>
+              <ResponseCode>ErrorInboxRulesValidationError</ResponseCode>
+              <RuleOperationErrors>
+                <RuleOperationError>
+                  <OperationIndex>1</OperationIndex>
+                  <ValidationErrors>
+                    <RuleValidationError>
+                      <FieldURI>ActionsMoveToFolder</FieldURI>
+                      <ErrorCode>FolderDoesNotExist</ErrorCode>
+                      <ErrorMessage>The folder does not exist.</ErrorMessage>
+                      <FieldValue>AAMkAGMissing</FieldValue>
+                    </RuleValidationError>
+                  </ValidationErrors>
+                </RuleOperationError>
+              </RuleOperationErrors>
+            </UpdateInboxRulesResponse>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            with_rule_operation_errors("Validation error occurred.".to_string(), &response),
+            "Validation error occurred.; operation 1: ActionsMoveToFolder: FolderDoesNotExist (The folder does not exist.)"
+        );
+    }
+}
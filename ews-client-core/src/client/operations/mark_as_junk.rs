@@ -10,6 +10,9 @@ impl EwsClient {
     ///
     /// Uses the `MarkAsJunk` operation (Exchange 2013+).
     /// For older versions, falls back to moving items to the junk folder.
+    /// The version is the one last reported by the server, so calling
+    /// [`EwsClient::detect_server_version`] first on a new endpoint avoids
+    /// falling back needlessly.
     ///
     /// # Arguments
     ///
@@ -27,28 +30,25 @@ impl EwsClient {
     /// - Any message does not exist
     /// - Network or authentication errors occur
     /// - The server returns an unexpected response
-    /// - For legacy versions: the junk folder ID is invalid or empty
+    /// - For legacy versions: the junk folder ID is invalid, or empty
+    ///   ([`EwsError::Unsupported`])
     pub async fn mark_as_junk(
         &self,
         item_ids: &[&str],
         is_junk: bool,
         legacy_junk_folder_id: &str,
     ) -> Result<Vec<String>, EwsError> {
-        // The `MarkAsJunk` operation was added in Exchange 2013
-        let server_version = Some(self.server_version.load());
-        let use_mark_as_junk =
-            server_version.is_none_or(|v| v >= ews::server_version::ExchangeServerVersion::Exchange2013); // Default to true if we can't determine version
+        let capabilities = self.capabilities();
 
-        if use_mark_as_junk {
+        if capabilities.mark_as_junk {
             // Try modern MarkAsJunk operation
             self.mark_as_junk_modern(item_ids, is_junk).await
         } else if !legacy_junk_folder_id.is_empty() {
             // Fall back to moving items for older Exchange versions
             self.move_items(legacy_junk_folder_id, item_ids).await
         } else {
-            Err(EwsError::Processing {
-                message: "Unable to mark as junk: MarkAsJunk operation not supported on this Exchange version and no legacy junk folder ID provided".to_string(),
-            })
+            // No folder to move the messages to
+            Err(capabilities.unsupported("MarkAsJunk"))
         }
     }
 
@@ -0,0 +1,203 @@
+//! Meeting workflow operations.
+//!
+//! This module contains the operations an attendee uses to answer a meeting
+//! request, the operation an organizer uses to cancel a meeting, and the
+//! meeting-specific properties of the meeting messages read with
+//! [`EwsClient::get_items`].
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    operations::calendar::ResponseType,
+    raw_requests::calendar::{cancel_meeting_request, meeting_response_request},
+    single_raw_response_or_error,
+    xml::XmlElement,
+};
+
+/// An attendee's answer to a meeting request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeetingResponse {
+    /// Accept the meeting.
+    Accept,
+    /// Tentatively accept the meeting.
+    Tentative,
+    /// Decline the meeting.
+    Decline,
+}
+
+impl MeetingResponse {
+    /// The name of the response object element for this answer.
+    pub(crate) fn element_name(self) -> &'static str {
+        match self {
+            Self::Accept => "AcceptItem",
+            Self::Tentative => "TentativelyAcceptItem",
+            Self::Decline => "DeclineItem",
+        }
+    }
+}
+
+/// The kind of a meeting message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeetingMessageKind {
+    /// An invitation to a meeting, or an update of one.
+    Request,
+    /// An attendee's answer to a meeting request.
+    Response,
+    /// The cancellation of a meeting by its organizer.
+    Cancellation,
+}
+
+impl MeetingMessageKind {
+    /// Gets the kind of a meeting message from the name of its element.
+    fn from_element_name(name: &str) -> Option<Self> {
+        match name {
+            "MeetingRequest" => Some(Self::Request),
+            "MeetingResponse" => Some(Self::Response),
+            "MeetingCancellation" => Some(Self::Cancellation),
+            _ => None,
+        }
+    }
+}
+
+/// The meeting-specific properties of a meeting message, in
+/// [`Message::meeting`](crate::client::Message::meeting).
+///
+/// Only the kind is always known: the other properties are set when the
+/// item shape returned them (e.g. with the `AllProperties` base shape).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeetingMessageInfo {
+    /// The kind of the meeting message.
+    pub kind: MeetingMessageKind,
+    /// The EWS ID of the calendar item the message relates to in the mailbox
+    /// owner's calendar, if any.
+    pub associated_calendar_item_id: Option<String>,
+    /// The start of the meeting, as a Unix timestamp.
+    pub start: Option<i64>,
+    /// The end of the meeting, as a Unix timestamp.
+    pub end: Option<i64>,
+    /// The location of the meeting.
+    pub location: Option<String>,
+    /// For a request, the mailbox owner's current response to the meeting.
+    /// For a response, the answer it carries.
+    pub response_type: Option<ResponseType>,
+    /// Whether a newer message about the same meeting has been received.
+    pub is_out_of_date: bool,
+    /// Whether the message was already processed into the calendar.
+    pub has_been_processed: bool,
+    /// The iCalendar UID of the meeting.
+    pub uid: Option<String>,
+}
+
+impl MeetingMessageInfo {
+    /// Reads the meeting properties of a `MeetingRequest`, `MeetingResponse`
+    /// or `MeetingCancellation` element. Returns `None` for other elements.
+    pub(crate) fn from_xml(element: &XmlElement) -> Option<Self> {
+        let kind = MeetingMessageKind::from_element_name(&element.name)?;
+
+        Some(Self {
+            kind,
+            associated_calendar_item_id: element.child_id("AssociatedCalendarItemId"),
+            start: element.child_date_time("Start"),
+            end: element.child_date_time("End"),
+            location: element.child_text("Location").map(ToString::to_string),
+            response_type: element.child_text("ResponseType").and_then(ResponseType::parse),
+            is_out_of_date: element.child_bool("IsOutOfDate").unwrap_or(false),
+            has_been_processed: element.child_bool("HasBeenProcessed").unwrap_or(false),
+            uid: element.child_text("UID").map(ToString::to_string),
+        })
+    }
+}
+
+impl EwsClient {
+    /// Answers a meeting request, as an attendee.
+    ///
+    /// The meeting is added to (or, when declined, removed from) the mailbox
+    /// owner's calendar, and the response is optionally sent to the
+    /// organizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the meeting request, or of the calendar
+    ///   item of the meeting
+    /// * `response` - The answer to the meeting request
+    /// * `message` - A text message to include in the response, if any
+    /// * `send_response` - Whether to send the response to the organizer. If
+    ///   `false`, the response is only saved in the Drafts folder
+    ///
+    /// # Returns
+    ///
+    /// The EWS ID of the meeting's calendar item in the mailbox owner's
+    /// calendar, if the server returned one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The item cannot be found or is not a meeting request
+    /// - The mailbox owner is the organizer of the meeting
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials, MeetingResponse};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// client
+    ///     .respond_to_meeting("AAMkAD...", MeetingResponse::Tentative, Some("I may be late."), true)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn respond_to_meeting(
+        &self,
+        item_id: &str,
+        response: MeetingResponse,
+        message: Option<&str>,
+        send_response: bool,
+    ) -> Result<Option<String>, EwsError> {
+        let body = meeting_response_request(item_id, response, message, send_response);
+
+        let response = self
+            .make_raw_operation_request("CreateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("CreateItem", &response)?;
+
+        Ok(message
+            .descendant(&["Items", "CalendarItem"])
+            .and_then(|item| item.child_id("ItemId")))
+    }
+
+    /// Cancels a meeting, as its organizer, and sends a cancellation to every
+    /// attendee.
+    ///
+    /// The calendar item of the meeting is removed from the organizer's
+    /// calendar.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the meeting's calendar item
+    /// * `message` - A text message to include in the cancellation, if any
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The item cannot be found or is not a meeting
+    /// - The mailbox owner is not the organizer of the meeting
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn cancel_meeting(&self, item_id: &str, message: Option<&str>) -> Result<(), EwsError> {
+        let body = cancel_meeting_request(item_id, message);
+
+        let response = self
+            .make_raw_operation_request("CreateItem", &body, OperationRequestOptions::default())
+            .await?;
+        single_raw_response_or_error("CreateItem", &response)?;
+
+        Ok(())
+    }
+}
@@ -4,8 +4,25 @@
 //! - `check_connectivity`: Test connection and authentication
 //! - `sync_folder_hierarchy`: Synchronize folder structure
 //! - `get_message`: Fetch individual messages
-//! - Folder operations: create, delete, update, copy, move
+//! - `get_items`: Fetch items with a chosen set of properties
+//! - Folder operations: create (including calendar, contacts, tasks and
+//!   search folders), delete, update, copy, move, and permissions
 //! - Message operations: sync, create, delete, change read status, mark as junk, copy, move
+//! - Notifications: pull subscriptions, and push subscriptions with a
+//!   callback listener (`push-notifications` feature)
+//! - Calendar: date range views, create, update, delete of calendar items,
+//!   and iCalendar import/export with time zone definitions
+//! - Meetings: responses to meeting requests, cancellations, meeting messages
+//! - Availability: free/busy information and suggested meeting times
+//! - Contacts: list, create, update, delete, and vCard import/export
+//! - Tasks: list, create, update, delete, recurrence, and flagging messages
+//!   for follow-up
+//! - Directory: name resolution, distribution list expansion, and people
+//!   search (`FindPeople`, Exchange 2013 and later)
+//! - Out of office: reading and changing automatic replies
+//! - Inbox rules: reading, creating, changing and deleting server-side rules
+//! - Delegates: listing, adding, changing and removing delegates, with their
+//!   folder permissions and the delivery of meeting requests
 //!
 //! Each operation is implemented as a method on `EwsClient` and returns
 //! a Result with appropriate error handling.
@@ -15,7 +32,10 @@ mod check_connectivity;
 pub mod copy_move_operations;
 mod create_folder;
 mod delete_folder;
+mod folder_permissions;
+mod get_items;
 mod get_message;
+mod search_folders;
 mod sync_folder_hierarchy;
 mod update_folder;
 mod update_item;
@@ -28,7 +48,60 @@ mod sync_messages;
 
 mod send_message;
 
+mod availability;
+mod calendar;
+mod contacts;
+mod content_lines;
+mod delegates;
+mod directory;
+mod inbox_rules;
+mod meetings;
+mod out_of_office;
+mod recurrence;
+mod tasks;
+
+mod notifications;
+#[cfg(feature = "push-notifications")]
+mod push_notifications;
+
 // Re-export public types
+pub use availability::{
+    AttendeeAvailability, AvailabilityEvent, MeetingSuggestion, SuggestionQuality, TimeWindow, UserAvailabilityResult,
+};
+pub use calendar::{
+    Attendee, AttendeeType, CalendarItem, CalendarItemDraft, CalendarItemType, CalendarItemUpdate, CalendarViewResult,
+    CreateCalendarItemResult, DaylightSavingTime, LegacyFreeBusyStatus, OccurrenceInfo, ResponseType,
+    SendMeetingInvitations, SendMeetingUpdates, TimeZoneDefinition, TimeZoneTransition, parse_icalendar,
+    write_icalendar,
+};
+pub use contacts::{
+    Contact, ContactEmailAddress, ContactPhoneNumber, ContactPhysicalAddress, ContactUpdate, CreateContactResult,
+    EmailAddressKey, FindContactsResult, PhoneNumberKey, PhysicalAddressKey, parse_vcards,
+};
+pub use create_folder::FolderKind;
 pub use create_message::CreateMessageResult;
+pub use delegates::{
+    DelegateFolderPermissionLevel, DelegatePermissions, DelegateSettings, DelegateUser, DeliverMeetingRequests,
+};
+pub use directory::{FindPeopleResult, MailboxType, Paging, Persona, ResolveNamesSearchScope, ResolvedName};
+pub use folder_permissions::{
+    FolderPermission, ItemAccess, PermissionLevel, PermissionSet, PermissionUser, ReadAccess,
+};
+pub(crate) use get_items::ItemProperty;
+pub use get_items::{BodyType, ItemBaseShape, ItemBody, ItemExtendedProperty, ItemShape, Message};
+pub use inbox_rules::{Importance, InboxRule, InboxRulesResult, RuleActions, RuleOperation, RulePredicates};
+pub use meetings::{MeetingMessageInfo, MeetingMessageKind, MeetingResponse};
+pub use notifications::{
+    Notification, NotificationEvent, NotificationEventType, PullSubscriptionPollResult, PullSubscriptionState,
+};
+pub use out_of_office::{ExternalAudience, OofSettings, OofState};
+#[cfg(feature = "push-notifications")]
+pub use push_notifications::{PushNotificationListener, PushNotificationResult, PushSubscription};
+pub use recurrence::{
+    DayOfWeek, DayOfWeekIndex, Recurrence, RecurrencePattern, RecurrenceRange, RegenerationFrequency,
+};
+pub use search_folders::{ContainmentMode, SearchExpression, SearchFolderDraft, SearchFolderTraversal, SearchProperty};
 pub use sync_folder_hierarchy::{FolderHierarchySyncResult, FolderInfo};
 pub use sync_messages::{SyncMessageInfo, SyncMessagesResult};
+pub use tasks::{CreateTaskResult, FindTasksResult, Task, TaskDraft, TaskStatus, TaskUpdate};
+pub use update_folder::{ExtendedPropertyKey, FolderExtendedProperty, FolderUpdate, MapiPropertyType};
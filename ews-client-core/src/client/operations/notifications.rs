@@ -0,0 +1,490 @@
+//! Notification subscriptions.
+//!
+//! This module contains the event types shared by every kind of subscription,
+//! and the operations for pull subscriptions, which let batch workers fetch
+//! events on their own schedule instead of holding a connection open.
+
+use serde::{Deserialize, Serialize};
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    raw_requests::notifications::{get_events_request, pull_subscribe_request, unsubscribe_request},
+    single_raw_response_or_error,
+    xml::XmlElement,
+};
+
+/// The longest lifetime, in minutes, which Exchange accepts for a pull
+/// subscription without any `GetEvents` request.
+const MAX_PULL_SUBSCRIPTION_TIMEOUT_MINUTES: u32 = 1440;
+
+/// Response codes indicating that a subscription no longer exists on the
+/// server and needs to be created again.
+const EXPIRED_SUBSCRIPTION_CODES: &[&str] = &[
+    "ErrorExpiredSubscription",
+    "ErrorSubscriptionNotFound",
+    "ErrorInvalidSubscription",
+];
+
+/// Response codes indicating that the server can no longer resume a
+/// subscription from the provided watermark.
+const INVALID_WATERMARK_CODES: &[&str] = &["ErrorInvalidWatermark", "ErrorReadEventsFailed"];
+
+/// The kinds of events a notification subscription can report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NotificationEventType {
+    /// An item or folder was copied.
+    Copied,
+    /// An item or folder was created.
+    Created,
+    /// An item or folder was deleted.
+    Deleted,
+    /// An item or folder was modified.
+    Modified,
+    /// An item or folder was moved.
+    Moved,
+    /// A new message arrived in the mailbox.
+    NewMail,
+    /// The free/busy information of a calendar item changed.
+    FreeBusyChanged,
+    /// A status (heartbeat) event. The server reports it when no other event
+    /// happened, and it cannot be subscribed to.
+    Status,
+}
+
+impl NotificationEventType {
+    /// The name of the element for this kind of event, which is also the value
+    /// used for it in a subscription request's `EventTypes`.
+    pub(crate) fn element_name(self) -> &'static str {
+        match self {
+            Self::Copied => "CopiedEvent",
+            Self::Created => "CreatedEvent",
+            Self::Deleted => "DeletedEvent",
+            Self::Modified => "ModifiedEvent",
+            Self::Moved => "MovedEvent",
+            Self::NewMail => "NewMailEvent",
+            Self::FreeBusyChanged => "FreeBusyChangedEvent",
+            Self::Status => "StatusEvent",
+        }
+    }
+
+    /// The kind of event matching the given element name, if any.
+    pub(crate) fn from_element_name(name: &str) -> Option<Self> {
+        let event_type = match name {
+            "CopiedEvent" => Self::Copied,
+            "CreatedEvent" => Self::Created,
+            "DeletedEvent" => Self::Deleted,
+            "ModifiedEvent" => Self::Modified,
+            "MovedEvent" => Self::Moved,
+            "NewMailEvent" => Self::NewMail,
+            "FreeBusyChangedEvent" => Self::FreeBusyChanged,
+            "StatusEvent" => Self::Status,
+            _ => return None,
+        };
+
+        Some(event_type)
+    }
+}
+
+/// An event reported by a notification subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationEvent {
+    /// The kind of event
+    pub event_type: NotificationEventType,
+    /// The watermark of the event, from which a subscription can be resumed
+    pub watermark: Option<String>,
+    /// When the event happened (`xs:dateTime`)
+    pub timestamp: Option<String>,
+    /// The EWS ID of the item the event is about, if it is about an item
+    pub item_id: Option<String>,
+    /// The EWS ID of the folder the event is about, if it is about a folder
+    pub folder_id: Option<String>,
+    /// The EWS ID of the folder containing the item or folder
+    pub parent_folder_id: Option<String>,
+    /// The previous EWS ID of a moved or copied item
+    pub old_item_id: Option<String>,
+    /// The previous EWS ID of a moved or copied folder
+    pub old_folder_id: Option<String>,
+    /// The previous parent folder of a moved or copied item or folder
+    pub old_parent_folder_id: Option<String>,
+    /// The number of unread items in a modified folder
+    pub unread_count: Option<u32>,
+}
+
+impl NotificationEvent {
+    /// Reads an event from its element, returning `None` if the element isn't
+    /// a known event.
+    pub(crate) fn from_xml(element: &XmlElement) -> Option<Self> {
+        let event_type = NotificationEventType::from_element_name(&element.name)?;
+
+        Some(Self {
+            event_type,
+            watermark: element.child_text("Watermark").map(ToString::to_string),
+            timestamp: element.child_text("TimeStamp").map(ToString::to_string),
+            item_id: element.child_id("ItemId"),
+            folder_id: element.child_id("FolderId"),
+            parent_folder_id: element.child_id("ParentFolderId"),
+            old_item_id: element.child_id("OldItemId"),
+            old_folder_id: element.child_id("OldFolderId"),
+            old_parent_folder_id: element.child_id("OldParentFolderId"),
+            unread_count: element.child_parse("UnreadCount"),
+        })
+    }
+}
+
+/// A batch of events for a subscription, as returned by `GetEvents` or pushed
+/// by the server with `SendNotification`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    /// The ID of the subscription the events belong to
+    pub subscription_id: String,
+    /// The watermark the events were read from
+    pub previous_watermark: Option<String>,
+    /// Whether the server has more events queued for this subscription
+    pub more_events: bool,
+    /// The events, in the order they happened
+    pub events: Vec<NotificationEvent>,
+}
+
+impl Notification {
+    /// Reads a notification from a `Notification` element.
+    pub(crate) fn from_xml(element: &XmlElement) -> Result<Self, EwsError> {
+        let subscription_id = element
+            .child_text("SubscriptionId")
+            .ok_or_else(|| EwsError::Processing {
+                message: "notification is missing a subscription ID".to_string(),
+            })?
+            .to_string();
+
+        Ok(Self {
+            subscription_id,
+            previous_watermark: element.child_text("PreviousWatermark").map(ToString::to_string),
+            more_events: element.child_bool("MoreEvents").unwrap_or(false),
+            events: element
+                .children
+                .iter()
+                .filter_map(NotificationEvent::from_xml)
+                .collect(),
+        })
+    }
+
+    /// The watermark of the most recent event in this notification, if any.
+    pub fn last_watermark(&self) -> Option<&str> {
+        self.events.iter().rev().find_map(|event| event.watermark.as_deref())
+    }
+}
+
+/// The state of a pull subscription.
+///
+/// It contains everything needed to keep reading events after a restart, or
+/// to re-create the subscription once it has expired, and can be persisted
+/// with [`PullSubscriptionState::to_json`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullSubscriptionState {
+    /// The ID of the subscription on the server
+    pub subscription_id: String,
+    /// The watermark of the last event which was read
+    pub watermark: String,
+    /// The folders the subscription watches (EWS or distinguished IDs); empty
+    /// if it watches all folders
+    pub folder_ids: Vec<String>,
+    /// The kinds of events the subscription reports
+    pub event_types: Vec<NotificationEventType>,
+    /// How long, in minutes, the subscription lives without `GetEvents` requests
+    pub timeout_minutes: u32,
+}
+
+impl PullSubscriptionState {
+    /// Serializes the state to JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state cannot be serialized.
+    pub fn to_json(&self) -> Result<String, EwsError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Reads a state previously serialized with [`PullSubscriptionState::to_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is not a valid subscription state.
+    pub fn from_json(json: &str) -> Result<Self, EwsError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// The result of polling a pull subscription.
+#[derive(Debug, Clone, Default)]
+pub struct PullSubscriptionPollResult {
+    /// The events which happened since the previous poll, excluding status
+    /// events
+    pub events: Vec<NotificationEvent>,
+    /// Whether the subscription had expired and could not be resumed from its
+    /// last watermark. Events which happened in the meantime are lost, so the
+    /// caller should perform a fresh sync (e.g. with `sync_messages`).
+    pub requires_resync: bool,
+}
+
+impl EwsClient {
+    /// Creates a pull subscription for events in the given folders.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_ids` - The EWS or distinguished IDs of the folders to watch;
+    ///   an empty slice watches every folder in the mailbox
+    /// * `event_types` - The kinds of events to report
+    /// * `timeout_minutes` - How long the subscription lives without a
+    ///   `GetEvents` request, between 1 and 1440 minutes
+    ///
+    /// # Returns
+    ///
+    /// The state of the new subscription, to be used with
+    /// [`EwsClient::poll_pull_subscription`] or [`EwsClient::get_events`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The timeout or event types are invalid
+    /// - Any folder does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{EwsClient, Credentials, NotificationEventType};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?;
+    /// let mut state = client.subscribe_pull(&["inbox"], &[NotificationEventType::NewMail], 30).await?;
+    /// let result = client.poll_pull_subscription(&mut state).await?;
+    /// println!("{} new events", result.events.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_pull(
+        &self,
+        folder_ids: &[&str],
+        event_types: &[NotificationEventType],
+        timeout_minutes: u32,
+    ) -> Result<PullSubscriptionState, EwsError> {
+        self.subscribe_pull_from_watermark(folder_ids, event_types, timeout_minutes, None)
+            .await
+    }
+
+    /// Fetches the events of a pull subscription which happened after the
+    /// given watermark.
+    ///
+    /// The server returns a limited number of events per request; if
+    /// `more_events` is set on the result, the request should be repeated with
+    /// the watermark of the last returned event.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscription_id` - The ID of the pull subscription
+    /// * `watermark` - The watermark of the last event which was read
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The subscription has expired or does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn get_events(&self, subscription_id: &str, watermark: &str) -> Result<Notification, EwsError> {
+        let body = get_events_request(subscription_id, watermark);
+
+        let response = self
+            .make_raw_operation_request("GetEvents", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("GetEvents", &response)?;
+
+        let notification = message.child("Notification").ok_or_else(|| EwsError::Processing {
+            message: "GetEvents response is missing a notification".to_string(),
+        })?;
+
+        Notification::from_xml(notification)
+    }
+
+    /// Reads every event queued for a pull subscription and advances its
+    /// watermark.
+    ///
+    /// If the subscription has expired, a new one is created with the same
+    /// folders and event types, and its events are read in turn. The new
+    /// subscription resumes from the last watermark when the server still
+    /// allows it; otherwise `requires_resync` is set on the result.
+    ///
+    /// The state is only updated when the events are returned: if any request
+    /// fails, it is left unchanged, and the next poll reads the same events
+    /// again.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state of the subscription, updated in place
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The subscription cannot be re-created
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn poll_pull_subscription(
+        &self,
+        state: &mut PullSubscriptionState,
+    ) -> Result<PullSubscriptionPollResult, EwsError> {
+        // Work on a copy, so that the watermark only moves past the events
+        // once they are returned.
+        let mut current = state.clone();
+        let mut result = PullSubscriptionPollResult::default();
+        let mut resubscribed = false;
+
+        loop {
+            let notification = match self.get_events(&current.subscription_id, &current.watermark).await {
+                Ok(notification) => notification,
+                Err(err) if !resubscribed && has_response_code(&err, EXPIRED_SUBSCRIPTION_CODES) => {
+                    log::info!(
+                        "pull subscription {} has expired, creating a new one",
+                        current.subscription_id
+                    );
+                    result.requires_resync = self.resubscribe_pull(&mut current).await?;
+                    resubscribed = true;
+
+                    // A subscription resumed from a watermark reports the
+                    // events which happened after it.
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            if let Some(watermark) = notification.last_watermark() {
+                current.watermark = watermark.to_string();
+            }
+
+            result.events.extend(
+                notification
+                    .events
+                    .into_iter()
+                    .filter(|event| event.event_type != NotificationEventType::Status),
+            );
+
+            if !notification.more_events {
+                break;
+            }
+        }
+
+        *state = current;
+        Ok(result)
+    }
+
+    /// Ends a pull or push subscription.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscription_id` - The ID of the subscription
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The subscription does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn unsubscribe(&self, subscription_id: &str) -> Result<(), EwsError> {
+        let body = unsubscribe_request(subscription_id);
+
+        let response = self
+            .make_raw_operation_request("Unsubscribe", &body, OperationRequestOptions::default())
+            .await?;
+        single_raw_response_or_error("Unsubscribe", &response)?;
+
+        Ok(())
+    }
+
+    /// Re-creates an expired pull subscription, updating the state in place.
+    ///
+    /// Returns whether the new subscription could not resume from the previous
+    /// watermark, meaning events were lost and the caller needs a fresh sync.
+    async fn resubscribe_pull(&self, state: &mut PullSubscriptionState) -> Result<bool, EwsError> {
+        let folder_ids: Vec<&str> = state.folder_ids.iter().map(String::as_str).collect();
+
+        // Try resuming from the last watermark first, so that no event is lost.
+        match self
+            .subscribe_pull_from_watermark(
+                &folder_ids,
+                &state.event_types,
+                state.timeout_minutes,
+                Some(&state.watermark),
+            )
+            .await
+        {
+            Ok(new_state) => {
+                *state = new_state;
+                Ok(false)
+            }
+            Err(err) if has_response_code(&err, INVALID_WATERMARK_CODES) => {
+                log::warn!("could not resume pull subscription from its last watermark, events were lost");
+                *state = self
+                    .subscribe_pull_from_watermark(&folder_ids, &state.event_types, state.timeout_minutes, None)
+                    .await?;
+                Ok(true)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Creates a pull subscription, optionally resuming from a watermark of a
+    /// previous subscription.
+    async fn subscribe_pull_from_watermark(
+        &self,
+        folder_ids: &[&str],
+        event_types: &[NotificationEventType],
+        timeout_minutes: u32,
+        watermark: Option<&str>,
+    ) -> Result<PullSubscriptionState, EwsError> {
+        if !(1..=MAX_PULL_SUBSCRIPTION_TIMEOUT_MINUTES).contains(&timeout_minutes) {
+            return Err(EwsError::Processing {
+                message: format!(
+                    "pull subscription timeout must be between 1 and {MAX_PULL_SUBSCRIPTION_TIMEOUT_MINUTES} minutes, got {timeout_minutes}"
+                ),
+            });
+        }
+
+        let body = pull_subscribe_request(
+            folder_ids,
+            event_types,
+            timeout_minutes,
+            watermark,
+            self.target_mailbox(),
+        )?;
+
+        let response = self
+            .make_raw_operation_request("Subscribe", &body, OperationRequestOptions::default())
+            .await?;
+        let (subscription_id, watermark) = read_subscribe_response(&response)?;
+
+        Ok(PullSubscriptionState {
+            subscription_id,
+            watermark: watermark.ok_or_else(|| EwsError::Processing {
+                message: "Subscribe response is missing a watermark".to_string(),
+            })?,
+            folder_ids: folder_ids.iter().map(ToString::to_string).collect(),
+            event_types: event_types.to_vec(),
+            timeout_minutes,
+        })
+    }
+}
+
+/// Reads the subscription ID and watermark from a `Subscribe` response.
+pub(crate) fn read_subscribe_response(response: &XmlElement) -> Result<(String, Option<String>), EwsError> {
+    let message = single_raw_response_or_error("Subscribe", response)?;
+
+    let subscription_id = message
+        .child_text("SubscriptionId")
+        .ok_or(EwsError::MissingIdInResponse)?
+        .to_string();
+    let watermark = message.child_text("Watermark").map(ToString::to_string);
+
+    Ok((subscription_id, watermark))
+}
+
+/// Whether the error is a response error with one of the given codes.
+fn has_response_code(err: &EwsError, codes: &[&str]) -> bool {
+    matches!(err, EwsError::RawResponseError { response_code, .. } if codes.contains(&response_code.as_str()))
+}
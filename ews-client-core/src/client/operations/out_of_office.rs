@@ -0,0 +1,264 @@
+//! Out-of-office (automatic replies) operations.
+//!
+//! This module contains the `GetUserOofSettings` and `SetUserOofSettings`
+//! operations, which read and change the automatic replies of a mailbox.
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    raw_requests::mailbox_settings::{get_user_oof_settings_request, set_user_oof_settings_request},
+    single_raw_response_or_error,
+    xml::XmlElement,
+};
+
+/// Whether automatic replies are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OofState {
+    /// No automatic replies are sent.
+    Disabled,
+    /// Automatic replies are sent until they are disabled.
+    Enabled,
+    /// Automatic replies are sent between two times.
+    Scheduled {
+        /// When the replies start being sent, as a Unix timestamp.
+        start: i64,
+        /// When the replies stop being sent, as a Unix timestamp.
+        end: i64,
+    },
+}
+
+/// Which senders outside the organization get automatic replies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalAudience {
+    /// No external sender.
+    None,
+    /// Only external senders in the user's contacts.
+    Known,
+    /// All external senders.
+    All,
+}
+
+impl ExternalAudience {
+    /// The EWS value of the audience.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Known => "Known",
+            Self::All => "All",
+        }
+    }
+
+    /// Parses an audience from its EWS value.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "None" => Some(Self::None),
+            "Known" => Some(Self::Known),
+            "All" => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// The automatic reply settings of a mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OofSettings {
+    /// Whether, and when, automatic replies are sent.
+    pub state: OofState,
+    /// Which external senders get the external reply.
+    pub external_audience: ExternalAudience,
+    /// The reply sent to senders inside the organization, usually HTML.
+    pub internal_reply: Option<String>,
+    /// The reply sent to senders outside the organization, usually HTML.
+    pub external_reply: Option<String>,
+    /// The widest external audience the administrator allows for the
+    /// mailbox, if returned.
+    pub allow_external_oof: Option<ExternalAudience>,
+}
+
+impl OofSettings {
+    /// Reads the settings from a `GetUserOofSettingsResponse` element.
+    fn from_xml(response: &XmlElement) -> Result<Self, EwsError> {
+        let settings = response.child("OofSettings").ok_or_else(|| EwsError::Processing {
+            message: "GetUserOofSettings response is missing the settings".to_string(),
+        })?;
+        let reply = |name: &str| {
+            settings
+                .child(name)
+                .and_then(|reply| reply.child_text("Message"))
+                .filter(|message| !message.trim().is_empty())
+                .map(ToString::to_string)
+        };
+
+        let state = match settings.child_text("OofState").map(str::trim) {
+            Some("Disabled") => OofState::Disabled,
+            Some("Enabled") => OofState::Enabled,
+            Some("Scheduled") => {
+                let duration = settings.child("Duration");
+                let time = |name: &str| duration.and_then(|duration| duration.child_date_time(name));
+                match (time("StartTime"), time("EndTime")) {
+                    (Some(start), Some(end)) => OofState::Scheduled { start, end },
+                    _ => {
+                        return Err(EwsError::Processing {
+                            message: "scheduled out-of-office settings are missing their duration".to_string(),
+                        });
+                    }
+                }
+            }
+            state => {
+                return Err(EwsError::Processing {
+                    message: format!("unexpected out-of-office state {state:?}"),
+                });
+            }
+        };
+
+        Ok(Self {
+            state,
+            external_audience: settings
+                .child_text("ExternalAudience")
+                .and_then(ExternalAudience::parse)
+                .unwrap_or(ExternalAudience::None),
+            internal_reply: reply("InternalReply"),
+            external_reply: reply("ExternalReply"),
+            allow_external_oof: response
+                .child_text("AllowExternalOof")
+                .and_then(ExternalAudience::parse),
+        })
+    }
+}
+
+impl EwsClient {
+    /// Reads the automatic reply settings of a mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The user may not read the settings of the mailbox
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{Credentials, EwsClient, OofState};
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let settings = client.get_user_oof_settings("user@example.com").await?;
+    /// if settings.state != OofState::Disabled {
+    ///     println!("{:?}", settings.internal_reply);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_user_oof_settings(&self, mailbox: &str) -> Result<OofSettings, EwsError> {
+        let body = get_user_oof_settings_request(mailbox)?;
+
+        let response = self
+            .make_raw_operation_request("GetUserOofSettings", &body, OperationRequestOptions::default())
+            .await?;
+        single_raw_response_or_error("GetUserOofSettings", &response)?;
+
+        OofSettings::from_xml(&response)
+    }
+
+    /// Changes the automatic reply settings of a mailbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailbox` - The email address of the mailbox
+    /// * `state` - Whether, and when, automatic replies are sent
+    /// * `internal_reply` - The reply sent to senders inside the organization
+    /// * `external_reply` - The reply sent to senders outside the
+    ///   organization
+    /// * `external_audience` - Which external senders get the external reply
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A scheduled state does not end after it starts
+    /// - The user may not change the settings of the mailbox
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn set_user_oof_settings(
+        &self,
+        mailbox: &str,
+        state: OofState,
+        internal_reply: &str,
+        external_reply: &str,
+        external_audience: ExternalAudience,
+    ) -> Result<(), EwsError> {
+        if let OofState::Scheduled { start, end } = state
+            && end <= start
+        {
+            return Err(EwsError::Processing {
+                message: "scheduled automatic replies must end after they start".to_string(),
+            });
+        }
+
+        let body = set_user_oof_settings_request(mailbox, state, internal_reply, external_reply, external_audience)?;
+
+        let response = self
+            .make_raw_operation_request("SetUserOofSettings", &body, OperationRequestOptions::default())
+            .await?;
+        single_raw_response_or_error("SetUserOofSettings", &response)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn settings_xml(state: &str) -> String {
+        format!(
+            r#"<GetUserOofSettingsResponse xmlns="http://schemas.microsoft.com/exchange/services/2006/messages">
+              <ResponseMessage ResponseClass="Success"><ResponseCode>NoError</ResponseCode></ResponseMessage>
+              <OofSettings xmlns="http://schemas.microsoft.com/exchange/services/2006/types">
+                <OofState>{state}</OofState>
+                <ExternalAudience>Known</ExternalAudience>
+                <Duration>
+                  <StartTime>2024-01-15T00:00:00Z</StartTime>
+                  <EndTime>2024-01-22T00:00:00Z</EndTime>
+                </Duration>
+                <InternalReply><Message>Back next week</Message></InternalReply>
+                <ExternalReply><Message /></ExternalReply>
+              </OofSettings>
+              <AllowExternalOof>All</AllowExternalOof>
+            </GetUserOofSettingsResponse>"#
+        )
+    }
+
+    #[test]
+    fn test_oof_settings_from_xml() {
+        let response = XmlElement::parse(settings_xml("Scheduled").as_bytes()).unwrap();
+        let settings = OofSettings::from_xml(&response).unwrap();
+
+        assert_eq!(
+            settings.state,
+            OofState::Scheduled {
+                start: 1_705_276_800,
+                end: 1_705_881_600
+            }
+        );
+        assert_eq!(settings.external_audience, ExternalAudience::Known);
+        assert_eq!(settings.internal_reply.as_deref(), Some("Back next week"));
+        assert_eq!(settings.external_reply, None);
+        assert_eq!(settings.allow_external_oof, Some(ExternalAudience::All));
+
+        // The duration is only meaningful for scheduled replies
+        let response = XmlElement::parse(settings_xml("Enabled").as_bytes()).unwrap();
+        assert_eq!(OofSettings::from_xml(&response).unwrap().state, OofState::Enabled);
+
+        let response = XmlElement::parse(settings_xml("Sometimes").as_bytes()).unwrap();
+        assert!(OofSettings::from_xml(&response).is_err());
+    }
+}
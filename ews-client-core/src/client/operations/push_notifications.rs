@@ -0,0 +1,744 @@
+//! Push notification subscriptions.
+//!
+//! With a push subscription, Exchange delivers events by sending
+//! `SendNotification` requests to an HTTP endpoint hosted by the client. This
+//! module provides the `Subscribe` operation for push subscriptions, and
+//! [`PushNotificationListener`], a small HTTP listener which hosts that
+//! endpoint on the tokio runtime.
+//!
+//! This module is only available with the `push-notifications` feature.
+
+use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
+
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::{TcpListener, ToSocketAddrs},
+    sync::Semaphore,
+};
+use url::Url;
+
+use super::notifications::read_subscribe_response;
+use crate::client::{
+    EwsClient, EwsError, MESSAGES_NS_URI, Notification, NotificationEventType, OperationRequestOptions, SOAP_NS_URI,
+    process_raw_response_message, raw_requests::notifications::push_subscribe_request, raw_response_messages,
+    xml::XmlElement,
+};
+
+/// The longest interval, in minutes, which Exchange accepts between status
+/// notifications of a push subscription.
+const MAX_STATUS_FREQUENCY_MINUTES: u32 = 1440;
+
+/// The largest request body the listener accepts. Exchange batches events, but
+/// a single notification stays well below this size.
+const MAX_REQUEST_BODY_SIZE: usize = 1024 * 1024;
+
+/// How long the listener waits for the whole of a request, or for the next
+/// request of a kept-alive connection, before closing the connection.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The largest number of connections the listener serves at once. Further
+/// connections wait in the backlog of the socket until one is closed.
+const MAX_CONNECTIONS: usize = 64;
+
+/// The largest number of header lines the listener accepts in a request.
+const MAX_REQUEST_HEADERS: usize = 100;
+
+/// The longest line the listener accepts, for the request line, headers,
+/// chunk sizes and trailers alike.
+const MAX_LINE_LENGTH: usize = 8 * 1024;
+
+/// A push subscription created on the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushSubscription {
+    /// The ID of the subscription on the server
+    pub subscription_id: String,
+    /// The watermark the subscription starts from, if the server provided one
+    pub watermark: Option<String>,
+}
+
+/// The answer to a `SendNotification` request, telling the server whether it
+/// should keep sending notifications for the subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PushNotificationResult {
+    /// Keep the subscription alive.
+    #[default]
+    Ok,
+    /// End the subscription.
+    Unsubscribe,
+}
+
+impl PushNotificationResult {
+    /// The value of the `SubscriptionStatus` element for this result.
+    fn subscription_status(self) -> &'static str {
+        match self {
+            Self::Ok => "OK",
+            Self::Unsubscribe => "Unsubscribe",
+        }
+    }
+}
+
+impl EwsClient {
+    /// Creates a push subscription, for which the server sends events to the
+    /// given callback URL.
+    ///
+    /// The callback URL must be reachable by the Exchange server, and is
+    /// usually served by a [`PushNotificationListener`].
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_ids` - The EWS or distinguished IDs of the folders to watch;
+    ///   an empty slice watches every folder in the mailbox
+    /// * `event_types` - The kinds of events to report
+    /// * `callback_url` - The URL the server sends `SendNotification` requests to
+    /// * `status_frequency_minutes` - How often the server sends a status
+    ///   notification when no event happened, between 1 and 1440 minutes
+    /// * `watermark` - A watermark from a previous subscription to resume from
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The status frequency or event types are invalid
+    /// - Any folder does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{
+    /// #     Credentials, EwsClient, NotificationEventType, PushNotificationListener, PushNotificationResult,
+    /// # };
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?;
+    ///
+    /// let callback_url = "https://client.example.com:8080/ews/notifications".parse()?;
+    /// let listener = PushNotificationListener::bind("0.0.0.0:8080", &callback_url).await?;
+    /// let subscription = client
+    ///     .subscribe_push(&["inbox"], &[NotificationEventType::NewMail], &callback_url, 5, None)
+    ///     .await?;
+    /// println!("subscribed with ID {}", subscription.subscription_id);
+    ///
+    /// listener
+    ///     .run(|notification| async move {
+    ///         println!("{} new events", notification.events.len());
+    ///         PushNotificationResult::Ok
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_push(
+        &self,
+        folder_ids: &[&str],
+        event_types: &[NotificationEventType],
+        callback_url: &Url,
+        status_frequency_minutes: u32,
+        watermark: Option<&str>,
+    ) -> Result<PushSubscription, EwsError> {
+        if !(1..=MAX_STATUS_FREQUENCY_MINUTES).contains(&status_frequency_minutes) {
+            return Err(EwsError::Processing {
+                message: format!(
+                    "push subscription status frequency must be between 1 and {MAX_STATUS_FREQUENCY_MINUTES} minutes, got {status_frequency_minutes}"
+                ),
+            });
+        }
+
+        let body = push_subscribe_request(
+            folder_ids,
+            event_types,
+            callback_url.as_str(),
+            status_frequency_minutes,
+            watermark,
+            self.target_mailbox(),
+        )?;
+
+        let response = self
+            .make_raw_operation_request("Subscribe", &body, OperationRequestOptions::default())
+            .await?;
+        let (subscription_id, watermark) = read_subscribe_response(&response)?;
+
+        Ok(PushSubscription {
+            subscription_id,
+            watermark,
+        })
+    }
+}
+
+/// An HTTP listener hosting the endpoint Exchange sends push notifications to.
+///
+/// The listener accepts plain HTTP connections; when the callback URL uses
+/// HTTPS, TLS needs to be terminated in front of it (e.g. by a reverse proxy).
+///
+/// Only requests to the path of the callback URL are served, and the listener
+/// bounds the size of requests, the time taken to send them and the number of
+/// connections served at once, since the endpoint is usually exposed to the
+/// network.
+#[derive(Debug)]
+pub struct PushNotificationListener {
+    listener: TcpListener,
+    callback_path: String,
+}
+
+impl PushNotificationListener {
+    /// Binds the listener to the given address, to serve the callback URL
+    /// given to [`EwsClient::subscribe_push`].
+    ///
+    /// Requests to any other path than that of `callback_url` are answered
+    /// with `404 Not Found`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(addr: impl ToSocketAddrs, callback_url: &Url) -> Result<Self, EwsError> {
+        let listener = TcpListener::bind(addr).await?;
+
+        Ok(Self {
+            listener,
+            callback_path: callback_url.path().to_string(),
+        })
+    }
+
+    /// The local address the listener is bound to, e.g. to find out which port
+    /// was picked when binding to port 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address of the underlying socket cannot be read.
+    pub fn local_addr(&self) -> Result<SocketAddr, EwsError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Accepts connections and passes every notification received to the
+    /// handler, answering the server with the handler's result.
+    ///
+    /// Each connection is served on its own task, up to 64 at once, and is
+    /// closed when a request takes more than 30 seconds to arrive. If a
+    /// request contains
+    /// several notifications and the handler asks to unsubscribe for any of
+    /// them, the server is told to end the subscription. This method only
+    /// returns on failure; to stop the listener, drop or abort the future
+    /// (e.g. with `tokio::select!`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if accepting a new connection fails.
+    pub async fn run<H, Fut>(self, handler: H) -> Result<(), EwsError>
+    where
+        H: Fn(Notification) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = PushNotificationResult> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let callback_path: Arc<str> = self.callback_path.into();
+        let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+
+        loop {
+            // Wait for a connection to close before accepting another one
+            // once the limit is reached.
+            let permit = Arc::clone(&connections)
+                .acquire_owned()
+                .await
+                .map_err(|err| EwsError::Processing {
+                    message: format!("push notification listener stopped: {err}"),
+                })?;
+            let (stream, peer) = self.listener.accept().await?;
+            let handler = Arc::clone(&handler);
+            let callback_path = Arc::clone(&callback_path);
+
+            tokio::spawn(async move {
+                if let Err(err) = handle_connection(stream, &callback_path, REQUEST_TIMEOUT, handler.as_ref()).await {
+                    log::warn!("failed to handle push notification connection from {peer}: {err}");
+                }
+                drop(permit);
+            });
+        }
+    }
+}
+
+/// A request read from a connection to the listener.
+struct HttpRequest {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+    keep_alive: bool,
+}
+
+/// Serves the requests of a single connection until the client closes it, or
+/// until a request takes longer than `request_timeout` to arrive.
+async fn handle_connection<S, H, Fut>(
+    stream: S,
+    callback_path: &str,
+    request_timeout: Duration,
+    handler: &H,
+) -> Result<(), EwsError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    H: Fn(Notification) -> Fut,
+    Fut: Future<Output = PushNotificationResult>,
+{
+    let mut stream = BufReader::new(stream);
+
+    loop {
+        let Ok(request) = tokio::time::timeout(request_timeout, read_request(&mut stream)).await else {
+            log::debug!("closing push notification connection after {request_timeout:?} without a request");
+            break;
+        };
+
+        let request = match request {
+            Ok(Some(request)) => request,
+            Ok(None) => break,
+            // Malformed requests, e.g. with overlong lines, are answered
+            // rather than dropped.
+            Err(err @ EwsError::Processing { .. }) => {
+                log::warn!("{err}");
+                write_response(&mut stream, "400 Bad Request", "", false).await?;
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+
+        if request.path != callback_path {
+            write_response(&mut stream, "404 Not Found", "", false).await?;
+            return Ok(());
+        }
+
+        if request.method != "POST" {
+            write_response(&mut stream, "405 Method Not Allowed", "", false).await?;
+            return Ok(());
+        }
+
+        let notifications = match parse_send_notification(&request.body) {
+            Ok(notifications) => notifications,
+            Err(err) => {
+                log::warn!("received an invalid push notification: {err}");
+                write_response(&mut stream, "400 Bad Request", "", false).await?;
+                return Ok(());
+            }
+        };
+
+        let mut result = PushNotificationResult::Ok;
+        for notification in notifications {
+            if handler(notification).await == PushNotificationResult::Unsubscribe {
+                result = PushNotificationResult::Unsubscribe;
+            }
+        }
+
+        write_response(
+            &mut stream,
+            "200 OK",
+            &send_notification_result_xml(result),
+            request.keep_alive,
+        )
+        .await?;
+
+        if !request.keep_alive {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the next HTTP/1.x request from a connection, returning `None` if the
+/// client closed the connection before sending one.
+async fn read_request<S>(stream: &mut S) -> Result<Option<HttpRequest>, EwsError>
+where
+    S: AsyncBufRead + AsyncWrite + Unpin,
+{
+    let mut line = String::new();
+    if read_line(stream, &mut line).await? == 0 {
+        return Ok(None);
+    }
+
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_path(request_line.next().unwrap_or_default());
+    let version = request_line.next().unwrap_or("HTTP/1.0");
+
+    let mut keep_alive = version == "HTTP/1.1";
+    let mut content_length = 0;
+    let mut chunked = false;
+    let mut expect_continue = false;
+
+    let mut header_count = 0;
+    loop {
+        if read_line(stream, &mut line).await? == 0 {
+            return Err(bad_request("connection closed before the end of the headers"));
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        header_count += 1;
+        if header_count > MAX_REQUEST_HEADERS {
+            return Err(bad_request("too many headers"));
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        if name.eq_ignore_ascii_case("Content-Length") {
+            content_length = value
+                .parse()
+                .map_err(|_| bad_request(&format!("invalid Content-Length: {value}")))?;
+        } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        } else if name.eq_ignore_ascii_case("Connection") {
+            if value.eq_ignore_ascii_case("close") {
+                keep_alive = false;
+            } else if value.eq_ignore_ascii_case("keep-alive") {
+                keep_alive = true;
+            }
+        } else if name.eq_ignore_ascii_case("Expect") {
+            expect_continue = value.eq_ignore_ascii_case("100-continue");
+        }
+    }
+
+    // .NET clients, which Exchange is one of, wait for an interim response
+    // before sending the body of a POST request.
+    if expect_continue {
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await?;
+        stream.flush().await?;
+    }
+
+    let body = if chunked {
+        read_chunked_body(stream).await?
+    } else {
+        if content_length > MAX_REQUEST_BODY_SIZE {
+            return Err(bad_request("request body is too large"));
+        }
+
+        let mut body = Vec::new();
+        read_body_part(stream, content_length, &mut body).await?;
+        body
+    };
+
+    Ok(Some(HttpRequest {
+        method,
+        path,
+        body,
+        keep_alive,
+    }))
+}
+
+/// Reads the path of a request target, which is either a path or, when sent
+/// to a proxy, an absolute URL. The query string is not part of the path.
+fn request_path(target: &str) -> String {
+    if target.starts_with('/') {
+        let end = target.find(['?', '#']).unwrap_or(target.len());
+        return target.get(..end).unwrap_or(target).to_string();
+    }
+
+    Url::parse(target).map(|url| url.path().to_string()).unwrap_or_default()
+}
+
+/// Appends the next `length` bytes of the stream to `body`.
+///
+/// The body grows as data arrives rather than being allocated up front, so
+/// that a client cannot claim memory by announcing a large body it never
+/// sends.
+async fn read_body_part<S>(stream: &mut S, length: usize, body: &mut Vec<u8>) -> Result<(), EwsError>
+where
+    S: AsyncBufRead + Unpin,
+{
+    let read = (&mut *stream).take(length as u64).read_to_end(body).await?;
+    if read < length {
+        return Err(bad_request("connection closed before the end of the body"));
+    }
+
+    Ok(())
+}
+
+/// Reads a request body sent with the `chunked` transfer encoding.
+async fn read_chunked_body<S>(stream: &mut S) -> Result<Vec<u8>, EwsError>
+where
+    S: AsyncBufRead + Unpin,
+{
+    let mut body = Vec::new();
+    let mut line = String::new();
+
+    loop {
+        read_line(stream, &mut line).await?;
+
+        // Chunk extensions, if any, follow the size after a semicolon.
+        let size = line.trim().split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size, 16).map_err(|_| bad_request(&format!("invalid chunk size: {size}")))?;
+
+        if size == 0 {
+            break;
+        }
+
+        if body
+            .len()
+            .checked_add(size)
+            .is_none_or(|length| length > MAX_REQUEST_BODY_SIZE)
+        {
+            return Err(bad_request("request body is too large"));
+        }
+
+        read_body_part(stream, size, &mut body).await?;
+
+        // Each chunk is followed by a line break.
+        read_line(stream, &mut line).await?;
+    }
+
+    // Skip the trailer section, which ends with an empty line.
+    let mut trailer_count = 0;
+    loop {
+        if read_line(stream, &mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+
+        trailer_count += 1;
+        if trailer_count > MAX_REQUEST_HEADERS {
+            return Err(bad_request("too many trailers"));
+        }
+    }
+
+    Ok(body)
+}
+
+/// Replaces `line` with the next line of the stream, returning the number of
+/// bytes read (0 at the end of the stream).
+///
+/// Lines longer than [`MAX_LINE_LENGTH`] are rejected without being read
+/// whole, so that a client cannot exhaust memory with a single line.
+async fn read_line<S>(stream: &mut S, line: &mut String) -> Result<usize, EwsError>
+where
+    S: AsyncBufRead + Unpin,
+{
+    line.clear();
+
+    // Allow one more byte than the limit, to tell a line of exactly the
+    // limit from a longer one.
+    let read = (&mut *stream).take(MAX_LINE_LENGTH as u64 + 1).read_line(line).await?;
+    if read > MAX_LINE_LENGTH {
+        return Err(bad_request("line is too long"));
+    }
+
+    Ok(read)
+}
+
+/// Writes an HTTP response with the given status and XML body.
+async fn write_response<S>(stream: &mut S, status: &str, body: &str, keep_alive: bool) -> Result<(), EwsError>
+where
+    S: AsyncWrite + Unpin,
+{
+    let connection = if keep_alive { "keep-alive" } else { "close" };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: {connection}\r\n\r\n{body}",
+        body.len()
+    );
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+
+    Ok(())
+}
+
+/// Reads the notifications from the body of a `SendNotification` request.
+///
+/// Response messages with an error class are logged and skipped, since the
+/// server expects an answer for the request as a whole.
+fn parse_send_notification(body: &[u8]) -> Result<Vec<Notification>, EwsError> {
+    let envelope = XmlElement::parse(body)?;
+
+    let send_notification = envelope
+        .descendant(&["Body", "SendNotification"])
+        .ok_or_else(|| EwsError::Processing {
+            message: "request is not a SendNotification request".to_string(),
+        })?;
+
+    let mut notifications = Vec::new();
+    for message in raw_response_messages(send_notification) {
+        let message = match process_raw_response_message("SendNotification", message) {
+            Ok(message) => message,
+            Err(err) => {
+                log::warn!("push notification contained an error: {err}");
+                continue;
+            }
+        };
+
+        if let Some(notification) = message.child("Notification") {
+            notifications.push(Notification::from_xml(notification)?);
+        }
+    }
+
+    Ok(notifications)
+}
+
+/// Writes the SOAP envelope answering a `SendNotification` request.
+fn send_notification_result_xml(result: PushNotificationResult) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="{SOAP_NS_URI}" xmlns:m="{MESSAGES_NS_URI}"><soap:Body><m:SendNotificationResult><m:SubscriptionStatus>{}</m:SubscriptionStatus></m:SendNotificationResult></soap:Body></soap:Envelope>"#,
+        result.subscription_status()
+    )
+}
+
+/// Creates the error for a malformed request.
+fn bad_request(message: &str) -> EwsError {
+    EwsError::Processing {
+        message: format!("malformed push notification request: {message}"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    const SEND_NOTIFICATION: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <m:SendNotification xmlns:m="http://schemas.microsoft.com/exchange/services/2006/messages"
+                        xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+      <m:ResponseMessages>
+        <m:SendNotificationResponseMessage ResponseClass="Success">
+          <m:ResponseCode>NoError</m:ResponseCode>
+          <m:Notification>
+            <t:SubscriptionId>sub-1</t:SubscriptionId>
+            <t:PreviousWatermark>wm-1</t:PreviousWatermark>
+            <t:MoreEvents>false</t:MoreEvents>
+            <t:CreatedEvent>
+              <t:Watermark>wm-2</t:Watermark>
+              <t:TimeStamp>2024-01-15T10:30:00Z</t:TimeStamp>
+              <t:ItemId Id="item-1" ChangeKey="CQAAAA==" />
+              <t:ParentFolderId Id="folder-1" ChangeKey="AQAAAA==" />
+            </t:CreatedEvent>
+            <t:ModifiedEvent>
+              <t:Watermark>wm-3</t:Watermark>
+              <t:TimeStamp>2024-01-15T10:30:01Z</t:TimeStamp>
+              <t:FolderId Id="folder-1" ChangeKey="AQAAAA==" />
+              <t:ParentFolderId Id="root" ChangeKey="AQAAAA==" />
+              <t:UnreadCount>4</t:UnreadCount>
+            </t:ModifiedEvent>
+          </m:Notification>
+        </m:SendNotificationResponseMessage>
+      </m:ResponseMessages>
+    </m:SendNotification>
+  </soap:Body>
+</soap:Envelope>"#;
+
+    #[test]
+    fn test_parse_send_notification() {
+        let notifications = parse_send_notification(SEND_NOTIFICATION.as_bytes()).unwrap();
+        assert_eq!(notifications.len(), 1);
+
+        let notification = &notifications[0];
+        assert_eq!(notification.subscription_id, "sub-1");
+        assert_eq!(notification.previous_watermark.as_deref(), Some("wm-1"));
+        assert_eq!(notification.events.len(), 2);
+
+        let created = &notification.events[0];
+        assert_eq!(created.event_type, NotificationEventType::Created);
+        assert_eq!(created.item_id.as_deref(), Some("item-1"));
+        assert_eq!(created.parent_folder_id.as_deref(), Some("folder-1"));
+
+        let modified = &notification.events[1];
+        assert_eq!(modified.event_type, NotificationEventType::Modified);
+        assert_eq!(modified.folder_id.as_deref(), Some("folder-1"));
+        assert_eq!(modified.unread_count, Some(4));
+
+        assert_eq!(notification.last_watermark(), Some("wm-3"));
+    }
+
+    #[test]
+    fn test_parse_send_notification_rejects_other_requests() {
+        let body = r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><GetEvents/></soap:Body></soap:Envelope>"#;
+        assert!(parse_send_notification(body.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_send_notification_result_xml() {
+        let ok = send_notification_result_xml(PushNotificationResult::Ok);
+        assert!(ok.contains("<m:SubscriptionStatus>OK</m:SubscriptionStatus>"));
+
+        let unsubscribe = send_notification_result_xml(PushNotificationResult::Unsubscribe);
+        assert!(unsubscribe.contains("<m:SubscriptionStatus>Unsubscribe</m:SubscriptionStatus>"));
+    }
+
+    #[tokio::test]
+    async fn test_read_chunked_body() {
+        let mut stream = BufReader::new(&b"5\r\nhello\r\n6;ext=1\r\n world\r\n0\r\n\r\n"[..]);
+        let body = read_chunked_body(&mut stream).await.unwrap();
+        assert_eq!(body, b"hello world");
+    }
+
+    #[tokio::test]
+    async fn test_read_chunked_body_rejects_huge_chunk() {
+        // The size of the second chunk would overflow the body length
+        let mut stream = BufReader::new(&b"1\r\na\r\nffffffffffffffff\r\n"[..]);
+        let result = read_chunked_body(&mut stream).await;
+        assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+    }
+
+    #[test]
+    fn test_request_path() {
+        assert_eq!(request_path("/ews/notifications"), "/ews/notifications");
+        assert_eq!(request_path("/ews/notifications?id=1"), "/ews/notifications");
+        assert_eq!(
+            request_path("http://client.example.com:8080/ews/notifications"),
+            "/ews/notifications"
+        );
+        assert_eq!(request_path("*"), "");
+    }
+
+    #[tokio::test]
+    async fn test_read_request_rejects_large_body() {
+        let request = format!(
+            "POST /notifications HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_REQUEST_BODY_SIZE + 1
+        );
+        let (mut client, server) = tokio::io::duplex(1024);
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        let mut stream = BufReader::new(server);
+        let result = read_request(&mut stream).await;
+        assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn test_handle_connection_times_out_incomplete_request() {
+        let (client, server) = tokio::io::duplex(1024);
+        let (mut client_read, mut client_write) = tokio::io::split(client);
+
+        // The body announced by the client never arrives
+        client_write
+            .write_all(b"POST /notifications HTTP/1.1\r\nContent-Length: 10\r\n\r\nabc")
+            .await
+            .unwrap();
+
+        let handled = tokio::time::timeout(
+            Duration::from_secs(5),
+            handle_connection(
+                server,
+                "/notifications",
+                Duration::from_millis(50),
+                &|_: Notification| async { PushNotificationResult::Ok },
+            ),
+        )
+        .await;
+        assert!(matches!(handled, Ok(Ok(()))), "{handled:?}");
+
+        // The connection is closed without an answer
+        let mut response = Vec::new();
+        client_read.read_to_end(&mut response).await.unwrap();
+        assert!(response.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_line_rejects_long_line() {
+        let long_line = format!("X-Header: {}\r\n", "a".repeat(MAX_LINE_LENGTH));
+        let mut stream = BufReader::new(long_line.as_bytes());
+        let mut line = String::new();
+        let result = read_line(&mut stream, &mut line).await;
+        assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+
+        let mut stream = BufReader::new(&b"Host: localhost\r\n\r\n"[..]);
+        assert_eq!(read_line(&mut stream, &mut line).await.unwrap(), 17);
+        assert_eq!(line, "Host: localhost\r\n");
+    }
+}
@@ -0,0 +1,441 @@
+//! Recurrence patterns of recurring items.
+//!
+//! EWS describes a recurrence as a pattern (e.g. "every second Tuesday of the
+//! month") and a range (when the recurrence starts and how it ends). The same
+//! model is used for tasks and calendar items, except that only tasks can
+//! regenerate, i.e. recur relative to the completion of the previous
+//! occurrence.
+
+use time::{Month, Weekday};
+
+use crate::client::xml::{XmlElement, parse_date};
+
+/// A day of the week, or a set of days, in a recurrence pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayOfWeek {
+    /// Sunday.
+    Sunday,
+    /// Monday.
+    Monday,
+    /// Tuesday.
+    Tuesday,
+    /// Wednesday.
+    Wednesday,
+    /// Thursday.
+    Thursday,
+    /// Friday.
+    Friday,
+    /// Saturday.
+    Saturday,
+    /// Any day of the week.
+    Day,
+    /// Any day from Monday to Friday.
+    Weekday,
+    /// Saturday or Sunday.
+    WeekendDay,
+}
+
+impl DayOfWeek {
+    /// The value used for this day in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Sunday => "Sunday",
+            Self::Monday => "Monday",
+            Self::Tuesday => "Tuesday",
+            Self::Wednesday => "Wednesday",
+            Self::Thursday => "Thursday",
+            Self::Friday => "Friday",
+            Self::Saturday => "Saturday",
+            Self::Day => "Day",
+            Self::Weekday => "Weekday",
+            Self::WeekendDay => "WeekendDay",
+        }
+    }
+
+    /// Parses the EWS value of a day.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Sunday" => Some(Self::Sunday),
+            "Monday" => Some(Self::Monday),
+            "Tuesday" => Some(Self::Tuesday),
+            "Wednesday" => Some(Self::Wednesday),
+            "Thursday" => Some(Self::Thursday),
+            "Friday" => Some(Self::Friday),
+            "Saturday" => Some(Self::Saturday),
+            "Day" => Some(Self::Day),
+            "Weekday" => Some(Self::Weekday),
+            "WeekendDay" => Some(Self::WeekendDay),
+            _ => None,
+        }
+    }
+
+    /// The days of the week this value stands for.
+    pub(crate) fn weekdays(self) -> &'static [Weekday] {
+        match self {
+            Self::Sunday => &[Weekday::Sunday],
+            Self::Monday => &[Weekday::Monday],
+            Self::Tuesday => &[Weekday::Tuesday],
+            Self::Wednesday => &[Weekday::Wednesday],
+            Self::Thursday => &[Weekday::Thursday],
+            Self::Friday => &[Weekday::Friday],
+            Self::Saturday => &[Weekday::Saturday],
+            Self::Day => &[
+                Weekday::Sunday,
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+                Weekday::Saturday,
+            ],
+            Self::Weekday => &[
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+            ],
+            Self::WeekendDay => &[Weekday::Saturday, Weekday::Sunday],
+        }
+    }
+
+    /// The single day of the week this value stands for, if it is not a set
+    /// of days.
+    pub(crate) fn weekday(self) -> Option<Weekday> {
+        match self.weekdays() {
+            [weekday] => Some(*weekday),
+            _ => None,
+        }
+    }
+
+    /// The value standing for a single day of the week.
+    pub(crate) fn from_weekday(weekday: Weekday) -> Self {
+        match weekday {
+            Weekday::Sunday => Self::Sunday,
+            Weekday::Monday => Self::Monday,
+            Weekday::Tuesday => Self::Tuesday,
+            Weekday::Wednesday => Self::Wednesday,
+            Weekday::Thursday => Self::Thursday,
+            Weekday::Friday => Self::Friday,
+            Weekday::Saturday => Self::Saturday,
+        }
+    }
+}
+
+/// The week of the month of a relative recurrence pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayOfWeekIndex {
+    /// The first week of the month.
+    First,
+    /// The second week of the month.
+    Second,
+    /// The third week of the month.
+    Third,
+    /// The fourth week of the month.
+    Fourth,
+    /// The last week of the month.
+    Last,
+}
+
+impl DayOfWeekIndex {
+    /// The value used for this week in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::First => "First",
+            Self::Second => "Second",
+            Self::Third => "Third",
+            Self::Fourth => "Fourth",
+            Self::Last => "Last",
+        }
+    }
+
+    /// Parses the EWS value of a week.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "First" => Some(Self::First),
+            "Second" => Some(Self::Second),
+            "Third" => Some(Self::Third),
+            "Fourth" => Some(Self::Fourth),
+            "Last" => Some(Self::Last),
+            _ => None,
+        }
+    }
+
+    /// The position of this week in the month, counting from the end for the
+    /// last week, as used by time zone transitions and iCalendar (`BYSETPOS`).
+    pub(crate) fn position(self) -> i8 {
+        match self {
+            Self::First => 1,
+            Self::Second => 2,
+            Self::Third => 3,
+            Self::Fourth => 4,
+            Self::Last => -1,
+        }
+    }
+
+    /// The week at the given position in the month, see [`Self::position`].
+    pub(crate) fn from_position(position: i8) -> Option<Self> {
+        match position {
+            1 => Some(Self::First),
+            2 => Some(Self::Second),
+            3 => Some(Self::Third),
+            4 => Some(Self::Fourth),
+            -1 => Some(Self::Last),
+            _ => None,
+        }
+    }
+}
+
+/// How often a regenerating task recurs after each completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegenerationFrequency {
+    /// A number of days after completion.
+    Daily,
+    /// A number of weeks after completion.
+    Weekly,
+    /// A number of months after completion.
+    Monthly,
+    /// A number of years after completion.
+    Yearly,
+}
+
+impl RegenerationFrequency {
+    /// The name of the pattern element for this frequency.
+    pub(crate) fn element_name(self) -> &'static str {
+        match self {
+            Self::Daily => "DailyRegeneration",
+            Self::Weekly => "WeeklyRegeneration",
+            Self::Monthly => "MonthlyRegeneration",
+            Self::Yearly => "YearlyRegeneration",
+        }
+    }
+}
+
+/// When the occurrences of a recurring item happen.
+///
+/// Months are numbered from 1 (January) to 12 (December).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecurrencePattern {
+    /// Every `interval` days.
+    Daily {
+        /// The number of days between occurrences.
+        interval: u32,
+    },
+    /// On the given days, every `interval` weeks.
+    Weekly {
+        /// The number of weeks between occurrences.
+        interval: u32,
+        /// The days of the week on which the item occurs.
+        days_of_week: Vec<DayOfWeek>,
+    },
+    /// On a day of the month, every `interval` months.
+    AbsoluteMonthly {
+        /// The number of months between occurrences.
+        interval: u32,
+        /// The day of the month, from 1 to 31.
+        day_of_month: u8,
+    },
+    /// On a day of a week of the month (e.g. the second Tuesday), every
+    /// `interval` months.
+    RelativeMonthly {
+        /// The number of months between occurrences.
+        interval: u32,
+        /// The day of the week.
+        day_of_week: DayOfWeek,
+        /// The week of the month.
+        day_of_week_index: DayOfWeekIndex,
+    },
+    /// On a day of a month, every year.
+    AbsoluteYearly {
+        /// The month, from 1 to 12.
+        month: u8,
+        /// The day of the month, from 1 to 31.
+        day_of_month: u8,
+    },
+    /// On a day of a week of a month (e.g. the last Friday of November),
+    /// every year.
+    RelativeYearly {
+        /// The month, from 1 to 12.
+        month: u8,
+        /// The day of the week.
+        day_of_week: DayOfWeek,
+        /// The week of the month.
+        day_of_week_index: DayOfWeekIndex,
+    },
+    /// A number of days, weeks, months or years after the previous occurrence
+    /// was completed. Only tasks can regenerate.
+    Regenerating {
+        /// The unit of the interval.
+        frequency: RegenerationFrequency,
+        /// The number of units between the completion of an occurrence and
+        /// the next occurrence.
+        interval: u32,
+    },
+}
+
+/// When a recurrence starts and how it ends.
+///
+/// Dates are Unix timestamps; only their UTC date is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecurrenceRange {
+    /// The recurrence never ends.
+    NoEnd {
+        /// The date of the first occurrence.
+        start_date: i64,
+    },
+    /// The recurrence ends on a date.
+    EndDate {
+        /// The date of the first occurrence.
+        start_date: i64,
+        /// The date after which there are no more occurrences.
+        end_date: i64,
+    },
+    /// The recurrence ends after a number of occurrences.
+    Numbered {
+        /// The date of the first occurrence.
+        start_date: i64,
+        /// The number of occurrences.
+        occurrences: u32,
+    },
+}
+
+/// The recurrence of a recurring task or calendar item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    /// When the occurrences happen.
+    pub pattern: RecurrencePattern,
+    /// When the recurrence starts and how it ends.
+    pub range: RecurrenceRange,
+}
+
+impl Recurrence {
+    /// Reads a recurrence from a `Recurrence` element. Returns `None` if the
+    /// pattern or the range is not understood.
+    pub(crate) fn from_xml(element: &XmlElement) -> Option<Self> {
+        let pattern = element.children.iter().find_map(pattern_from_xml)?;
+        let range = element.children.iter().find_map(range_from_xml)?;
+
+        Some(Self { pattern, range })
+    }
+}
+
+/// Reads a recurrence pattern from its element, if it is one.
+fn pattern_from_xml(element: &XmlElement) -> Option<RecurrencePattern> {
+    let interval = || element.child_parse::<u32>("Interval");
+    let day_of_month = || element.child_parse::<u8>("DayOfMonth");
+    let month = || element.child_text("Month").and_then(month_number);
+    let day_of_week = || element.child_text("DaysOfWeek").and_then(DayOfWeek::parse);
+    let day_of_week_index = || element.child_text("DayOfWeekIndex").and_then(DayOfWeekIndex::parse);
+    let regenerating = |frequency| {
+        Some(RecurrencePattern::Regenerating {
+            frequency,
+            interval: interval()?,
+        })
+    };
+
+    match element.name.as_str() {
+        "DailyRecurrence" => Some(RecurrencePattern::Daily { interval: interval()? }),
+        "WeeklyRecurrence" => Some(RecurrencePattern::Weekly {
+            interval: interval()?,
+            days_of_week: element
+                .child_text("DaysOfWeek")?
+                .split_whitespace()
+                .filter_map(DayOfWeek::parse)
+                .collect(),
+        }),
+        "AbsoluteMonthlyRecurrence" => Some(RecurrencePattern::AbsoluteMonthly {
+            interval: interval()?,
+            day_of_month: day_of_month()?,
+        }),
+        "RelativeMonthlyRecurrence" => Some(RecurrencePattern::RelativeMonthly {
+            interval: interval()?,
+            day_of_week: day_of_week()?,
+            day_of_week_index: day_of_week_index()?,
+        }),
+        "AbsoluteYearlyRecurrence" => Some(RecurrencePattern::AbsoluteYearly {
+            month: month()?,
+            day_of_month: day_of_month()?,
+        }),
+        "RelativeYearlyRecurrence" => Some(RecurrencePattern::RelativeYearly {
+            month: month()?,
+            day_of_week: day_of_week()?,
+            day_of_week_index: day_of_week_index()?,
+        }),
+        "DailyRegeneration" => regenerating(RegenerationFrequency::Daily),
+        "WeeklyRegeneration" => regenerating(RegenerationFrequency::Weekly),
+        "MonthlyRegeneration" => regenerating(RegenerationFrequency::Monthly),
+        "YearlyRegeneration" => regenerating(RegenerationFrequency::Yearly),
+        _ => None,
+    }
+}
+
+/// Reads a recurrence range from its element, if it is one.
+fn range_from_xml(element: &XmlElement) -> Option<RecurrenceRange> {
+    let start_date = element.child_text("StartDate").and_then(parse_date)?;
+
+    match element.name.as_str() {
+        "NoEndRecurrence" => Some(RecurrenceRange::NoEnd { start_date }),
+        "EndDateRecurrence" => Some(RecurrenceRange::EndDate {
+            start_date,
+            end_date: element.child_text("EndDate").and_then(parse_date)?,
+        }),
+        "NumberedRecurrence" => Some(RecurrenceRange::Numbered {
+            start_date,
+            occurrences: element.child_parse("NumberOfOccurrences")?,
+        }),
+        _ => None,
+    }
+}
+
+/// Parses the EWS name of a month (e.g. `November`) into its number.
+fn month_number(name: &str) -> Option<u8> {
+    (1..=12).find(|number| Month::try_from(*number).is_ok_and(|month| month.to_string() == name))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recurrence_round_trip() {
+        let recurrences = [
+            Recurrence {
+                pattern: RecurrencePattern::Weekly {
+                    interval: 2,
+                    days_of_week: vec![DayOfWeek::Monday, DayOfWeek::Thursday],
+                },
+                range: RecurrenceRange::Numbered {
+                    start_date: 1_705_276_800,
+                    occurrences: 10,
+                },
+            },
+            Recurrence {
+                pattern: RecurrencePattern::RelativeYearly {
+                    month: 11,
+                    day_of_week: DayOfWeek::Thursday,
+                    day_of_week_index: DayOfWeekIndex::Fourth,
+                },
+                range: RecurrenceRange::NoEnd {
+                    start_date: 1_705_276_800,
+                },
+            },
+            Recurrence {
+                pattern: RecurrencePattern::Regenerating {
+                    frequency: RegenerationFrequency::Monthly,
+                    interval: 1,
+                },
+                range: RecurrenceRange::EndDate {
+                    start_date: 1_705_276_800,
+                    end_date: 1_735_603_200,
+                },
+            },
+        ];
+
+        for recurrence in recurrences {
+            let xml = recurrence.to_xml().unwrap();
+            let element = XmlElement::parse(xml.as_bytes()).unwrap();
+            assert_eq!(Recurrence::from_xml(&element), Some(recurrence));
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Create search folders, which show the items of other folders matching a
+//! restriction.
+
+use crate::client::{
+    EwsClient, EwsError,
+    operations::update_folder::{ExtendedPropertyKey, MapiPropertyType},
+};
+
+/// A property of the items searched by a restriction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchProperty {
+    /// A property identified by its field URI, e.g. `message:IsRead` or
+    /// `item:Importance`.
+    Field(String),
+    /// An extended MAPI property.
+    Extended {
+        /// The property.
+        key: ExtendedPropertyKey,
+        /// The type of the value of the property.
+        property_type: MapiPropertyType,
+    },
+}
+
+/// How a `Contains` restriction matches the value of a property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContainmentMode {
+    /// The whole value is the given string.
+    FullString,
+    /// The value starts with the given string.
+    Prefixed,
+    /// The value contains the given string anywhere.
+    #[default]
+    Substring,
+    /// A word of the value starts with the given string.
+    PrefixOnWords,
+    /// The value contains the given phrase.
+    ExactPhrase,
+}
+
+impl ContainmentMode {
+    /// The EWS value of the mode.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FullString => "FullString",
+            Self::Prefixed => "Prefixed",
+            Self::Substring => "Substring",
+            Self::PrefixOnWords => "PrefixOnWords",
+            Self::ExactPhrase => "ExactPhrase",
+        }
+    }
+}
+
+/// A restriction on the items shown by a search folder.
+///
+/// Comparisons take the value as EWS writes it, e.g. `"false"` for a
+/// boolean or `"High"` for an importance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchExpression {
+    /// All the expressions match.
+    And(Vec<SearchExpression>),
+    /// Any of the expressions match.
+    Or(Vec<SearchExpression>),
+    /// The expression does not match.
+    Not(Box<SearchExpression>),
+    /// The item has the property.
+    Exists(SearchProperty),
+    /// The property equals the value.
+    IsEqualTo(SearchProperty, String),
+    /// The property differs from the value.
+    IsNotEqualTo(SearchProperty, String),
+    /// The property is greater than the value.
+    IsGreaterThan(SearchProperty, String),
+    /// The property is greater than or equal to the value.
+    IsGreaterThanOrEqualTo(SearchProperty, String),
+    /// The property is less than the value.
+    IsLessThan(SearchProperty, String),
+    /// The property is less than or equal to the value.
+    IsLessThanOrEqualTo(SearchProperty, String),
+    /// The string property contains the value.
+    Contains {
+        /// The property to search.
+        property: SearchProperty,
+        /// The string to look for.
+        value: String,
+        /// How the string is matched.
+        mode: ContainmentMode,
+        /// Whether the case of the string is ignored.
+        ignore_case: bool,
+    },
+}
+
+/// Which items of the base folders a search folder searches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchFolderTraversal {
+    /// Only the items of the base folders.
+    Shallow,
+    /// The items of the base folders and of all their subfolders.
+    #[default]
+    Deep,
+}
+
+impl SearchFolderTraversal {
+    /// The EWS value of the traversal.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Shallow => "Shallow",
+            Self::Deep => "Deep",
+        }
+    }
+}
+
+/// A search folder to create.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchFolderDraft {
+    /// The display name of the folder.
+    pub display_name: String,
+    /// The folder class (e.g. `IPF.Note`), or `None` to let the server pick.
+    pub folder_class: Option<String>,
+    /// The restriction the items must match.
+    pub restriction: SearchExpression,
+    /// The EWS IDs or distinguished folder names of the folders to search.
+    pub base_folder_ids: Vec<String>,
+    /// Whether the subfolders of the base folders are searched.
+    pub traversal: SearchFolderTraversal,
+}
+
+impl EwsClient {
+    /// Creates a search folder, showing the items of its base folders that
+    /// match its restriction.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_id` - The EWS ID of the parent folder, or a distinguished
+    ///   folder name (usually `searchfolders`)
+    /// * `draft` - The search folder to create
+    ///
+    /// # Returns
+    ///
+    /// The EWS ID of the newly created folder
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The draft has no base folders, or an `And`/`Or` restriction without
+    ///   operands
+    /// - The server rejects the restriction or a base folder
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{
+    /// #     EwsClient, Credentials, ContainmentMode, SearchExpression, SearchFolderDraft, SearchFolderTraversal,
+    /// #     SearchProperty,
+    /// # };
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?;
+    /// let from_vip = |address: &str| SearchExpression::Contains {
+    ///     property: SearchProperty::Field("message:From".to_string()),
+    ///     value: address.to_string(),
+    ///     mode: ContainmentMode::Substring,
+    ///     ignore_case: true,
+    /// };
+    /// let draft = SearchFolderDraft {
+    ///     display_name: "Unread from VIPs".to_string(),
+    ///     folder_class: Some("IPF.Note".to_string()),
+    ///     restriction: SearchExpression::And(vec![
+    ///         SearchExpression::IsEqualTo(SearchProperty::Field("message:IsRead".to_string()), "false".to_string()),
+    ///         SearchExpression::Or(vec![from_vip("ceo@example.com"), from_vip("cto@example.com")]),
+    ///     ]),
+    ///     base_folder_ids: vec!["inbox".to_string()],
+    ///     traversal: SearchFolderTraversal::Deep,
+    /// };
+    /// let folder_id = client.create_search_folder("searchfolders", &draft).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_search_folder(&self, parent_id: &str, draft: &SearchFolderDraft) -> Result<String, EwsError> {
+        let folder = draft.to_xml(self.target_mailbox())?;
+        self.create_raw_folder(parent_id, &folder).await
+    }
+}
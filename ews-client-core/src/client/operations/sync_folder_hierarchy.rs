@@ -8,9 +8,13 @@ use std::collections::HashMap;
 
 use crate::client::{
     EWS_ROOT_FOLDER, EwsClient, EwsError, OperationRequestOptions, process_response_message_class,
-    single_response_or_error, validate_get_folder_response_message,
+    raw_requests::folders::sync_folder_hierarchy_request, single_raw_response_or_error, single_response_or_error,
 };
 
+/// The elements of the kinds of folders which are synced, in raw
+/// `SyncFolderHierarchy` responses.
+const SYNCED_FOLDER_ELEMENTS: &[&str] = &["Folder", "CalendarFolder", "ContactsFolder", "TasksFolder"];
+
 /// The result of a folder hierarchy sync operation.
 #[derive(Debug, Clone)]
 pub struct FolderHierarchySyncResult {
@@ -27,6 +31,16 @@ pub struct FolderHierarchySyncResult {
     pub well_known_folders: Option<HashMap<String, String>>,
 }
 
+/// The folder changes of a single `SyncFolderHierarchy` response.
+#[derive(Debug, Default)]
+struct HierarchyChangesPage {
+    created_ids: Vec<String>,
+    updated_ids: Vec<String>,
+    deleted_ids: Vec<String>,
+    sync_state: String,
+    includes_last_folder_in_range: bool,
+}
+
 /// Information about a folder.
 #[derive(Debug, Clone)]
 pub struct FolderInfo {
@@ -36,7 +50,9 @@ pub struct FolderInfo {
     pub parent_folder_id: String,
     /// The display name of the folder
     pub display_name: String,
-    /// The folder class (e.g., "IPF.Note" for mail folders)
+    /// The folder class (e.g., "IPF.Note" for mail folders, "IPF.Appointment"
+    /// for calendar folders, "IPF.Contact" for contacts folders, "IPF.Task" for
+    /// tasks folders)
     pub folder_class: Option<String>,
     /// Total number of items in the folder
     pub total_count: Option<u32>,
@@ -107,57 +123,27 @@ impl EwsClient {
         let mut current_sync_state = sync_state;
         let mut final_sync_state;
 
+        // Folder sync can happen starting with any folder, but we always
+        // choose "msgfolderroot" as sync is recursive and this simplifies
+        // managing sync state. The mail, calendar, contacts and tasks folders
+        // of the user are all below it.
         loop {
             // Folder sync returns results in batches, with sync state providing
             // the mechanism by which we can specify the next batch to receive.
-            let op = SyncFolderHierarchy {
-                folder_shape: FolderShape {
-                    base_shape: BaseShape::IdOnly,
-                },
-                sync_folder_id: Some(BaseFolderId::DistinguishedFolderId {
-                    // Folder sync can happen starting with any folder, but we
-                    // always choose "msgfolderroot" as sync is recursive and
-                    // this simplifies managing sync state. There is a "root"
-                    // folder one level up as well, but it includes calendars,
-                    // contacts, etc., which we aren't trying to support yet.
-                    id: EWS_ROOT_FOLDER.to_string(),
-                    change_key: None,
-                }),
-                sync_state: current_sync_state.clone(),
-            };
-
-            let response = self
-                .make_operation_request(op, OperationRequestOptions::default())
-                .await?
-                .into_response_messages();
-
-            let response = single_response_or_error(response)?;
-            let message = process_response_message_class("SyncFolderHierarchy", response)?;
-
-            // Build lists of all of the changed folder IDs
-            for change in message.changes.inner {
-                match change {
-                    sync_folder_hierarchy::Change::Create { folder } => {
-                        if let Folder::Folder { folder_id, .. } = folder {
-                            let folder_id = folder_id.ok_or(EwsError::MissingIdInResponse)?;
-                            all_created_ids.push(folder_id.id);
-                        }
-                    }
-                    sync_folder_hierarchy::Change::Update { folder } => {
-                        if let Folder::Folder { folder_id, .. } = folder {
-                            let folder_id = folder_id.ok_or(EwsError::MissingIdInResponse)?;
-                            all_updated_ids.push(folder_id.id);
-                        }
-                    }
-                    sync_folder_hierarchy::Change::Delete { folder_id } => {
-                        all_deleted_ids.push(folder_id.id);
-                    }
+            let page = match self.target_mailbox() {
+                Some(mailbox) => {
+                    self.sync_folder_hierarchy_page_in_mailbox(mailbox, current_sync_state.as_deref())
+                        .await?
                 }
-            }
+                None => self.sync_folder_hierarchy_page(current_sync_state.clone()).await?,
+            };
 
-            final_sync_state = message.sync_state;
+            all_created_ids.extend(page.created_ids);
+            all_updated_ids.extend(page.updated_ids);
+            all_deleted_ids.extend(page.deleted_ids);
+            final_sync_state = page.sync_state;
 
-            if message.includes_last_folder_in_range {
+            if page.includes_last_folder_in_range {
                 // EWS has signaled to us that there are no more changes at this time
                 break;
             }
@@ -191,6 +177,119 @@ impl EwsClient {
         })
     }
 
+    /// Fetches the next page of changes below the root folder of the user's
+    /// mailbox.
+    async fn sync_folder_hierarchy_page(&self, sync_state: Option<String>) -> Result<HierarchyChangesPage, EwsError> {
+        let op = SyncFolderHierarchy {
+            folder_shape: FolderShape {
+                base_shape: BaseShape::IdOnly,
+            },
+            sync_folder_id: Some(BaseFolderId::DistinguishedFolderId {
+                id: EWS_ROOT_FOLDER.to_string(),
+                change_key: None,
+            }),
+            sync_state,
+        };
+
+        let response = self
+            .make_operation_request(op, OperationRequestOptions::default())
+            .await?
+            .into_response_messages();
+
+        let response = single_response_or_error(response)?;
+        let message = process_response_message_class("SyncFolderHierarchy", response)?;
+
+        // Build lists of all of the changed folder IDs
+        let mut page = HierarchyChangesPage {
+            sync_state: message.sync_state,
+            includes_last_folder_in_range: message.includes_last_folder_in_range,
+            ..HierarchyChangesPage::default()
+        };
+        for change in message.changes.inner {
+            match change {
+                sync_folder_hierarchy::Change::Create { folder } => {
+                    if let Folder::Folder { folder_id, .. }
+                    | Folder::CalendarFolder { folder_id, .. }
+                    | Folder::ContactsFolder { folder_id, .. }
+                    | Folder::TasksFolder { folder_id, .. } = folder
+                    {
+                        let folder_id = folder_id.ok_or(EwsError::MissingIdInResponse)?;
+                        page.created_ids.push(folder_id.id);
+                    }
+                }
+                sync_folder_hierarchy::Change::Update { folder } => {
+                    if let Folder::Folder { folder_id, .. }
+                    | Folder::CalendarFolder { folder_id, .. }
+                    | Folder::ContactsFolder { folder_id, .. }
+                    | Folder::TasksFolder { folder_id, .. } = folder
+                    {
+                        let folder_id = folder_id.ok_or(EwsError::MissingIdInResponse)?;
+                        page.updated_ids.push(folder_id.id);
+                    }
+                }
+                sync_folder_hierarchy::Change::Delete { folder_id } => {
+                    page.deleted_ids.push(folder_id.id);
+                }
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// Fetches the next page of changes below the root folder of another
+    /// mailbox.
+    ///
+    /// The `ews` crate's distinguished folder IDs cannot name a mailbox, so
+    /// the request is written by hand.
+    async fn sync_folder_hierarchy_page_in_mailbox(
+        &self,
+        mailbox: &str,
+        sync_state: Option<&str>,
+    ) -> Result<HierarchyChangesPage, EwsError> {
+        let body = sync_folder_hierarchy_request(EWS_ROOT_FOLDER, sync_state, Some(mailbox));
+
+        let response = self
+            .make_raw_operation_request("SyncFolderHierarchy", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("SyncFolderHierarchy", &response)?;
+
+        let mut page = HierarchyChangesPage {
+            sync_state: message
+                .child_text("SyncState")
+                .ok_or_else(|| EwsError::Processing {
+                    message: "SyncFolderHierarchy response is missing a sync state".to_string(),
+                })?
+                .to_string(),
+            includes_last_folder_in_range: message.child_bool("IncludesLastFolderInRange").unwrap_or(true),
+            ..HierarchyChangesPage::default()
+        };
+
+        let changes = message.child("Changes").map(|changes| changes.children.as_slice());
+        for change in changes.unwrap_or_default() {
+            let ids = match change.name.as_str() {
+                "Create" => &mut page.created_ids,
+                "Update" => &mut page.updated_ids,
+                "Delete" => {
+                    page.deleted_ids
+                        .push(change.child_id("FolderId").ok_or(EwsError::MissingIdInResponse)?);
+                    continue;
+                }
+                _ => continue,
+            };
+
+            // Other kinds of folders, such as search folders, are skipped
+            if let Some(folder) = change
+                .children
+                .iter()
+                .find(|folder| SYNCED_FOLDER_ELEMENTS.contains(&folder.name.as_str()))
+            {
+                ids.push(folder.child_id("FolderId").ok_or(EwsError::MissingIdInResponse)?);
+            }
+        }
+
+        Ok(page)
+    }
+
     /// Builds a map from remote folder ID to distinguished folder ID.
     ///
     /// This allows translating from the folder ID returned by `GetFolder`
@@ -220,73 +319,34 @@ impl EwsClient {
             "expected first fetched folder to be root"
         );
 
-        let ids = DISTINGUISHED_IDS
-            .iter()
-            .map(|id| BaseFolderId::DistinguishedFolderId {
-                id: (*id).to_string(),
-                change_key: None,
-            })
-            .collect();
-
         // Fetch all distinguished folder IDs at once, since we have few enough
-        // that they fit within Microsoft's recommended batch size of ten.
-        let op = ews::get_folder::GetFolder {
-            folder_shape: FolderShape {
-                base_shape: BaseShape::IdOnly,
-            },
-            folder_ids: ids,
-        };
-
-        let response = self
-            .make_operation_request(op, OperationRequestOptions::default())
-            .await?;
-
-        let response_messages = response.into_response_messages();
-        crate::client::validate_response_message_count(&response_messages, DISTINGUISHED_IDS.len())?;
-
-        // We expect results from EWS to be in the same order as given in the
-        // request. EWS docs aren't explicit about response ordering, but
-        // responses don't contain another means of mapping requested ID to response.
-        let mut message_iter = DISTINGUISHED_IDS.iter().zip(response_messages);
-
-        // Record the root folder for messages before processing the other responses
-        let (_, response_class) = message_iter.next().ok_or_else(|| EwsError::Processing {
-            message: "no response for root folder".to_string(),
-        })?;
-        let message = process_response_message_class("GetFolder", response_class)?;
+        // that they fit within Microsoft's recommended batch size of ten. The
+        // lookup is a raw request, so that the folders of a target mailbox
+        // can be named.
+        let folder_ids = self.get_distinguished_folder_ids(DISTINGUISHED_IDS).await?;
+        let mut results = DISTINGUISHED_IDS.iter().zip(folder_ids);
 
         // Any error fetching the root folder is fatal, since we can't correctly
         // set the parents of any folders it contains without knowing its ID.
-        let _root_folder_id = validate_get_folder_response_message(&message)?;
+        let (_, root_folder_id) = results.next().ok_or_else(|| EwsError::Processing {
+            message: "no response for root folder".to_string(),
+        })?;
+        let _root_folder_id = root_folder_id?;
 
         // Build the mapping for the remaining folders
-        message_iter
-            .filter_map(|(&distinguished_id, response_class)| {
-                let message = match process_response_message_class("GetFolder", response_class) {
-                    Ok(message) => Some(message),
-
-                    // Not every Exchange account will have all queried
-                    // well-known folders, so we skip any which were not found.
-                    Err(EwsError::ResponseError(ref err))
-                        if err.response_code == ews::response::ResponseCode::ErrorFolderNotFound =>
-                    {
-                        None
-                    }
-
-                    // Return any other error
-                    Err(err) => {
-                        return Some(Err(err));
-                    }
-                };
+        results
+            .filter_map(|(&distinguished_id, folder_id)| match folder_id {
+                // Map from EWS folder ID to distinguished ID
+                Ok(folder_id) => Some(Ok((folder_id, distinguished_id.to_string()))),
+
+                // Not every Exchange account will have all queried
+                // well-known folders, so we skip any which were not found.
+                Err(EwsError::RawResponseError { ref response_code, .. }) if response_code == "ErrorFolderNotFound" => {
+                    None
+                }
 
-                message.map(|message| {
-                    // Validate the message (and propagate any error) if it's not `None`
-                    match validate_get_folder_response_message(&message) {
-                        // Map from EWS folder ID to distinguished ID
-                        Ok(folder_id) => Ok((folder_id.id, distinguished_id.to_string())),
-                        Err(err) => Err(err),
-                    }
-                })
+                // Return any other error
+                Err(err) => Some(Err(err)),
             })
             .collect()
     }
@@ -297,8 +357,8 @@ impl EwsClient {
 
         folders
             .into_iter()
-            .map(|folder| match folder {
-                Folder::Folder {
+            .map(|folder| {
+                let (
                     folder_id,
                     parent_folder_id,
                     display_name,
@@ -306,27 +366,85 @@ impl EwsClient {
                     total_count,
                     unread_count,
                     child_folder_count,
-                    ..
-                } => {
-                    let folder_id = folder_id.ok_or(EwsError::MissingIdInResponse)?;
-                    let parent_folder_id = parent_folder_id.ok_or(EwsError::MissingIdInResponse)?;
-                    let display_name = display_name.ok_or_else(|| EwsError::Processing {
-                        message: "folder missing display name".to_string(),
-                    })?;
-
-                    Ok(FolderInfo {
-                        folder_id: folder_id.id,
-                        parent_folder_id: parent_folder_id.id,
+                ) = match folder {
+                    Folder::Folder {
+                        folder_id,
+                        parent_folder_id,
                         display_name,
                         folder_class,
                         total_count,
                         unread_count,
                         child_folder_count,
-                    })
-                }
-                _ => Err(EwsError::Processing {
-                    message: "unexpected folder type".to_string(),
-                }),
+                        ..
+                    }
+                    | Folder::TasksFolder {
+                        folder_id,
+                        parent_folder_id,
+                        display_name,
+                        folder_class,
+                        total_count,
+                        unread_count,
+                        child_folder_count,
+                        ..
+                    } => (
+                        folder_id,
+                        parent_folder_id,
+                        display_name,
+                        folder_class,
+                        total_count,
+                        unread_count,
+                        child_folder_count,
+                    ),
+                    // Calendar and contacts folders don't keep track of unread
+                    // items
+                    Folder::CalendarFolder {
+                        folder_id,
+                        parent_folder_id,
+                        display_name,
+                        folder_class,
+                        total_count,
+                        child_folder_count,
+                        ..
+                    }
+                    | Folder::ContactsFolder {
+                        folder_id,
+                        parent_folder_id,
+                        display_name,
+                        folder_class,
+                        total_count,
+                        child_folder_count,
+                        ..
+                    } => (
+                        folder_id,
+                        parent_folder_id,
+                        display_name,
+                        folder_class,
+                        total_count,
+                        None,
+                        child_folder_count,
+                    ),
+                    _ => {
+                        return Err(EwsError::Processing {
+                            message: "unexpected folder type".to_string(),
+                        });
+                    }
+                };
+
+                let folder_id = folder_id.ok_or(EwsError::MissingIdInResponse)?;
+                let parent_folder_id = parent_folder_id.ok_or(EwsError::MissingIdInResponse)?;
+                let display_name = display_name.ok_or_else(|| EwsError::Processing {
+                    message: "folder missing display name".to_string(),
+                })?;
+
+                Ok(FolderInfo {
+                    folder_id: folder_id.id,
+                    parent_folder_id: parent_folder_id.id,
+                    display_name,
+                    folder_class,
+                    total_count,
+                    unread_count,
+                    child_folder_count,
+                })
             })
             .collect()
     }
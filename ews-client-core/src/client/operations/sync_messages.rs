@@ -49,6 +49,10 @@ pub struct SyncMessageInfo {
 impl EwsClient {
     /// Synchronizes messages in a folder since the last sync state.
     ///
+    /// Only the IDs of changed messages are returned. Their properties,
+    /// including the meeting properties of meeting messages, are read with
+    /// [`EwsClient::get_items`].
+    ///
     /// # Arguments
     ///
     /// * `folder_id` - The EWS ID of the folder to sync
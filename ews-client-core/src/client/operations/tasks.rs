@@ -0,0 +1,530 @@
+//! Task operations.
+//!
+//! This module contains the operations for listing, creating, updating and
+//! deleting tasks in `IPF.Task` folders, and for flagging a message for
+//! follow-up so that it shows up in the mailbox owner's task list.
+
+use ews::{
+    BaseItemId, DeleteType, Operation, OperationResponse,
+    delete_item::{AffectedTaskOccurrences, DeleteItem},
+};
+
+use crate::client::{
+    EwsClient, EwsError, OperationRequestOptions,
+    operations::{
+        get_items::{BodyType, ItemBaseShape, ItemShape},
+        recurrence::Recurrence,
+    },
+    process_response_message_class,
+    raw_requests::tasks::{create_task_request, find_tasks_request, flag_message_request, update_task_request},
+    single_raw_response_or_error, single_response_or_error,
+    xml::XmlElement,
+};
+
+/// The distinguished ID of the default tasks folder.
+const DEFAULT_TASKS_FOLDER: &str = "tasks";
+
+/// The progress of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskStatus {
+    /// Work on the task has not started.
+    #[default]
+    NotStarted,
+    /// Work on the task is in progress.
+    InProgress,
+    /// The task is complete.
+    Completed,
+    /// The task is waiting on someone else.
+    WaitingOnOthers,
+    /// The task has been postponed.
+    Deferred,
+}
+
+impl TaskStatus {
+    /// The value used for this status in EWS.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotStarted => "NotStarted",
+            Self::InProgress => "InProgress",
+            Self::Completed => "Completed",
+            Self::WaitingOnOthers => "WaitingOnOthers",
+            Self::Deferred => "Deferred",
+        }
+    }
+
+    /// Parses the EWS value of a status.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "NotStarted" => Some(Self::NotStarted),
+            "InProgress" => Some(Self::InProgress),
+            "Completed" => Some(Self::Completed),
+            "WaitingOnOthers" => Some(Self::WaitingOnOthers),
+            "Deferred" => Some(Self::Deferred),
+            _ => None,
+        }
+    }
+}
+
+/// A task.
+#[derive(Debug, Clone)]
+pub struct Task {
+    /// The EWS ID of the task.
+    pub item_id: String,
+    /// The change key of the task, if returned.
+    pub change_key: Option<String>,
+    /// The subject of the task.
+    pub subject: Option<String>,
+    /// The text body of the task.
+    pub body: Option<String>,
+    /// The start date of the task, as a Unix timestamp.
+    pub start_date: Option<i64>,
+    /// The due date of the task, as a Unix timestamp.
+    pub due_date: Option<i64>,
+    /// The date the task was completed, as a Unix timestamp.
+    pub complete_date: Option<i64>,
+    /// The progress of the task.
+    pub status: TaskStatus,
+    /// How much of the task is done, from 0 to 100.
+    pub percent_complete: f64,
+    /// Whether the task is complete.
+    pub is_complete: bool,
+    /// Whether the task recurs.
+    pub is_recurring: bool,
+    /// The recurrence of the task. Only returned by
+    /// [`EwsClient::get_tasks`].
+    pub recurrence: Option<Recurrence>,
+    /// When the reminder of the task is shown, as a Unix timestamp, or `None`
+    /// if the task has no reminder.
+    pub reminder: Option<i64>,
+}
+
+impl Task {
+    /// Reads a task from a `Task` element.
+    pub(crate) fn from_xml(element: &XmlElement) -> Result<Self, EwsError> {
+        let id = element.child("ItemId").ok_or(EwsError::MissingIdInResponse)?;
+        let reminder = if element.child_bool("ReminderIsSet").unwrap_or(false) {
+            element.child_date_time("ReminderDueBy")
+        } else {
+            None
+        };
+
+        Ok(Self {
+            item_id: id.attribute("Id").ok_or(EwsError::MissingIdInResponse)?.to_string(),
+            change_key: id.attribute("ChangeKey").map(ToString::to_string),
+            subject: element.child_text("Subject").map(ToString::to_string),
+            body: element
+                .child_text("Body")
+                .filter(|body| !body.is_empty())
+                .map(ToString::to_string),
+            start_date: element.child_date_time("StartDate"),
+            due_date: element.child_date_time("DueDate"),
+            complete_date: element.child_date_time("CompleteDate"),
+            status: element
+                .child_text("Status")
+                .and_then(TaskStatus::parse)
+                .unwrap_or_default(),
+            percent_complete: element.child_parse("PercentComplete").unwrap_or(0.0),
+            is_complete: element.child_bool("IsComplete").unwrap_or(false),
+            is_recurring: element.child_bool("IsRecurring").unwrap_or(false),
+            recurrence: element.child("Recurrence").and_then(Recurrence::from_xml),
+            reminder,
+        })
+    }
+}
+
+/// The properties of a new task.
+#[derive(Debug, Clone, Default)]
+pub struct TaskDraft {
+    /// The subject of the task.
+    pub subject: String,
+    /// The text body of the task.
+    pub body: Option<String>,
+    /// The start date of the task, as a Unix timestamp.
+    pub start_date: Option<i64>,
+    /// The due date of the task, as a Unix timestamp.
+    pub due_date: Option<i64>,
+    /// The progress of the task. Defaults to not started.
+    pub status: Option<TaskStatus>,
+    /// How much of the task is done, from 0 to 100.
+    pub percent_complete: Option<f64>,
+    /// The recurrence of the task, if it recurs.
+    pub recurrence: Option<Recurrence>,
+    /// When to show a reminder, as a Unix timestamp, or `None` for no
+    /// reminder.
+    pub reminder: Option<i64>,
+}
+
+/// Changes to apply to an existing task. Fields left as `None` are not
+/// changed; for optional properties, `Some(None)` removes the property.
+#[derive(Debug, Clone, Default)]
+pub struct TaskUpdate {
+    /// The new subject of the task.
+    pub subject: Option<String>,
+    /// The new text body of the task.
+    pub body: Option<String>,
+    /// The new start date of the task, as a Unix timestamp.
+    pub start_date: Option<Option<i64>>,
+    /// The new due date of the task, as a Unix timestamp.
+    pub due_date: Option<Option<i64>>,
+    /// The new progress of the task.
+    pub status: Option<TaskStatus>,
+    /// How much of the task is now done, from 0 to 100.
+    pub percent_complete: Option<f64>,
+    /// The new recurrence of the task.
+    pub recurrence: Option<Option<Recurrence>>,
+    /// When to show the reminder, as a Unix timestamp.
+    pub reminder: Option<Option<i64>>,
+}
+
+/// Result of listing the tasks of a folder.
+#[derive(Debug, Clone)]
+pub struct FindTasksResult {
+    /// The tasks in the page.
+    pub tasks: Vec<Task>,
+    /// The total number of tasks in the folder.
+    pub total_items: Option<u32>,
+    /// Whether the page contains the last task of the folder.
+    pub includes_last_item: bool,
+    /// The offset to request the next page from, if there is one.
+    pub next_offset: Option<u32>,
+}
+
+/// Result of creating a task.
+#[derive(Debug, Clone)]
+pub struct CreateTaskResult {
+    /// The EWS ID of the new task.
+    pub item_id: String,
+    /// The change key of the new task, if returned.
+    pub change_key: Option<String>,
+}
+
+impl EwsClient {
+    /// Lists the tasks of a folder, one page at a time.
+    ///
+    /// The recurrence of recurring tasks is not returned when listing tasks;
+    /// use [`EwsClient::get_tasks`] to read it.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the tasks folder, or `None` for the
+    ///   default tasks folder
+    /// * `offset` - The index of the first task of the page
+    /// * `max_entries` - The maximum number of tasks in the page
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The folder does not exist
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn find_tasks(
+        &self,
+        folder_id: Option<&str>,
+        offset: u32,
+        max_entries: u32,
+    ) -> Result<FindTasksResult, EwsError> {
+        let body = find_tasks_request(
+            folder_id.unwrap_or(DEFAULT_TASKS_FOLDER),
+            offset,
+            max_entries,
+            self.target_mailbox(),
+        );
+
+        let response = self
+            .make_raw_operation_request("FindItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("FindItem", &response)?;
+
+        let root_folder = message.child("RootFolder").ok_or_else(|| EwsError::Processing {
+            message: "FindItem response is missing a root folder".to_string(),
+        })?;
+
+        // Flagged messages also show up in task folders; only tasks are read.
+        let tasks = root_folder
+            .child("Items")
+            .map(|items| {
+                items
+                    .children_named("Task")
+                    .map(Task::from_xml)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let includes_last_item = root_folder
+            .attribute("IncludesLastItemInRange")
+            .is_none_or(|value| value == "true");
+        let next_offset = if includes_last_item {
+            None
+        } else {
+            root_folder
+                .attribute("IndexedPagingOffset")
+                .and_then(|offset| offset.parse().ok())
+        };
+
+        Ok(FindTasksResult {
+            tasks,
+            total_items: root_folder
+                .attribute("TotalItemsInView")
+                .and_then(|total| total.parse().ok()),
+            includes_last_item,
+            next_offset,
+        })
+    }
+
+    /// Reads tasks by ID, including their recurrence.
+    ///
+    /// # Arguments
+    ///
+    /// * `ids` - The EWS IDs of the tasks to read
+    ///
+    /// # Returns
+    ///
+    /// The tasks, in the order of the IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Any of the items cannot be found or is not a task
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn get_tasks(&self, ids: &[&str]) -> Result<Vec<Task>, EwsError> {
+        let item_shape = ItemShape::new(ItemBaseShape::AllProperties)
+            .with_body_type(BodyType::Text)
+            .with_field("task:Recurrence");
+
+        self.get_items_with(ids, &item_shape, |item| {
+            if item.name != "Task" {
+                return Err(EwsError::Processing {
+                    message: "GetItem response does not contain a task".to_string(),
+                });
+            }
+            Task::from_xml(item)
+        })
+        .await
+    }
+
+    /// Creates a task.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder_id` - The EWS ID of the tasks folder, or `None` for the
+    ///   default tasks folder
+    /// * `draft` - The properties of the new task
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The percentage of completion is not between 0 and 100
+    /// - The recurrence is not valid
+    /// - The folder does not exist or is not a tasks folder
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ews_client_core::client::{
+    /// #     Credentials, EwsClient, Recurrence, RecurrencePattern, RecurrenceRange, TaskDraft,
+    /// # };
+    /// # use url::Url;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = EwsClient::new(
+    ///     Url::parse("https://outlook.office365.com/EWS/Exchange.asmx")?,
+    ///     Credentials::basic("user@example.com", "password"),
+    /// )?;
+    ///
+    /// let draft = TaskDraft {
+    ///     subject: "Send the monthly report".to_string(),
+    ///     due_date: Some(1_706_659_200),
+    ///     recurrence: Some(Recurrence {
+    ///         pattern: RecurrencePattern::AbsoluteMonthly {
+    ///             interval: 1,
+    ///             day_of_month: 31,
+    ///         },
+    ///         range: RecurrenceRange::NoEnd {
+    ///             start_date: 1_706_659_200,
+    ///         },
+    ///     }),
+    ///     reminder: Some(1_706_623_200),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let result = client.create_task(None, &draft).await?;
+    /// println!("Created task: {}", result.item_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_task(&self, folder_id: Option<&str>, draft: &TaskDraft) -> Result<CreateTaskResult, EwsError> {
+        let body = create_task_request(folder_id.unwrap_or(DEFAULT_TASKS_FOLDER), draft, self.target_mailbox())?;
+
+        let response = self
+            .make_raw_operation_request("CreateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("CreateItem", &response)?;
+
+        let id = message
+            .descendant(&["Items", "Task", "ItemId"])
+            .ok_or(EwsError::MissingIdInResponse)?;
+
+        Ok(CreateTaskResult {
+            item_id: id.attribute("Id").ok_or(EwsError::MissingIdInResponse)?.to_string(),
+            change_key: id.attribute("ChangeKey").map(ToString::to_string),
+        })
+    }
+
+    /// Updates an existing task.
+    ///
+    /// Completing an occurrence of a recurring task (by setting its status to
+    /// [`TaskStatus::Completed`] or its percentage of completion to 100)
+    /// makes the server create the next occurrence.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the task
+    /// * `change_key` - The change key of the task, if known. When provided,
+    ///   the update fails if the task was changed since it was read
+    /// * `update` - The changes to apply
+    ///
+    /// # Returns
+    ///
+    /// The new change key of the task.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The update does not change anything
+    /// - The percentage of completion is not between 0 and 100
+    /// - The recurrence is not valid
+    /// - The task cannot be found, or was changed since it was read
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn update_task(
+        &self,
+        item_id: &str,
+        change_key: Option<&str>,
+        update: &TaskUpdate,
+    ) -> Result<String, EwsError> {
+        let updates = update.updates_xml()?;
+
+        if updates.is_empty() {
+            return Err(EwsError::Processing {
+                message: "task update does not change anything".to_string(),
+            });
+        }
+
+        let body = update_task_request(item_id, change_key, &updates);
+
+        let response = self
+            .make_raw_operation_request("UpdateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("UpdateItem", &response)?;
+
+        message
+            .descendant(&["Items", "Task", "ItemId"])
+            .and_then(|id| id.attribute("ChangeKey"))
+            .map(ToString::to_string)
+            .ok_or(EwsError::MissingIdInResponse)
+    }
+
+    /// Deletes a task, including all its occurrences if it recurs, by moving
+    /// it to the Deleted Items folder.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the task
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The task cannot be found
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn delete_task(&self, item_id: &str) -> Result<(), EwsError> {
+        let delete_item = DeleteItem {
+            item_ids: vec![BaseItemId::ItemId {
+                id: item_id.to_string(),
+                change_key: None,
+            }],
+            delete_type: DeleteType::MoveToDeletedItems,
+            send_meeting_cancellations: None,
+            affected_task_occurrences: Some(AffectedTaskOccurrences::AllOccurrences),
+            suppress_read_receipts: None,
+        };
+
+        let response = self
+            .make_operation_request(delete_item, OperationRequestOptions::default())
+            .await?
+            .into_response_messages();
+        let response = single_response_or_error(response)?;
+        process_response_message_class(DeleteItem::NAME, response)?;
+
+        Ok(())
+    }
+
+    /// Flags a message for follow-up, which makes it show up as a task in the
+    /// mailbox owner's task list.
+    ///
+    /// Requires Exchange 2013 or later.
+    ///
+    /// # Arguments
+    ///
+    /// * `item_id` - The EWS ID of the message
+    /// * `change_key` - The change key of the message, if known. When
+    ///   provided, flagging fails if the message was changed since it was read
+    /// * `start_date` - The start date of the follow-up, as a Unix timestamp
+    /// * `due_date` - The due date of the follow-up, as a Unix timestamp.
+    ///   The start and due dates must be provided together
+    ///
+    /// # Returns
+    ///
+    /// The new change key of the message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The server is older than Exchange 2013 ([`EwsError::Unsupported`])
+    /// - Only one of the start and due dates is provided, or the due date is
+    ///   before the start date
+    /// - The message cannot be found
+    /// - Network or authentication errors occur
+    /// - The server returns an unexpected response
+    pub async fn mark_message_as_task(
+        &self,
+        item_id: &str,
+        change_key: Option<&str>,
+        start_date: Option<i64>,
+        due_date: Option<i64>,
+    ) -> Result<String, EwsError> {
+        let capabilities = self.capabilities();
+        capabilities.require(capabilities.flag_for_follow_up, "Flagging messages")?;
+
+        let dates = match (start_date, due_date) {
+            (Some(start_date), Some(due_date)) if due_date < start_date => {
+                return Err(EwsError::Processing {
+                    message: "the due date of a task must not be before its start date".to_string(),
+                });
+            }
+            (Some(start_date), Some(due_date)) => Some((start_date, due_date)),
+            (None, None) => None,
+            _ => {
+                return Err(EwsError::Processing {
+                    message: "the start and due dates of a flag must be provided together".to_string(),
+                });
+            }
+        };
+
+        let body = flag_message_request(item_id, change_key, dates)?;
+
+        let response = self
+            .make_raw_operation_request("UpdateItem", &body, OperationRequestOptions::default())
+            .await?;
+        let message = single_raw_response_or_error("UpdateItem", &response)?;
+
+        message
+            .descendant(&["Items", "Message", "ItemId"])
+            .and_then(|id| id.attribute("ChangeKey"))
+            .map(ToString::to_string)
+            .ok_or(EwsError::MissingIdInResponse)
+    }
+}
@@ -1,83 +1,178 @@
-//! Update a folder's display name.
+//! Update the properties of a folder.
 
 use crate::client::{
-    EwsClient, EwsError, OperationRequestOptions, process_response_message_class, single_response_or_error,
-};
-use ews::{
-    BaseFolderId, Folder, Operation, OperationResponse, PathToElement,
-    update_folder::{FolderChange, FolderChanges, UpdateFolder, Updates},
+    EwsClient, EwsError, OperationRequestOptions, operations::folder_permissions::PermissionSet,
+    raw_requests::folders::update_folder_request, single_raw_response_or_error,
 };
 
+/// The type of the value of an extended MAPI property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapiPropertyType {
+    /// A base64-encoded byte array (e.g. a retention policy tag).
+    Binary,
+    /// A boolean (e.g. `PR_ATTR_HIDDEN`).
+    Boolean,
+    /// A 32-bit integer (e.g. `PR_RETENTION_FLAGS`).
+    Integer,
+    /// A 64-bit integer.
+    Long,
+    /// A string.
+    String,
+    /// An `xs:dateTime`.
+    SystemTime,
+}
+
+impl MapiPropertyType {
+    /// The EWS value of the property type.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Binary => "Binary",
+            Self::Boolean => "Boolean",
+            Self::Integer => "Integer",
+            Self::Long => "Long",
+            Self::String => "String",
+            Self::SystemTime => "SystemTime",
+        }
+    }
+
+    /// Parses the EWS value of a property type.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "Binary" => Some(Self::Binary),
+            "Boolean" => Some(Self::Boolean),
+            "Integer" => Some(Self::Integer),
+            "Long" => Some(Self::Long),
+            "String" => Some(Self::String),
+            "SystemTime" => Some(Self::SystemTime),
+            _ => None,
+        }
+    }
+}
+
+/// Identifies an extended MAPI property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtendedPropertyKey {
+    /// A property identified by its tag, e.g. `0x10F4` for `PR_ATTR_HIDDEN`.
+    Tag(u16),
+    /// A named property, identified by the GUID of its property set and its
+    /// name.
+    Named {
+        /// The GUID of the property set.
+        property_set_id: String,
+        /// The name of the property.
+        name: String,
+    },
+    /// A named property, identified by the GUID of its property set and its
+    /// numeric ID.
+    Id {
+        /// The GUID of the property set.
+        property_set_id: String,
+        /// The numeric ID of the property.
+        id: u32,
+    },
+}
+
+/// A change to an extended MAPI property of a folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FolderExtendedProperty {
+    /// The property to change.
+    pub key: ExtendedPropertyKey,
+    /// The type of the value of the property.
+    pub property_type: MapiPropertyType,
+    /// The new value of the property, as EWS writes it (e.g. `"true"`,
+    /// `"2592000"` or base64 data), or `None` to remove the property.
+    pub value: Option<String>,
+}
+
+/// Changes to apply to an existing folder. Fields left as `None` are not
+/// changed.
+#[derive(Debug, Clone, Default)]
+pub struct FolderUpdate {
+    /// The new display name.
+    pub display_name: Option<String>,
+    /// The new folder class (e.g. `IPF.Note`).
+    pub folder_class: Option<String>,
+    /// Extended MAPI properties to set or remove.
+    pub extended_properties: Vec<FolderExtendedProperty>,
+    /// The new permissions of the folder, replacing all the current ones
+    /// (see [`EwsClient::get_folder_permissions`]).
+    pub permission_set: Option<PermissionSet>,
+}
+
 impl EwsClient {
-    /// Updates a folder's display name.
+    /// Updates the properties of a folder.
     ///
     /// # Arguments
     ///
-    /// * `folder_id` - The EWS ID of the folder to update
-    /// * `folder_name` - The new display name for the folder
+    /// * `folder_id` - The EWS ID of the folder to update, or a distinguished
+    ///   folder name
+    /// * `change_key` - The change key of the folder, if known. When
+    ///   provided, the update fails if the folder was changed since it was
+    ///   read
+    /// * `update` - The changes to apply
+    ///
+    /// # Returns
+    ///
+    /// The new change key of the folder.
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The folder does not exist
+    /// - The update does not change anything, or has an invalid permission
+    ///   set
+    /// - The folder does not exist, or was changed since it was read
     /// - Network or authentication errors occur
     /// - The server returns an unexpected response
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use ews_client_core::client::{EwsClient, Credentials};
+    /// # use ews_client_core::client::{
+    /// #     EwsClient, Credentials, ExtendedPropertyKey, FolderExtendedProperty, FolderUpdate, MapiPropertyType,
+    /// # };
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let client = EwsClient::new("https://outlook.office365.com/EWS/Exchange.asmx".parse()?, Credentials::basic("user", "pass"))?;
-    /// client.update_folder("folder_id", "New Name").await?;
+    /// let update = FolderUpdate {
+    ///     display_name: Some("New Name".to_string()),
+    ///     // PR_ATTR_HIDDEN
+    ///     extended_properties: vec![FolderExtendedProperty {
+    ///         key: ExtendedPropertyKey::Tag(0x10F4),
+    ///         property_type: MapiPropertyType::Boolean,
+    ///         value: Some("true".to_string()),
+    ///     }],
+    ///     ..FolderUpdate::default()
+    /// };
+    /// let change_key = client.update_folder("folder_id", None, &update).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn update_folder(&self, folder_id: &str, folder_name: &str) -> Result<(), EwsError> {
-        let update_folder = UpdateFolder {
-            folder_changes: FolderChanges {
-                folder_change: FolderChange {
-                    folder_id: BaseFolderId::FolderId {
-                        id: folder_id.to_string(),
-                        change_key: None,
-                    },
-                    updates: Updates::SetFolderField {
-                        field_URI: PathToElement::FieldURI {
-                            field_URI: "folder:DisplayName".to_string(),
-                        },
-                        folder: Folder::Folder {
-                            display_name: Some(folder_name.to_string()),
-                            folder_id: None,
-                            parent_folder_id: None,
-                            folder_class: None,
-                            total_count: None,
-                            child_folder_count: None,
-                            extended_property: None,
-                            unread_count: None,
-                        },
-                    },
-                },
-            },
-        };
+    pub async fn update_folder(
+        &self,
+        folder_id: &str,
+        change_key: Option<&str>,
+        update: &FolderUpdate,
+    ) -> Result<String, EwsError> {
+        let updates = update.updates_xml()?;
+
+        if updates.is_empty() {
+            return Err(EwsError::Processing {
+                message: "folder update does not change anything".to_string(),
+            });
+        }
+
+        let body = update_folder_request(folder_id, change_key, &updates, self.target_mailbox());
 
         let response = self
-            .make_operation_request(update_folder, OperationRequestOptions::default())
+            .make_raw_operation_request("UpdateFolder", &body, OperationRequestOptions::default())
             .await?;
-        let response_messages = response.into_response_messages();
-        let response_message = single_response_or_error(response_messages)?;
-        process_response_message_class(UpdateFolder::NAME, response_message)?;
+        let message = single_raw_response_or_error("UpdateFolder", &response)?;
 
-        Ok(())
+        message
+            .child("Folders")
+            .and_then(|folders| folders.children.first())
+            .and_then(|folder| folder.child("FolderId"))
+            .and_then(|id| id.attribute("ChangeKey"))
+            .map(ToString::to_string)
+            .ok_or(EwsError::MissingIdInResponse)
     }
 }
-
-// Note: Integration tests for this module should be placed in the tests/ directory
-// and should be marked with #[ignore] by default to avoid requiring a live EWS server for CI.
-// Example:
-// #[tokio::test]
-// #[ignore]
-// async fn test_update_folder_integration() {
-//     let client = EwsClient::new(...);
-//     let result = client.update_folder("folder_id", "new_name").await;
-//     assert!(result.is_ok());
-// }
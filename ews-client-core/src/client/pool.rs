@@ -0,0 +1,239 @@
+//! A pool of clients working on many mailboxes of the same server.
+
+use std::sync::Arc;
+
+use crossbeam::atomic::AtomicCell;
+use dashmap::DashMap;
+use ews::server_version::ExchangeServerVersion;
+use reqwest::Client;
+use tokio::sync::Semaphore;
+use url::Url;
+
+use crate::client::{
+    Credentials, EwsClient, EwsError, ImpersonatedUserId, VersionStore,
+    limits::{BatchConfig, EWS_MAX_CONCURRENCY, RequestLimits},
+    mailbox_access::MailboxAccess,
+    server_version,
+};
+
+/// Concurrency limits of an [`EwsClientPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EwsClientPoolConfig {
+    /// The maximum number of requests in flight across all the mailboxes.
+    pub max_concurrent_requests: usize,
+    /// The maximum number of requests in flight for a single mailbox, which
+    /// should not exceed the server's `EWSMaxConcurrency` throttling policy.
+    pub max_concurrent_requests_per_mailbox: usize,
+}
+
+impl Default for EwsClientPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: 100,
+            max_concurrent_requests_per_mailbox: EWS_MAX_CONCURRENCY,
+        }
+    }
+}
+
+/// Hands out lightweight clients for many mailboxes of the same EWS
+/// endpoint.
+///
+/// All the clients of a pool share one HTTP connection pool, the store of
+/// the server version detected for the endpoint, and the pool's concurrency
+/// limits.
+/// Clients for the same mailbox share that mailbox's limit.
+///
+/// # Example
+///
+/// ```no_run
+/// # use ews_client_core::client::{EwsClientPool, Credentials};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let pool = EwsClientPool::new(
+///     "https://outlook.office365.com/EWS/Exchange.asmx".parse()?,
+///     Credentials::oauth2("service-token"),
+/// )?;
+/// for address in ["alice@example.com", "bob@example.com"] {
+///     let client = pool.impersonated_client(address);
+///     let hierarchy = client.sync_folder_hierarchy(None).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct EwsClientPool {
+    endpoint: Url,
+    credentials: Credentials,
+    client: Client,
+    config: EwsClientPoolConfig,
+    global_limit: Arc<Semaphore>,
+    version_store: Arc<dyn VersionStore>,
+    /// The version pinned on the pool's clients, if any.
+    server_version: Option<ExchangeServerVersion>,
+    /// The limits of the mailboxes which have live clients, by lowercase
+    /// address.
+    mailbox_limits: DashMap<String, Arc<Semaphore>>,
+}
+
+impl EwsClientPool {
+    /// Creates a pool with the default concurrency limits.
+    ///
+    /// `credentials` are used by the pool's clients, unless replaced with
+    /// [`EwsClient::with_credentials`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pool cannot be initialized.
+    pub fn new(endpoint: Url, credentials: Credentials) -> Result<Self, EwsError> {
+        Self::with_config(endpoint, credentials, EwsClientPoolConfig::default())
+    }
+
+    /// Creates a pool with the given concurrency limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a limit is zero.
+    pub fn with_config(endpoint: Url, credentials: Credentials, config: EwsClientPoolConfig) -> Result<Self, EwsError> {
+        if config.max_concurrent_requests == 0 || config.max_concurrent_requests_per_mailbox == 0 {
+            return Err(EwsError::Processing {
+                message: "client pool concurrency limits must be at least 1".to_string(),
+            });
+        }
+
+        Ok(Self {
+            endpoint,
+            credentials,
+            client: Client::new(),
+            config,
+            global_limit: Arc::new(Semaphore::new(config.max_concurrent_requests)),
+            version_store: server_version::default_version_store(),
+            server_version: None,
+            mailbox_limits: DashMap::new(),
+        })
+    }
+
+    /// Replaces the store the server version is read from and stored to by
+    /// the pool's clients (see [`EwsClient::with_version_store`]).
+    pub fn with_version_store(mut self, store: Arc<dyn VersionStore>) -> Self {
+        self.version_store = store;
+        self
+    }
+
+    /// Pins the version sent in the `RequestServerVersion` header by the
+    /// pool's clients (see [`EwsClient::with_server_version`]).
+    pub fn with_server_version(mut self, version: ExchangeServerVersion) -> Self {
+        self.server_version = Some(version);
+        self
+    }
+
+    /// Get the endpoint URL
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Get the concurrency limits of the pool
+    pub fn config(&self) -> EwsClientPoolConfig {
+        self.config
+    }
+
+    /// Creates a client for a mailbox, using the pool's credentials.
+    ///
+    /// The address is sent in the `X-AnchorMailbox` header and counts
+    /// towards the mailbox's concurrency limit. The client can be further
+    /// configured, e.g. with [`EwsClient::with_credentials`] or
+    /// [`EwsClient::with_target_mailbox`].
+    pub fn client(&self, address: &str) -> EwsClient {
+        let client = EwsClient {
+            endpoint: self.endpoint.clone(),
+            credentials: self.credentials.clone(),
+            client: self.client.clone(),
+            server_version: AtomicCell::new(server_version::read_server_version(
+                &*self.version_store,
+                &self.endpoint,
+            )),
+            server_version_pinned: false,
+            version_store: Arc::clone(&self.version_store),
+            mailbox_access: MailboxAccess {
+                anchor_mailbox: Some(address.to_string()),
+                ..MailboxAccess::default()
+            },
+            limits: RequestLimits {
+                global: Some(Arc::clone(&self.global_limit)),
+                mailbox: Some(self.mailbox_limit(address)),
+                ..RequestLimits::default()
+            },
+            batch_config: BatchConfig::default(),
+        };
+
+        match self.server_version {
+            Some(version) => client.with_server_version(version),
+            None => client,
+        }
+    }
+
+    /// Creates a client impersonating the user with the given SMTP address.
+    pub fn impersonated_client(&self, address: &str) -> EwsClient {
+        self.client(address)
+            .with_impersonation(ImpersonatedUserId::SmtpAddress(address.to_string()))
+    }
+
+    /// Gets the limit of a mailbox, shared by all its live clients.
+    ///
+    /// Limits of mailboxes without live clients are dropped at the same time,
+    /// so that the pool does not grow with every mailbox it has worked on.
+    fn mailbox_limit(&self, address: &str) -> Arc<Semaphore> {
+        self.mailbox_limits
+            .retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+
+        let limit = self
+            .mailbox_limits
+            .entry(address.to_lowercase())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.config.max_concurrent_requests_per_mailbox)));
+        Arc::clone(limit.value())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mailbox_limits_are_shared() {
+        let pool = EwsClientPool::new(
+            "https://pool.example.com/EWS/Exchange.asmx".parse().unwrap(),
+            Credentials::basic("service", "pass"),
+        )
+        .unwrap();
+
+        let alice = pool.client("alice@example.com");
+        let alice_again = pool.impersonated_client("Alice@Example.com");
+        let bob = pool.client("bob@example.com");
+
+        let alice_limit = alice.limits.mailbox.as_ref().unwrap();
+        assert!(Arc::ptr_eq(alice_limit, alice_again.limits.mailbox.as_ref().unwrap()));
+        assert!(!Arc::ptr_eq(alice_limit, bob.limits.mailbox.as_ref().unwrap()));
+        assert!(Arc::ptr_eq(
+            alice.limits.global.as_ref().unwrap(),
+            bob.limits.global.as_ref().unwrap()
+        ));
+        assert_eq!(alice_limit.available_permits(), EWS_MAX_CONCURRENCY);
+        assert_eq!(pool.mailbox_limits.len(), 2);
+
+        // The limits of mailboxes without clients are dropped
+        drop(bob);
+        let _carol = pool.client("carol@example.com");
+        assert!(!pool.mailbox_limits.contains_key("bob@example.com"));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let result = EwsClientPool::with_config(
+            "https://pool.example.com/EWS/Exchange.asmx".parse().unwrap(),
+            Credentials::basic("service", "pass"),
+            EwsClientPoolConfig {
+                max_concurrent_requests: 0,
+                ..EwsClientPoolConfig::default()
+            },
+        );
+        assert!(matches!(result, Err(EwsError::Processing { .. })));
+    }
+}
@@ -0,0 +1,64 @@
+//! Availability requests: free/busy information and suggested meeting times.
+
+use crate::client::{
+    EwsError,
+    operations::{Attendee, AttendeeType, TimeWindow},
+    xml::{format_date_time, text_element},
+};
+
+/// The time zone of availability requests.
+///
+/// Times in availability requests and responses carry no offset and are
+/// expressed in the request's time zone. Using UTC without daylight saving
+/// time lets every time be exchanged as UTC.
+const UTC_TIME_ZONE_XML: &str = "<t:TimeZone><t:Bias>0</t:Bias><t:StandardTime><t:Bias>0</t:Bias><t:Time>00:00:00</t:Time><t:DayOrder>1</t:DayOrder><t:Month>1</t:Month><t:DayOfWeek>Sunday</t:DayOfWeek></t:StandardTime><t:DaylightTime><t:Bias>0</t:Bias><t:Time>00:00:00</t:Time><t:DayOrder>1</t:DayOrder><t:Month>1</t:Month><t:DayOfWeek>Sunday</t:DayOfWeek></t:DaylightTime></t:TimeZone>";
+
+/// Writes a `GetUserAvailabilityRequest` for the merged free/busy
+/// information of the attendees over a window, with meeting suggestions when
+/// a meeting duration is given.
+pub(crate) fn user_availability_request(
+    attendees: &[Attendee],
+    window: TimeWindow,
+    interval_minutes: u32,
+    meeting_duration_minutes: Option<u32>,
+) -> Result<String, EwsError> {
+    let time_window = format!(
+        "<t:StartTime>{}</t:StartTime><t:EndTime>{}</t:EndTime>",
+        availability_date_time(window.start)?,
+        availability_date_time(window.end)?,
+    );
+
+    let mailbox_data: String = attendees
+        .iter()
+        .map(|attendee| {
+            let attendee_type = match attendee.attendee_type {
+                AttendeeType::Required => "Required",
+                AttendeeType::Optional => "Optional",
+                AttendeeType::Resource => "Resource",
+            };
+            format!(
+                "<t:MailboxData><t:Email>{}</t:Email>{}<t:ExcludeConflicts>false</t:ExcludeConflicts></t:MailboxData>",
+                text_element("t:Address", &attendee.email),
+                text_element("t:AttendeeType", attendee_type),
+            )
+        })
+        .collect();
+
+    let suggestions_view_options = meeting_duration_minutes
+        .map(|duration| {
+            format!(
+                "<t:SuggestionsViewOptions><t:MeetingDurationInMinutes>{duration}</t:MeetingDurationInMinutes><t:DetailedSuggestionsWindow>{time_window}</t:DetailedSuggestionsWindow></t:SuggestionsViewOptions>"
+            )
+        })
+        .unwrap_or_default();
+
+    Ok(format!(
+        "<m:GetUserAvailabilityRequest>{UTC_TIME_ZONE_XML}<m:MailboxDataArray>{mailbox_data}</m:MailboxDataArray><t:FreeBusyViewOptions><t:TimeWindow>{time_window}</t:TimeWindow><t:MergedFreeBusyIntervalInMinutes>{interval_minutes}</t:MergedFreeBusyIntervalInMinutes><t:RequestedView>DetailedMerged</t:RequestedView></t:FreeBusyViewOptions>{suggestions_view_options}</m:GetUserAvailabilityRequest>"
+    ))
+}
+
+/// Formats a Unix timestamp for an availability request, i.e. without an
+/// offset, in the UTC time zone of the request.
+fn availability_date_time(timestamp: i64) -> Result<String, EwsError> {
+    format_date_time(timestamp).map(|date_time| date_time.trim_end_matches('Z').to_string())
+}
@@ -0,0 +1,451 @@
+//! Calendar requests: calendar views, calendar items, time zones and meeting
+//! responses.
+
+use crate::client::{
+    EwsError, ServerCapabilities,
+    operations::{
+        Attendee, AttendeeType, CalendarItemDraft, CalendarItemUpdate, LegacyFreeBusyStatus, MeetingResponse,
+        SendMeetingInvitations, SendMeetingUpdates, TimeZoneDefinition, TimeZoneTransition,
+    },
+    xml::{escape_text, format_date_time, format_duration, optional_text_element, text_element},
+};
+
+use super::{
+    base_folder_id_xml, bool_str, delete_item_field_xml, optional_body_xml, saved_item_folder_id_xml,
+    set_item_field_xml, update_item_xml,
+};
+
+/// The attendee types, in the order of their lists in the EWS schema.
+const ATTENDEE_TYPES: [AttendeeType; 3] = [AttendeeType::Required, AttendeeType::Optional, AttendeeType::Resource];
+
+/// Writes a `FindItem` request reading the calendar items of a folder between
+/// two dates, with recurring items expanded into their occurrences.
+pub(crate) fn calendar_view_request(
+    folder_id: &str,
+    start: i64,
+    end: i64,
+    max_entries: Option<u32>,
+    mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    let max_entries = max_entries
+        .map(|max| format!(r#" MaxEntriesReturned="{max}""#))
+        .unwrap_or_default();
+
+    Ok(format!(
+        r#"<m:FindItem Traversal="Shallow"><m:ItemShape><t:BaseShape>AllProperties</t:BaseShape></m:ItemShape><m:CalendarView{max_entries} StartDate="{}" EndDate="{}"/><m:ParentFolderIds>{}</m:ParentFolderIds></m:FindItem>"#,
+        format_date_time(start)?,
+        format_date_time(end)?,
+        base_folder_id_xml(folder_id, mailbox),
+    ))
+}
+
+/// Writes a `CreateItem` request for a single calendar item, given as the XML
+/// of its `CalendarItem` element.
+pub(crate) fn create_calendar_item_request(
+    folder_id: Option<&str>,
+    item: &str,
+    send_invitations: SendMeetingInvitations,
+    mailbox: Option<&str>,
+) -> String {
+    format!(
+        r#"<m:CreateItem SendMeetingInvitations="{}">{}<m:Items>{item}</m:Items></m:CreateItem>"#,
+        send_invitations.as_str(),
+        saved_item_folder_id_xml(folder_id, mailbox),
+    )
+}
+
+/// Writes an `UpdateItem` request applying the given updates to a calendar
+/// item.
+pub(crate) fn update_calendar_item_request(
+    item_id: &str,
+    change_key: Option<&str>,
+    updates: &str,
+    send_updates: SendMeetingUpdates,
+) -> String {
+    let attributes = format!(r#" SendMeetingInvitationsOrCancellations="{}""#, send_updates.as_str());
+    update_item_xml(item_id, change_key, &attributes, updates)
+}
+
+/// Writes a `CreateItem` request answering a meeting request, which is sent
+/// to the organizer or only saved in the Drafts folder.
+pub(crate) fn meeting_response_request(
+    item_id: &str,
+    response: MeetingResponse,
+    message: Option<&str>,
+    send_response: bool,
+) -> String {
+    let disposition = if send_response { "SendAndSaveCopy" } else { "SaveOnly" };
+    let element = response.element_name();
+
+    format!(
+        r#"<m:CreateItem MessageDisposition="{disposition}"><m:Items><t:{element}>{}<t:ReferenceItemId Id="{}"/></t:{element}></m:Items></m:CreateItem>"#,
+        optional_body_xml(message),
+        escape_text(item_id),
+    )
+}
+
+/// Writes a `CreateItem` request cancelling a meeting and sending the
+/// cancellation to its attendees.
+pub(crate) fn cancel_meeting_request(item_id: &str, message: Option<&str>) -> String {
+    let new_body = message
+        .map(|message| {
+            format!(
+                r#"<t:NewBodyContent BodyType="Text">{}</t:NewBodyContent>"#,
+                escape_text(message)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        r#"<m:CreateItem MessageDisposition="SendAndSaveCopy"><m:Items><t:CancelCalendarItem>{new_body}<t:ReferenceItemId Id="{}"/></t:CancelCalendarItem></m:Items></m:CreateItem>"#,
+        escape_text(item_id),
+    )
+}
+
+impl CalendarItemDraft {
+    /// Writes the draft as a `CalendarItem` element, with its time zone in
+    /// the form the server knows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a date, the recurrence or the time zone definition
+    /// is not valid.
+    pub(crate) fn to_xml(&self, capabilities: ServerCapabilities) -> Result<String, EwsError> {
+        // The order of the properties is dictated by the EWS schema.
+        let mut properties = text_element("t:Subject", &self.subject);
+        properties.push_str(&optional_body_xml(self.body.as_deref()));
+        if let Some(minutes) = self.reminder_minutes {
+            properties.push_str(&text_element("t:ReminderIsSet", "true"));
+            properties.push_str(&text_element("t:ReminderMinutesBeforeStart", &minutes.to_string()));
+        } else {
+            properties.push_str(&text_element("t:ReminderIsSet", "false"));
+        }
+        properties.push_str(&optional_text_element("t:UID", self.uid.as_deref()));
+        properties.push_str(&text_element("t:Start", &format_date_time(self.start)?));
+        properties.push_str(&text_element("t:End", &format_date_time(self.end)?));
+        properties.push_str(&text_element("t:IsAllDayEvent", bool_str(self.is_all_day_event)));
+        properties.push_str(&optional_text_element(
+            "t:LegacyFreeBusyStatus",
+            self.legacy_free_busy_status.map(LegacyFreeBusyStatus::as_str),
+        ));
+        properties.push_str(&optional_text_element("t:Location", self.location.as_deref()));
+        for attendee_type in ATTENDEE_TYPES {
+            properties.push_str(&attendee_list_xml(&self.attendees, attendee_type));
+        }
+        if let Some(recurrence) = &self.recurrence {
+            properties.push_str(&recurrence.to_xml()?);
+        }
+        if let Some(definition) = &self.time_zone_definition {
+            properties.push_str(&time_zone_definition_xml(definition, capabilities)?);
+        } else if let Some(time_zone) = &self.time_zone {
+            properties.push_str(&time_zone_xml(time_zone, capabilities));
+        }
+
+        Ok(format!("<t:CalendarItem>{properties}</t:CalendarItem>"))
+    }
+}
+
+impl CalendarItemUpdate {
+    /// Writes the `SetItemField` and `DeleteItemField` updates of a calendar
+    /// item. Returns an empty string if the update does not change anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a date is not valid.
+    pub(crate) fn updates_xml(&self, capabilities: ServerCapabilities) -> Result<String, EwsError> {
+        let mut updates = String::new();
+
+        if let Some(subject) = &self.subject {
+            updates.push_str(&set_field_xml("item:Subject", &text_element("t:Subject", subject)));
+        }
+        if let Some(body) = &self.body {
+            updates.push_str(&set_field_xml("item:Body", &optional_body_xml(Some(body))));
+        }
+        if let Some(start) = self.start {
+            updates.push_str(&set_field_xml(
+                "calendar:Start",
+                &text_element("t:Start", &format_date_time(start)?),
+            ));
+        }
+        if let Some(end) = self.end {
+            updates.push_str(&set_field_xml(
+                "calendar:End",
+                &text_element("t:End", &format_date_time(end)?),
+            ));
+        }
+        if let Some(is_all_day_event) = self.is_all_day_event {
+            updates.push_str(&set_field_xml(
+                "calendar:IsAllDayEvent",
+                &text_element("t:IsAllDayEvent", bool_str(is_all_day_event)),
+            ));
+        }
+        if let Some(location) = &self.location {
+            updates.push_str(&set_field_xml(
+                "calendar:Location",
+                &text_element("t:Location", location),
+            ));
+        }
+        if let Some(status) = self.legacy_free_busy_status {
+            updates.push_str(&set_field_xml(
+                "calendar:LegacyFreeBusyStatus",
+                &text_element("t:LegacyFreeBusyStatus", status.as_str()),
+            ));
+        }
+        if let Some(time_zone) = &self.time_zone {
+            updates.push_str(&time_zone_updates_xml(time_zone, capabilities));
+        }
+        if let Some(attendees) = &self.attendees {
+            for attendee_type in ATTENDEE_TYPES {
+                let list = attendee_list_xml(attendees, attendee_type);
+                if list.is_empty() {
+                    updates.push_str(&delete_item_field_xml(attendee_type.field_uri()));
+                } else {
+                    updates.push_str(&set_field_xml(attendee_type.field_uri(), &list));
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+}
+
+impl Attendee {
+    /// Writes the `Attendee` element for this attendee.
+    fn to_xml(&self) -> String {
+        format!(
+            "<t:Attendee><t:Mailbox>{}{}</t:Mailbox></t:Attendee>",
+            optional_text_element("t:Name", self.name.as_deref()),
+            text_element("t:EmailAddress", &self.email),
+        )
+    }
+}
+
+/// Writes the list of attendees of the given type, or nothing if there are
+/// none.
+fn attendee_list_xml(attendees: &[Attendee], attendee_type: AttendeeType) -> String {
+    let list: String = attendees
+        .iter()
+        .filter(|attendee| attendee.attendee_type == attendee_type)
+        .map(Attendee::to_xml)
+        .collect();
+
+    if list.is_empty() {
+        list
+    } else {
+        let name = attendee_type.list_element_name();
+        format!("<t:{name}>{list}</t:{name}>")
+    }
+}
+
+/// Writes a `SetItemField` update setting a calendar item property to the
+/// given value.
+fn set_field_xml(field_uri: &str, value: &str) -> String {
+    set_item_field_xml(field_uri, "t:CalendarItem", value)
+}
+
+/// Writes the time zone properties of a calendar item.
+///
+/// Exchange 2010 and later take separate start and end time zones, while
+/// Exchange 2007 only knows a single meeting time zone.
+fn time_zone_xml(time_zone: &str, capabilities: ServerCapabilities) -> String {
+    let time_zone = escape_text(time_zone);
+
+    if capabilities.time_zone_definitions {
+        format!(r#"<t:StartTimeZone Id="{time_zone}"/><t:EndTimeZone Id="{time_zone}"/>"#)
+    } else {
+        format!(r#"<t:MeetingTimeZone TimeZoneName="{time_zone}"/>"#)
+    }
+}
+
+/// Writes the time zone properties of a calendar item from a full time zone
+/// definition.
+fn time_zone_definition_xml(
+    definition: &TimeZoneDefinition,
+    capabilities: ServerCapabilities,
+) -> Result<String, EwsError> {
+    if capabilities.time_zone_definitions {
+        Ok(definition.to_xml("t:StartTimeZone")? + &definition.to_xml("t:EndTimeZone")?)
+    } else {
+        definition.meeting_time_zone_xml()
+    }
+}
+
+/// Writes the updates setting the time zone of a calendar item.
+fn time_zone_updates_xml(time_zone: &str, capabilities: ServerCapabilities) -> String {
+    let escaped = escape_text(time_zone);
+
+    if capabilities.time_zone_definitions {
+        format!(
+            "{}{}",
+            set_field_xml(
+                "calendar:StartTimeZone",
+                &format!(r#"<t:StartTimeZone Id="{escaped}"/>"#)
+            ),
+            set_field_xml("calendar:EndTimeZone", &format!(r#"<t:EndTimeZone Id="{escaped}"/>"#)),
+        )
+    } else {
+        set_field_xml(
+            "calendar:MeetingTimeZone",
+            &format!(r#"<t:MeetingTimeZone TimeZoneName="{escaped}"/>"#),
+        )
+    }
+}
+
+impl TimeZoneDefinition {
+    /// Writes the time zone as a `TimeZoneDefinition` element with the given
+    /// name (e.g. `t:StartTimeZone`), for Exchange 2010 and later.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a transition is not valid.
+    pub(crate) fn to_xml(&self, element_name: &str) -> Result<String, EwsError> {
+        let id = escape_text(&self.id);
+        let name = self
+            .name
+            .as_deref()
+            .map(|name| format!(r#" Name="{}""#, escape_text(name)))
+            .unwrap_or_default();
+        let standard_period = format!("{id}/Standard");
+        let daylight_period = format!("{id}/Daylight");
+
+        let mut periods = period_xml(&standard_period, "Standard", self.standard_offset);
+        let group = match &self.daylight {
+            Some(daylight) => {
+                periods.push_str(&period_xml(&daylight_period, "Daylight", daylight.offset));
+                daylight.start.to_xml(&daylight_period)? + &daylight.end.to_xml(&standard_period)?
+            }
+            None => format!(r#"<t:Transition><t:To Kind="Period">{standard_period}</t:To></t:Transition>"#),
+        };
+
+        Ok(format!(
+            r#"<{element_name} Id="{id}"{name}><t:Periods>{periods}</t:Periods><t:TransitionsGroups><t:TransitionsGroup Id="0">{group}</t:TransitionsGroup></t:TransitionsGroups><t:Transitions><t:Transition><t:To Kind="Group">0</t:To></t:Transition></t:Transitions></{element_name}>"#
+        ))
+    }
+
+    /// Writes the time zone as a `MeetingTimeZone` element, for Exchange 2007.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a transition is not valid.
+    fn meeting_time_zone_xml(&self) -> Result<String, EwsError> {
+        // The offsets of the periods are relative to the base offset.
+        let changes = match &self.daylight {
+            Some(daylight) => format!(
+                "<t:Standard>{}</t:Standard><t:Daylight>{}</t:Daylight>",
+                daylight.end.time_change_xml(0)?,
+                daylight.start.time_change_xml(self.standard_offset - daylight.offset)?,
+            ),
+            None => String::new(),
+        };
+
+        Ok(format!(
+            r#"<t:MeetingTimeZone TimeZoneName="{}"><t:BaseOffset>{}</t:BaseOffset>{changes}</t:MeetingTimeZone>"#,
+            escape_text(&self.id),
+            format_duration(-self.standard_offset),
+        ))
+    }
+}
+
+impl TimeZoneTransition {
+    /// Writes the transition as a `RecurringDayTransition` element to the
+    /// period with the given ID.
+    fn to_xml(&self, period_id: &str) -> Result<String, EwsError> {
+        self.validate()?;
+
+        Ok(format!(
+            r#"<t:RecurringDayTransition><t:To Kind="Period">{period_id}</t:To>{}{}{}{}</t:RecurringDayTransition>"#,
+            text_element("t:TimeOffset", &format_duration(i32::from(self.time))),
+            text_element("t:Month", &self.month.to_string()),
+            text_element("t:DayOfWeek", self.day_of_week.as_str()),
+            text_element("t:Occurrence", &self.day_of_week_index.position().to_string()),
+        ))
+    }
+
+    /// Writes the children of a `Standard` or `Daylight` element of a
+    /// `MeetingTimeZone`, with the given offset from the base offset.
+    fn time_change_xml(&self, offset: i32) -> Result<String, EwsError> {
+        let month = self.validate()?;
+
+        Ok(format!(
+            "{}<t:RelativeYearlyRecurrence>{}{}{}</t:RelativeYearlyRecurrence>{}",
+            text_element("t:Offset", &format_duration(offset)),
+            text_element("t:DaysOfWeek", self.day_of_week.as_str()),
+            text_element("t:DayOfWeekIndex", self.day_of_week_index.as_str()),
+            text_element("t:Month", &month.to_string()),
+            text_element("t:Time", &format!("{:02}:{:02}:00", self.time / 60, self.time % 60)),
+        ))
+    }
+}
+
+/// Writes a `Period` element.
+fn period_xml(id: &str, name: &str, offset: i32) -> String {
+    format!(
+        r#"<t:Period Bias="{}" Name="{name}" Id="{id}"/>"#,
+        format_duration(-offset)
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use ews::server_version::ExchangeServerVersion;
+
+    use super::*;
+    use crate::client::operations::{DayOfWeek, DayOfWeekIndex, DaylightSavingTime};
+
+    fn w_europe() -> TimeZoneDefinition {
+        let transition = |month, time| TimeZoneTransition {
+            month,
+            day_of_week: DayOfWeek::Sunday,
+            day_of_week_index: DayOfWeekIndex::Last,
+            time,
+        };
+
+        TimeZoneDefinition {
+            id: "W. Europe Standard Time".to_string(),
+            name: None,
+            standard_offset: 60,
+            daylight: Some(DaylightSavingTime {
+                offset: 120,
+                start: transition(3, 120),
+                end: transition(10, 180),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_meeting_time_zone_xml() {
+        let xml = w_europe().meeting_time_zone_xml().unwrap();
+        assert!(xml.starts_with(
+            r#"<t:MeetingTimeZone TimeZoneName="W. Europe Standard Time"><t:BaseOffset>-PT1H</t:BaseOffset><t:Standard><t:Offset>PT0M</t:Offset>"#
+        ));
+        assert!(xml.contains("<t:Daylight><t:Offset>-PT1H</t:Offset>"));
+        assert!(xml.contains("<t:Month>March</t:Month></t:RelativeYearlyRecurrence><t:Time>02:00:00</t:Time>"));
+    }
+
+    #[test]
+    fn test_calendar_item_update_xml() {
+        let capabilities = ServerCapabilities::from_version(ExchangeServerVersion::Exchange2013_SP1);
+        let update = CalendarItemUpdate {
+            location: Some("Room 1".to_string()),
+            attendees: Some(vec![Attendee::new("alice@example.com", AttendeeType::Optional)]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            update.updates_xml(capabilities).unwrap(),
+            concat!(
+                r#"<t:SetItemField><t:FieldURI FieldURI="calendar:Location"/><t:CalendarItem><t:Location>Room 1</t:Location></t:CalendarItem></t:SetItemField>"#,
+                r#"<t:DeleteItemField><t:FieldURI FieldURI="calendar:RequiredAttendees"/></t:DeleteItemField>"#,
+                r#"<t:SetItemField><t:FieldURI FieldURI="calendar:OptionalAttendees"/><t:CalendarItem><t:OptionalAttendees><t:Attendee><t:Mailbox><t:EmailAddress>alice@example.com</t:EmailAddress></t:Mailbox></t:Attendee></t:OptionalAttendees></t:CalendarItem></t:SetItemField>"#,
+                r#"<t:DeleteItemField><t:FieldURI FieldURI="calendar:Resources"/></t:DeleteItemField>"#,
+            )
+        );
+        assert!(
+            CalendarItemUpdate::default()
+                .updates_xml(capabilities)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Contact requests: listing, creating and updating contacts.
+
+use crate::client::{
+    EwsError,
+    operations::{
+        Contact, ContactEmailAddress, ContactPhoneNumber, ContactPhysicalAddress, ContactUpdate, EmailAddressKey,
+        PhoneNumberKey, PhysicalAddressKey,
+    },
+    xml::{escape_text, format_date_time, optional_text_element, text_element},
+};
+
+use super::{base_folder_id_xml, optional_body_xml, saved_item_folder_id_xml, update_item_xml};
+
+/// Writes a `FindItem` request for a page of the contacts of a folder,
+/// sorted by display name.
+pub(crate) fn find_contacts_request(folder_id: &str, offset: u32, max_entries: u32, mailbox: Option<&str>) -> String {
+    format!(
+        r#"<m:FindItem Traversal="Shallow"><m:ItemShape><t:BaseShape>AllProperties</t:BaseShape></m:ItemShape><m:IndexedPageItemView MaxEntriesReturned="{max_entries}" Offset="{offset}" BasePoint="Beginning"/><m:SortOrder><t:FieldOrder Order="Ascending"><t:FieldURI FieldURI="contacts:DisplayName"/></t:FieldOrder></m:SortOrder><m:ParentFolderIds>{}</m:ParentFolderIds></m:FindItem>"#,
+        base_folder_id_xml(folder_id, mailbox),
+    )
+}
+
+/// Writes a `CreateItem` request for a single contact.
+pub(crate) fn create_contact_request(
+    folder_id: &str,
+    contact: &Contact,
+    mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    Ok(format!(
+        "<m:CreateItem>{}<m:Items><t:Contact>{}</t:Contact></m:Items></m:CreateItem>",
+        saved_item_folder_id_xml(Some(folder_id), mailbox),
+        contact.properties_xml()?,
+    ))
+}
+
+/// Writes an `UpdateItem` request applying the given updates to a contact.
+pub(crate) fn update_contact_request(item_id: &str, change_key: Option<&str>, updates: &str) -> String {
+    update_item_xml(item_id, change_key, "", updates)
+}
+
+impl Contact {
+    /// Writes the properties of the contact, in the order of the EWS schema.
+    fn properties_xml(&self) -> Result<String, EwsError> {
+        let mut properties = optional_body_xml(self.notes.as_deref());
+        properties.push_str(&optional_text_element("t:DisplayName", self.display_name.as_deref()));
+        properties.push_str(&optional_text_element("t:GivenName", self.given_name.as_deref()));
+        properties.push_str(&optional_text_element("t:MiddleName", self.middle_name.as_deref()));
+        properties.push_str(&optional_text_element("t:Nickname", self.nickname.as_deref()));
+        properties.push_str(&optional_text_element("t:CompanyName", self.company_name.as_deref()));
+        properties.push_str(&email_addresses_xml(&self.email_addresses));
+        properties.push_str(&physical_addresses_xml(&self.physical_addresses));
+        properties.push_str(&phone_numbers_xml(&self.phone_numbers));
+        if let Some(birthday) = self.birthday {
+            properties.push_str(&text_element("t:Birthday", &format_date_time(birthday)?));
+        }
+        properties.push_str(&optional_text_element("t:Department", self.department.as_deref()));
+        properties.push_str(&optional_text_element("t:JobTitle", self.job_title.as_deref()));
+        properties.push_str(&optional_text_element("t:Surname", self.surname.as_deref()));
+
+        Ok(properties)
+    }
+}
+
+impl ContactUpdate {
+    /// Writes the `SetItemField` and `DeleteItemField` changes of the update.
+    pub(crate) fn updates_xml(&self) -> String {
+        let mut updates = String::new();
+
+        let text_fields = [
+            ("item:Body", "Body", &self.notes),
+            ("contacts:DisplayName", "DisplayName", &self.display_name),
+            ("contacts:GivenName", "GivenName", &self.given_name),
+            ("contacts:MiddleName", "MiddleName", &self.middle_name),
+            ("contacts:Nickname", "Nickname", &self.nickname),
+            ("contacts:CompanyName", "CompanyName", &self.company_name),
+            ("contacts:Department", "Department", &self.department),
+            ("contacts:JobTitle", "JobTitle", &self.job_title),
+            ("contacts:Surname", "Surname", &self.surname),
+        ];
+        for (field_uri, element, value) in text_fields {
+            match value.as_deref() {
+                None => {}
+                Some("") => updates.push_str(&delete_field_xml(field_uri, None)),
+                Some(value) if element == "Body" => {
+                    updates.push_str(&set_field_xml(field_uri, None, &optional_body_xml(Some(value))));
+                }
+                Some(value) => {
+                    updates.push_str(&set_field_xml(
+                        field_uri,
+                        None,
+                        &text_element(&format!("t:{element}"), value),
+                    ));
+                }
+            }
+        }
+
+        if let Some(email_addresses) = &self.email_addresses {
+            for key in EmailAddressKey::ALL {
+                let index = Some(key.as_str());
+                match email_addresses.iter().find(|email| email.key == key) {
+                    Some(email) => updates.push_str(&set_field_xml(
+                        "contacts:EmailAddress",
+                        index,
+                        &email_addresses_xml(std::slice::from_ref(email)),
+                    )),
+                    None => updates.push_str(&delete_field_xml("contacts:EmailAddress", index)),
+                }
+            }
+        }
+
+        if let Some(phone_numbers) = &self.phone_numbers {
+            for key in PhoneNumberKey::ALL {
+                let index = Some(key.as_str());
+                match phone_numbers.iter().find(|phone| phone.key == key) {
+                    Some(phone) => updates.push_str(&set_field_xml(
+                        "contacts:PhoneNumber",
+                        index,
+                        &phone_numbers_xml(std::slice::from_ref(phone)),
+                    )),
+                    None => updates.push_str(&delete_field_xml("contacts:PhoneNumber", index)),
+                }
+            }
+        }
+
+        if let Some(physical_addresses) = &self.physical_addresses {
+            let empty = ContactPhysicalAddress::default();
+            for key in PhysicalAddressKey::ALL {
+                let index = Some(key.as_str());
+                let address = physical_addresses
+                    .iter()
+                    .find(|address| address.key == key)
+                    .unwrap_or(&empty);
+
+                // Each part of an address is a separate indexed property.
+                for (part, value) in address.parts() {
+                    let field_uri = format!("contacts:PhysicalAddress:{part}");
+                    match value {
+                        Some(value) => updates.push_str(&set_field_xml(
+                            &field_uri,
+                            index,
+                            &format!(
+                                r#"<t:PhysicalAddresses><t:Entry Key="{}">{}</t:Entry></t:PhysicalAddresses>"#,
+                                key.as_str(),
+                                text_element(&format!("t:{part}"), value),
+                            ),
+                        )),
+                        None => updates.push_str(&delete_field_xml(&field_uri, index)),
+                    }
+                }
+            }
+        }
+
+        updates
+    }
+}
+
+/// Writes the `EmailAddresses` element of a contact, or nothing if there are
+/// no addresses.
+fn email_addresses_xml(email_addresses: &[ContactEmailAddress]) -> String {
+    let entries: String = email_addresses
+        .iter()
+        .map(|email| {
+            format!(
+                r#"<t:Entry Key="{}">{}</t:Entry>"#,
+                email.key.as_str(),
+                escape_text(&email.address)
+            )
+        })
+        .collect();
+
+    if entries.is_empty() {
+        entries
+    } else {
+        format!("<t:EmailAddresses>{entries}</t:EmailAddresses>")
+    }
+}
+
+/// Writes the `PhoneNumbers` element of a contact, or nothing if there are no
+/// phone numbers.
+fn phone_numbers_xml(phone_numbers: &[ContactPhoneNumber]) -> String {
+    let entries: String = phone_numbers
+        .iter()
+        .map(|phone| {
+            format!(
+                r#"<t:Entry Key="{}">{}</t:Entry>"#,
+                phone.key.as_str(),
+                escape_text(&phone.number)
+            )
+        })
+        .collect();
+
+    if entries.is_empty() {
+        entries
+    } else {
+        format!("<t:PhoneNumbers>{entries}</t:PhoneNumbers>")
+    }
+}
+
+/// Writes the `PhysicalAddresses` element of a contact, or nothing if there
+/// are no addresses.
+fn physical_addresses_xml(physical_addresses: &[ContactPhysicalAddress]) -> String {
+    let entries: String = physical_addresses
+        .iter()
+        .map(|address| {
+            let parts: String = address
+                .parts()
+                .into_iter()
+                .map(|(name, value)| optional_text_element(&format!("t:{name}"), value))
+                .collect();
+            format!(r#"<t:Entry Key="{}">{parts}</t:Entry>"#, address.key.as_str())
+        })
+        .collect();
+
+    if entries.is_empty() {
+        entries
+    } else {
+        format!("<t:PhysicalAddresses>{entries}</t:PhysicalAddresses>")
+    }
+}
+
+/// Writes a `SetItemField` update setting a contact property, optionally
+/// indexed, to the given value.
+fn set_field_xml(field_uri: &str, field_index: Option<&str>, value: &str) -> String {
+    format!(
+        "<t:SetItemField>{}<t:Contact>{value}</t:Contact></t:SetItemField>",
+        field_uri_xml(field_uri, field_index)
+    )
+}
+
+/// Writes a `DeleteItemField` update clearing a contact property, optionally
+/// indexed.
+fn delete_field_xml(field_uri: &str, field_index: Option<&str>) -> String {
+    format!(
+        "<t:DeleteItemField>{}</t:DeleteItemField>",
+        field_uri_xml(field_uri, field_index)
+    )
+}
+
+/// Writes the element identifying a contact property, optionally indexed.
+fn field_uri_xml(field_uri: &str, field_index: Option<&str>) -> String {
+    match field_index {
+        Some(index) => format!(r#"<t:IndexedFieldURI FieldURI="{field_uri}" FieldIndex="{index}"/>"#),
+        None => format!(r#"<t:FieldURI FieldURI="{field_uri}"/>"#),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_update_xml() {
+        let update = ContactUpdate {
+            display_name: Some("Alice & Bob".to_string()),
+            job_title: Some(String::new()),
+            email_addresses: Some(vec![ContactEmailAddress {
+                key: EmailAddressKey::EmailAddress2,
+                address: "alice@example.com".to_string(),
+            }]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            update.updates_xml(),
+            concat!(
+                r#"<t:SetItemField><t:FieldURI FieldURI="contacts:DisplayName"/><t:Contact><t:DisplayName>Alice &amp; Bob</t:DisplayName></t:Contact></t:SetItemField>"#,
+                r#"<t:DeleteItemField><t:FieldURI FieldURI="contacts:JobTitle"/></t:DeleteItemField>"#,
+                r#"<t:DeleteItemField><t:IndexedFieldURI FieldURI="contacts:EmailAddress" FieldIndex="EmailAddress1"/></t:DeleteItemField>"#,
+                r#"<t:SetItemField><t:IndexedFieldURI FieldURI="contacts:EmailAddress" FieldIndex="EmailAddress2"/><t:Contact><t:EmailAddresses><t:Entry Key="EmailAddress2">alice@example.com</t:Entry></t:EmailAddresses></t:Contact></t:SetItemField>"#,
+                r#"<t:DeleteItemField><t:IndexedFieldURI FieldURI="contacts:EmailAddress" FieldIndex="EmailAddress3"/></t:DeleteItemField>"#,
+            )
+        );
+        assert!(ContactUpdate::default().updates_xml().is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+//! Directory requests: name resolution, distribution lists and people search.
+
+use crate::client::{
+    operations::ResolveNamesSearchScope,
+    xml::{escape_text, text_element},
+};
+
+use super::distinguished_folder_id_xml;
+
+/// Writes a `ResolveNames` request for a partial name or address.
+pub(crate) fn resolve_names_request(
+    query: &str,
+    search_scope: ResolveNamesSearchScope,
+    return_full_contact_data: bool,
+) -> String {
+    format!(
+        r#"<m:ResolveNames ReturnFullContactData="{return_full_contact_data}" SearchScope="{}">{}</m:ResolveNames>"#,
+        search_scope.as_str(),
+        text_element("m:UnresolvedEntry", query),
+    )
+}
+
+/// Writes an `ExpandDL` request for the members of a distribution list.
+pub(crate) fn expand_dl_request(address: &str) -> String {
+    format!(
+        "<m:ExpandDL><m:Mailbox><t:EmailAddress>{}</t:EmailAddress></m:Mailbox></m:ExpandDL>",
+        escape_text(address)
+    )
+}
+
+/// Writes a `FindPeople` request for the first `max_entries` people matching
+/// a query in a distinguished folder of personas.
+pub(crate) fn find_people_request(folder: &str, query: &str, max_entries: u32) -> String {
+    format!(
+        r#"<m:FindPeople><m:PersonaShape><t:BaseShape>Default</t:BaseShape></m:PersonaShape><m:IndexedPageItemView MaxEntriesReturned="{max_entries}" Offset="0" BasePoint="Beginning"/><m:ParentFolderId>{}</m:ParentFolderId>{}</m:FindPeople>"#,
+        distinguished_folder_id_xml(&escape_text(folder), None),
+        text_element("m:QueryString", query),
+    )
+}
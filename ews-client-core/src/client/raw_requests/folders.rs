@@ -0,0 +1,528 @@
+//! Folder requests: creation, updates, permissions, search folders, hierarchy
+//! synchronization, copies and moves.
+
+use crate::client::{
+    EwsError,
+    operations::{
+        ExtendedPropertyKey, FolderExtendedProperty, FolderKind, FolderPermission, FolderUpdate, MapiPropertyType,
+        PermissionLevel, PermissionSet, PermissionUser, ReadAccess, SearchExpression, SearchFolderDraft,
+        SearchProperty, copy_move_operations::is_distinguished_folder_id,
+    },
+    xml::{escape_text, optional_text_element, text_element},
+};
+
+use super::{base_folder_id_xml, bool_str, distinguished_folder_id_xml};
+
+/// Writes a `GetFolder` request for the IDs of folders, given by EWS ID or
+/// distinguished folder name. Distinguished folder IDs refer to the folders
+/// of `mailbox` when given.
+pub(crate) fn get_folder_ids_request(folder_ids: &[&str], mailbox: Option<&str>) -> String {
+    let folder_ids: String = folder_ids.iter().map(|id| base_folder_id_xml(id, mailbox)).collect();
+    get_folder_xml("", &folder_ids)
+}
+
+/// Writes a `GetFolder` request for the IDs of distinguished folders, in
+/// `mailbox` if given.
+pub(crate) fn get_distinguished_folder_ids_request(names: &[&str], mailbox: Option<&str>) -> String {
+    let folder_ids: String = names
+        .iter()
+        .map(|name| distinguished_folder_id_xml(&escape_text(name), mailbox))
+        .collect();
+    get_folder_xml("", &folder_ids)
+}
+
+/// Writes a `GetFolder` request for the permission set of a folder.
+pub(crate) fn get_folder_permissions_request(folder_id: &str, mailbox: Option<&str>) -> String {
+    get_folder_xml(
+        r#"<t:AdditionalProperties><t:FieldURI FieldURI="folder:PermissionSet"/></t:AdditionalProperties>"#,
+        &base_folder_id_xml(folder_id, mailbox),
+    )
+}
+
+/// Writes a `GetFolder` request with the `IdOnly` shape and the given
+/// additional properties.
+fn get_folder_xml(additional_properties: &str, folder_ids: &str) -> String {
+    format!(
+        "<m:GetFolder><m:FolderShape><t:BaseShape>IdOnly</t:BaseShape>{additional_properties}</m:FolderShape><m:FolderIds>{folder_ids}</m:FolderIds></m:GetFolder>"
+    )
+}
+
+/// Writes a `SyncFolderHierarchy` request for the folders below a folder,
+/// resuming from a sync state if given.
+pub(crate) fn sync_folder_hierarchy_request(
+    folder_id: &str,
+    sync_state: Option<&str>,
+    mailbox: Option<&str>,
+) -> String {
+    format!(
+        "<m:SyncFolderHierarchy><m:FolderShape><t:BaseShape>IdOnly</t:BaseShape></m:FolderShape><m:SyncFolderId>{}</m:SyncFolderId>{}</m:SyncFolderHierarchy>",
+        base_folder_id_xml(folder_id, mailbox),
+        optional_text_element("m:SyncState", sync_state),
+    )
+}
+
+/// Writes a `CreateFolder` request for a single folder, given as the XML of
+/// its folder element.
+pub(crate) fn create_folder_request(parent_id: &str, folder: &str, mailbox: Option<&str>) -> String {
+    format!(
+        "<m:CreateFolder><m:ParentFolderId>{}</m:ParentFolderId><m:Folders>{folder}</m:Folders></m:CreateFolder>",
+        base_folder_id_xml(parent_id, mailbox)
+    )
+}
+
+/// Writes the folder element of a new folder of the given kind.
+pub(crate) fn folder_xml(kind: FolderKind, name: &str, folder_class: &str) -> String {
+    let element = kind.element_name();
+    format!(
+        "<{element}>{}{}</{element}>",
+        text_element("t:FolderClass", folder_class),
+        text_element("t:DisplayName", name),
+    )
+}
+
+/// Writes an `UpdateFolder` request applying the given updates to a folder.
+pub(crate) fn update_folder_request(
+    folder_id: &str,
+    change_key: Option<&str>,
+    updates: &str,
+    mailbox: Option<&str>,
+) -> String {
+    format!(
+        "<m:UpdateFolder><m:FolderChanges><t:FolderChange>{}<t:Updates>{updates}</t:Updates></t:FolderChange></m:FolderChanges></m:UpdateFolder>",
+        folder_id_xml(folder_id, change_key, mailbox),
+    )
+}
+
+/// Writes the element identifying the folder to update, with its change key
+/// if known. Distinguished folder IDs refer to the folders of `mailbox` when
+/// given.
+fn folder_id_xml(folder_id: &str, change_key: Option<&str>, mailbox: Option<&str>) -> String {
+    let Some(change_key) = change_key else {
+        return base_folder_id_xml(folder_id, mailbox);
+    };
+
+    let attributes = format!(
+        r#"Id="{}" ChangeKey="{}""#,
+        escape_text(folder_id),
+        escape_text(change_key)
+    );
+    if !is_distinguished_folder_id(folder_id) {
+        return format!("<t:FolderId {attributes}/>");
+    }
+
+    match mailbox {
+        Some(mailbox) => format!(
+            "<t:DistinguishedFolderId {attributes}><t:Mailbox>{}</t:Mailbox></t:DistinguishedFolderId>",
+            text_element("t:EmailAddress", mailbox)
+        ),
+        None => format!("<t:DistinguishedFolderId {attributes}/>"),
+    }
+}
+
+/// Writes a `CopyFolder`, `MoveFolder`, `CopyItem` or `MoveItem` request.
+///
+/// `ReturnNewItemIds` only applies to items, and was introduced in Exchange
+/// Server 2010 SP1.
+pub(crate) fn copy_move_request(
+    op_name: &str,
+    destination_id: &str,
+    ids: &[&str],
+    return_new_item_ids: bool,
+    mailbox: Option<&str>,
+) -> String {
+    let (ids_element, id_element) = if op_name.ends_with("Folder") {
+        ("FolderIds", "FolderId")
+    } else {
+        ("ItemIds", "ItemId")
+    };
+    let source_ids: String = ids
+        .iter()
+        .map(|id| format!(r#"<t:{id_element} Id="{}"/>"#, escape_text(id)))
+        .collect();
+    let return_new_item_ids = if id_element == "ItemId" && return_new_item_ids {
+        "<m:ReturnNewItemIds>true</m:ReturnNewItemIds>"
+    } else {
+        ""
+    };
+
+    format!(
+        "<m:{op_name}><m:ToFolderId>{}</m:ToFolderId><m:{ids_element}>{source_ids}</m:{ids_element}>{return_new_item_ids}</m:{op_name}>",
+        base_folder_id_xml(destination_id, mailbox)
+    )
+}
+
+impl ExtendedPropertyKey {
+    /// Writes the `ExtendedFieldURI` element identifying the property.
+    pub(crate) fn field_uri_xml(&self, property_type: MapiPropertyType) -> String {
+        let property_type = property_type.as_str();
+        match self {
+            Self::Tag(tag) => {
+                format!(r#"<t:ExtendedFieldURI PropertyTag="0x{tag:04X}" PropertyType="{property_type}"/>"#)
+            }
+            Self::Named { property_set_id, name } => format!(
+                r#"<t:ExtendedFieldURI PropertySetId="{}" PropertyName="{}" PropertyType="{property_type}"/>"#,
+                escape_text(property_set_id),
+                escape_text(name)
+            ),
+            Self::Id { property_set_id, id } => format!(
+                r#"<t:ExtendedFieldURI PropertySetId="{}" PropertyId="{id}" PropertyType="{property_type}"/>"#,
+                escape_text(property_set_id)
+            ),
+        }
+    }
+}
+
+impl FolderExtendedProperty {
+    /// Writes the `SetFolderField` or `DeleteFolderField` update of the
+    /// change.
+    fn update_xml(&self) -> String {
+        let field_uri = self.key.field_uri_xml(self.property_type);
+        match &self.value {
+            Some(value) => format!(
+                "<t:SetFolderField>{field_uri}<t:Folder><t:ExtendedProperty>{field_uri}{}</t:ExtendedProperty></t:Folder></t:SetFolderField>",
+                text_element("t:Value", value)
+            ),
+            None => format!("<t:DeleteFolderField>{field_uri}</t:DeleteFolderField>"),
+        }
+    }
+}
+
+impl FolderUpdate {
+    /// Writes the `SetFolderField` and `DeleteFolderField` changes of the
+    /// update.
+    pub(crate) fn updates_xml(&self) -> Result<String, EwsError> {
+        let mut updates = String::new();
+
+        let text_fields = [
+            ("folder:FolderClass", "t:FolderClass", &self.folder_class),
+            ("folder:DisplayName", "t:DisplayName", &self.display_name),
+        ];
+        for (field_uri, element, value) in text_fields {
+            if let Some(value) = value {
+                updates.push_str(&format!(
+                    r#"<t:SetFolderField><t:FieldURI FieldURI="{field_uri}"/><t:Folder>{}</t:Folder></t:SetFolderField>"#,
+                    text_element(element, value)
+                ));
+            }
+        }
+
+        for property in &self.extended_properties {
+            updates.push_str(&property.update_xml());
+        }
+
+        if let Some(permission_set) = &self.permission_set {
+            updates.push_str(&format!(
+                r#"<t:SetFolderField><t:FieldURI FieldURI="folder:PermissionSet"/>{}</t:SetFolderField>"#,
+                permission_set.to_folder_xml()?
+            ));
+        }
+
+        Ok(updates)
+    }
+}
+
+impl PermissionUser {
+    /// Writes the `UserId` element of the user.
+    fn to_xml(&self) -> Result<String, EwsError> {
+        let user = match self {
+            Self::Default => text_element("t:DistinguishedUser", "Default"),
+            Self::Anonymous => text_element("t:DistinguishedUser", "Anonymous"),
+            Self::User(mailbox) => match mailbox.email_address.as_deref().map(str::trim) {
+                Some(address) if !address.is_empty() => text_element("t:PrimarySmtpAddress", address),
+                _ => {
+                    return Err(EwsError::Processing {
+                        message: "folder permissions require the primary SMTP address of the user".to_string(),
+                    });
+                }
+            },
+        };
+
+        Ok(format!("<t:UserId>{user}</t:UserId>"))
+    }
+}
+
+impl FolderPermission {
+    /// Writes the `Permission` or `CalendarPermission` element of the
+    /// permission.
+    pub(crate) fn to_xml(&self, calendar: bool) -> Result<String, EwsError> {
+        let (element, level_element) = if calendar {
+            ("t:CalendarPermission", "t:CalendarPermissionLevel")
+        } else {
+            ("t:Permission", "t:PermissionLevel")
+        };
+
+        let free_busy_only = self.level.is_calendar_only()
+            || (self.level == PermissionLevel::Custom
+                && matches!(
+                    self.read_items,
+                    ReadAccess::TimeOnly | ReadAccess::TimeAndSubjectAndLocation
+                ));
+        if free_busy_only && !calendar {
+            return Err(EwsError::Processing {
+                message: "free/busy access can only be given on calendar folders".to_string(),
+            });
+        }
+
+        let rights = if self.level == PermissionLevel::Custom {
+            [
+                text_element("t:CanCreateItems", bool_str(self.can_create_items)),
+                text_element("t:CanCreateSubFolders", bool_str(self.can_create_subfolders)),
+                text_element("t:IsFolderOwner", bool_str(self.is_folder_owner)),
+                text_element("t:IsFolderVisible", bool_str(self.is_folder_visible)),
+                text_element("t:IsFolderContact", bool_str(self.is_folder_contact)),
+                text_element("t:EditItems", self.edit_items.as_str()),
+                text_element("t:DeleteItems", self.delete_items.as_str()),
+                text_element("t:ReadItems", self.read_items.as_str()),
+            ]
+            .concat()
+        } else {
+            String::new()
+        };
+
+        Ok(format!(
+            "<{element}>{}{rights}{}</{element}>",
+            self.user.to_xml()?,
+            text_element(level_element, self.level.as_str()),
+        ))
+    }
+}
+
+impl PermissionSet {
+    /// Writes the folder element of a `SetFolderField` update holding the
+    /// permission set, e.g. `<t:CalendarFolder><t:PermissionSet>...`.
+    pub(crate) fn to_folder_xml(&self) -> Result<String, EwsError> {
+        let (folder, list) = if self.is_calendar {
+            ("t:CalendarFolder", "t:CalendarPermissions")
+        } else {
+            ("t:Folder", "t:Permissions")
+        };
+        let permissions = self
+            .permissions
+            .iter()
+            .map(|permission| permission.to_xml(self.is_calendar))
+            .collect::<Result<String, _>>()?;
+
+        Ok(format!(
+            "<{folder}><t:PermissionSet><{list}>{permissions}</{list}></t:PermissionSet></{folder}>"
+        ))
+    }
+}
+
+impl SearchProperty {
+    /// Writes the element identifying the property.
+    fn to_xml(&self) -> String {
+        match self {
+            Self::Field(field_uri) => format!(r#"<t:FieldURI FieldURI="{}"/>"#, escape_text(field_uri)),
+            Self::Extended { key, property_type } => key.field_uri_xml(*property_type),
+        }
+    }
+}
+
+impl SearchExpression {
+    /// Writes the expression as an EWS search expression element.
+    fn to_xml(&self) -> Result<String, EwsError> {
+        let (element, property, value) = match self {
+            Self::And(expressions) => return Self::multiple_operands_xml("t:And", expressions),
+            Self::Or(expressions) => return Self::multiple_operands_xml("t:Or", expressions),
+            Self::Not(expression) => return Ok(format!("<t:Not>{}</t:Not>", expression.to_xml()?)),
+            Self::Exists(property) => return Ok(format!("<t:Exists>{}</t:Exists>", property.to_xml())),
+            Self::Contains {
+                property,
+                value,
+                mode,
+                ignore_case,
+            } => {
+                let comparison = if *ignore_case { "IgnoreCase" } else { "Exact" };
+                return Ok(format!(
+                    r#"<t:Contains ContainmentMode="{}" ContainmentComparison="{comparison}">{}<t:Constant Value="{}"/></t:Contains>"#,
+                    mode.as_str(),
+                    property.to_xml(),
+                    escape_text(value)
+                ));
+            }
+            Self::IsEqualTo(property, value) => ("t:IsEqualTo", property, value),
+            Self::IsNotEqualTo(property, value) => ("t:IsNotEqualTo", property, value),
+            Self::IsGreaterThan(property, value) => ("t:IsGreaterThan", property, value),
+            Self::IsGreaterThanOrEqualTo(property, value) => ("t:IsGreaterThanOrEqualTo", property, value),
+            Self::IsLessThan(property, value) => ("t:IsLessThan", property, value),
+            Self::IsLessThanOrEqualTo(property, value) => ("t:IsLessThanOrEqualTo", property, value),
+        };
+
+        Ok(format!(
+            r#"<{element}>{}<t:FieldURIOrConstant><t:Constant Value="{}"/></t:FieldURIOrConstant></{element}>"#,
+            property.to_xml(),
+            escape_text(value)
+        ))
+    }
+
+    /// Writes an `And` or `Or` expression, which needs at least one operand.
+    fn multiple_operands_xml(element: &str, expressions: &[SearchExpression]) -> Result<String, EwsError> {
+        if expressions.is_empty() {
+            return Err(EwsError::Processing {
+                message: format!("{element} search expression has no operands"),
+            });
+        }
+
+        let operands = expressions
+            .iter()
+            .map(SearchExpression::to_xml)
+            .collect::<Result<String, EwsError>>()?;
+        Ok(format!("<{element}>{operands}</{element}>"))
+    }
+}
+
+impl SearchFolderDraft {
+    /// Writes the `SearchFolder` element of the draft. Distinguished base
+    /// folder IDs refer to the folders of `mailbox` when given.
+    pub(crate) fn to_xml(&self, mailbox: Option<&str>) -> Result<String, EwsError> {
+        if self.base_folder_ids.is_empty() {
+            return Err(EwsError::Processing {
+                message: "search folder has no base folders".to_string(),
+            });
+        }
+
+        let base_folder_ids: String = self
+            .base_folder_ids
+            .iter()
+            .map(|id| base_folder_id_xml(id, mailbox))
+            .collect();
+
+        Ok(format!(
+            r#"<t:SearchFolder>{}{}<t:SearchParameters Traversal="{}"><t:Restriction>{}</t:Restriction><t:BaseFolderIds>{base_folder_ids}</t:BaseFolderIds></t:SearchParameters></t:SearchFolder>"#,
+            optional_text_element("t:FolderClass", self.folder_class.as_deref()),
+            text_element("t:DisplayName", &self.display_name),
+            self.traversal.as_str(),
+            self.restriction.to_xml()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::client::operations::{ContainmentMode, SearchFolderTraversal};
+
+    fn field(field_uri: &str) -> SearchProperty {
+        SearchProperty::Field(field_uri.to_string())
+    }
+
+    #[test]
+    fn test_folder_update_xml() {
+        let update = FolderUpdate {
+            display_name: Some("Archive & Co".to_string()),
+            extended_properties: vec![
+                FolderExtendedProperty {
+                    key: ExtendedPropertyKey::Tag(0x301D),
+                    property_type: MapiPropertyType::Integer,
+                    value: Some("137".to_string()),
+                },
+                FolderExtendedProperty {
+                    key: ExtendedPropertyKey::Named {
+                        property_set_id: "00020329-0000-0000-C000-000000000046".to_string(),
+                        name: "Keywords".to_string(),
+                    },
+                    property_type: MapiPropertyType::String,
+                    value: None,
+                },
+            ],
+            ..FolderUpdate::default()
+        };
+        let xml = update.updates_xml().unwrap();
+
+        assert!(xml.contains(
+            r#"<t:FieldURI FieldURI="folder:DisplayName"/><t:Folder><t:DisplayName>Archive &amp; Co</t:DisplayName>"#
+        ));
+        assert!(
+            xml.contains(r#"<t:ExtendedFieldURI PropertyTag="0x301D" PropertyType="Integer"/><t:Value>137</t:Value>"#)
+        );
+        assert!(xml.contains(r#"<t:DeleteFolderField><t:ExtendedFieldURI PropertySetId="00020329-0000-0000-C000-000000000046" PropertyName="Keywords" PropertyType="String"/></t:DeleteFolderField>"#));
+        assert!(!xml.contains("FolderClass"));
+
+        assert!(FolderUpdate::default().updates_xml().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_folder_id_xml() {
+        assert_eq!(folder_id_xml("AAMkAD", None, None), r#"<t:FolderId Id="AAMkAD"/>"#);
+        assert_eq!(
+            folder_id_xml("AAMkAD", Some("AQAAAA=="), None),
+            r#"<t:FolderId Id="AAMkAD" ChangeKey="AQAAAA=="/>"#
+        );
+        assert_eq!(
+            folder_id_xml("inbox", Some("AQAAAA=="), None),
+            r#"<t:DistinguishedFolderId Id="inbox" ChangeKey="AQAAAA=="/>"#
+        );
+        assert_eq!(
+            folder_id_xml("inbox", Some("AQAAAA=="), Some("shared@example.com")),
+            r#"<t:DistinguishedFolderId Id="inbox" ChangeKey="AQAAAA=="><t:Mailbox><t:EmailAddress>shared@example.com</t:EmailAddress></t:Mailbox></t:DistinguishedFolderId>"#
+        );
+    }
+
+    #[test]
+    fn test_copy_move_request() {
+        assert_eq!(
+            copy_move_request("MoveItem", "inbox", &["AAMkAD"], true, Some("shared@example.com")),
+            r#"<m:MoveItem><m:ToFolderId><t:DistinguishedFolderId Id="inbox"><t:Mailbox><t:EmailAddress>shared@example.com</t:EmailAddress></t:Mailbox></t:DistinguishedFolderId></m:ToFolderId><m:ItemIds><t:ItemId Id="AAMkAD"/></m:ItemIds><m:ReturnNewItemIds>true</m:ReturnNewItemIds></m:MoveItem>"#
+        );
+        assert_eq!(
+            copy_move_request("CopyFolder", "AAMkAE", &["AAMkAD"], true, None),
+            r#"<m:CopyFolder><m:ToFolderId><t:FolderId Id="AAMkAE"/></m:ToFolderId><m:FolderIds><t:FolderId Id="AAMkAD"/></m:FolderIds></m:CopyFolder>"#
+        );
+    }
+
+    #[test]
+    fn test_search_expression_xml() {
+        let expression = SearchExpression::And(vec![
+            SearchExpression::IsEqualTo(field("message:IsRead"), "false".to_string()),
+            SearchExpression::Not(Box::new(SearchExpression::Exists(SearchProperty::Extended {
+                key: ExtendedPropertyKey::Tag(0x1090),
+                property_type: MapiPropertyType::Integer,
+            }))),
+            SearchExpression::Contains {
+                property: field("item:Subject"),
+                value: "R&D".to_string(),
+                mode: ContainmentMode::PrefixOnWords,
+                ignore_case: true,
+            },
+        ]);
+
+        assert_eq!(
+            expression.to_xml().unwrap(),
+            concat!(
+                r#"<t:And><t:IsEqualTo><t:FieldURI FieldURI="message:IsRead"/><t:FieldURIOrConstant><t:Constant Value="false"/></t:FieldURIOrConstant></t:IsEqualTo>"#,
+                r#"<t:Not><t:Exists><t:ExtendedFieldURI PropertyTag="0x1090" PropertyType="Integer"/></t:Exists></t:Not>"#,
+                r#"<t:Contains ContainmentMode="PrefixOnWords" ContainmentComparison="IgnoreCase"><t:FieldURI FieldURI="item:Subject"/><t:Constant Value="R&amp;D"/></t:Contains></t:And>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_search_expression_without_operands() {
+        let expression = SearchExpression::Not(Box::new(SearchExpression::Or(Vec::new())));
+        let result = expression.to_xml();
+        assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+    }
+
+    #[test]
+    fn test_search_folder_draft_xml() {
+        let mut draft = SearchFolderDraft {
+            display_name: "Important".to_string(),
+            folder_class: None,
+            restriction: SearchExpression::IsEqualTo(field("item:Importance"), "High".to_string()),
+            base_folder_ids: vec!["inbox".to_string(), "AAMkAD".to_string()],
+            traversal: SearchFolderTraversal::Shallow,
+        };
+        let xml = draft.to_xml(None).unwrap();
+
+        assert!(xml.starts_with(r#"<t:SearchFolder><t:DisplayName>Important</t:DisplayName><t:SearchParameters Traversal="Shallow"><t:Restriction><t:IsEqualTo>"#));
+        assert!(xml.contains(
+            r#"<t:BaseFolderIds><t:DistinguishedFolderId Id="inbox"/><t:FolderId Id="AAMkAD"/></t:BaseFolderIds>"#
+        ));
+        assert!(!xml.contains("FolderClass"));
+
+        draft.base_folder_ids.clear();
+        let result = draft.to_xml(None);
+        assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+    }
+}
@@ -0,0 +1,288 @@
+//! Item requests: item shapes, `GetItem` and recurrences.
+
+use time::Month;
+
+use crate::client::{
+    EwsError, ServerCapabilities,
+    operations::{ItemProperty, ItemShape, Recurrence, RecurrencePattern, RecurrenceRange},
+    xml::{escape_text, format_date, text_element},
+};
+
+use super::item_id_xml;
+
+/// Writes a `GetItem` request for items by EWS ID, with the given
+/// `ItemShape` element.
+pub(crate) fn get_item_request(item_shape: &str, ids: &[String]) -> String {
+    let item_ids: String = ids.iter().map(|id| item_id_xml(id, None)).collect();
+    format!("<m:GetItem>{item_shape}<m:ItemIds>{item_ids}</m:ItemIds></m:GetItem>")
+}
+
+impl ItemProperty {
+    /// Writes the element identifying the property.
+    fn path_xml(&self) -> String {
+        match self {
+            Self::Field(field_uri) => format!(r#"<t:FieldURI FieldURI="{}"/>"#, escape_text(field_uri)),
+            Self::Indexed { field_uri, index } => format!(
+                r#"<t:IndexedFieldURI FieldURI="{}" FieldIndex="{}"/>"#,
+                escape_text(field_uri),
+                escape_text(index)
+            ),
+            Self::Extended { key, property_type } => key.field_uri_xml(*property_type),
+        }
+    }
+}
+
+impl ItemShape {
+    /// Writes the `ItemShape` element, leaving out `FilterHtmlContent` when
+    /// the server does not know it.
+    pub(crate) fn to_xml(&self, capabilities: ServerCapabilities) -> String {
+        let mut xml = format!("<m:ItemShape><t:BaseShape>{}</t:BaseShape>", self.base_shape.as_str());
+
+        if self.include_mime_content {
+            xml.push_str("<t:IncludeMimeContent>true</t:IncludeMimeContent>");
+        }
+        if let Some(body_type) = self.body_type {
+            xml.push_str(&format!("<t:BodyType>{}</t:BodyType>", body_type.as_str()));
+        }
+        if self.filter_html_content && capabilities.filter_html_content {
+            xml.push_str("<t:FilterHtmlContent>true</t:FilterHtmlContent>");
+        }
+        if !self.properties.is_empty() {
+            let paths: String = self.properties.iter().map(ItemProperty::path_xml).collect();
+            xml.push_str(&format!("<t:AdditionalProperties>{paths}</t:AdditionalProperties>"));
+        }
+
+        xml.push_str("</m:ItemShape>");
+        xml
+    }
+}
+
+impl Recurrence {
+    /// Writes the recurrence as a `Recurrence` element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not valid, e.g. if its interval is
+    /// zero or its month is out of range.
+    pub(crate) fn to_xml(&self) -> Result<String, EwsError> {
+        Ok(format!(
+            "<t:Recurrence>{}{}</t:Recurrence>",
+            pattern_xml(&self.pattern)?,
+            range_xml(&self.range)?
+        ))
+    }
+}
+
+/// Writes the element of a recurrence pattern, with its children in the order
+/// of the EWS schema.
+fn pattern_xml(pattern: &RecurrencePattern) -> Result<String, EwsError> {
+    let interval_xml = |interval: u32| {
+        if interval == 0 {
+            Err(EwsError::Processing {
+                message: "the interval of a recurrence must be at least 1".to_string(),
+            })
+        } else {
+            Ok(text_element("t:Interval", &interval.to_string()))
+        }
+    };
+
+    let (name, children) = match pattern {
+        RecurrencePattern::Daily { interval } => ("DailyRecurrence", interval_xml(*interval)?),
+        RecurrencePattern::Weekly { interval, days_of_week } => {
+            if days_of_week.is_empty() {
+                return Err(EwsError::Processing {
+                    message: "a weekly recurrence must occur on at least one day".to_string(),
+                });
+            }
+            let days: Vec<&str> = days_of_week.iter().map(|day| day.as_str()).collect();
+            (
+                "WeeklyRecurrence",
+                interval_xml(*interval)? + &text_element("t:DaysOfWeek", &days.join(" ")),
+            )
+        }
+        RecurrencePattern::AbsoluteMonthly { interval, day_of_month } => (
+            "AbsoluteMonthlyRecurrence",
+            interval_xml(*interval)? + &day_of_month_xml(*day_of_month)?,
+        ),
+        RecurrencePattern::RelativeMonthly {
+            interval,
+            day_of_week,
+            day_of_week_index,
+        } => (
+            "RelativeMonthlyRecurrence",
+            interval_xml(*interval)?
+                + &text_element("t:DaysOfWeek", day_of_week.as_str())
+                + &text_element("t:DayOfWeekIndex", day_of_week_index.as_str()),
+        ),
+        RecurrencePattern::AbsoluteYearly { month, day_of_month } => (
+            "AbsoluteYearlyRecurrence",
+            day_of_month_xml(*day_of_month)? + &month_xml(*month)?,
+        ),
+        RecurrencePattern::RelativeYearly {
+            month,
+            day_of_week,
+            day_of_week_index,
+        } => (
+            "RelativeYearlyRecurrence",
+            text_element("t:DaysOfWeek", day_of_week.as_str())
+                + &text_element("t:DayOfWeekIndex", day_of_week_index.as_str())
+                + &month_xml(*month)?,
+        ),
+        RecurrencePattern::Regenerating { frequency, interval } => (frequency.element_name(), interval_xml(*interval)?),
+    };
+
+    Ok(format!("<t:{name}>{children}</t:{name}>"))
+}
+
+/// Writes the element of a recurrence range.
+fn range_xml(range: &RecurrenceRange) -> Result<String, EwsError> {
+    Ok(match range {
+        RecurrenceRange::NoEnd { start_date } => format!(
+            "<t:NoEndRecurrence>{}</t:NoEndRecurrence>",
+            text_element("t:StartDate", &format_date(*start_date)?)
+        ),
+        RecurrenceRange::EndDate { start_date, end_date } => {
+            if end_date < start_date {
+                return Err(EwsError::Processing {
+                    message: "the end date of a recurrence must not be before its start date".to_string(),
+                });
+            }
+            format!(
+                "<t:EndDateRecurrence>{}{}</t:EndDateRecurrence>",
+                text_element("t:StartDate", &format_date(*start_date)?),
+                text_element("t:EndDate", &format_date(*end_date)?)
+            )
+        }
+        RecurrenceRange::Numbered {
+            start_date,
+            occurrences,
+        } => format!(
+            "<t:NumberedRecurrence>{}{}</t:NumberedRecurrence>",
+            text_element("t:StartDate", &format_date(*start_date)?),
+            text_element("t:NumberOfOccurrences", &occurrences.to_string())
+        ),
+    })
+}
+
+/// Writes the `DayOfMonth` element of a pattern.
+fn day_of_month_xml(day_of_month: u8) -> Result<String, EwsError> {
+    if (1..=31).contains(&day_of_month) {
+        Ok(text_element("t:DayOfMonth", &day_of_month.to_string()))
+    } else {
+        Err(EwsError::Processing {
+            message: format!("{day_of_month} is not a valid day of the month"),
+        })
+    }
+}
+
+/// Writes the `Month` element of a pattern.
+fn month_xml(month: u8) -> Result<String, EwsError> {
+    Month::try_from(month)
+        .map(|month| text_element("t:Month", &month.to_string()))
+        .map_err(|_| EwsError::Processing {
+            message: format!("{month} is not a valid month"),
+        })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use ews::server_version::ExchangeServerVersion;
+
+    use super::*;
+    use crate::client::operations::{BodyType, ExtendedPropertyKey, ItemBaseShape, MapiPropertyType};
+
+    #[test]
+    fn test_get_item_request() {
+        assert_eq!(
+            get_item_request(
+                "<m:ItemShape><t:BaseShape>IdOnly</t:BaseShape></m:ItemShape>",
+                &["AAMkAD".to_string(), "A&B".to_string()]
+            ),
+            r#"<m:GetItem><m:ItemShape><t:BaseShape>IdOnly</t:BaseShape></m:ItemShape><m:ItemIds><t:ItemId Id="AAMkAD"/><t:ItemId Id="A&amp;B"/></m:ItemIds></m:GetItem>"#
+        );
+    }
+
+    #[test]
+    fn test_item_shape_xml() {
+        let shape = ItemShape::new(ItemBaseShape::Default)
+            .with_field("item:Categories")
+            .with_indexed_field("message:InternetMessageHeader", "List-Id")
+            .with_extended_property(ExtendedPropertyKey::Tag(0x1081), MapiPropertyType::Integer)
+            .with_body_type(BodyType::Html)
+            .with_filter_html_content(true)
+            .with_mime_content(true);
+
+        assert_eq!(
+            shape.to_xml(ServerCapabilities::from_version(
+                ExchangeServerVersion::Exchange2013_SP1
+            )),
+            concat!(
+                "<m:ItemShape><t:BaseShape>Default</t:BaseShape><t:IncludeMimeContent>true</t:IncludeMimeContent>",
+                "<t:BodyType>HTML</t:BodyType><t:FilterHtmlContent>true</t:FilterHtmlContent><t:AdditionalProperties>",
+                r#"<t:FieldURI FieldURI="item:Categories"/>"#,
+                r#"<t:IndexedFieldURI FieldURI="message:InternetMessageHeader" FieldIndex="List-Id"/>"#,
+                r#"<t:ExtendedFieldURI PropertyTag="0x1081" PropertyType="Integer"/>"#,
+                "</t:AdditionalProperties></m:ItemShape>"
+            )
+        );
+
+        // Exchange 2007 does not know FilterHtmlContent
+        let xml = shape.to_xml(ServerCapabilities::from_version(
+            ExchangeServerVersion::Exchange2007_SP1,
+        ));
+        assert!(!xml.contains("FilterHtmlContent"), "{xml}");
+
+        assert_eq!(
+            ItemShape::default().to_xml(ServerCapabilities::from_version(
+                ExchangeServerVersion::Exchange2013_SP1
+            )),
+            "<m:ItemShape><t:BaseShape>IdOnly</t:BaseShape></m:ItemShape>"
+        );
+    }
+
+    #[test]
+    fn test_recurrence_xml() {
+        let recurrence = Recurrence {
+            pattern: RecurrencePattern::AbsoluteYearly {
+                month: 2,
+                day_of_month: 29,
+            },
+            range: RecurrenceRange::NoEnd {
+                start_date: 1_705_314_600,
+            },
+        };
+
+        assert_eq!(
+            recurrence.to_xml().unwrap(),
+            "<t:Recurrence><t:AbsoluteYearlyRecurrence><t:DayOfMonth>29</t:DayOfMonth><t:Month>February</t:Month></t:AbsoluteYearlyRecurrence><t:NoEndRecurrence><t:StartDate>2024-01-15</t:StartDate></t:NoEndRecurrence></t:Recurrence>"
+        );
+    }
+
+    #[test]
+    fn test_invalid_recurrence() {
+        let invalid_patterns = [
+            RecurrencePattern::Daily { interval: 0 },
+            RecurrencePattern::Weekly {
+                interval: 1,
+                days_of_week: Vec::new(),
+            },
+            RecurrencePattern::AbsoluteMonthly {
+                interval: 1,
+                day_of_month: 32,
+            },
+            RecurrencePattern::AbsoluteYearly {
+                month: 13,
+                day_of_month: 1,
+            },
+        ];
+
+        for pattern in invalid_patterns {
+            let recurrence = Recurrence {
+                pattern,
+                range: RecurrenceRange::NoEnd { start_date: 0 },
+            };
+            assert!(recurrence.to_xml().is_err());
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! Mailbox settings requests: automatic replies, Inbox rules and delegates.
+
+use crate::client::{
+    EwsError, OwnedMailbox,
+    operations::{
+        DelegateFolderPermissionLevel, DelegatePermissions, DelegateUser, DeliverMeetingRequests, ExternalAudience,
+        Importance, InboxRule, OofState, RuleActions, RuleOperation, RulePredicates,
+    },
+    xml::{format_date_time, optional_text_element, text_element},
+};
+
+use super::{base_folder_id_xml, bool_str};
+
+/// Writes a `GetUserOofSettingsRequest` for the automatic replies of a
+/// mailbox.
+pub(crate) fn get_user_oof_settings_request(mailbox: &str) -> Result<String, EwsError> {
+    Ok(format!(
+        "<m:GetUserOofSettingsRequest>{}</m:GetUserOofSettingsRequest>",
+        oof_mailbox_xml(mailbox)?
+    ))
+}
+
+/// Writes a `SetUserOofSettingsRequest` changing the automatic replies of a
+/// mailbox.
+pub(crate) fn set_user_oof_settings_request(
+    mailbox: &str,
+    state: OofState,
+    internal_reply: &str,
+    external_reply: &str,
+    external_audience: ExternalAudience,
+) -> Result<String, EwsError> {
+    let (state, duration) = match state {
+        OofState::Disabled => ("Disabled", String::new()),
+        OofState::Enabled => ("Enabled", String::new()),
+        OofState::Scheduled { start, end } => (
+            "Scheduled",
+            format!(
+                "<t:Duration>{}{}</t:Duration>",
+                text_element("t:StartTime", &format_date_time(start)?),
+                text_element("t:EndTime", &format_date_time(end)?),
+            ),
+        ),
+    };
+
+    Ok(format!(
+        "<m:SetUserOofSettingsRequest>{}<t:UserOofSettings>{}{}{duration}<t:InternalReply>{}</t:InternalReply><t:ExternalReply>{}</t:ExternalReply></t:UserOofSettings></m:SetUserOofSettingsRequest>",
+        oof_mailbox_xml(mailbox)?,
+        text_element("t:OofState", state),
+        text_element("t:ExternalAudience", external_audience.as_str()),
+        text_element("t:Message", internal_reply),
+        text_element("t:Message", external_reply),
+    ))
+}
+
+/// Writes the `Mailbox` element identifying the mailbox whose automatic
+/// replies are read or changed.
+fn oof_mailbox_xml(mailbox: &str) -> Result<String, EwsError> {
+    if mailbox.trim().is_empty() {
+        return Err(EwsError::Processing {
+            message: "out-of-office settings require a mailbox address".to_string(),
+        });
+    }
+
+    Ok(format!(
+        "<t:Mailbox>{}</t:Mailbox>",
+        text_element("t:Address", mailbox.trim())
+    ))
+}
+
+/// Writes a `GetInboxRules` request for the rules of a mailbox, or of the
+/// user's own mailbox.
+pub(crate) fn get_inbox_rules_request(mailbox: Option<&str>) -> String {
+    format!(
+        "<m:GetInboxRules>{}</m:GetInboxRules>",
+        optional_text_element("m:MailboxSmtpAddress", mailbox.map(str::trim))
+    )
+}
+
+/// Writes an `UpdateInboxRules` request applying the given operations.
+/// Distinguished folder IDs in rule actions refer to the folders of
+/// `folder_mailbox` when given.
+pub(crate) fn update_inbox_rules_request(
+    mailbox: Option<&str>,
+    operations: &[RuleOperation],
+    remove_outlook_rule_blob: bool,
+    folder_mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    let operations = operations
+        .iter()
+        .map(|operation| operation.to_xml(folder_mailbox))
+        .collect::<Result<String, _>>()?;
+
+    Ok(format!(
+        "<m:UpdateInboxRules>{}{}<m:Operations>{operations}</m:Operations></m:UpdateInboxRules>",
+        optional_text_element("m:MailboxSmtpAddress", mailbox.map(str::trim)),
+        text_element("m:RemoveOutlookRuleBlob", bool_str(remove_outlook_rule_blob)),
+    ))
+}
+
+impl RulePredicates {
+    /// Writes the predicates as an element with the given name, or nothing if
+    /// no predicate is set.
+    pub(crate) fn to_xml(&self, name: &str) -> String {
+        let flag = |element: &str, value: bool| {
+            if value {
+                text_element(element, "true")
+            } else {
+                String::new()
+            }
+        };
+
+        let predicates = [
+            strings_xml("t:Categories", &self.categories),
+            strings_xml("t:ContainsBodyStrings", &self.contains_body_strings),
+            strings_xml("t:ContainsHeaderStrings", &self.contains_header_strings),
+            strings_xml("t:ContainsRecipientStrings", &self.contains_recipient_strings),
+            strings_xml("t:ContainsSenderStrings", &self.contains_sender_strings),
+            strings_xml("t:ContainsSubjectOrBodyStrings", &self.contains_subject_or_body_strings),
+            strings_xml("t:ContainsSubjectStrings", &self.contains_subject_strings),
+            addresses_xml("t:FromAddresses", &self.from_addresses),
+            flag("t:HasAttachments", self.has_attachments),
+            optional_text_element("t:Importance", self.importance.map(Importance::as_str)),
+            flag("t:IsAutomaticReply", self.is_automatic_reply),
+            flag("t:IsMeetingRequest", self.is_meeting_request),
+            flag("t:NotSentToMe", self.not_sent_to_me),
+            flag("t:SentCcMe", self.sent_cc_me),
+            flag("t:SentOnlyToMe", self.sent_only_to_me),
+            addresses_xml("t:SentToAddresses", &self.sent_to_addresses),
+            flag("t:SentToMe", self.sent_to_me),
+            flag("t:SentToOrCcMe", self.sent_to_or_cc_me),
+        ]
+        .concat();
+
+        if predicates.is_empty() {
+            predicates
+        } else {
+            format!("<{name}>{predicates}</{name}>")
+        }
+    }
+}
+
+impl RuleActions {
+    /// Writes the `Actions` element of a rule. Distinguished folder IDs refer
+    /// to the folders of `mailbox` when given.
+    fn to_xml(&self, mailbox: Option<&str>) -> String {
+        let flag = |element: &str, value: bool| {
+            if value {
+                text_element(element, "true")
+            } else {
+                String::new()
+            }
+        };
+        let folder = |element: &str, id: Option<&String>| {
+            id.map(|id| format!("<{element}>{}</{element}>", base_folder_id_xml(id, mailbox)))
+                .unwrap_or_default()
+        };
+
+        let actions = [
+            strings_xml("t:AssignCategories", &self.assign_categories),
+            folder("t:CopyToFolder", self.copy_to_folder.as_ref()),
+            flag("t:Delete", self.delete),
+            addresses_xml(
+                "t:ForwardAsAttachmentToRecipients",
+                &self.forward_as_attachment_to_recipients,
+            ),
+            addresses_xml("t:ForwardToRecipients", &self.forward_to_recipients),
+            optional_text_element("t:MarkImportance", self.mark_importance.map(Importance::as_str)),
+            flag("t:MarkAsRead", self.mark_as_read),
+            folder("t:MoveToFolder", self.move_to_folder.as_ref()),
+            flag("t:PermanentDelete", self.permanent_delete),
+            addresses_xml("t:RedirectToRecipients", &self.redirect_to_recipients),
+            flag("t:StopProcessingRules", self.stop_processing_rules),
+        ]
+        .concat();
+
+        format!("<t:Actions>{actions}</t:Actions>")
+    }
+}
+
+impl InboxRule {
+    /// Writes the `Rule` element of a create or set operation. The rule ID is
+    /// only written for set operations.
+    pub(crate) fn to_xml(&self, include_rule_id: bool, mailbox: Option<&str>) -> String {
+        let rule_id = if include_rule_id {
+            optional_text_element("t:RuleId", self.rule_id.as_deref())
+        } else {
+            String::new()
+        };
+
+        format!(
+            "<t:Rule>{rule_id}{}{}{}{}{}{}</t:Rule>",
+            text_element("t:DisplayName", &self.display_name),
+            text_element("t:Priority", &self.priority.to_string()),
+            text_element("t:IsEnabled", bool_str(self.is_enabled)),
+            self.conditions.to_xml("t:Conditions"),
+            self.exceptions.to_xml("t:Exceptions"),
+            self.actions.to_xml(mailbox),
+        )
+    }
+}
+
+impl RuleOperation {
+    /// Writes the operation element of an `UpdateInboxRules` request.
+    pub(crate) fn to_xml(&self, mailbox: Option<&str>) -> Result<String, EwsError> {
+        match self {
+            Self::Create(rule) => Ok(format!(
+                "<t:CreateRuleOperation>{}</t:CreateRuleOperation>",
+                rule.to_xml(false, mailbox)
+            )),
+            Self::Set(rule) => {
+                if rule.rule_id.as_deref().is_none_or(|id| id.trim().is_empty()) {
+                    return Err(EwsError::Processing {
+                        message: format!("cannot change rule {:?} without its rule ID", rule.display_name),
+                    });
+                }
+                Ok(format!(
+                    "<t:SetRuleOperation>{}</t:SetRuleOperation>",
+                    rule.to_xml(true, mailbox)
+                ))
+            }
+            Self::Delete(rule_id) => {
+                if rule_id.trim().is_empty() {
+                    return Err(EwsError::Processing {
+                        message: "cannot delete a rule without its rule ID".to_string(),
+                    });
+                }
+                Ok(format!(
+                    "<t:DeleteRuleOperation>{}</t:DeleteRuleOperation>",
+                    text_element("t:RuleId", rule_id.trim())
+                ))
+            }
+        }
+    }
+}
+
+/// Writes an `ArrayOfStringsType` element, or nothing if there are no
+/// strings.
+fn strings_xml(name: &str, strings: &[String]) -> String {
+    if strings.is_empty() {
+        return String::new();
+    }
+
+    let strings: String = strings.iter().map(|string| text_element("t:String", string)).collect();
+    format!("<{name}>{strings}</{name}>")
+}
+
+/// Writes an `ArrayOfEmailAddressesType` element, or nothing if there are no
+/// addresses.
+fn addresses_xml(name: &str, addresses: &[OwnedMailbox]) -> String {
+    if addresses.is_empty() {
+        return String::new();
+    }
+
+    let addresses: String = addresses
+        .iter()
+        .map(|address| {
+            format!(
+                "<t:Address>{}{}</t:Address>",
+                optional_text_element("t:Name", address.name.as_deref()),
+                optional_text_element("t:EmailAddress", address.email_address.as_deref()),
+            )
+        })
+        .collect();
+    format!("<{name}>{addresses}</{name}>")
+}
+
+/// Writes a `GetDelegate` request for the delegates of a mailbox.
+pub(crate) fn get_delegate_request(mailbox: &str, include_permissions: bool) -> Result<String, EwsError> {
+    Ok(format!(
+        r#"<m:GetDelegate IncludePermissions="{}">{}</m:GetDelegate>"#,
+        bool_str(include_permissions),
+        delegate_mailbox_xml(mailbox)?
+    ))
+}
+
+/// Writes an `AddDelegate` request.
+pub(crate) fn add_delegate_request(
+    mailbox: &str,
+    delegates: &[DelegateUser],
+    deliver_meeting_requests: Option<DeliverMeetingRequests>,
+) -> Result<String, EwsError> {
+    Ok(format!(
+        "<m:AddDelegate>{}{}{}</m:AddDelegate>",
+        delegate_mailbox_xml(mailbox)?,
+        delegate_users_xml(delegates)?,
+        optional_text_element(
+            "m:DeliverMeetingRequests",
+            deliver_meeting_requests.map(DeliverMeetingRequests::as_str)
+        ),
+    ))
+}
+
+/// Writes an `UpdateDelegate` request. The `DelegateUsers` element is left
+/// out when only the delivery of meeting requests changes.
+pub(crate) fn update_delegate_request(
+    mailbox: &str,
+    delegates: &[DelegateUser],
+    deliver_meeting_requests: Option<DeliverMeetingRequests>,
+) -> Result<String, EwsError> {
+    let delegate_users = if delegates.is_empty() {
+        String::new()
+    } else {
+        delegate_users_xml(delegates)?
+    };
+
+    Ok(format!(
+        "<m:UpdateDelegate>{}{delegate_users}{}</m:UpdateDelegate>",
+        delegate_mailbox_xml(mailbox)?,
+        optional_text_element(
+            "m:DeliverMeetingRequests",
+            deliver_meeting_requests.map(DeliverMeetingRequests::as_str)
+        ),
+    ))
+}
+
+/// Writes a `RemoveDelegate` request for delegates identified by their
+/// primary SMTP addresses.
+pub(crate) fn remove_delegate_request(mailbox: &str, delegates: &[&str]) -> Result<String, EwsError> {
+    let user_ids = delegates
+        .iter()
+        .map(|delegate| user_id_xml(delegate))
+        .collect::<Result<String, _>>()?;
+
+    Ok(format!(
+        "<m:RemoveDelegate>{}<m:UserIds>{user_ids}</m:UserIds></m:RemoveDelegate>",
+        delegate_mailbox_xml(mailbox)?
+    ))
+}
+
+impl DelegatePermissions {
+    /// Writes the `DelegatePermissions` element, or nothing if no permission
+    /// is set.
+    fn to_xml(self) -> Result<String, EwsError> {
+        let mut levels = String::new();
+        for (name, level) in self.levels() {
+            match level {
+                Some(DelegateFolderPermissionLevel::Custom) => {
+                    return Err(EwsError::Processing {
+                        message: "custom delegate permissions cannot be set, use folder permissions instead"
+                            .to_string(),
+                    });
+                }
+                Some(level) => levels.push_str(&text_element(&format!("t:{name}"), level.as_str())),
+                None => {}
+            }
+        }
+
+        if levels.is_empty() {
+            Ok(levels)
+        } else {
+            Ok(format!("<t:DelegatePermissions>{levels}</t:DelegatePermissions>"))
+        }
+    }
+}
+
+impl DelegateUser {
+    /// Writes the `DelegateUser` element of an add or update request.
+    pub(crate) fn to_xml(&self) -> Result<String, EwsError> {
+        Ok(format!(
+            "<t:DelegateUser>{}{}{}{}</t:DelegateUser>",
+            user_id_xml(&self.primary_smtp_address)?,
+            self.permissions.to_xml()?,
+            text_element(
+                "t:ReceiveCopiesOfMeetingMessages",
+                bool_str(self.receive_copies_of_meeting_messages)
+            ),
+            text_element("t:ViewPrivateItems", bool_str(self.view_private_items)),
+        ))
+    }
+}
+
+/// Writes the `Mailbox` element identifying the owner of the delegated
+/// mailbox.
+fn delegate_mailbox_xml(mailbox: &str) -> Result<String, EwsError> {
+    if mailbox.trim().is_empty() {
+        return Err(EwsError::Processing {
+            message: "delegate operations require a mailbox address".to_string(),
+        });
+    }
+
+    Ok(format!(
+        "<m:Mailbox>{}</m:Mailbox>",
+        text_element("t:EmailAddress", mailbox.trim())
+    ))
+}
+
+/// Writes the `DelegateUsers` element of an add or update request.
+fn delegate_users_xml(delegates: &[DelegateUser]) -> Result<String, EwsError> {
+    let delegates = delegates
+        .iter()
+        .map(DelegateUser::to_xml)
+        .collect::<Result<String, _>>()?;
+    Ok(format!("<m:DelegateUsers>{delegates}</m:DelegateUsers>"))
+}
+
+/// Writes the `UserId` element identifying a delegate.
+fn user_id_xml(primary_smtp_address: &str) -> Result<String, EwsError> {
+    if primary_smtp_address.trim().is_empty() {
+        return Err(EwsError::Processing {
+            message: "delegates are identified by their primary SMTP address, which is missing".to_string(),
+        });
+    }
+
+    Ok(format!(
+        "<t:UserId>{}</t:UserId>",
+        text_element("t:PrimarySmtpAddress", primary_smtp_address.trim())
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_user_oof_settings_request() {
+        let body = set_user_oof_settings_request(
+            " user@example.com ",
+            OofState::Scheduled {
+                start: 1_705_276_800,
+                end: 1_705_881_600,
+            },
+            "Back next week",
+            "",
+            ExternalAudience::Known,
+        )
+        .unwrap();
+
+        assert_eq!(
+            body,
+            concat!(
+                "<m:SetUserOofSettingsRequest><t:Mailbox><t:Address>user@example.com</t:Address></t:Mailbox>",
+                "<t:UserOofSettings><t:OofState>Scheduled</t:OofState><t:ExternalAudience>Known</t:ExternalAudience>",
+                "<t:Duration><t:StartTime>2024-01-15T00:00:00Z</t:StartTime><t:EndTime>2024-01-22T00:00:00Z</t:EndTime></t:Duration>",
+                "<t:InternalReply><t:Message>Back next week</t:Message></t:InternalReply>",
+                "<t:ExternalReply><t:Message></t:Message></t:ExternalReply>",
+                "</t:UserOofSettings></m:SetUserOofSettingsRequest>"
+            )
+        );
+    }
+
+    #[test]
+    fn test_requests_require_a_mailbox() {
+        assert!(get_user_oof_settings_request(" ").is_err());
+        assert!(get_delegate_request("", false).is_err());
+        assert!(remove_delegate_request("owner@example.com", &[" "]).is_err());
+    }
+}
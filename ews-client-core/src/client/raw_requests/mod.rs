@@ -0,0 +1,159 @@
+//! Request bodies for the EWS operations which the `ews` crate does not model.
+//!
+//! Operations use the `ews` crate's typed requests wherever it has them (e.g.
+//! `GetFolder` by a plain folder ID, `DeleteItem`, `SyncFolderItems`). The
+//! others, and requests needing elements the typed ones lack (e.g. the
+//! `Mailbox` of a distinguished folder ID in a shared mailbox), are written by
+//! hand with the builders of this module, grouped by area:
+//!
+//! - [`folders`]: folder creation, updates, permissions, search folders,
+//!   hierarchy synchronization, copies and moves
+//! - [`items`]: item shapes, `GetItem` and recurrences
+//! - [`calendar`]: calendar items, time zones and meeting responses
+//! - [`availability`]: free/busy information and suggested meeting times
+//! - [`contacts`] and [`tasks`]
+//! - [`directory`]: name resolution, distribution lists and people search
+//! - [`mailbox_settings`]: automatic replies, inbox rules and delegates
+//! - [`notifications`]: pull and push subscriptions
+//!
+//! Each builder returns the element of one request, or a part of one, with
+//! every value escaped. Elements use the `m:` and `t:` prefixes, which the
+//! envelope written by [`EwsClient::make_raw_operation_request`] binds to the
+//! messages and types namespaces. Builders check what the schema cannot
+//! express (e.g. an empty mailbox address) and return an error rather than a
+//! request the server would reject. Responses are read with
+//! [`XmlElement`](super::xml::XmlElement) by the operation modules.
+//!
+//! [`EwsClient::make_raw_operation_request`]: super::EwsClient::make_raw_operation_request
+
+pub(crate) mod availability;
+pub(crate) mod calendar;
+pub(crate) mod contacts;
+pub(crate) mod directory;
+pub(crate) mod folders;
+pub(crate) mod items;
+pub(crate) mod mailbox_settings;
+pub(crate) mod notifications;
+pub(crate) mod tasks;
+
+use crate::client::{
+    operations::copy_move_operations::is_distinguished_folder_id,
+    xml::{escape_text, text_element},
+};
+
+/// Writes the XML for a folder ID, as either a `t:DistinguishedFolderId` or a
+/// `t:FolderId` element.
+///
+/// Distinguished folder IDs refer to the folders of `mailbox` when given,
+/// e.g. a shared mailbox, and to those of the authenticated user otherwise.
+pub(crate) fn base_folder_id_xml(id: &str, mailbox: Option<&str>) -> String {
+    let id = escape_text(id);
+    if is_distinguished_folder_id(&id) {
+        distinguished_folder_id_xml(&id, mailbox)
+    } else {
+        format!(r#"<t:FolderId Id="{id}"/>"#)
+    }
+}
+
+/// Writes a `t:DistinguishedFolderId` element for an escaped folder name,
+/// with the mailbox it belongs to if given.
+fn distinguished_folder_id_xml(id: &str, mailbox: Option<&str>) -> String {
+    match mailbox {
+        Some(mailbox) => format!(
+            r#"<t:DistinguishedFolderId Id="{id}"><t:Mailbox>{}</t:Mailbox></t:DistinguishedFolderId>"#,
+            text_element("t:EmailAddress", mailbox)
+        ),
+        None => format!(r#"<t:DistinguishedFolderId Id="{id}"/>"#),
+    }
+}
+
+/// Writes an `ItemId` element, with a change key if one is provided.
+pub(crate) fn item_id_xml(item_id: &str, change_key: Option<&str>) -> String {
+    let change_key = change_key
+        .map(|change_key| format!(r#" ChangeKey="{}""#, escape_text(change_key)))
+        .unwrap_or_default();
+    format!(r#"<t:ItemId Id="{}"{change_key}/>"#, escape_text(item_id))
+}
+
+/// Gets the `ConflictResolution` of an `UpdateItem` request.
+///
+/// With a change key, the update must fail rather than overwrite changes made
+/// since the item was read. Without one, there is nothing to compare the
+/// item to, and conflicting changes are merged by the server.
+fn conflict_resolution(change_key: Option<&str>) -> &'static str {
+    if change_key.is_some() {
+        "NeverOverwrite"
+    } else {
+        "AutoResolve"
+    }
+}
+
+/// Writes an `UpdateItem` request changing a single item, with the given
+/// attributes after its `ConflictResolution` (e.g. `MessageDisposition`).
+fn update_item_xml(item_id: &str, change_key: Option<&str>, attributes: &str, updates: &str) -> String {
+    format!(
+        r#"<m:UpdateItem ConflictResolution="{}"{attributes}><m:ItemChanges><t:ItemChange>{}<t:Updates>{updates}</t:Updates></t:ItemChange></m:ItemChanges></m:UpdateItem>"#,
+        conflict_resolution(change_key),
+        item_id_xml(item_id, change_key),
+    )
+}
+
+/// Writes a `SetItemField` update setting a property of an item, whose
+/// element is named after the kind of item (e.g. `t:Contact`), to the given
+/// value.
+fn set_item_field_xml(field_uri: &str, item_element: &str, value: &str) -> String {
+    format!(
+        r#"<t:SetItemField><t:FieldURI FieldURI="{field_uri}"/><{item_element}>{value}</{item_element}></t:SetItemField>"#
+    )
+}
+
+/// Writes a `DeleteItemField` update clearing a property of an item.
+fn delete_item_field_xml(field_uri: &str) -> String {
+    format!(r#"<t:DeleteItemField><t:FieldURI FieldURI="{field_uri}"/></t:DeleteItemField>"#)
+}
+
+/// Writes the text `Body` element of an item, if there is a body.
+fn optional_body_xml(body: Option<&str>) -> String {
+    body.map(|body| format!(r#"<t:Body BodyType="Text">{}</t:Body>"#, escape_text(body)))
+        .unwrap_or_default()
+}
+
+/// Writes the `SavedItemFolderId` of a `CreateItem` request, if a folder is
+/// given.
+fn saved_item_folder_id_xml(folder_id: Option<&str>, mailbox: Option<&str>) -> String {
+    folder_id
+        .map(|id| {
+            format!(
+                "<m:SavedItemFolderId>{}</m:SavedItemFolderId>",
+                base_folder_id_xml(id, mailbox)
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Formats a boolean as an `xs:boolean`.
+fn bool_str(value: bool) -> &'static str {
+    if value { "true" } else { "false" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_folder_id_xml() {
+        assert_eq!(base_folder_id_xml("AAMkAD", None), r#"<t:FolderId Id="AAMkAD"/>"#);
+        assert_eq!(
+            base_folder_id_xml("AAMkAD", Some("shared@example.com")),
+            r#"<t:FolderId Id="AAMkAD"/>"#
+        );
+        assert_eq!(
+            base_folder_id_xml("inbox", None),
+            r#"<t:DistinguishedFolderId Id="inbox"/>"#
+        );
+        assert_eq!(
+            base_folder_id_xml("inbox", Some("shared@example.com")),
+            r#"<t:DistinguishedFolderId Id="inbox"><t:Mailbox><t:EmailAddress>shared@example.com</t:EmailAddress></t:Mailbox></t:DistinguishedFolderId>"#
+        );
+    }
+}
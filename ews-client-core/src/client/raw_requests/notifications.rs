@@ -0,0 +1,147 @@
+//! Notification requests: pull and push subscriptions, and the events of pull
+//! subscriptions.
+
+use crate::client::{EwsError, operations::NotificationEventType, xml::text_element};
+
+use super::base_folder_id_xml;
+
+/// Writes a `Subscribe` request for a pull subscription, resuming from the
+/// watermark of a previous subscription if given.
+pub(crate) fn pull_subscribe_request(
+    folder_ids: &[&str],
+    event_types: &[NotificationEventType],
+    timeout_minutes: u32,
+    watermark: Option<&str>,
+    mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    subscribe_xml(
+        "m:PullSubscriptionRequest",
+        folder_ids,
+        event_types,
+        watermark,
+        &text_element("t:Timeout", &timeout_minutes.to_string()),
+        mailbox,
+    )
+}
+
+/// Writes a `Subscribe` request for a push subscription, whose notifications
+/// are sent to `callback_url`.
+#[cfg(feature = "push-notifications")]
+pub(crate) fn push_subscribe_request(
+    folder_ids: &[&str],
+    event_types: &[NotificationEventType],
+    callback_url: &str,
+    status_frequency_minutes: u32,
+    watermark: Option<&str>,
+    mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    subscribe_xml(
+        "m:PushSubscriptionRequest",
+        folder_ids,
+        event_types,
+        watermark,
+        &(text_element("t:StatusFrequency", &status_frequency_minutes.to_string())
+            + &text_element("t:URL", callback_url)),
+        mailbox,
+    )
+}
+
+/// Writes a `GetEvents` request for the events of a pull subscription since
+/// a watermark.
+pub(crate) fn get_events_request(subscription_id: &str, watermark: &str) -> String {
+    format!(
+        "<m:GetEvents>{}{}</m:GetEvents>",
+        text_element("m:SubscriptionId", subscription_id),
+        text_element("m:Watermark", watermark),
+    )
+}
+
+/// Writes an `Unsubscribe` request.
+pub(crate) fn unsubscribe_request(subscription_id: &str) -> String {
+    format!(
+        "<m:Unsubscribe>{}</m:Unsubscribe>",
+        text_element("m:SubscriptionId", subscription_id)
+    )
+}
+
+/// Writes a `Subscribe` request with the given kind of subscription request,
+/// whose settings follow its watermark.
+fn subscribe_xml(
+    request_element: &str,
+    folder_ids: &[&str],
+    event_types: &[NotificationEventType],
+    watermark: Option<&str>,
+    settings: &str,
+    mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    Ok(format!(
+        "<m:Subscribe><{request_element}{}{}{}{settings}</{request_element}></m:Subscribe>",
+        subscription_scope_xml(folder_ids, mailbox),
+        event_types_xml(event_types)?,
+        watermark
+            .map(|watermark| text_element("t:Watermark", watermark))
+            .unwrap_or_default(),
+    ))
+}
+
+/// Writes the part of a subscription request selecting the watched folders.
+///
+/// The result starts with an attribute for the request element, and is
+/// followed by the `FolderIds` element when specific folders are watched.
+/// Distinguished folder IDs refer to the folders of `mailbox` when given.
+fn subscription_scope_xml(folder_ids: &[&str], mailbox: Option<&str>) -> String {
+    if folder_ids.is_empty() {
+        r#" SubscribeToAllFolders="true">"#.to_string()
+    } else {
+        let ids: String = folder_ids.iter().map(|id| base_folder_id_xml(id, mailbox)).collect();
+        format!("><t:FolderIds>{ids}</t:FolderIds>")
+    }
+}
+
+/// Writes the `EventTypes` element of a subscription request.
+fn event_types_xml(event_types: &[NotificationEventType]) -> Result<String, EwsError> {
+    let event_types: String = event_types
+        .iter()
+        .filter(|event_type| **event_type != NotificationEventType::Status)
+        .map(|event_type| text_element("t:EventType", event_type.element_name()))
+        .collect();
+
+    if event_types.is_empty() {
+        return Err(EwsError::Processing {
+            message: "a subscription needs at least one event type other than status events".to_string(),
+        });
+    }
+
+    Ok(format!("<t:EventTypes>{event_types}</t:EventTypes>"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pull_subscribe_request() {
+        assert_eq!(
+            pull_subscribe_request(
+                &["inbox"],
+                &[NotificationEventType::NewMail, NotificationEventType::Status],
+                30,
+                Some("AQAAAA=="),
+                Some("shared@example.com"),
+            )
+            .unwrap(),
+            concat!(
+                "<m:Subscribe><m:PullSubscriptionRequest><t:FolderIds>",
+                r#"<t:DistinguishedFolderId Id="inbox"><t:Mailbox><t:EmailAddress>shared@example.com</t:EmailAddress></t:Mailbox></t:DistinguishedFolderId>"#,
+                "</t:FolderIds><t:EventTypes><t:EventType>NewMailEvent</t:EventType></t:EventTypes>",
+                "<t:Watermark>AQAAAA==</t:Watermark><t:Timeout>30</t:Timeout></m:PullSubscriptionRequest></m:Subscribe>"
+            )
+        );
+
+        let all_folders = pull_subscribe_request(&[], &[NotificationEventType::Created], 30, None, None).unwrap();
+        assert!(all_folders.starts_with(r#"<m:Subscribe><m:PullSubscriptionRequest SubscribeToAllFolders="true">"#));
+
+        assert!(pull_subscribe_request(&[], &[NotificationEventType::Status], 30, None, None).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Task requests: listing, creating and updating tasks, and flagging messages
+//! for follow-up.
+
+use crate::client::{
+    EwsError,
+    operations::{TaskDraft, TaskStatus, TaskUpdate},
+    xml::{format_date_time, optional_text_element, text_element},
+};
+
+use super::{
+    base_folder_id_xml, delete_item_field_xml, optional_body_xml, saved_item_folder_id_xml, set_item_field_xml,
+    update_item_xml,
+};
+
+/// Writes a `FindItem` request for a page of the tasks of a folder.
+pub(crate) fn find_tasks_request(folder_id: &str, offset: u32, max_entries: u32, mailbox: Option<&str>) -> String {
+    format!(
+        r#"<m:FindItem Traversal="Shallow"><m:ItemShape><t:BaseShape>AllProperties</t:BaseShape></m:ItemShape><m:IndexedPageItemView MaxEntriesReturned="{max_entries}" Offset="{offset}" BasePoint="Beginning"/><m:ParentFolderIds>{}</m:ParentFolderIds></m:FindItem>"#,
+        base_folder_id_xml(folder_id, mailbox),
+    )
+}
+
+/// Writes a `CreateItem` request for a single task.
+pub(crate) fn create_task_request(
+    folder_id: &str,
+    draft: &TaskDraft,
+    mailbox: Option<&str>,
+) -> Result<String, EwsError> {
+    Ok(format!(
+        "<m:CreateItem>{}<m:Items>{}</m:Items></m:CreateItem>",
+        saved_item_folder_id_xml(Some(folder_id), mailbox),
+        draft.to_xml()?,
+    ))
+}
+
+/// Writes an `UpdateItem` request applying the given updates to a task.
+pub(crate) fn update_task_request(item_id: &str, change_key: Option<&str>, updates: &str) -> String {
+    update_item_xml(item_id, change_key, "", updates)
+}
+
+/// Writes an `UpdateItem` request flagging a message for follow-up, with the
+/// start and due dates of the follow-up if given.
+pub(crate) fn flag_message_request(
+    item_id: &str,
+    change_key: Option<&str>,
+    dates: Option<(i64, i64)>,
+) -> Result<String, EwsError> {
+    let dates = match dates {
+        Some((start_date, due_date)) => format!(
+            "{}{}",
+            text_element("t:StartDate", &format_date_time(start_date)?),
+            text_element("t:DueDate", &format_date_time(due_date)?)
+        ),
+        None => String::new(),
+    };
+    let flag = set_item_field_xml(
+        "item:Flag",
+        "t:Message",
+        &format!("<t:Flag><t:FlagStatus>Flagged</t:FlagStatus>{dates}</t:Flag>"),
+    );
+
+    Ok(update_item_xml(
+        item_id,
+        change_key,
+        r#" MessageDisposition="SaveOnly""#,
+        &flag,
+    ))
+}
+
+impl TaskDraft {
+    /// Writes the draft as a `Task` element.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a date, the percentage of completion or the
+    /// recurrence is not valid.
+    fn to_xml(&self) -> Result<String, EwsError> {
+        // The order of the properties is dictated by the EWS schema.
+        let mut properties = text_element("t:Subject", &self.subject);
+        properties.push_str(&optional_body_xml(self.body.as_deref()));
+        if let Some(reminder) = self.reminder {
+            properties.push_str(&text_element("t:ReminderDueBy", &format_date_time(reminder)?));
+            properties.push_str(&text_element("t:ReminderIsSet", "true"));
+        } else {
+            properties.push_str(&text_element("t:ReminderIsSet", "false"));
+        }
+        if let Some(due_date) = self.due_date {
+            properties.push_str(&text_element("t:DueDate", &format_date_time(due_date)?));
+        }
+        if let Some(percent_complete) = self.percent_complete {
+            properties.push_str(&percent_complete_xml(percent_complete)?);
+        }
+        if let Some(recurrence) = &self.recurrence {
+            properties.push_str(&recurrence.to_xml()?);
+        }
+        if let Some(start_date) = self.start_date {
+            properties.push_str(&text_element("t:StartDate", &format_date_time(start_date)?));
+        }
+        properties.push_str(&optional_text_element("t:Status", self.status.map(TaskStatus::as_str)));
+
+        Ok(format!("<t:Task>{properties}</t:Task>"))
+    }
+}
+
+impl TaskUpdate {
+    /// Writes the `SetItemField` and `DeleteItemField` updates of a task.
+    /// Returns an empty string if the update does not change anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a date, the percentage of completion or the
+    /// recurrence is not valid.
+    pub(crate) fn updates_xml(&self) -> Result<String, EwsError> {
+        let mut updates = String::new();
+
+        if let Some(subject) = &self.subject {
+            updates.push_str(&set_field_xml("item:Subject", &text_element("t:Subject", subject)));
+        }
+        if let Some(body) = &self.body {
+            updates.push_str(&set_field_xml("item:Body", &optional_body_xml(Some(body))));
+        }
+        match self.reminder {
+            Some(Some(reminder)) => {
+                updates.push_str(&set_field_xml(
+                    "item:ReminderDueBy",
+                    &text_element("t:ReminderDueBy", &format_date_time(reminder)?),
+                ));
+                updates.push_str(&set_field_xml(
+                    "item:ReminderIsSet",
+                    &text_element("t:ReminderIsSet", "true"),
+                ));
+            }
+            Some(None) => updates.push_str(&set_field_xml(
+                "item:ReminderIsSet",
+                &text_element("t:ReminderIsSet", "false"),
+            )),
+            None => {}
+        }
+        for (field_uri, element, date) in [
+            ("task:StartDate", "t:StartDate", self.start_date),
+            ("task:DueDate", "t:DueDate", self.due_date),
+        ] {
+            match date {
+                Some(Some(date)) => {
+                    updates.push_str(&set_field_xml(
+                        field_uri,
+                        &text_element(element, &format_date_time(date)?),
+                    ));
+                }
+                Some(None) => updates.push_str(&delete_item_field_xml(field_uri)),
+                None => {}
+            }
+        }
+        if let Some(status) = self.status {
+            updates.push_str(&set_field_xml(
+                "task:Status",
+                &text_element("t:Status", status.as_str()),
+            ));
+        }
+        if let Some(percent_complete) = self.percent_complete {
+            updates.push_str(&set_field_xml(
+                "task:PercentComplete",
+                &percent_complete_xml(percent_complete)?,
+            ));
+        }
+        match &self.recurrence {
+            Some(Some(recurrence)) => {
+                updates.push_str(&set_field_xml("task:Recurrence", &recurrence.to_xml()?));
+            }
+            Some(None) => updates.push_str(&delete_item_field_xml("task:Recurrence")),
+            None => {}
+        }
+
+        Ok(updates)
+    }
+}
+
+/// Writes the `PercentComplete` element of a task.
+fn percent_complete_xml(percent_complete: f64) -> Result<String, EwsError> {
+    if (0.0..=100.0).contains(&percent_complete) {
+        Ok(text_element("t:PercentComplete", &percent_complete.to_string()))
+    } else {
+        Err(EwsError::Processing {
+            message: format!("{percent_complete} is not a valid percentage of completion"),
+        })
+    }
+}
+
+/// Writes a `SetItemField` update setting a task property to the given value.
+fn set_field_xml(field_uri: &str, value: &str) -> String {
+    set_item_field_xml(field_uri, "t:Task", value)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_message_request() {
+        assert_eq!(
+            flag_message_request("AAMkAD", Some("CQAAAA"), None).unwrap(),
+            r#"<m:UpdateItem ConflictResolution="NeverOverwrite" MessageDisposition="SaveOnly"><m:ItemChanges><t:ItemChange><t:ItemId Id="AAMkAD" ChangeKey="CQAAAA"/><t:Updates><t:SetItemField><t:FieldURI FieldURI="item:Flag"/><t:Message><t:Flag><t:FlagStatus>Flagged</t:FlagStatus></t:Flag></t:Message></t:SetItemField></t:Updates></t:ItemChange></m:ItemChanges></m:UpdateItem>"#
+        );
+    }
+
+    #[test]
+    fn test_task_update_xml() {
+        let update = TaskUpdate {
+            due_date: Some(None),
+            percent_complete: Some(50.0),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            update.updates_xml().unwrap(),
+            concat!(
+                r#"<t:DeleteItemField><t:FieldURI FieldURI="task:DueDate"/></t:DeleteItemField>"#,
+                r#"<t:SetItemField><t:FieldURI FieldURI="task:PercentComplete"/><t:Task><t:PercentComplete>50</t:PercentComplete></t:Task></t:SetItemField>"#,
+            )
+        );
+        assert!(
+            TaskUpdate {
+                percent_complete: Some(101.0),
+                ..Default::default()
+            }
+            .updates_xml()
+            .is_err()
+        );
+    }
+}
@@ -1,10 +1,20 @@
 //! Server version detection and management.
 
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
 use dashmap::DashMap;
 use ews::server_version::ExchangeServerVersion;
-use std::sync::LazyLock;
 use url::Url;
 
+use crate::client::EwsError;
+
 /// The Exchange Server version to use in requests when we cannot figure out
 /// which one to use (e.g. if the server hasn't provided us with a version
 /// identifier yet). We default to Exchange Server 2007 SP1, which ensures
@@ -14,24 +24,263 @@ use url::Url;
 /// more modern servers, so it is preferable over plain Exchange Server 2007.
 pub(super) const DEFAULT_EWS_SERVER_VERSION: ExchangeServerVersion = ExchangeServerVersion::Exchange2007_SP1;
 
-/// Runtime cache for storing the mapping between EWS endpoints and their detected versions.
-/// This cache persists for the lifetime of the application process.
+/// Stores the server versions detected for EWS endpoints, so that clients
+/// send an appropriate `RequestServerVersion` from their first request.
 ///
-/// Uses `DashMap` for lock-free concurrent access across multiple `EwsClient` instances.
-static SERVER_VERSION_CACHE: LazyLock<DashMap<String, ExchangeServerVersion>> = LazyLock::new(DashMap::new);
+/// Clients store the version the server reports in every response. Stores
+/// which persist versions (e.g. [`JsonFileVersionStore`]) let clients use the
+/// right version from the first request of a new process.
+///
+/// Both [`Self::load`] and [`Self::store`] are called on the async path of
+/// requests, so they must be cheap and must not block. Slow work, such as
+/// writing a file, belongs in [`Self::persist`].
+pub trait VersionStore: Send + Sync {
+    /// Reads the version stored for an endpoint, if any.
+    fn load(&self, endpoint: &Url) -> Option<ExchangeServerVersion>;
+
+    /// Stores the version detected for an endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the version cannot be stored. Clients log such
+    /// errors rather than failing the request which reported the version.
+    fn store(&self, endpoint: &Url, version: ExchangeServerVersion) -> Result<(), EwsError>;
+
+    /// Persists the stored versions, e.g. by writing them to a file.
+    ///
+    /// Clients call this on a blocking thread once
+    /// [`EwsClient::detect_server_version`](crate::client::EwsClient::detect_server_version)
+    /// or Autodiscover has stored a version. The default implementation does
+    /// nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the versions cannot be persisted. Clients log such
+    /// errors rather than failing the operation.
+    fn persist(&self) -> Result<(), EwsError> {
+        Ok(())
+    }
+}
+
+/// A [`VersionStore`] keeping versions in memory, for the lifetime of the
+/// store.
+///
+/// Uses `DashMap` for lock-free concurrent access across multiple `EwsClient`
+/// instances.
+#[derive(Debug, Default)]
+pub struct InMemoryVersionStore {
+    versions: DashMap<String, ExchangeServerVersion>,
+}
+
+impl InMemoryVersionStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
-/// Reads the version stored for a given EWS endpoint from the runtime cache.
+impl VersionStore for InMemoryVersionStore {
+    fn load(&self, endpoint: &Url) -> Option<ExchangeServerVersion> {
+        self.versions.get(endpoint.as_str()).map(|entry| *entry.value())
+    }
+
+    fn store(&self, endpoint: &Url, version: ExchangeServerVersion) -> Result<(), EwsError> {
+        self.versions.insert(endpoint.to_string(), version);
+        Ok(())
+    }
+}
+
+/// A [`VersionStore`] keeping versions in a JSON file, so that they survive
+/// restarts.
+///
+/// The file maps endpoint URLs to version identifiers, e.g.
+/// `{"https://mail.example.com/EWS/Exchange.asmx": "Exchange2013_SP1"}`.
+/// Storing a version only updates the store in memory. The file is rewritten
+/// by [`VersionStore::persist`] and when the store is dropped, if a version
+/// changed since it was last written, through a temporary file so that it is
+/// never left half-written.
+#[derive(Debug)]
+pub struct JsonFileVersionStore {
+    path: PathBuf,
+    versions: Mutex<BTreeMap<String, String>>,
+    /// Whether the versions changed since the file was last written.
+    dirty: AtomicBool,
+    /// Serializes writes, so that an older snapshot of the versions never
+    /// overwrites a newer one.
+    write_lock: Mutex<()>,
+}
+
+impl JsonFileVersionStore {
+    /// Opens a store, reading the versions already in the file if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read, or is not a
+    /// JSON object of strings.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, EwsError> {
+        let path = path.into();
+        let versions = match std::fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            path,
+            versions: Mutex::new(versions),
+            dirty: AtomicBool::new(false),
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// Get the path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes the versions to the file, through a temporary file.
+    fn write_file(&self) -> Result<(), EwsError> {
+        let contents = {
+            let versions = self.versions.lock().unwrap_or_else(PoisonError::into_inner);
+            serde_json::to_vec_pretty(&*versions)?
+        };
+
+        let mut temporary_path = self.path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        std::fs::write(&temporary_path, contents)?;
+        std::fs::rename(&temporary_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl VersionStore for JsonFileVersionStore {
+    fn load(&self, endpoint: &Url) -> Option<ExchangeServerVersion> {
+        let versions = self.versions.lock().unwrap_or_else(PoisonError::into_inner);
+        // Versions which this client does not know are ignored, and replaced
+        // once the server reports a version again
+        ExchangeServerVersion::try_from(versions.get(endpoint.as_str())?.as_str()).ok()
+    }
+
+    fn store(&self, endpoint: &Url, version: ExchangeServerVersion) -> Result<(), EwsError> {
+        let mut versions = self.versions.lock().unwrap_or_else(PoisonError::into_inner);
+        let version = version_header_value(version);
+        if versions.get(endpoint.as_str()) == Some(&version) {
+            return Ok(());
+        }
+        versions.insert(endpoint.to_string(), version);
+        self.dirty.store(true, Ordering::Release);
+
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<(), EwsError> {
+        let _write_guard = self.write_lock.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        let result = self.write_file();
+        if result.is_err() {
+            // Write the versions again on the next call
+            self.dirty.store(true, Ordering::Release);
+        }
+        result
+    }
+}
+
+impl Drop for JsonFileVersionStore {
+    fn drop(&mut self) {
+        if let Err(err) = self.persist() {
+            log::warn!("Failed to write server versions to {}: {err}", self.path.display());
+        }
+    }
+}
+
+/// The store of clients which are not given one, shared by all of them so
+/// that a version detected by one client (or by Autodiscover) is used by the
+/// clients created afterwards for the same endpoint.
+static DEFAULT_VERSION_STORE: LazyLock<Arc<InMemoryVersionStore>> = LazyLock::new(Arc::default);
+
+/// Gets the store of clients which are not given one.
+pub(super) fn default_version_store() -> Arc<dyn VersionStore> {
+    Arc::<InMemoryVersionStore>::clone(&DEFAULT_VERSION_STORE)
+}
+
+/// Reads the version stored for a given EWS endpoint.
 ///
 /// If no version could be read for this endpoint, returns the default version.
-pub(super) fn read_server_version(endpoint: &Url) -> ExchangeServerVersion {
-    SERVER_VERSION_CACHE
-        .get(endpoint.as_str())
-        .map_or(DEFAULT_EWS_SERVER_VERSION, |entry| *entry.value())
+pub(super) fn read_server_version(store: &dyn VersionStore, endpoint: &Url) -> ExchangeServerVersion {
+    store.load(endpoint).unwrap_or(DEFAULT_EWS_SERVER_VERSION)
+}
+
+/// Stores the server version for a given EWS endpoint, logging failures.
+pub(super) fn store_server_version(store: &dyn VersionStore, endpoint: &Url, version: ExchangeServerVersion) {
+    if let Err(err) = store.store(endpoint, version) {
+        log::warn!("Failed to store server version {version:?} for endpoint {endpoint}: {err}");
+    }
 }
 
-/// Stores the server version for a given EWS endpoint in the runtime cache.
-pub(super) fn store_server_version(endpoint: &Url, version: ExchangeServerVersion) {
-    SERVER_VERSION_CACHE.insert(endpoint.to_string(), version);
+/// Persists the versions of a store on a blocking thread, logging failures.
+pub(super) async fn persist_server_versions(store: Arc<dyn VersionStore>) {
+    match tokio::task::spawn_blocking(move || store.persist()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => log::warn!("Failed to persist server versions: {err}"),
+        Err(err) => log::warn!("Failed to persist server versions: {err}"),
+    }
+}
+
+/// Gets the value of the `Version` attribute of a `RequestServerVersion`
+/// header for the given version.
+///
+/// This is used by requests which aren't serialized by the `ews` crate. The
+/// variant names of `ExchangeServerVersion` mirror the values of the schema's
+/// `ExchangeVersionType`, so the debug representation can be used as is.
+pub(super) fn version_header_value(version: ExchangeServerVersion) -> String {
+    format!("{version:?}")
+}
+
+/// Maps the version numbers of an Exchange build (e.g. 15.1.2507) to the most
+/// recent request version it supports, if it is a known Exchange release.
+///
+/// This is used when a server reports its build rather than a version
+/// identifier, e.g. in Autodiscover responses.
+pub(super) fn version_from_build(major: u32, minor: u32, build: u32) -> Option<ExchangeServerVersion> {
+    match (major, minor) {
+        (8, 1..) => Some(ExchangeServerVersion::Exchange2007_SP1),
+        (14, _) => Some(ExchangeServerVersion::Exchange2010),
+        // Exchange 2013 SP1 is build 15.0.847.
+        (15, 0) if build < 847 => Some(ExchangeServerVersion::Exchange2013),
+        (15.., _) => Some(ExchangeServerVersion::Exchange2013_SP1),
+        _ => None,
+    }
+}
+
+/// Maps a version identifier reported by a server to the request version to
+/// use with it.
+///
+/// Identifiers unknown to the `ews` crate are mapped from the release they
+/// name: newer releases (e.g. `Exchange2016` or the `V2017_07_11` style of
+/// Exchange 2016 and Exchange Online) to the most recent known version, and
+/// unknown service packs to the release's oldest known version. Other
+/// identifiers cannot be mapped.
+fn version_from_identifier(identifier: &str) -> Option<ExchangeServerVersion> {
+    if let Ok(version) = ExchangeServerVersion::try_from(identifier) {
+        return Some(version);
+    }
+
+    if identifier.strip_prefix("V20").is_some_and(|date| date.contains('_')) {
+        return Some(ExchangeServerVersion::Exchange2013_SP1);
+    }
+
+    let year: u32 = identifier.strip_prefix("Exchange")?.get(..4)?.parse().ok()?;
+    match year {
+        2007 => Some(ExchangeServerVersion::Exchange2007_SP1),
+        2010 => Some(ExchangeServerVersion::Exchange2010),
+        // Unknown service packs of Exchange 2013 come after SP1.
+        2013.. => Some(ExchangeServerVersion::Exchange2013_SP1),
+        _ => None,
+    }
 }
 
 /// Updates the server version from a `ServerVersionInfo` header.
@@ -39,27 +288,29 @@ pub(super) fn store_server_version(endpoint: &Url, version: ExchangeServerVersio
 /// This function:
 /// 1. Parses the version string from the header
 /// 2. Converts it to an `ExchangeServerVersion` enum
-/// 3. Stores it in the runtime cache for future use
+/// 3. Stores it in the version store for future use
 ///
-/// If the server provides an unknown version, defaults to `Exchange2013_SP1`.
+/// If the server provides a version which cannot be mapped (see
+/// [`version_from_identifier`]), keeps the current version.
 pub(super) fn update_server_version_from_header(
+    store: &dyn VersionStore,
     endpoint: &Url,
     header: ews::server_version::ServerVersionInfo,
 ) -> ExchangeServerVersion {
     let version = match header.version {
         Some(version) if !version.is_empty() => version,
-        // If the server did not include a version identifier, return current cached version
-        _ => return read_server_version(endpoint),
+        // If the server did not include a version identifier, return current stored version
+        _ => return read_server_version(store, endpoint),
     };
 
-    let version = ExchangeServerVersion::try_from(version.as_str()).unwrap_or_else(|_| {
-        // If the server included an unknown version, default to the most recent known version
-        log::warn!("Unknown server version '{version}' for endpoint {endpoint}, defaulting to Exchange2013_SP1");
-        ExchangeServerVersion::Exchange2013_SP1
-    });
+    let Some(version) = version_from_identifier(&version) else {
+        let current = read_server_version(store, endpoint);
+        log::warn!("Unknown server version '{version}' for endpoint {endpoint}, keeping {current:?}");
+        return current;
+    };
 
-    // Store the version in the runtime cache for future use
-    store_server_version(endpoint, version);
+    // Store the version for future use
+    store_server_version(store, endpoint, version);
 
     version
 }
@@ -72,24 +323,64 @@ mod tests {
     #[test]
     fn test_read_default_version() {
         let endpoint: Url = "https://test.example.com/EWS/Exchange.asmx".parse().unwrap();
-        let version = read_server_version(&endpoint);
+        let store = InMemoryVersionStore::new();
+        let version = read_server_version(&store, &endpoint);
         assert_eq!(version, DEFAULT_EWS_SERVER_VERSION);
     }
 
     #[test]
     fn test_store_and_read_version() {
         let endpoint: Url = "https://test2.example.com/EWS/Exchange.asmx".parse().unwrap();
+        let store = InMemoryVersionStore::new();
         let version = ExchangeServerVersion::Exchange2013_SP1;
 
-        store_server_version(&endpoint, version);
-        let read_version = read_server_version(&endpoint);
+        store_server_version(&store, &endpoint, version);
+        let read_version = read_server_version(&store, &endpoint);
 
         assert_eq!(read_version, version);
     }
 
+    #[test]
+    fn test_version_header_value() {
+        assert_eq!(
+            version_header_value(ExchangeServerVersion::Exchange2007_SP1),
+            "Exchange2007_SP1"
+        );
+        assert_eq!(
+            version_header_value(ExchangeServerVersion::Exchange2013),
+            "Exchange2013"
+        );
+    }
+
+    #[test]
+    fn test_version_from_build() {
+        assert_eq!(
+            version_from_build(8, 3, 0),
+            Some(ExchangeServerVersion::Exchange2007_SP1)
+        );
+        assert_eq!(
+            version_from_build(14, 3, 123),
+            Some(ExchangeServerVersion::Exchange2010)
+        );
+        assert_eq!(
+            version_from_build(15, 0, 516),
+            Some(ExchangeServerVersion::Exchange2013)
+        );
+        assert_eq!(
+            version_from_build(15, 0, 847),
+            Some(ExchangeServerVersion::Exchange2013_SP1)
+        );
+        assert_eq!(
+            version_from_build(15, 2, 1118),
+            Some(ExchangeServerVersion::Exchange2013_SP1)
+        );
+        assert_eq!(version_from_build(6, 5, 0), None);
+    }
+
     #[test]
     fn test_update_from_header() {
         let endpoint: Url = "https://test3.example.com/EWS/Exchange.asmx".parse().unwrap();
+        let store = InMemoryVersionStore::new();
         let header = ews::server_version::ServerVersionInfo {
             major_version: None,
             minor_version: None,
@@ -98,17 +389,18 @@ mod tests {
             version: Some("Exchange2013_SP1".to_string()),
         };
 
-        let version = update_server_version_from_header(&endpoint, header);
+        let version = update_server_version_from_header(&store, &endpoint, header);
         assert_eq!(version, ExchangeServerVersion::Exchange2013_SP1);
 
-        // Verify it was cached
-        let cached_version = read_server_version(&endpoint);
+        // Verify it was stored
+        let cached_version = read_server_version(&store, &endpoint);
         assert_eq!(cached_version, ExchangeServerVersion::Exchange2013_SP1);
     }
 
     #[test]
     fn test_update_from_header_unknown_version() {
         let endpoint: Url = "https://test4.example.com/EWS/Exchange.asmx".parse().unwrap();
+        let store = InMemoryVersionStore::new();
         let header = ews::server_version::ServerVersionInfo {
             major_version: None,
             minor_version: None,
@@ -117,17 +409,63 @@ mod tests {
             version: Some("Exchange2025_Unknown".to_string()),
         };
 
-        let version = update_server_version_from_header(&endpoint, header);
-        // Should default to Exchange2013_SP1 for unknown versions
+        let version = update_server_version_from_header(&store, &endpoint, header);
+        // Should map unknown newer releases to the most recent known version
         assert_eq!(version, ExchangeServerVersion::Exchange2013_SP1);
     }
 
+    #[test]
+    fn test_version_from_identifier() {
+        assert_eq!(
+            version_from_identifier("Exchange2010"),
+            Some(ExchangeServerVersion::Exchange2010)
+        );
+        assert_eq!(
+            version_from_identifier("Exchange2010_SP9"),
+            Some(ExchangeServerVersion::Exchange2010)
+        );
+        assert_eq!(
+            version_from_identifier("Exchange2007_SP9"),
+            Some(ExchangeServerVersion::Exchange2007_SP1)
+        );
+        assert_eq!(
+            version_from_identifier("Exchange2019"),
+            Some(ExchangeServerVersion::Exchange2013_SP1)
+        );
+        assert_eq!(
+            version_from_identifier("V2017_07_11"),
+            Some(ExchangeServerVersion::Exchange2013_SP1)
+        );
+        assert_eq!(version_from_identifier("Exchange2003"), None);
+        assert_eq!(version_from_identifier("Unknown"), None);
+    }
+
+    #[test]
+    fn test_update_from_header_unmapped_version() {
+        let endpoint: Url = "https://test6.example.com/EWS/Exchange.asmx".parse().unwrap();
+        let store = InMemoryVersionStore::new();
+        store_server_version(&store, &endpoint, ExchangeServerVersion::Exchange2010);
+
+        let header = ews::server_version::ServerVersionInfo {
+            major_version: None,
+            minor_version: None,
+            major_build_number: None,
+            minor_build_number: None,
+            version: Some("Unknown".to_string()),
+        };
+
+        // Should keep the stored version rather than assume a recent server
+        let version = update_server_version_from_header(&store, &endpoint, header);
+        assert_eq!(version, ExchangeServerVersion::Exchange2010);
+    }
+
     #[test]
     fn test_update_from_header_empty_version() {
         let endpoint: Url = "https://test5.example.com/EWS/Exchange.asmx".parse().unwrap();
+        let store = InMemoryVersionStore::new();
 
         // Store a known version first
-        store_server_version(&endpoint, ExchangeServerVersion::Exchange2010);
+        store_server_version(&store, &endpoint, ExchangeServerVersion::Exchange2010);
 
         let header = ews::server_version::ServerVersionInfo {
             major_version: None,
@@ -137,8 +475,52 @@ mod tests {
             version: None,
         };
 
-        let version = update_server_version_from_header(&endpoint, header);
-        // Should return cached version when header has no version
+        let version = update_server_version_from_header(&store, &endpoint, header);
+        // Should return stored version when header has no version
         assert_eq!(version, ExchangeServerVersion::Exchange2010);
     }
+
+    #[test]
+    fn test_json_file_version_store() {
+        let path = std::env::temp_dir().join(format!("ews-versions-{}.json", std::process::id()));
+        let endpoint: Url = "https://json.example.com/EWS/Exchange.asmx".parse().unwrap();
+
+        let store = JsonFileVersionStore::open(&path).unwrap();
+        assert_eq!(store.load(&endpoint), None);
+        store.store(&endpoint, ExchangeServerVersion::Exchange2010).unwrap();
+        assert_eq!(store.load(&endpoint), Some(ExchangeServerVersion::Exchange2010));
+
+        // Storing a version does not write the file
+        assert!(!path.exists());
+        store.persist().unwrap();
+
+        // The version survives reopening the store
+        let reopened = JsonFileVersionStore::open(&path).unwrap();
+        assert_eq!(reopened.load(&endpoint), Some(ExchangeServerVersion::Exchange2010));
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            contents.contains(r#""https://json.example.com/EWS/Exchange.asmx": "Exchange2010""#),
+            "{contents}"
+        );
+
+        // Versions which were not persisted are written when the store is
+        // dropped
+        reopened.store(&endpoint, ExchangeServerVersion::Exchange2013).unwrap();
+        drop(reopened);
+        let reopened = JsonFileVersionStore::open(&path).unwrap();
+        assert_eq!(reopened.load(&endpoint), Some(ExchangeServerVersion::Exchange2013));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_json_file_version_store_invalid_file() {
+        let path = std::env::temp_dir().join(format!("ews-versions-invalid-{}.json", std::process::id()));
+        std::fs::write(&path, "not json").unwrap();
+
+        let result = JsonFileVersionStore::open(&path);
+        assert!(matches!(result, Err(EwsError::Serialization(_))), "{result:?}");
+
+        std::fs::remove_file(&path).unwrap();
+    }
 }
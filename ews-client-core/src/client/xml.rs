@@ -0,0 +1,412 @@
+//! Minimal XML tree used for EWS operations which the `ews` crate does not
+//! model.
+//!
+//! Requests for these operations are written by hand, and responses are read
+//! into a small element tree so that the operation modules can pick out the
+//! values they need without a dedicated set of deserialization types.
+
+use quick_xml::{Reader, escape::escape, events::BytesStart, events::Event};
+use time::{
+    Date, OffsetDateTime, PrimitiveDateTime, format_description::well_known::Rfc3339, macros::format_description,
+};
+
+use crate::client::EwsError;
+
+/// An XML element with namespace prefixes stripped from its name and from the
+/// names of its attributes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct XmlElement {
+    /// The local name of the element (e.g. `ResponseCode` for
+    /// `<m:ResponseCode>`).
+    pub name: String,
+    /// The element's attributes, in document order, excluding namespace
+    /// declarations.
+    pub attributes: Vec<(String, String)>,
+    /// The element's child elements, in document order.
+    pub children: Vec<XmlElement>,
+    /// The unescaped text content directly contained by this element.
+    pub text: String,
+}
+
+impl XmlElement {
+    /// Parses an XML document and returns its root element.
+    pub(crate) fn parse(document: &[u8]) -> Result<Self, EwsError> {
+        let document = std::str::from_utf8(document).map_err(|err| EwsError::Processing {
+            message: format!("XML document is not valid UTF-8: {err}"),
+        })?;
+
+        let mut reader = Reader::from_str(document);
+        reader.config_mut().trim_text(true);
+
+        // Elements which have been opened but not closed yet. The last element
+        // in the stack is the innermost one.
+        let mut stack: Vec<XmlElement> = Vec::new();
+
+        loop {
+            match reader.read_event().map_err(xml_error)? {
+                Event::Start(start) => stack.push(element_from_start(&start)?),
+                Event::Empty(start) => {
+                    let element = element_from_start(&start)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::End(_) => {
+                    let element = stack.pop().ok_or_else(|| EwsError::Processing {
+                        message: "unbalanced closing tag in XML document".to_string(),
+                    })?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Ok(element),
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some(current) = stack.last_mut() {
+                        current.text.push_str(&text.unescape().map_err(xml_error)?);
+                    }
+                }
+                Event::CData(data) => {
+                    if let Some(current) = stack.last_mut() {
+                        current.text.push_str(&String::from_utf8_lossy(&data.into_inner()));
+                    }
+                }
+                Event::Eof => {
+                    return Err(EwsError::Processing {
+                        message: "unexpected end of XML document".to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The value of the attribute with the given local name, if any.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The first child element with the given local name, if any.
+    pub(crate) fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// All child elements with the given local name.
+    pub(crate) fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// The element reached by following the given path of local names from
+    /// this element, if any.
+    pub(crate) fn descendant(&self, path: &[&str]) -> Option<&XmlElement> {
+        path.iter().try_fold(self, |element, name| element.child(name))
+    }
+
+    /// The text of the first child element with the given local name, if any.
+    pub(crate) fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.as_str())
+    }
+
+    /// The text of the first child element with the given local name, parsed
+    /// into the requested type. Returns `None` if the child is missing or its
+    /// text cannot be parsed.
+    pub(crate) fn child_parse<T: std::str::FromStr>(&self, name: &str) -> Option<T> {
+        self.child_text(name).and_then(|text| text.trim().parse().ok())
+    }
+
+    /// The text of the first child element with the given local name, parsed
+    /// as an `xs:boolean`.
+    pub(crate) fn child_bool(&self, name: &str) -> Option<bool> {
+        self.child_text(name).and_then(|text| match text.trim() {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => None,
+        })
+    }
+
+    /// The text of the first child element with the given local name, parsed
+    /// as an `xs:dateTime` into a Unix timestamp.
+    pub(crate) fn child_date_time(&self, name: &str) -> Option<i64> {
+        self.child_text(name).and_then(parse_date_time)
+    }
+
+    /// The `Id` attribute of the first child element with the given local name
+    /// (e.g. `ItemId` or `FolderId`), if any.
+    pub(crate) fn child_id(&self, name: &str) -> Option<String> {
+        self.child(name)
+            .and_then(|child| child.attribute("Id"))
+            .map(ToString::to_string)
+    }
+
+    /// Finds the first element with the given local name in this element's
+    /// subtree (including the element itself), searching depth-first.
+    pub(crate) fn find(&self, name: &str) -> Option<&XmlElement> {
+        if self.name == name {
+            return Some(self);
+        }
+
+        self.children.iter().find_map(|child| child.find(name))
+    }
+}
+
+/// Writes an element containing the given text, escaping it as needed.
+///
+/// Example: `text_element("t:Subject", "A & B")` gives
+/// `<t:Subject>A &amp; B</t:Subject>`.
+pub(crate) fn text_element(name: &str, text: &str) -> String {
+    format!("<{name}>{}</{name}>", escape(text))
+}
+
+/// Writes an element containing the given text if there is any, or nothing
+/// otherwise.
+pub(crate) fn optional_text_element(name: &str, text: Option<&str>) -> String {
+    text.map(|text| text_element(name, text)).unwrap_or_default()
+}
+
+/// Escapes a value for use in XML text or attribute values.
+pub(crate) fn escape_text(text: &str) -> String {
+    escape(text).into_owned()
+}
+
+/// Formats a Unix timestamp as an `xs:dateTime` in UTC (e.g.
+/// `2024-01-15T10:30:00Z`).
+pub(crate) fn format_date_time(timestamp: i64) -> Result<String, EwsError> {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|date_time| date_time.format(&Rfc3339).ok())
+        .ok_or_else(|| EwsError::Processing {
+            message: format!("timestamp {timestamp} cannot be represented as a date"),
+        })
+}
+
+/// Parses an `xs:dateTime` into a Unix timestamp.
+///
+/// Exchange omits the offset in a few places (e.g. in availability
+/// responses), in which case the value is read as UTC.
+pub(crate) fn parse_date_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+
+    if let Ok(date_time) = OffsetDateTime::parse(value, &Rfc3339) {
+        return Some(date_time.unix_timestamp());
+    }
+
+    // Drop fractional seconds, if any, before parsing a value without offset.
+    let value = value.split('.').next().unwrap_or(value);
+    PrimitiveDateTime::parse(
+        value,
+        format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]"),
+    )
+    .ok()
+    .map(|date_time| date_time.assume_utc().unix_timestamp())
+}
+
+/// Formats the UTC date of a Unix timestamp as an `xs:date` (e.g.
+/// `2024-01-15`).
+pub(crate) fn format_date(timestamp: i64) -> Result<String, EwsError> {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|date_time| {
+            date_time
+                .date()
+                .format(format_description!("[year]-[month]-[day]"))
+                .ok()
+        })
+        .ok_or_else(|| EwsError::Processing {
+            message: format!("timestamp {timestamp} cannot be represented as a date"),
+        })
+}
+
+/// Parses an `xs:date` into the Unix timestamp of midnight UTC on that date.
+///
+/// Any offset following the date (e.g. `2024-01-15Z` or `2024-01-15-08:00`)
+/// is ignored, since the date itself is what matters.
+pub(crate) fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim().get(..10)?;
+
+    Date::parse(value, format_description!("[year]-[month]-[day]"))
+        .ok()
+        .map(|date| date.midnight().assume_utc().unix_timestamp())
+}
+
+/// Formats minutes as an `xs:duration`, e.g. `-PT5H30M`.
+pub(crate) fn format_duration(minutes: i32) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    let minutes = minutes.unsigned_abs();
+
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{sign}PT{minutes}M"),
+        (hours, 0) => format!("{sign}PT{hours}H"),
+        (hours, minutes) => format!("{sign}PT{hours}H{minutes}M"),
+    }
+}
+
+/// Parses an `xs:duration` of days, hours and minutes (e.g. `-PT5H30M`) into
+/// minutes. Seconds are ignored.
+pub(crate) fn parse_duration_minutes(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+
+    let mut minutes = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if !in_time && number.is_empty() => in_time = true,
+            _ => {
+                let amount: i32 = number.parse().ok()?;
+                number.clear();
+                minutes += match (c, in_time) {
+                    ('D', false) => amount * 24 * 60,
+                    ('H', true) => amount * 60,
+                    ('M', true) => amount,
+                    ('S', true) => 0,
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    number.is_empty().then_some(if negative { -minutes } else { minutes })
+}
+
+/// Builds an `XmlElement` from the start tag of an element.
+fn element_from_start(start: &BytesStart<'_>) -> Result<XmlElement, EwsError> {
+    let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+
+    let mut attributes = Vec::new();
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(xml_error)?;
+
+        // Namespace declarations aren't meaningful once prefixes are stripped.
+        if attribute.key.as_ref().starts_with(b"xmlns") {
+            continue;
+        }
+
+        let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+        let value = attribute.unescape_value().map_err(xml_error)?.into_owned();
+        attributes.push((key, value));
+    }
+
+    Ok(XmlElement {
+        name,
+        attributes,
+        ..Default::default()
+    })
+}
+
+/// Converts an error from the XML reader into an `EwsError`.
+fn xml_error(err: impl std::fmt::Display) -> EwsError {
+    EwsError::Processing {
+        message: format!("failed to parse XML: {err}"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_strips_prefixes_and_namespaces() {
+        let document = br#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/">
+  <s:Body>
+    <m:ResponseMessage ResponseClass="Success" xmlns:m="urn:messages">
+      <m:ResponseCode>NoError</m:ResponseCode>
+      <t:ItemId Id="item-1" ChangeKey="ck" xmlns:t="urn:types"/>
+    </m:ResponseMessage>
+  </s:Body>
+</s:Envelope>"#;
+
+        let envelope = XmlElement::parse(document).unwrap();
+        assert_eq!(envelope.name, "Envelope");
+        assert!(envelope.attributes.is_empty());
+
+        let message = envelope.descendant(&["Body", "ResponseMessage"]).unwrap();
+        assert_eq!(message.attribute("ResponseClass"), Some("Success"));
+        assert_eq!(message.child_text("ResponseCode"), Some("NoError"));
+        assert_eq!(message.child_id("ItemId").as_deref(), Some("item-1"));
+    }
+
+    #[test]
+    fn test_parse_unescapes_text_and_attributes() {
+        let document = br#"<Root Name="a &amp; b"><Text>1 &lt; 2</Text><Data><![CDATA[<raw>]]></Data></Root>"#;
+
+        let root = XmlElement::parse(document).unwrap();
+        assert_eq!(root.attribute("Name"), Some("a & b"));
+        assert_eq!(root.child_text("Text"), Some("1 < 2"));
+        assert_eq!(root.child_text("Data"), Some("<raw>"));
+    }
+
+    #[test]
+    fn test_typed_child_accessors() {
+        let document = b"<Root><Count>42</Count><Flag>true</Flag><Bad>nope</Bad></Root>";
+
+        let root = XmlElement::parse(document).unwrap();
+        assert_eq!(root.child_parse::<u32>("Count"), Some(42));
+        assert_eq!(root.child_bool("Flag"), Some(true));
+        assert_eq!(root.child_parse::<u32>("Bad"), None);
+        assert_eq!(root.child_bool("Missing"), None);
+    }
+
+    #[test]
+    fn test_find_searches_subtree() {
+        let document = b"<A><B><C>deep</C></B><C>shallow</C></A>";
+
+        let root = XmlElement::parse(document).unwrap();
+        assert_eq!(root.find("C").map(|c| c.text.as_str()), Some("deep"));
+        assert_eq!(root.children_named("C").count(), 1);
+        assert!(root.find("D").is_none());
+    }
+
+    #[test]
+    fn test_parse_rejects_truncated_document() {
+        assert!(XmlElement::parse(b"<A><B>").is_err());
+    }
+
+    #[test]
+    fn test_date_time_round_trip() {
+        assert_eq!(format_date_time(1_705_314_600).unwrap(), "2024-01-15T10:30:00Z");
+        assert_eq!(parse_date_time("2024-01-15T10:30:00Z"), Some(1_705_314_600));
+        assert_eq!(parse_date_time("2024-01-15T11:30:00+01:00"), Some(1_705_314_600));
+        assert_eq!(parse_date_time("2024-01-15T10:30:00"), Some(1_705_314_600));
+        assert_eq!(parse_date_time("2024-01-15T10:30:00.000"), Some(1_705_314_600));
+        assert_eq!(parse_date_time("yesterday"), None);
+    }
+
+    #[test]
+    fn test_date_round_trip() {
+        assert_eq!(format_date(1_705_314_600).unwrap(), "2024-01-15");
+        assert_eq!(parse_date("2024-01-15"), Some(1_705_276_800));
+        assert_eq!(parse_date("2024-01-15Z"), Some(1_705_276_800));
+        assert_eq!(parse_date("2024-01-15-08:00"), Some(1_705_276_800));
+        assert_eq!(parse_date("15/01/2024"), None);
+    }
+
+    #[test]
+    fn test_duration_round_trip() {
+        assert_eq!(parse_duration_minutes("-PT1H"), Some(-60));
+        assert_eq!(parse_duration_minutes("PT5H30M"), Some(330));
+        assert_eq!(parse_duration_minutes("P0DT2H0M0S"), Some(120));
+        assert_eq!(parse_duration_minutes("2H"), None);
+        assert_eq!(format_duration(-330), "-PT5H30M");
+        assert_eq!(format_duration(0), "PT0M");
+        assert_eq!(format_duration(120), "PT2H");
+    }
+
+    #[test]
+    fn test_text_element_escapes() {
+        assert_eq!(
+            text_element("t:Subject", "A & <B>"),
+            "<t:Subject>A &amp; &lt;B&gt;</t:Subject>"
+        );
+        assert_eq!(optional_text_element("t:Subject", None), "");
+    }
+}
@@ -6,7 +6,8 @@
 pub mod client;
 
 pub use client::{
-    Credentials, EwsClient, EwsError, Mailbox, MessageHeaders, MessagePriority, make_header_string_for_mailbox_list,
+    AutodiscoverClient, AutodiscoverResult, Credentials, EwsClient, EwsClientPool, EwsError, Mailbox, MessageHeaders,
+    MessagePriority, make_header_string_for_mailbox_list,
 };
 
 pub use ews;
@@ -368,6 +368,33 @@ pub fn create_folder_response(folder_id: &str) -> String {
     folder_success!("CreateFolder", folder_id)
 }
 
+/// Response for successful `CreateFolder` operation creating a folder of the
+/// given type (e.g. `CalendarFolder` or `SearchFolder`)
+pub fn create_typed_folder_response(folder_type: &str, folder_id: &str) -> String {
+    op_response!(
+        "CreateFolder",
+        response_message_xml!(
+            "CreateFolder",
+            "Success",
+            "NoError",
+            container_xml!("Folders", folder_type, id_xml!("FolderId", folder_id, "AQAAAA=="))
+        )
+    )
+}
+
+/// Response for `CreateFolder` when the server rejects a search folder
+/// restriction
+pub fn create_search_folder_invalid_restriction_response() -> String {
+    op_response!(
+        "CreateFolder",
+        response_error_xml!(
+            "CreateFolder",
+            "ErrorInvalidRestriction",
+            "The restriction contains an unsupported property."
+        )
+    )
+}
+
 /// Response for successful `DeleteFolder` operation
 pub fn delete_folder_response() -> String {
     simple_success!("DeleteFolder")
@@ -496,6 +523,62 @@ pub fn sync_folder_hierarchy_with_changes_response(
     )
 }
 
+/// XML of a typed folder (e.g. `CalendarFolder`, `ContactsFolder` or
+/// `TasksFolder`), without an unread count since calendar and contacts
+/// folders have none
+fn typed_folder_xml(element: &str, folder_id: &str, display_name: &str, folder_class: &str) -> String {
+    format!(
+        r"<t:{element}>
+              {}
+              {}
+              <t:FolderClass>{folder_class}</t:FolderClass>
+              <t:DisplayName>{display_name}</t:DisplayName>
+              <t:TotalCount>0</t:TotalCount>
+              <t:ChildFolderCount>0</t:ChildFolderCount>
+            </t:{element}>",
+        id_xml!("FolderId", folder_id, "AQAAAA=="),
+        id_xml!("ParentFolderId", "root-folder-id", "AQAAAA==")
+    )
+}
+
+/// Response for `SyncFolderHierarchy` creating a typed folder (e.g.
+/// `ContactsFolder` or `TasksFolder`)
+pub fn sync_folder_hierarchy_typed_folder_response(
+    sync_state: &str,
+    element: &str,
+    folder_id: &str,
+    folder_class: &str,
+) -> String {
+    success_response!(
+        "SyncFolderHierarchy",
+        format!(
+            r"<m:SyncState>{}</m:SyncState>
+          <m:IncludesLastFolderInRange>true</m:IncludesLastFolderInRange>
+          <m:Changes>
+            <t:Create>
+              {}
+            </t:Create>
+          </m:Changes>",
+            sync_state,
+            typed_folder_xml(element, folder_id, "TypedFolder", folder_class)
+        )
+    )
+}
+
+/// Response for `GetFolder` returning a typed folder (e.g. `ContactsFolder`
+/// or `TasksFolder`)
+pub fn get_typed_folder_response(element: &str, folder_id: &str, display_name: &str, folder_class: &str) -> String {
+    success_response!(
+        "GetFolder",
+        format!(
+            r"<m:Folders>
+            {}
+          </m:Folders>",
+            typed_folder_xml(element, folder_id, display_name, folder_class)
+        )
+    )
+}
+
 /// Response for `SyncFolderHierarchy` with pagination (IncludesLastFolderInRange=false)
 pub fn sync_folder_hierarchy_paginated_response(_sync_state: &str, next_sync_state: &str, folder_id: &str) -> String {
     success_response!(
@@ -520,6 +603,58 @@ pub fn sync_folder_hierarchy_paginated_response(_sync_state: &str, next_sync_sta
     )
 }
 
+/// Response for `GetFolder` with the permission set of a calendar folder
+pub fn get_calendar_folder_permissions_response(folder_id: &str) -> String {
+    success_response!(
+        "GetFolder",
+        format!(
+            r"<m:Folders>
+            <t:CalendarFolder>
+              {}
+              <t:PermissionSet>
+                <t:CalendarPermissions>
+                  <t:CalendarPermission>
+                    <t:UserId>
+                      <t:DistinguishedUser>Default</t:DistinguishedUser>
+                    </t:UserId>
+                    <t:CanCreateItems>false</t:CanCreateItems>
+                    <t:CanCreateSubFolders>false</t:CanCreateSubFolders>
+                    <t:IsFolderOwner>false</t:IsFolderOwner>
+                    <t:IsFolderVisible>false</t:IsFolderVisible>
+                    <t:IsFolderContact>false</t:IsFolderContact>
+                    <t:EditItems>None</t:EditItems>
+                    <t:DeleteItems>None</t:DeleteItems>
+                    <t:ReadItems>TimeOnly</t:ReadItems>
+                    <t:CalendarPermissionLevel>FreeBusyTimeOnly</t:CalendarPermissionLevel>
+                  </t:CalendarPermission>
+                  <t:CalendarPermission>
+                    <t:UserId>
+                      <t:SID>S-1-5-21-1333220396-2200287332-232816053-1117</t:SID>
+                      <t:PrimarySmtpAddress>colleague@example.com</t:PrimarySmtpAddress>
+                      <t:DisplayName>Casey Colleague</t:DisplayName>
+                    </t:UserId>
+                    <t:CanCreateItems>true</t:CanCreateItems>
+                    <t:CanCreateSubFolders>false</t:CanCreateSubFolders>
+                    <t:IsFolderOwner>false</t:IsFolderOwner>
+                    <t:IsFolderVisible>true</t:IsFolderVisible>
+                    <t:IsFolderContact>false</t:IsFolderContact>
+                    <t:EditItems>All</t:EditItems>
+                    <t:DeleteItems>All</t:DeleteItems>
+                    <t:ReadItems>FullDetails</t:ReadItems>
+                    <t:CalendarPermissionLevel>Editor</t:CalendarPermissionLevel>
+                  </t:CalendarPermission>
+                </t:CalendarPermissions>
+                <t:UnknownEntries>
+                  <t:UnknownEntry>NT User:S-1-5-21-1333220396-2200287332-232816053-1999</t:UnknownEntry>
+                </t:UnknownEntries>
+              </t:PermissionSet>
+            </t:CalendarFolder>
+          </m:Folders>",
+            id_xml!("FolderId", folder_id, "AgAAAA==")
+        )
+    )
+}
+
 // ============================================================================
 // Item Operations Fixtures
 // ============================================================================
@@ -645,6 +780,35 @@ pub fn get_item_full_response(
     )
 }
 
+/// Response for `GetItem` with a text body, Internet headers, categories and
+/// an extended property (used in `get_items`)
+pub fn get_item_with_properties_response(item_id: &str, body: &str) -> String {
+    success_response!(
+        "GetItem",
+        format!(
+            r#"<m:Items>
+            <t:Message>
+              {}
+              <t:Body BodyType="Text" IsTruncated="false">{}</t:Body>
+              <t:Categories>
+                <t:String>Project</t:String>
+              </t:Categories>
+              <t:InternetMessageHeaders>
+                <t:InternetMessageHeader HeaderName="List-Id">team.example.com</t:InternetMessageHeader>
+                <t:InternetMessageHeader HeaderName="X-Priority">1</t:InternetMessageHeader>
+              </t:InternetMessageHeaders>
+              <t:ExtendedProperty>
+                <t:ExtendedFieldURI PropertyTag="0x1081" PropertyType="Integer"/>
+                <t:Value>261</t:Value>
+              </t:ExtendedProperty>
+            </t:Message>
+          </m:Items>"#,
+            id_xml!("ItemId", item_id, "CQAAAA=="),
+            body
+        )
+    )
+}
+
 /// Response for `GetItem` with recipients (To, Cc, Bcc, `ReplyTo`)
 #[allow(clippy::too_many_arguments)]
 pub fn get_item_with_recipients_response(
@@ -1049,14 +1213,40 @@ pub fn error_access_denied() -> String {
 
 /// Response for server busy error
 pub fn error_server_busy() -> String {
+    server_busy_fault(5000)
+}
+
+/// SOAP fault asking the client to back off for the given time, which
+/// Exchange sends with a `500 Internal Server Error` status
+pub fn server_busy_fault(backoff_ms: u32) -> String {
     fault_response!(
         "ErrorServerBusy",
         "The server is busy.",
         "ErrorServerBusy",
         "The server is busy.",
-        r#"<m:MessageXml xmlns:m="http://schemas.microsoft.com/exchange/services/2006/messages" xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
-          <t:Value Name="BackOffMilliseconds">5000</t:Value>
+        format!(
+            r#"<m:MessageXml xmlns:m="http://schemas.microsoft.com/exchange/services/2006/messages" xmlns:t="http://schemas.microsoft.com/exchange/services/2006/types">
+          <t:Value Name="BackOffMilliseconds">{backoff_ms}</t:Value>
         </m:MessageXml>"#
+        )
+    )
+}
+
+/// Response for an operation whose first response message asks the client
+/// to back off for the given time
+pub fn server_busy_response(op: &str, backoff_ms: u32) -> String {
+    op_response!(
+        op,
+        format!(
+            r#"<m:{op}ResponseMessage ResponseClass="Error">
+          <m:MessageText>The server is busy.</m:MessageText>
+          <m:ResponseCode>ErrorServerBusy</m:ResponseCode>
+          <m:DescriptiveLinkKey>0</m:DescriptiveLinkKey>
+          <m:MessageXml>
+            <t:Value Name="BackOffMilliseconds">{backoff_ms}</t:Value>
+          </m:MessageXml>
+        </m:{op}ResponseMessage>"#
+        )
     )
 }
 
@@ -1379,6 +1569,1074 @@ pub fn batch_delete_folder_mixed_response(success_count: usize, error_count: usi
     op_response!("DeleteFolder", messages)
 }
 
+// ============================================================================
+// Notification Operations Fixtures
+// ============================================================================
+
+/// Response for a `Subscribe` operation
+pub fn subscribe_response(subscription_id: &str, watermark: &str) -> String {
+    success_response!(
+        "Subscribe",
+        format!(
+            "<m:SubscriptionId>{subscription_id}</m:SubscriptionId>\n          <m:Watermark>{watermark}</m:Watermark>"
+        )
+    )
+}
+
+/// Response for a `GetEvents` operation reporting a new message in a folder
+pub fn get_events_response(subscription_id: &str, item_id: &str, watermark: &str, more_events: bool) -> String {
+    success_response!(
+        "GetEvents",
+        format!(
+            r"<m:Notification>
+            <t:SubscriptionId>{subscription_id}</t:SubscriptionId>
+            <t:PreviousWatermark>previous-{watermark}</t:PreviousWatermark>
+            <t:MoreEvents>{more_events}</t:MoreEvents>
+            <t:NewMailEvent>
+              <t:Watermark>{watermark}</t:Watermark>
+              <t:TimeStamp>2024-01-15T10:30:00Z</t:TimeStamp>
+              {}
+              {}
+            </t:NewMailEvent>
+          </m:Notification>",
+            id_xml!("ItemId", item_id, "CQAAAA=="),
+            id_xml!("ParentFolderId", "inbox-folder-id", "AQAAAA==")
+        )
+    )
+}
+
+/// Response for a `GetEvents` operation with only a status event
+pub fn get_events_status_response(subscription_id: &str, watermark: &str) -> String {
+    success_response!(
+        "GetEvents",
+        format!(
+            r"<m:Notification>
+            <t:SubscriptionId>{subscription_id}</t:SubscriptionId>
+            <t:PreviousWatermark>{watermark}</t:PreviousWatermark>
+            <t:MoreEvents>false</t:MoreEvents>
+            <t:StatusEvent>
+              <t:Watermark>{watermark}</t:Watermark>
+            </t:StatusEvent>
+          </m:Notification>"
+        )
+    )
+}
+
+/// Response for an `Unsubscribe` operation
+pub fn unsubscribe_response() -> String {
+    simple_success!("Unsubscribe")
+}
+
+/// Response for a `GetEvents` operation on an expired subscription
+pub fn error_expired_subscription() -> String {
+    error_response!("GetEvents", "ErrorExpiredSubscription", "The subscription has expired.")
+}
+
+/// Response for a `Subscribe` operation with a watermark the server can no longer resume from
+pub fn error_invalid_watermark() -> String {
+    error_response!("Subscribe", "ErrorInvalidWatermark", "The watermark is invalid.")
+}
+
+/// Body of a `SendNotification` request sent by the server to a push
+/// subscription's callback URL, reporting a new message
+pub fn send_notification_request(subscription_id: &str, item_id: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <m:SendNotification {NS}>
+      <m:ResponseMessages>
+        <m:SendNotificationResponseMessage ResponseClass="Success">
+          <m:ResponseCode>NoError</m:ResponseCode>
+          <m:Notification>
+            <t:SubscriptionId>{subscription_id}</t:SubscriptionId>
+            <t:PreviousWatermark>wm-1</t:PreviousWatermark>
+            <t:MoreEvents>false</t:MoreEvents>
+            <t:NewMailEvent>
+              <t:Watermark>wm-2</t:Watermark>
+              <t:TimeStamp>2024-01-15T10:30:00Z</t:TimeStamp>
+              {}
+              {}
+            </t:NewMailEvent>
+          </m:Notification>
+        </m:SendNotificationResponseMessage>
+      </m:ResponseMessages>
+    </m:SendNotification>
+  </soap:Body>
+</soap:Envelope>"#,
+        id_xml!("ItemId", item_id, "CQAAAA=="),
+        id_xml!("ParentFolderId", "inbox-folder-id", "AQAAAA==")
+    )
+}
+
+// ============================================================================
+// Autodiscover Fixtures
+// ============================================================================
+
+/// SOAP Autodiscover `GetUserSettings` response with the given EWS URL and
+/// CAS version (e.g. `15.01.2507.006`)
+pub fn autodiscover_soap_response(ews_url: &str, cas_version: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" xmlns:a="http://www.w3.org/2005/08/addressing">
+  <s:Body>
+    <GetUserSettingsResponseMessage xmlns="http://schemas.microsoft.com/exchange/2010/Autodiscover">
+      <Response xmlns:i="http://www.w3.org/2001/XMLSchema-instance">
+        <ErrorCode>NoError</ErrorCode>
+        <ErrorMessage />
+        <UserResponses>
+          <UserResponse>
+            <ErrorCode>NoError</ErrorCode>
+            <ErrorMessage>No error.</ErrorMessage>
+            <RedirectTarget i:nil="true" />
+            <UserSettingErrors />
+            <UserSettings>
+              <UserSetting i:type="StringSetting">
+                <Name>ExternalEwsUrl</Name>
+                <Value>{ews_url}</Value>
+              </UserSetting>
+              <UserSetting i:type="StringSetting">
+                <Name>CasVersion</Name>
+                <Value>{cas_version}</Value>
+              </UserSetting>
+            </UserSettings>
+          </UserResponse>
+        </UserResponses>
+      </Response>
+    </GetUserSettingsResponseMessage>
+  </s:Body>
+</s:Envelope>"#
+    )
+}
+
+/// Wraps the content of a POX Autodiscover `Account` element in a response
+macro_rules! pox_account {
+    ($account:expr) => {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<Autodiscover xmlns="http://schemas.microsoft.com/exchange/autodiscover/responseschema/2006">
+  <Response xmlns="http://schemas.microsoft.com/exchange/autodiscover/outlook/responseschema/2006a">
+    <Account>
+      {}
+    </Account>
+  </Response>
+</Autodiscover>"#,
+            $account
+        )
+    };
+}
+
+/// POX Autodiscover response with the given EWS URL and hexadecimal server
+/// version (e.g. `738180DA`)
+pub fn autodiscover_pox_response(ews_url: &str, server_version: &str) -> String {
+    pox_account!(format!(
+        r"<AccountType>email</AccountType>
+      <Action>settings</Action>
+      <Protocol>
+        <Type>EXPR</Type>
+        <ServerVersion>{server_version}</ServerVersion>
+        <EwsUrl>{ews_url}</EwsUrl>
+      </Protocol>"
+    ))
+}
+
+/// POX Autodiscover response redirecting to another email address
+pub fn autodiscover_pox_redirect_addr_response(email: &str) -> String {
+    pox_account!(format!(
+        r"<Action>redirectAddr</Action>
+      <RedirectAddr>{email}</RedirectAddr>"
+    ))
+}
+
+/// POX Autodiscover response redirecting to another Autodiscover URL
+pub fn autodiscover_pox_redirect_url_response(url: &str) -> String {
+    pox_account!(format!(
+        r"<Action>redirectUrl</Action>
+      <RedirectUrl>{url}</RedirectUrl>"
+    ))
+}
+
+// ============================================================================
+// Calendar Operations Fixtures
+// ============================================================================
+
+/// A `CalendarItem` element as returned by `FindItem` in a calendar view
+fn calendar_view_item_xml(item_id: &str, subject: &str, start: &str, end: &str, item_type: &str) -> String {
+    format!(
+        r"<t:CalendarItem>
+              {}
+              <t:Subject>{subject}</t:Subject>
+              <t:Start>{start}</t:Start>
+              <t:End>{end}</t:End>
+              <t:IsAllDayEvent>false</t:IsAllDayEvent>
+              <t:LegacyFreeBusyStatus>Busy</t:LegacyFreeBusyStatus>
+              <t:Location>Room 1</t:Location>
+              <t:CalendarItemType>{item_type}</t:CalendarItemType>
+              <t:IsMeeting>true</t:IsMeeting>
+            </t:CalendarItem>",
+        id_xml!("ItemId", item_id, "DwAAAA==")
+    )
+}
+
+/// Response for a `FindItem` operation with a `CalendarView`, containing two
+/// occurrences of a recurring meeting
+pub fn calendar_view_response(includes_last: bool) -> String {
+    success_response!(
+        "FindItem",
+        format!(
+            r#"<m:RootFolder TotalItemsInView="2" IncludesLastItemInRange="{includes_last}">
+            <t:Items>
+              {}
+              {}
+            </t:Items>
+          </m:RootFolder>"#,
+            calendar_view_item_xml(
+                "occurrence-1",
+                "Weekly sync",
+                "2024-01-15T10:30:00Z",
+                "2024-01-15T11:00:00Z",
+                "Occurrence"
+            ),
+            calendar_view_item_xml(
+                "occurrence-2",
+                "Weekly sync",
+                "2024-01-22T10:30:00Z",
+                "2024-01-22T11:00:00Z",
+                "Exception"
+            )
+        )
+    )
+}
+
+/// Response for a `GetItem` operation returning a meeting with attendees
+pub fn get_calendar_item_response(item_id: &str) -> String {
+    success_response!(
+        "GetItem",
+        format!(
+            r#"<m:Items>
+            <t:CalendarItem>
+              {}
+              <t:Subject>Planning</t:Subject>
+              <t:Body BodyType="Text">Agenda &amp; notes</t:Body>
+              <t:Start>2024-01-15T10:30:00Z</t:Start>
+              <t:End>2024-01-15T11:30:00Z</t:End>
+              <t:IsAllDayEvent>false</t:IsAllDayEvent>
+              <t:LegacyFreeBusyStatus>Busy</t:LegacyFreeBusyStatus>
+              <t:Location>Room 1</t:Location>
+              <t:IsMeeting>true</t:IsMeeting>
+              <t:IsCancelled>false</t:IsCancelled>
+              <t:CalendarItemType>Single</t:CalendarItemType>
+              <t:MyResponseType>Organizer</t:MyResponseType>
+              <t:Organizer>
+                <t:Mailbox>
+                  <t:Name>Organizer</t:Name>
+                  <t:EmailAddress>organizer@example.com</t:EmailAddress>
+                </t:Mailbox>
+              </t:Organizer>
+              <t:RequiredAttendees>
+                <t:Attendee>
+                  <t:Mailbox>
+                    <t:Name>Alice</t:Name>
+                    <t:EmailAddress>alice@example.com</t:EmailAddress>
+                  </t:Mailbox>
+                  <t:ResponseType>Accept</t:ResponseType>
+                </t:Attendee>
+              </t:RequiredAttendees>
+              <t:OptionalAttendees>
+                <t:Attendee>
+                  <t:Mailbox>
+                    <t:EmailAddress>bob@example.com</t:EmailAddress>
+                  </t:Mailbox>
+                  <t:ResponseType>NoResponseReceived</t:ResponseType>
+                </t:Attendee>
+              </t:OptionalAttendees>
+              <t:UID>040000008200E00074C5B7101A82E008</t:UID>
+              <t:StartTimeZone Id="W. Europe Standard Time" Name="(UTC+01:00) Amsterdam, Berlin" />
+              <t:EndTimeZone Id="W. Europe Standard Time" Name="(UTC+01:00) Amsterdam, Berlin" />
+            </t:CalendarItem>
+          </m:Items>"#,
+            id_xml!("ItemId", item_id, "DwAAAA==")
+        )
+    )
+}
+
+/// Response for a `GetItem` operation returning the master of a weekly
+/// meeting, with a moved and a deleted occurrence and the full definition of
+/// its time zone
+pub fn get_recurring_calendar_item_response(item_id: &str) -> String {
+    success_response!(
+        "GetItem",
+        format!(
+            r#"<m:Items>
+            <t:CalendarItem>
+              {}
+              <t:Subject>Weekly sync</t:Subject>
+              <t:Start>2024-01-15T10:30:00Z</t:Start>
+              <t:End>2024-01-15T11:30:00Z</t:End>
+              <t:IsAllDayEvent>false</t:IsAllDayEvent>
+              <t:LegacyFreeBusyStatus>Busy</t:LegacyFreeBusyStatus>
+              <t:Location>Room 1</t:Location>
+              <t:IsMeeting>true</t:IsMeeting>
+              <t:CalendarItemType>RecurringMaster</t:CalendarItemType>
+              <t:Organizer>
+                <t:Mailbox>
+                  <t:Name>Organizer</t:Name>
+                  <t:EmailAddress>organizer@example.com</t:EmailAddress>
+                </t:Mailbox>
+              </t:Organizer>
+              <t:RequiredAttendees>
+                <t:Attendee>
+                  <t:Mailbox>
+                    <t:EmailAddress>alice@example.com</t:EmailAddress>
+                  </t:Mailbox>
+                  <t:ResponseType>Accept</t:ResponseType>
+                </t:Attendee>
+              </t:RequiredAttendees>
+              <t:Recurrence>
+                <t:WeeklyRecurrence>
+                  <t:Interval>1</t:Interval>
+                  <t:DaysOfWeek>Monday</t:DaysOfWeek>
+                </t:WeeklyRecurrence>
+                <t:EndDateRecurrence>
+                  <t:StartDate>2024-01-15+01:00</t:StartDate>
+                  <t:EndDate>2024-03-25+01:00</t:EndDate>
+                </t:EndDateRecurrence>
+              </t:Recurrence>
+              <t:ModifiedOccurrences>
+                <t:Occurrence>
+                  {}
+                  <t:Start>2024-01-29T13:00:00Z</t:Start>
+                  <t:End>2024-01-29T14:00:00Z</t:End>
+                  <t:OriginalStart>2024-01-29T10:30:00Z</t:OriginalStart>
+                </t:Occurrence>
+              </t:ModifiedOccurrences>
+              <t:DeletedOccurrences>
+                <t:DeletedOccurrence>
+                  <t:Start>2024-01-22T10:30:00Z</t:Start>
+                </t:DeletedOccurrence>
+              </t:DeletedOccurrences>
+              <t:UID>040000008200E00074C5B7101A82E008</t:UID>
+              <t:StartTimeZone Id="W. Europe Standard Time" Name="(UTC+01:00) Amsterdam, Berlin">
+                <t:Periods>
+                  <t:Period Bias="-PT1H" Name="Standard" Id="trule:Microsoft/Registry/W. Europe Standard Time/2006-Standard"/>
+                  <t:Period Bias="-PT2H" Name="Daylight" Id="trule:Microsoft/Registry/W. Europe Standard Time/2006-Daylight"/>
+                </t:Periods>
+                <t:TransitionsGroups>
+                  <t:TransitionsGroup Id="0">
+                    <t:RecurringDayTransition>
+                      <t:To Kind="Period">trule:Microsoft/Registry/W. Europe Standard Time/2006-Daylight</t:To>
+                      <t:TimeOffset>PT2H</t:TimeOffset>
+                      <t:Month>3</t:Month>
+                      <t:DayOfWeek>Sunday</t:DayOfWeek>
+                      <t:Occurrence>-1</t:Occurrence>
+                    </t:RecurringDayTransition>
+                    <t:RecurringDayTransition>
+                      <t:To Kind="Period">trule:Microsoft/Registry/W. Europe Standard Time/2006-Standard</t:To>
+                      <t:TimeOffset>PT3H</t:TimeOffset>
+                      <t:Month>10</t:Month>
+                      <t:DayOfWeek>Sunday</t:DayOfWeek>
+                      <t:Occurrence>-1</t:Occurrence>
+                    </t:RecurringDayTransition>
+                  </t:TransitionsGroup>
+                </t:TransitionsGroups>
+                <t:Transitions>
+                  <t:Transition>
+                    <t:To Kind="Group">0</t:To>
+                  </t:Transition>
+                </t:Transitions>
+              </t:StartTimeZone>
+              <t:EndTimeZone Id="W. Europe Standard Time" Name="(UTC+01:00) Amsterdam, Berlin" />
+            </t:CalendarItem>
+          </m:Items>"#,
+            id_xml!("ItemId", item_id, "DwAAAA=="),
+            id_xml!("ItemId", "exception-1", "DwAAAA==")
+        )
+    )
+}
+
+/// Response for a successful `UpdateItem` operation on a `CalendarItem`
+pub fn update_calendar_item_response(item_id: &str, change_key: &str) -> String {
+    op_response!(
+        "UpdateItem",
+        response_message_xml!(
+            "UpdateItem",
+            "Success",
+            "NoError",
+            update_item_success_xml!("CalendarItem", item_id, change_key)
+        )
+    )
+}
+
+/// Response for a `GetItem` operation returning a meeting request
+pub fn get_meeting_request_response(item_id: &str, calendar_item_id: &str) -> String {
+    success_response!(
+        "GetItem",
+        format!(
+            r"<m:Items>
+            <t:MeetingRequest>
+              {}
+              <t:Subject>Planning</t:Subject>
+              <t:ResponseType>NoResponseReceived</t:ResponseType>
+              {}
+              <t:IsOutOfDate>false</t:IsOutOfDate>
+              <t:HasBeenProcessed>true</t:HasBeenProcessed>
+              <t:UID>040000008200E00074C5B7101A82E008</t:UID>
+              <t:Start>2024-01-15T10:30:00Z</t:Start>
+              <t:End>2024-01-15T11:30:00Z</t:End>
+              <t:Location>Room 1</t:Location>
+            </t:MeetingRequest>
+          </m:Items>",
+            id_xml!("ItemId", item_id, "CQAAAA=="),
+            id_xml!("AssociatedCalendarItemId", calendar_item_id, "DwAAAA==")
+        )
+    )
+}
+
+// ============================================================================
+// Availability Operations Fixtures
+// ============================================================================
+
+/// Response for a `GetUserAvailability` operation with one available
+/// attendee, one unknown attendee and two suggestions
+pub fn get_user_availability_response() -> String {
+    soap!(format!(
+        r#"<GetUserAvailabilityResponse xmlns="http://schemas.microsoft.com/exchange/services/2006/messages">
+      <FreeBusyResponseArray>
+        <FreeBusyResponse>
+          <ResponseMessage ResponseClass="Success">
+            <ResponseCode>NoError</ResponseCode>
+          </ResponseMessage>
+          <FreeBusyView>
+            <FreeBusyViewType xmlns="http://schemas.microsoft.com/exchange/services/2006/types">DetailedMerged</FreeBusyViewType>
+            <MergedFreeBusy xmlns="http://schemas.microsoft.com/exchange/services/2006/types">0022</MergedFreeBusy>
+            <CalendarEventArray xmlns="http://schemas.microsoft.com/exchange/services/2006/types">
+              <CalendarEvent>
+                <StartTime>2024-01-15T11:00:00</StartTime>
+                <EndTime>2024-01-15T12:00:00</EndTime>
+                <BusyType>Busy</BusyType>
+                <CalendarEventDetails>
+                  <ID>{}</ID>
+                  <Subject>Review</Subject>
+                  <Location>Room 2</Location>
+                  <IsMeeting>true</IsMeeting>
+                  <IsRecurring>false</IsRecurring>
+                  <IsException>false</IsException>
+                  <IsReminderSet>true</IsReminderSet>
+                  <IsPrivate>false</IsPrivate>
+                </CalendarEventDetails>
+              </CalendarEvent>
+            </CalendarEventArray>
+          </FreeBusyView>
+        </FreeBusyResponse>
+        <FreeBusyResponse>
+          <ResponseMessage ResponseClass="Error">
+            <MessageText>No mailbox with such guid.</MessageText>
+            <ResponseCode>ErrorMailRecipientNotFound</ResponseCode>
+            <DescriptiveLinkKey>0</DescriptiveLinkKey>
+          </ResponseMessage>
+        </FreeBusyResponse>
+      </FreeBusyResponseArray>
+      <SuggestionsResponse>
+        <ResponseMessage ResponseClass="Success">
+          <ResponseCode>NoError</ResponseCode>
+        </ResponseMessage>
+        <SuggestionDayResultArray>
+          <SuggestionDayResult xmlns="http://schemas.microsoft.com/exchange/services/2006/types">
+            <Date>2024-01-15T00:00:00</Date>
+            <DayQuality>Good</DayQuality>
+            <SuggestionArray>
+              <Suggestion>
+                <MeetingTime>2024-01-15T12:00:00</MeetingTime>
+                <IsWorkTime>true</IsWorkTime>
+                <SuggestionQuality>Good</SuggestionQuality>
+              </Suggestion>
+              <Suggestion>
+                <MeetingTime>2024-01-15T10:00:00</MeetingTime>
+                <IsWorkTime>true</IsWorkTime>
+                <SuggestionQuality>Excellent</SuggestionQuality>
+              </Suggestion>
+            </SuggestionArray>
+          </SuggestionDayResult>
+        </SuggestionDayResultArray>
+      </SuggestionsResponse>
+    </GetUserAvailabilityResponse>"#,
+        "event-1"
+    ))
+}
+
+// ============================================================================
+// Contact Operations Fixtures
+// ============================================================================
+
+/// Response for a `FindItem` operation on a contacts folder, containing two
+/// contacts
+pub fn find_contacts_response(includes_last: bool) -> String {
+    success_response!(
+        "FindItem",
+        format!(
+            r#"<m:RootFolder IndexedPagingOffset="2" TotalItemsInView="3" IncludesLastItemInRange="{includes_last}">
+            <t:Items>
+              <t:Contact>
+                {}
+                <t:DisplayName>Alice Smith</t:DisplayName>
+                <t:GivenName>Alice</t:GivenName>
+                <t:Surname>Smith</t:Surname>
+                <t:EmailAddresses>
+                  <t:Entry Key="EmailAddress1">alice@example.com</t:Entry>
+                </t:EmailAddresses>
+              </t:Contact>
+              <t:Contact>
+                {}
+                <t:DisplayName>Bob Jones</t:DisplayName>
+              </t:Contact>
+            </t:Items>
+          </m:RootFolder>"#,
+            id_xml!("ItemId", "contact-1", "EQAAAA=="),
+            id_xml!("ItemId", "contact-2", "EQAAAA==")
+        )
+    )
+}
+
+/// Response for a `GetItem` operation returning a contact with email
+/// addresses, phone numbers and a physical address
+pub fn get_contact_response(item_id: &str) -> String {
+    success_response!(
+        "GetItem",
+        format!(
+            r#"<m:Items>
+            <t:Contact>
+              {}
+              <t:Body BodyType="Text">Met at the conference</t:Body>
+              <t:DisplayName>Alice Smith</t:DisplayName>
+              <t:GivenName>Alice</t:GivenName>
+              <t:CompanyName>Contoso</t:CompanyName>
+              <t:EmailAddresses>
+                <t:Entry Key="EmailAddress1">SMTP:alice@example.com</t:Entry>
+                <t:Entry Key="EmailAddress2">alice@home.example</t:Entry>
+              </t:EmailAddresses>
+              <t:PhysicalAddresses>
+                <t:Entry Key="Business">
+                  <t:Street>1 Main Street</t:Street>
+                  <t:City>Springfield</t:City>
+                  <t:CountryOrRegion>USA</t:CountryOrRegion>
+                  <t:PostalCode>12345</t:PostalCode>
+                </t:Entry>
+              </t:PhysicalAddresses>
+              <t:PhoneNumbers>
+                <t:Entry Key="BusinessPhone">+1 555 0100</t:Entry>
+                <t:Entry Key="MobilePhone">+1 555 0199</t:Entry>
+                <t:Entry Key="HomePhone" />
+              </t:PhoneNumbers>
+              <t:Birthday>1990-02-01T00:00:00Z</t:Birthday>
+              <t:JobTitle>Engineer</t:JobTitle>
+              <t:Surname>Smith</t:Surname>
+            </t:Contact>
+          </m:Items>"#,
+            id_xml!("ItemId", item_id, "EQAAAA==")
+        )
+    )
+}
+
+/// Response for a successful `CreateItem` operation creating a `Contact`
+pub fn create_contact_response(item_id: &str) -> String {
+    item_success!("CreateItem", "Contact", item_id)
+}
+
+/// Response for a successful `UpdateItem` operation on a `Contact`
+pub fn update_contact_response(item_id: &str, change_key: &str) -> String {
+    op_response!(
+        "UpdateItem",
+        response_message_xml!(
+            "UpdateItem",
+            "Success",
+            "NoError",
+            update_item_success_xml!("Contact", item_id, change_key)
+        )
+    )
+}
+
+// ============================================================================
+// Server Version Fixtures
+// ============================================================================
+
+/// Adds a `Version` identifier to the `ServerVersionInfo` header of a
+/// response, so that the client learns which version the server runs
+pub fn with_server_version(response: &str, version: &str) -> String {
+    response.replacen(
+        "<h:ServerVersionInfo ",
+        &format!(r#"<h:ServerVersionInfo Version="{version}" "#),
+        1,
+    )
+}
+
+// ============================================================================
+// Task Operations Fixtures
+// ============================================================================
+
+/// Response for a `FindItem` operation on a tasks folder, containing a task
+/// and a flagged message
+pub fn find_tasks_response(includes_last: bool) -> String {
+    success_response!(
+        "FindItem",
+        format!(
+            r#"<m:RootFolder IndexedPagingOffset="2" TotalItemsInView="2" IncludesLastItemInRange="{includes_last}">
+            <t:Items>
+              <t:Task>
+                {}
+                <t:Subject>Write the report</t:Subject>
+                <t:ReminderIsSet>false</t:ReminderIsSet>
+                <t:DueDate>2024-01-31T00:00:00Z</t:DueDate>
+                <t:IsComplete>false</t:IsComplete>
+                <t:IsRecurring>false</t:IsRecurring>
+                <t:PercentComplete>50</t:PercentComplete>
+                <t:Status>InProgress</t:Status>
+              </t:Task>
+              <t:Message>
+                {}
+                <t:Subject>Flagged message</t:Subject>
+              </t:Message>
+            </t:Items>
+          </m:RootFolder>"#,
+            id_xml!("ItemId", "task-1", "EwAAAA=="),
+            id_xml!("ItemId", "message-1", "CQAAAA==")
+        )
+    )
+}
+
+/// Response for a `GetItem` operation returning a recurring task with a
+/// reminder
+pub fn get_task_response(item_id: &str) -> String {
+    success_response!(
+        "GetItem",
+        format!(
+            r#"<m:Items>
+            <t:Task>
+              {}
+              <t:Subject>Send the monthly report</t:Subject>
+              <t:Body BodyType="Text">To the whole team</t:Body>
+              <t:ReminderDueBy>2024-01-30T09:00:00Z</t:ReminderDueBy>
+              <t:ReminderIsSet>true</t:ReminderIsSet>
+              <t:DueDate>2024-01-31T00:00:00Z</t:DueDate>
+              <t:IsComplete>false</t:IsComplete>
+              <t:IsRecurring>true</t:IsRecurring>
+              <t:PercentComplete>0</t:PercentComplete>
+              <t:Recurrence>
+                <t:RelativeMonthlyRecurrence>
+                  <t:Interval>1</t:Interval>
+                  <t:DaysOfWeek>Weekday</t:DaysOfWeek>
+                  <t:DayOfWeekIndex>Last</t:DayOfWeekIndex>
+                </t:RelativeMonthlyRecurrence>
+                <t:NumberedRecurrence>
+                  <t:StartDate>2024-01-31Z</t:StartDate>
+                  <t:NumberOfOccurrences>12</t:NumberOfOccurrences>
+                </t:NumberedRecurrence>
+              </t:Recurrence>
+              <t:StartDate>2024-01-15T00:00:00Z</t:StartDate>
+              <t:Status>NotStarted</t:Status>
+            </t:Task>
+          </m:Items>"#,
+            id_xml!("ItemId", item_id, "EwAAAA==")
+        )
+    )
+}
+
+/// Response for a successful `CreateItem` operation creating a `Task`
+pub fn create_task_response(item_id: &str) -> String {
+    item_success!("CreateItem", "Task", item_id)
+}
+
+/// Response for a successful `UpdateItem` operation on a `Task`
+pub fn update_task_response(item_id: &str, change_key: &str) -> String {
+    op_response!(
+        "UpdateItem",
+        response_message_xml!(
+            "UpdateItem",
+            "Success",
+            "NoError",
+            update_item_success_xml!("Task", item_id, change_key)
+        )
+    )
+}
+
+// ============================================================================
+// Directory Operations Fixtures
+// ============================================================================
+
+/// Response for a `ResolveNames` operation matching a user of the directory,
+/// with contact details, and a distribution list
+pub fn resolve_names_response() -> String {
+    op_response!(
+        "ResolveNames",
+        response_message_xml!(
+            "ResolveNames",
+            "Warning",
+            "ErrorNameResolutionMultipleResults",
+            r#"<m:ResolutionSet TotalItemsInView="2" IncludesLastItemInRange="true">
+            <t:Resolution>
+              <t:Mailbox>
+                <t:Name>Alice Smith</t:Name>
+                <t:EmailAddress>alice@example.com</t:EmailAddress>
+                <t:RoutingType>SMTP</t:RoutingType>
+                <t:MailboxType>Mailbox</t:MailboxType>
+              </t:Mailbox>
+              <t:Contact>
+                <t:DisplayName>Alice Smith</t:DisplayName>
+                <t:GivenName>Alice</t:GivenName>
+                <t:CompanyName>Example</t:CompanyName>
+                <t:EmailAddresses>
+                  <t:Entry Key="EmailAddress1">SMTP:alice@example.com</t:Entry>
+                </t:EmailAddresses>
+                <t:PhoneNumbers>
+                  <t:Entry Key="BusinessPhone">+1 555 0100</t:Entry>
+                </t:PhoneNumbers>
+                <t:Department>Engineering</t:Department>
+                <t:JobTitle>Engineer</t:JobTitle>
+                <t:Surname>Smith</t:Surname>
+                <t:ContactSource>ActiveDirectory</t:ContactSource>
+              </t:Contact>
+            </t:Resolution>
+            <t:Resolution>
+              <t:Mailbox>
+                <t:Name>All Engineering</t:Name>
+                <t:EmailAddress>engineering@example.com</t:EmailAddress>
+                <t:RoutingType>SMTP</t:RoutingType>
+                <t:MailboxType>PublicDL</t:MailboxType>
+              </t:Mailbox>
+            </t:Resolution>
+          </m:ResolutionSet>"#
+        )
+    )
+}
+
+/// Response for a `ResolveNames` operation without any match
+pub fn resolve_names_no_results_response() -> String {
+    op_response!(
+        "ResolveNames",
+        response_error_xml!("ResolveNames", "ErrorNameResolutionNoResults", "No results were found.")
+    )
+}
+
+/// Response for an `ExpandDL` operation listing two members
+pub fn expand_dl_response() -> String {
+    success_response!(
+        "ExpandDL",
+        r#"<m:DLExpansion TotalItemsInView="2" IncludesLastItemInRange="true">
+            <t:Mailbox>
+              <t:Name>Alice Smith</t:Name>
+              <t:EmailAddress>alice@example.com</t:EmailAddress>
+              <t:RoutingType>SMTP</t:RoutingType>
+              <t:MailboxType>Mailbox</t:MailboxType>
+            </t:Mailbox>
+            <t:Mailbox>
+              <t:Name>Bob Jones</t:Name>
+              <t:EmailAddress>bob@example.com</t:EmailAddress>
+              <t:RoutingType>SMTP</t:RoutingType>
+              <t:MailboxType>Mailbox</t:MailboxType>
+            </t:Mailbox>
+          </m:DLExpansion>"#
+    )
+}
+
+/// Response for a `FindPeople` operation, which is not wrapped in response
+/// messages, returning the first of three people
+pub fn find_people_response() -> String {
+    soap!(format!(
+        r#"<m:FindPeopleResponse ResponseClass="Success" {NS}>
+      <m:ResponseCode>NoError</m:ResponseCode>
+      <m:People>
+        <t:Persona>
+          <t:PersonaId Id="AAUQAA=="/>
+          <t:PersonaType>Person</t:PersonaType>
+          <t:DisplayName>Alice Smith</t:DisplayName>
+          <t:GivenName>Alice</t:GivenName>
+          <t:Surname>Smith</t:Surname>
+          <t:EmailAddress>
+            <t:Name>Alice Smith</t:Name>
+            <t:EmailAddress>alice@example.com</t:EmailAddress>
+            <t:RoutingType>SMTP</t:RoutingType>
+          </t:EmailAddress>
+          <t:RelevanceScore>2147483647</t:RelevanceScore>
+        </t:Persona>
+      </m:People>
+      <m:TotalNumberOfPeopleInView>3</m:TotalNumberOfPeopleInView>
+      <m:FirstMatchingRowIndex>0</m:FirstMatchingRowIndex>
+      <m:FirstLoadedRowIndex>0</m:FirstLoadedRowIndex>
+    </m:FindPeopleResponse>"#
+    ))
+}
+
+/// Response for a `FindPeople` operation searching the user's contacts,
+/// returning Alice, also found in the directory by
+/// [`find_people_response`] but less relevant here, and Carol
+pub fn find_people_contacts_response() -> String {
+    soap!(format!(
+        r#"<m:FindPeopleResponse ResponseClass="Success" {NS}>
+      <m:ResponseCode>NoError</m:ResponseCode>
+      <m:People>
+        <t:Persona>
+          <t:PersonaId Id="AAUQAB=="/>
+          <t:PersonaType>Person</t:PersonaType>
+          <t:DisplayName>Alice Smith</t:DisplayName>
+          <t:EmailAddress>
+            <t:Name>Alice Smith</t:Name>
+            <t:EmailAddress>Alice@example.com</t:EmailAddress>
+            <t:RoutingType>SMTP</t:RoutingType>
+          </t:EmailAddress>
+          <t:RelevanceScore>100</t:RelevanceScore>
+        </t:Persona>
+        <t:Persona>
+          <t:PersonaId Id="AAUQAC=="/>
+          <t:PersonaType>Person</t:PersonaType>
+          <t:DisplayName>Carol White</t:DisplayName>
+          <t:EmailAddress>
+            <t:Name>Carol White</t:Name>
+            <t:EmailAddress>carol@example.com</t:EmailAddress>
+            <t:RoutingType>SMTP</t:RoutingType>
+          </t:EmailAddress>
+          <t:RelevanceScore>200</t:RelevanceScore>
+        </t:Persona>
+      </m:People>
+      <m:TotalNumberOfPeopleInView>2</m:TotalNumberOfPeopleInView>
+      <m:FirstMatchingRowIndex>0</m:FirstMatchingRowIndex>
+      <m:FirstLoadedRowIndex>0</m:FirstLoadedRowIndex>
+    </m:FindPeopleResponse>"#
+    ))
+}
+
+// ============================================================================
+// Out of Office Fixtures
+// ============================================================================
+
+/// Response for a `GetUserOofSettings` operation returning scheduled
+/// automatic replies
+pub fn get_user_oof_settings_response() -> String {
+    soap!(
+        r#"<GetUserOofSettingsResponse xmlns="http://schemas.microsoft.com/exchange/services/2006/messages">
+      <ResponseMessage ResponseClass="Success">
+        <ResponseCode>NoError</ResponseCode>
+      </ResponseMessage>
+      <OofSettings xmlns="http://schemas.microsoft.com/exchange/services/2006/types">
+        <OofState>Scheduled</OofState>
+        <ExternalAudience>All</ExternalAudience>
+        <Duration>
+          <StartTime>2024-01-15T00:00:00Z</StartTime>
+          <EndTime>2024-01-22T00:00:00Z</EndTime>
+        </Duration>
+        <InternalReply>
+          <Message>&lt;p&gt;Back on Monday&lt;/p&gt;</Message>
+        </InternalReply>
+        <ExternalReply>
+          <Message>I am out of the office.</Message>
+        </ExternalReply>
+      </OofSettings>
+      <AllowExternalOof>All</AllowExternalOof>
+    </GetUserOofSettingsResponse>"#
+    )
+}
+
+/// Response for a successful `SetUserOofSettings` operation
+pub fn set_user_oof_settings_response() -> String {
+    soap!(
+        r#"<SetUserOofSettingsResponse xmlns="http://schemas.microsoft.com/exchange/services/2006/messages">
+      <ResponseMessage ResponseClass="Success">
+        <ResponseCode>NoError</ResponseCode>
+      </ResponseMessage>
+    </SetUserOofSettingsResponse>"#
+    )
+}
+
+/// Response for a `SetUserOofSettings` operation on a mailbox the user may
+/// not change
+pub fn set_user_oof_settings_access_denied_response() -> String {
+    soap!(
+        r#"<SetUserOofSettingsResponse xmlns="http://schemas.microsoft.com/exchange/services/2006/messages">
+      <ResponseMessage ResponseClass="Error">
+        <MessageText>Access is denied.</MessageText>
+        <ResponseCode>ErrorAccessDenied</ResponseCode>
+        <DescriptiveLinkKey>0</DescriptiveLinkKey>
+      </ResponseMessage>
+    </SetUserOofSettingsResponse>"#
+    )
+}
+
+// ============================================================================
+// Inbox Rules Fixtures
+// ============================================================================
+
+/// Response for a `GetInboxRules` operation, which is not wrapped in response
+/// messages, returning a moving rule and a forwarding rule
+pub fn get_inbox_rules_response() -> String {
+    soap!(format!(
+        r#"<m:GetInboxRulesResponse ResponseClass="Success" {NS}>
+      <m:ResponseCode>NoError</m:ResponseCode>
+      <m:OutlookRuleBlobExists>true</m:OutlookRuleBlobExists>
+      <m:InboxRules>
+        <t:Rule>
+          <t:RuleId>AQAAAJRHm8s=</t:RuleId>
+          <t:DisplayName>Newsletters</t:DisplayName>
+          <t:Priority>1</t:Priority>
+          <t:IsEnabled>true</t:IsEnabled>
+          <t:Conditions>
+            <t:ContainsSubjectStrings>
+              <t:String>Newsletter</t:String>
+            </t:ContainsSubjectStrings>
+          </t:Conditions>
+          <t:Exceptions>
+            <t:Importance>High</t:Importance>
+          </t:Exceptions>
+          <t:Actions>
+            <t:MarkAsRead>true</t:MarkAsRead>
+            <t:MoveToFolder>
+              <t:FolderId Id="AAMkAGNewsletters" ChangeKey="AQAAAA=="/>
+            </t:MoveToFolder>
+            <t:StopProcessingRules>true</t:StopProcessingRules>
+          </t:Actions>
+        </t:Rule>
+        <t:Rule>
+          <t:RuleId>AQAAAJRHm8t=</t:RuleId>
+          <t:DisplayName>Forward from boss</t:DisplayName>
+          <t:Priority>2</t:Priority>
+          <t:IsEnabled>false</t:IsEnabled>
+          <t:IsInError>true</t:IsInError>
+          <t:Conditions>
+            <t:FromAddresses>
+              <t:Address>
+                <t:Name>The Boss</t:Name>
+                <t:EmailAddress>boss@example.com</t:EmailAddress>
+                <t:RoutingType>SMTP</t:RoutingType>
+              </t:Address>
+            </t:FromAddresses>
+            <t:SentToMe>true</t:SentToMe>
+          </t:Conditions>
+          <t:Actions>
+            <t:ForwardToRecipients>
+              <t:Address>
+                <t:EmailAddress>assistant@example.com</t:EmailAddress>
+              </t:Address>
+            </t:ForwardToRecipients>
+          </t:Actions>
+        </t:Rule>
+      </m:InboxRules>
+    </m:GetInboxRulesResponse>"#
+    ))
+}
+
+/// Response for a successful `UpdateInboxRules` operation
+pub fn update_inbox_rules_response() -> String {
+    soap!(format!(
+        r#"<m:UpdateInboxRulesResponse ResponseClass="Success" {NS}>
+      <m:ResponseCode>NoError</m:ResponseCode>
+    </m:UpdateInboxRulesResponse>"#
+    ))
+}
+
+/// Response for an `UpdateInboxRules` operation whose rule moves messages to
+/// a folder which does not exist
+pub fn update_inbox_rules_validation_error_response() -> String {
+    soap!(format!(
+        r#"<m:UpdateInboxRulesResponse ResponseClass="Error" {NS}>
+      <m:MessageText>Validation error occurred.</m:MessageText>
+      <m:ResponseCode>ErrorInboxRulesValidationError</m:ResponseCode>
+      <m:DescriptiveLinkKey>0</m:DescriptiveLinkKey>
+      <m:RuleOperationErrors>
+        <t:RuleOperationError>
+          <t:OperationIndex>0</t:OperationIndex>
+          <t:ValidationErrors>
+            <t:RuleValidationError>
+              <t:FieldURI>ActionsMoveToFolder</t:FieldURI>
+              <t:ErrorCode>FolderDoesNotExist</t:ErrorCode>
+              <t:ErrorMessage>The folder does not exist.</t:ErrorMessage>
+              <t:FieldValue>AAMkAGMissing</t:FieldValue>
+            </t:RuleValidationError>
+          </t:ValidationErrors>
+        </t:RuleOperationError>
+      </m:RuleOperationErrors>
+    </m:UpdateInboxRulesResponse>"#
+    ))
+}
+
+// ============================================================================
+// Delegate Fixtures
+// ============================================================================
+
+/// Writes a successful delegate operation response with the given delegate
+/// response messages
+fn delegate_response(op: &str, messages: &str, deliver_meeting_requests: &str) -> String {
+    soap!(format!(
+        r#"<m:{op}Response ResponseClass="Success" {NS}>
+      <m:ResponseCode>NoError</m:ResponseCode>
+      <m:ResponseMessages>{messages}</m:ResponseMessages>
+      {deliver_meeting_requests}
+    </m:{op}Response>"#
+    ))
+}
+
+/// Response message for a delegate with Calendar and Inbox permissions
+fn delegate_user_message() -> &'static str {
+    r#"<m:DelegateUserResponseMessageType ResponseClass="Success">
+          <m:ResponseCode>NoError</m:ResponseCode>
+          <m:DelegateUser>
+            <t:UserId>
+              <t:SID>S-1-5-21-1333220396-2200287332-232816053-1116</t:SID>
+              <t:PrimarySmtpAddress>assistant@example.com</t:PrimarySmtpAddress>
+              <t:DisplayName>Alex Assistant</t:DisplayName>
+            </t:UserId>
+            <t:DelegatePermissions>
+              <t:CalendarFolderPermissionLevel>Editor</t:CalendarFolderPermissionLevel>
+              <t:TasksFolderPermissionLevel>None</t:TasksFolderPermissionLevel>
+              <t:InboxFolderPermissionLevel>Reviewer</t:InboxFolderPermissionLevel>
+              <t:ContactsFolderPermissionLevel>None</t:ContactsFolderPermissionLevel>
+              <t:NotesFolderPermissionLevel>None</t:NotesFolderPermissionLevel>
+              <t:JournalFolderPermissionLevel>Custom</t:JournalFolderPermissionLevel>
+            </t:DelegatePermissions>
+            <t:ReceiveCopiesOfMeetingMessages>true</t:ReceiveCopiesOfMeetingMessages>
+            <t:ViewPrivateItems>false</t:ViewPrivateItems>
+          </m:DelegateUser>
+        </m:DelegateUserResponseMessageType>"#
+}
+
+/// Response for a `GetDelegate` operation returning one delegate
+pub fn get_delegate_response() -> String {
+    delegate_response(
+        "GetDelegate",
+        delegate_user_message(),
+        "<m:DeliverMeetingRequests>DelegatesAndMe</m:DeliverMeetingRequests>",
+    )
+}
+
+/// Response for an `AddDelegate` operation adding one delegate
+pub fn add_delegate_response() -> String {
+    delegate_response(
+        "AddDelegate",
+        delegate_user_message(),
+        "<m:DeliverMeetingRequests>DelegatesOnly</m:DeliverMeetingRequests>",
+    )
+}
+
+/// Response for an `AddDelegate` operation whose user already is a delegate
+pub fn add_delegate_already_exists_response() -> String {
+    delegate_response(
+        "AddDelegate",
+        r#"<m:DelegateUserResponseMessageType ResponseClass="Error">
+          <m:MessageText>The delegate already exists.</m:MessageText>
+          <m:ResponseCode>ErrorDelegateAlreadyExists</m:ResponseCode>
+          <m:DescriptiveLinkKey>0</m:DescriptiveLinkKey>
+        </m:DelegateUserResponseMessageType>"#,
+        "",
+    )
+}
+
+/// Response for a successful `RemoveDelegate` operation
+pub fn remove_delegate_response() -> String {
+    delegate_response(
+        "RemoveDelegate",
+        r#"<m:DelegateUserResponseMessageType ResponseClass="Success">
+          <m:ResponseCode>NoError</m:ResponseCode>
+        </m:DelegateUserResponseMessageType>"#,
+        "",
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1932,4 +3190,262 @@ mod tests {
         assert_eq!(error_count, 2);
         assert!(response.contains("ErrorItemNotFound"));
     }
+
+    #[test]
+    fn test_subscribe_response() {
+        let response = subscribe_response("sub-1", "wm-1");
+        assert!(response.contains("SubscribeResponse"));
+        assert!(response.contains("<m:SubscriptionId>sub-1</m:SubscriptionId>"));
+        assert!(response.contains("<m:Watermark>wm-1</m:Watermark>"));
+    }
+
+    #[test]
+    fn test_get_events_response() {
+        let response = get_events_response("sub-1", "item-1", "wm-2", true);
+        assert!(response.contains("GetEventsResponse"));
+        assert!(response.contains("<t:NewMailEvent>"));
+        assert!(response.contains("item-1"));
+        assert!(response.contains("<t:MoreEvents>true</t:MoreEvents>"));
+    }
+
+    #[test]
+    fn test_error_expired_subscription() {
+        let response = error_expired_subscription();
+        assert!(response.contains("GetEventsResponse"));
+        assert!(response.contains("ErrorExpiredSubscription"));
+    }
+
+    #[test]
+    fn test_send_notification_request() {
+        let request = send_notification_request("sub-1", "item-1");
+        assert!(request.contains("<m:SendNotification "));
+        assert!(request.contains("sub-1"));
+        assert!(request.contains("item-1"));
+    }
+
+    #[test]
+    fn test_autodiscover_soap_response() {
+        let response = autodiscover_soap_response("https://mail.example.com/EWS/Exchange.asmx", "15.01.2507.006");
+        assert!(response.contains("GetUserSettingsResponseMessage"));
+        assert!(response.contains("<Value>https://mail.example.com/EWS/Exchange.asmx</Value>"));
+        assert!(response.contains("<Value>15.01.2507.006</Value>"));
+    }
+
+    #[test]
+    fn test_autodiscover_pox_redirect_responses() {
+        let response = autodiscover_pox_redirect_addr_response("user@example.org");
+        assert!(response.contains("<Action>redirectAddr</Action>"));
+        assert!(response.contains("user@example.org"));
+
+        let response = autodiscover_pox_redirect_url_response("https://example.org/autodiscover/autodiscover.xml");
+        assert!(response.contains("<Action>redirectUrl</Action>"));
+    }
+
+    #[test]
+    fn test_calendar_view_response() {
+        let response = calendar_view_response(false);
+        assert!(response.contains("FindItemResponse"));
+        assert!(response.contains(r#"IncludesLastItemInRange="false""#));
+        assert!(response.contains("occurrence-1"));
+        assert!(response.contains("occurrence-2"));
+    }
+
+    #[test]
+    fn test_get_calendar_item_response() {
+        let response = get_calendar_item_response("meeting-1");
+        assert!(response.contains("GetItemResponse"));
+        assert!(response.contains("meeting-1"));
+        assert!(response.contains("<t:RequiredAttendees>"));
+    }
+
+    #[test]
+    fn test_get_recurring_calendar_item_response() {
+        let response = get_recurring_calendar_item_response("master-1");
+        assert!(response.contains("master-1"));
+        assert!(response.contains("<t:WeeklyRecurrence>"));
+        assert!(response.contains("<t:ModifiedOccurrences>"));
+        assert!(response.contains("<t:TransitionsGroups>"));
+    }
+
+    #[test]
+    fn test_get_meeting_request_response() {
+        let response = get_meeting_request_response("request-1", "meeting-1");
+        assert!(response.contains("<t:MeetingRequest>"));
+        assert!(response.contains("request-1"));
+        assert!(response.contains(r#"<t:AssociatedCalendarItemId Id="meeting-1""#));
+    }
+
+    #[test]
+    fn test_get_user_availability_response() {
+        let response = get_user_availability_response();
+        assert!(response.contains("<GetUserAvailabilityResponse"));
+        assert!(response.contains("<MergedFreeBusy"));
+        assert!(response.contains("ErrorMailRecipientNotFound"));
+    }
+
+    #[test]
+    fn test_find_contacts_response() {
+        let response = find_contacts_response(false);
+        assert!(response.contains("FindItemResponse"));
+        assert!(response.contains(r#"IndexedPagingOffset="2""#));
+        assert!(response.contains("contact-1"));
+        assert!(response.contains("contact-2"));
+    }
+
+    #[test]
+    fn test_get_contact_response() {
+        let response = get_contact_response("contact-1");
+        assert!(response.contains("GetItemResponse"));
+        assert!(response.contains("contact-1"));
+        assert!(response.contains("<t:PhoneNumbers>"));
+        assert!(response.contains("<t:PhysicalAddresses>"));
+    }
+
+    #[test]
+    fn test_create_and_update_contact_responses() {
+        let response = create_contact_response("contact-1");
+        assert!(response.contains("CreateItemResponse"));
+        assert!(response.contains("<t:Contact>"));
+
+        let response = update_contact_response("contact-1", "EgAAAA==");
+        assert!(response.contains("UpdateItemResponse"));
+        assert!(response.contains("EgAAAA=="));
+    }
+
+    #[test]
+    fn test_find_tasks_response() {
+        let response = find_tasks_response(true);
+        assert!(response.contains("FindItemResponse"));
+        assert!(response.contains("task-1"));
+        assert!(response.contains("<t:Message>"));
+    }
+
+    #[test]
+    fn test_get_task_response() {
+        let response = get_task_response("task-1");
+        assert!(response.contains("GetItemResponse"));
+        assert!(response.contains("task-1"));
+        assert!(response.contains("<t:RelativeMonthlyRecurrence>"));
+    }
+
+    #[test]
+    fn test_create_and_update_task_responses() {
+        let response = create_task_response("task-1");
+        assert!(response.contains("CreateItemResponse"));
+        assert!(response.contains("<t:Task>"));
+
+        let response = update_task_response("task-1", "FAAAAA==");
+        assert!(response.contains("UpdateItemResponse"));
+        assert!(response.contains("FAAAAA=="));
+    }
+
+    #[test]
+    fn test_with_server_version() {
+        let response = with_server_version(&create_task_response("task-1"), "Exchange2013_SP1");
+        assert!(response.contains(r#"<h:ServerVersionInfo Version="Exchange2013_SP1" MajorVersion="15""#));
+    }
+
+    #[test]
+    fn test_resolve_names_responses() {
+        let response = resolve_names_response();
+        assert!(response.contains("ResolveNamesResponse"));
+        assert!(response.contains(r#"ResponseClass="Warning""#));
+        assert!(response.contains("<t:Resolution>"));
+
+        let response = resolve_names_no_results_response();
+        assert!(response.contains("ErrorNameResolutionNoResults"));
+    }
+
+    #[test]
+    fn test_find_people_response() {
+        let response = find_people_response();
+        assert!(response.contains(r#"<m:FindPeopleResponse ResponseClass="Success""#));
+        assert!(response.contains("<t:Persona>"));
+
+        let response = find_people_contacts_response();
+        assert!(response.contains(r#"<t:PersonaId Id="AAUQAC=="/>"#));
+    }
+
+    #[test]
+    fn test_expand_dl_response() {
+        let response = expand_dl_response();
+        assert!(response.contains("ExpandDLResponse"));
+        assert!(response.contains("<m:DLExpansion"));
+    }
+
+    #[test]
+    fn test_user_oof_settings_responses() {
+        let response = get_user_oof_settings_response();
+        assert!(response.contains("<GetUserOofSettingsResponse"));
+        assert!(response.contains("<OofState>Scheduled</OofState>"));
+
+        assert!(set_user_oof_settings_response().contains(r#"ResponseClass="Success""#));
+        assert!(set_user_oof_settings_access_denied_response().contains("ErrorAccessDenied"));
+    }
+
+    #[test]
+    fn test_inbox_rules_responses() {
+        let response = get_inbox_rules_response();
+        assert!(response.contains(r#"<m:GetInboxRulesResponse ResponseClass="Success""#));
+        assert_eq!(response.matches("<t:Rule>").count(), 2);
+
+        assert!(update_inbox_rules_response().contains(r#"ResponseClass="Success""#));
+        assert!(update_inbox_rules_validation_error_response().contains("ErrorInboxRulesValidationError"));
+    }
+
+    #[test]
+    fn test_delegate_responses() {
+        let response = get_delegate_response();
+        assert!(response.contains(r#"<m:GetDelegateResponse ResponseClass="Success""#));
+        assert!(response.contains("<m:DeliverMeetingRequests>DelegatesAndMe</m:DeliverMeetingRequests>"));
+
+        assert!(add_delegate_response().contains("<m:DelegateUser>"));
+        assert!(add_delegate_already_exists_response().contains("ErrorDelegateAlreadyExists"));
+        assert!(remove_delegate_response().contains("RemoveDelegateResponse"));
+    }
+
+    #[test]
+    fn test_get_calendar_folder_permissions_response() {
+        let response = get_calendar_folder_permissions_response("calendar-id");
+        assert!(response.contains("calendar-id"));
+        assert_eq!(response.matches("<t:CalendarPermission>").count(), 2);
+        assert!(response.contains("<t:UnknownEntry>"));
+    }
+
+    #[test]
+    fn test_create_typed_folder_responses() {
+        let response = create_typed_folder_response("SearchFolder", "search-id");
+        assert!(response.contains("<t:SearchFolder>"));
+        assert!(response.contains("search-id"));
+
+        assert!(create_search_folder_invalid_restriction_response().contains("ErrorInvalidRestriction"));
+    }
+
+    #[test]
+    fn test_get_item_with_properties_response() {
+        let response = get_item_with_properties_response("item-1", "Hello");
+        assert!(response.contains(r#"<t:Body BodyType="Text" IsTruncated="false">Hello</t:Body>"#));
+        assert!(response.contains("<t:InternetMessageHeader HeaderName=\"List-Id\">"));
+        assert!(response.contains(r#"PropertyTag="0x1081""#));
+    }
+
+    #[test]
+    fn test_server_busy_response() {
+        let response = server_busy_response("CreateFolder", 50);
+        assert!(response.contains("<m:CreateFolderResponseMessage ResponseClass=\"Error\">"));
+        assert!(response.contains(r#"<t:Value Name="BackOffMilliseconds">50</t:Value>"#));
+    }
+
+    #[test]
+    fn test_typed_folder_responses() {
+        let response =
+            sync_folder_hierarchy_typed_folder_response("state", "ContactsFolder", "contacts-id", "IPF.Contact");
+        assert!(response.contains("<t:ContactsFolder>"));
+        assert!(response.contains("contacts-id"));
+        assert!(!response.contains("UnreadCount"));
+
+        let response = get_typed_folder_response("TasksFolder", "tasks-id", "Tasks", "IPF.Task");
+        assert!(response.contains("<t:TasksFolder>"));
+        assert!(response.contains("<t:FolderClass>IPF.Task</t:FolderClass>"));
+    }
 }
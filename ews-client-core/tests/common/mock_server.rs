@@ -19,6 +19,8 @@ pub struct MockEwsServer {
 }
 
 const EWS_PATH: &str = "/EWS/Exchange.asmx";
+const SOAP_AUTODISCOVER_PATH: &str = "/autodiscover/autodiscover.svc";
+const POX_AUTODISCOVER_PATH: &str = "/autodiscover/autodiscover.xml";
 
 impl MockEwsServer {
     /// Create a new mock EWS server
@@ -160,6 +162,66 @@ impl MockEwsServer {
             .await;
     }
 
+    /// Register a response for the next request of an operation only
+    ///
+    /// Mocks of the same priority are matched in the order they were
+    /// mounted, so this must be called before
+    /// [`register_operation`](Self::register_operation) for the same
+    /// operation.
+    pub async fn register_operation_once(&self, operation: &str, response_body: String) {
+        self.register_operation_once_with_status(operation, 200, response_body)
+            .await;
+    }
+
+    /// Register a response with a custom status code for the next request of
+    /// an operation only, e.g. a SOAP fault sent with
+    /// `500 Internal Server Error`
+    ///
+    /// See [`register_operation_once`](Self::register_operation_once).
+    pub async fn register_operation_once_with_status(&self, operation: &str, status_code: u16, response_body: String) {
+        let operation_with_prefix = format!("<m:{operation}");
+
+        Mock::given(method("POST"))
+            .and(path(EWS_PATH))
+            .and(move |req: &wiremock::Request| String::from_utf8_lossy(&req.body).contains(&operation_with_prefix))
+            .respond_with(
+                ResponseTemplate::new(status_code)
+                    .set_body_string(response_body)
+                    .append_header("Content-Type", "text/xml; charset=utf-8"),
+            )
+            .up_to_n_times(1)
+            .with_priority(1)
+            .named(format!("{operation} one-off operation mock"))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Register a response for the requests of an operation whose body
+    /// contains `needle`
+    ///
+    /// This is for operations sent more than once with different arguments,
+    /// such as `FindPeople` searching several folders.
+    pub async fn register_operation_containing(&self, operation: &str, needle: &str, response_body: String) {
+        let operation_with_prefix = format!("<m:{operation}");
+        let needle = needle.to_string();
+
+        Mock::given(method("POST"))
+            .and(path(EWS_PATH))
+            .and(move |req: &wiremock::Request| {
+                let body = String::from_utf8_lossy(&req.body);
+                body.contains(&operation_with_prefix) && body.contains(&needle)
+            })
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string(response_body)
+                    .append_header("Content-Type", "text/xml; charset=utf-8"),
+            )
+            .with_priority(1)
+            .named(format!("{operation} operation mock"))
+            .mount(&self.server)
+            .await;
+    }
+
     /// Register a response with custom status code
     pub async fn register_response_with_status(&self, status_code: u16, response_body: String) {
         Mock::given(method("POST"))
@@ -200,6 +262,68 @@ impl MockEwsServer {
             .await;
     }
 
+    /// Register a response for SOAP Autodiscover requests
+    /// (`POST /autodiscover/autodiscover.svc`)
+    pub async fn mock_autodiscover_soap(&self, response_body: String) {
+        self.mock_xml_response("POST", SOAP_AUTODISCOVER_PATH, 200, response_body)
+            .await;
+    }
+
+    /// Register a response for POX Autodiscover requests
+    /// (`POST /autodiscover/autodiscover.xml`)
+    pub async fn mock_autodiscover_pox(&self, response_body: String) {
+        self.mock_xml_response("POST", POX_AUTODISCOVER_PATH, 200, response_body)
+            .await;
+    }
+
+    /// Register a redirection for unauthenticated `GET` requests to the POX
+    /// Autodiscover endpoint
+    pub async fn mock_autodiscover_http_redirect(&self, location: &str) {
+        Mock::given(method("GET"))
+            .and(path(POX_AUTODISCOVER_PATH))
+            .respond_with(ResponseTemplate::new(302).append_header("Location", location))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Register a 401 Unauthorized response for all Autodiscover requests
+    pub async fn mock_autodiscover_unauthorized(&self) {
+        for autodiscover_path in [SOAP_AUTODISCOVER_PATH, POX_AUTODISCOVER_PATH] {
+            self.mock_xml_response("POST", autodiscover_path, 401, String::new())
+                .await;
+        }
+    }
+
+    /// Helper to register an XML response for a method and path
+    async fn mock_xml_response(&self, http_method: &str, request_path: &str, status_code: u16, response_body: String) {
+        Mock::given(method(http_method))
+            .and(path(request_path))
+            .respond_with(
+                ResponseTemplate::new(status_code)
+                    .set_body_string(response_body)
+                    .append_header("Content-Type", "text/xml; charset=utf-8"),
+            )
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Get all the requests received so far, whatever their path
+    pub async fn received_requests(&self) -> Vec<wiremock::Request> {
+        self.server.received_requests().await.unwrap_or_default()
+    }
+
+    /// Get the requests received by the EWS endpoint, in the order they
+    /// were received
+    pub async fn received_ews_requests(&self) -> Vec<wiremock::Request> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|request| request.url.path() == EWS_PATH)
+            .collect()
+    }
+
     /// Reset all registered mocks
     pub async fn reset(&self) {
         self.server.reset().await;
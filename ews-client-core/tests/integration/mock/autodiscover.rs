@@ -0,0 +1,257 @@
+//! Integration tests for Autodiscover using mock HTTP servers
+//!
+//! Every host probed by Autodiscover is overridden to point at a local mock
+//! server, so that the probing order and redirections can be tested without
+//! any network access. Hosts which should not answer point at a mock server
+//! without any registered response.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::{
+    EwsError,
+    client::{AutodiscoverClient, BoxFuture, Credentials, SrvRecord, SrvResolver},
+    ews::server_version::ExchangeServerVersion,
+};
+
+const EMAIL: &str = "user@contoso.com";
+const EWS_URL: &str = "https://mail.contoso.com/EWS/Exchange.asmx";
+
+/// SRV resolver returning a fixed set of records
+struct StaticSrvResolver(Vec<SrvRecord>);
+
+impl SrvResolver for StaticSrvResolver {
+    fn lookup_srv<'a>(&'a self, _name: &'a str) -> BoxFuture<'a, Result<Vec<SrvRecord>, EwsError>> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+/// Creates an Autodiscover client with the given host overrides and no SRV
+/// lookups
+fn autodiscover_client(overrides: &[(&str, &MockEwsServer)]) -> AutodiscoverClient {
+    overrides.iter().fold(
+        AutodiscoverClient::new(Credentials::basic("user", "pass"))
+            .unwrap()
+            .without_srv_lookup(),
+        |client, (host, mock)| client.with_host_override(*host, mock.url().parse().unwrap()),
+    )
+}
+
+/// Test discovery through SOAP Autodiscover on the domain itself
+#[tokio::test]
+async fn test_discover_soap_on_domain() {
+    let mock = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    mock.mock_autodiscover_soap(fixtures::autodiscover_soap_response(EWS_URL, "15.01.2507.006"))
+        .await;
+
+    let client = autodiscover_client(&[("contoso.com", &mock), ("autodiscover.contoso.com", &dead)]);
+    let result = client.discover(EMAIL).await;
+    assert!(result.is_ok(), "discover failed: {:?}", result.err());
+
+    let result = result.unwrap();
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+    assert_eq!(result.server_version, Some(ExchangeServerVersion::Exchange2013_SP1));
+    assert_eq!(result.email_address, EMAIL);
+    assert!(result.autodiscover_url.path().ends_with("autodiscover.svc"));
+}
+
+/// Test falling back to POX Autodiscover on the `autodiscover` subdomain
+#[tokio::test]
+async fn test_discover_falls_back_to_autodiscover_subdomain() {
+    let mock = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    mock.mock_autodiscover_pox(fixtures::autodiscover_pox_response(EWS_URL, "738180DA"))
+        .await;
+
+    let client = autodiscover_client(&[("contoso.com", &dead), ("autodiscover.contoso.com", &mock)]);
+    let result = client.discover(EMAIL).await.unwrap();
+
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+    assert_eq!(result.server_version, Some(ExchangeServerVersion::Exchange2010));
+    assert!(result.autodiscover_url.path().ends_with("autodiscover.xml"));
+}
+
+/// Test following a redirection to another email address
+#[tokio::test]
+async fn test_discover_follows_address_redirect() {
+    let contoso = MockEwsServer::new().await;
+    let fabrikam = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    contoso
+        .mock_autodiscover_pox(fixtures::autodiscover_pox_redirect_addr_response("user@fabrikam.com"))
+        .await;
+    fabrikam
+        .mock_autodiscover_soap(fixtures::autodiscover_soap_response(EWS_URL, "15.00.0847.032"))
+        .await;
+
+    let client = autodiscover_client(&[
+        ("contoso.com", &contoso),
+        ("autodiscover.contoso.com", &dead),
+        ("fabrikam.com", &fabrikam),
+        ("autodiscover.fabrikam.com", &dead),
+    ]);
+    let result = client.discover(EMAIL).await.unwrap();
+
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+    assert_eq!(result.email_address, "user@fabrikam.com");
+    assert_eq!(result.server_version, Some(ExchangeServerVersion::Exchange2013_SP1));
+}
+
+/// Test following a redirection to another Autodiscover URL
+#[tokio::test]
+async fn test_discover_follows_url_redirect() {
+    let first = MockEwsServer::new().await;
+    let second = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    let redirect_url = format!("{}/autodiscover/autodiscover.xml", second.url());
+    first
+        .mock_autodiscover_pox(fixtures::autodiscover_pox_redirect_url_response(&redirect_url))
+        .await;
+    second
+        .mock_autodiscover_pox(fixtures::autodiscover_pox_response(EWS_URL, "738180DA"))
+        .await;
+
+    let client =
+        autodiscover_client(&[("contoso.com", &first), ("autodiscover.contoso.com", &dead)]).require_https(false);
+    let result = client.discover(EMAIL).await.unwrap();
+
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+    assert_eq!(result.autodiscover_url.as_str(), redirect_url);
+}
+
+/// Test the unauthenticated HTTP redirection step
+#[tokio::test]
+async fn test_discover_follows_http_redirect() {
+    let redirector = MockEwsServer::new().await;
+    let target = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    redirector
+        .mock_autodiscover_http_redirect("https://mail.contoso.com/autodiscover/autodiscover.xml")
+        .await;
+    target
+        .mock_autodiscover_pox(fixtures::autodiscover_pox_response(EWS_URL, "738180DA"))
+        .await;
+
+    let client = autodiscover_client(&[
+        ("contoso.com", &dead),
+        ("autodiscover.contoso.com", &redirector),
+        ("mail.contoso.com", &target),
+    ]);
+    let result = client.discover(EMAIL).await.unwrap();
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+
+    // A redirection to a plain HTTP URL is refused by default
+    let http_redirector = MockEwsServer::new().await;
+    http_redirector
+        .mock_autodiscover_http_redirect("http://mail.contoso.com/autodiscover/autodiscover.xml")
+        .await;
+
+    let client = autodiscover_client(&[
+        ("contoso.com", &dead),
+        ("autodiscover.contoso.com", &http_redirector),
+        ("mail.contoso.com", &target),
+    ]);
+    assert!(client.discover(EMAIL).await.is_err());
+
+    let result = client.require_https(false).discover(EMAIL).await.unwrap();
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+}
+
+/// Test that credentials are not sent to a host outside of the email's domain
+/// named by the unauthenticated HTTP redirection or an SRV record
+#[tokio::test]
+async fn test_discover_refuses_foreign_redirect() {
+    let redirector = MockEwsServer::new().await;
+    let foreign = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    redirector
+        .mock_autodiscover_http_redirect("https://autodiscover.hosting.net/autodiscover/autodiscover.xml")
+        .await;
+    foreign
+        .mock_autodiscover_pox(fixtures::autodiscover_pox_response(EWS_URL, "738180DA"))
+        .await;
+
+    let client = autodiscover_client(&[
+        ("contoso.com", &dead),
+        ("autodiscover.contoso.com", &redirector),
+        ("autodiscover.hosting.net", &foreign),
+    ])
+    .with_srv_resolver(StaticSrvResolver(vec![SrvRecord {
+        priority: 10,
+        weight: 0,
+        port: 443,
+        target: "autodiscover.hosting.net.".to_string(),
+    }]));
+
+    let result = client.discover(EMAIL).await;
+    assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+    assert!(foreign.received_requests().await.is_empty());
+
+    // The host can be trusted explicitly
+    let result = client
+        .with_redirect_validator(|url| url.host_str() == Some("autodiscover.hosting.net"))
+        .discover(EMAIL)
+        .await
+        .unwrap();
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+}
+
+/// Test discovery through the targets of SRV records
+#[tokio::test]
+async fn test_discover_uses_srv_records() {
+    let mock = MockEwsServer::new().await;
+    let dead = MockEwsServer::new().await;
+    mock.mock_autodiscover_pox(fixtures::autodiscover_pox_response(EWS_URL, "738180DA"))
+        .await;
+
+    let client = autodiscover_client(&[
+        ("contoso.com", &dead),
+        ("autodiscover.contoso.com", &dead),
+        ("backup.contoso.com", &dead),
+        ("mail.contoso.com", &mock),
+    ])
+    .with_srv_resolver(StaticSrvResolver(vec![
+        SrvRecord {
+            priority: 20,
+            weight: 0,
+            port: 443,
+            target: "backup.contoso.com.".to_string(),
+        },
+        SrvRecord {
+            priority: 10,
+            weight: 0,
+            port: 443,
+            target: "mail.contoso.com.".to_string(),
+        },
+    ]));
+    let result = client.discover(EMAIL).await.unwrap();
+
+    assert_eq!(result.ews_url.as_str(), EWS_URL);
+    assert!(result.autodiscover_url.as_str().starts_with(&mock.url()));
+}
+
+/// Test that rejected credentials are reported as an authentication failure
+#[tokio::test]
+async fn test_discover_authentication_failure() {
+    let mock = MockEwsServer::new().await;
+    mock.mock_autodiscover_unauthorized().await;
+
+    let client = autodiscover_client(&[("contoso.com", &mock), ("autodiscover.contoso.com", &mock)]);
+    let result = client.discover(EMAIL).await;
+
+    assert!(matches!(result, Err(EwsError::Authentication)));
+}
+
+/// Test that an invalid email address is rejected
+#[tokio::test]
+async fn test_discover_invalid_email() {
+    let client = autodiscover_client(&[]);
+    assert!(client.discover("not-an-email").await.is_err());
+}
@@ -0,0 +1,116 @@
+//! Integration tests for availability operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for free/busy
+//! and scheduling requests without requiring a real Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Attendee, AttendeeType, Credentials, EwsClient, LegacyFreeBusyStatus, SuggestionQuality, TimeWindow,
+};
+
+/// 2024-01-15T10:00:00Z
+const WINDOW_START: i64 = 1_705_312_800;
+
+fn window() -> TimeWindow {
+    TimeWindow {
+        start: WINDOW_START,
+        end: WINDOW_START + 2 * 3600,
+    }
+}
+
+/// Test reading free/busy information and suggestions with mock server
+#[tokio::test]
+async fn test_get_user_availability_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetUserAvailability", fixtures::get_user_availability_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let attendees = [
+        Attendee::new("alice@example.com", AttendeeType::Required),
+        Attendee::new("nobody@example.com", AttendeeType::Optional),
+    ];
+
+    let result = client.get_user_availability(&attendees, window(), 30, Some(60)).await;
+    assert!(result.is_ok(), "get_user_availability failed: {:?}", result.err());
+
+    let availability = result.unwrap();
+    assert_eq!(availability.attendees.len(), 2);
+
+    let alice = &availability.attendees[0];
+    assert_eq!(alice.email, "alice@example.com");
+    assert!(alice.error.is_none());
+    assert_eq!(alice.merged_free_busy.as_deref(), Some("0022"));
+    assert_eq!(
+        alice.merged_statuses(),
+        vec![
+            LegacyFreeBusyStatus::Free,
+            LegacyFreeBusyStatus::Free,
+            LegacyFreeBusyStatus::Busy,
+            LegacyFreeBusyStatus::Busy,
+        ]
+    );
+
+    // Times without an offset are in the UTC time zone of the request
+    assert_eq!(alice.events.len(), 1);
+    assert_eq!(alice.events[0].start, WINDOW_START + 3600);
+    assert_eq!(alice.events[0].end, WINDOW_START + 2 * 3600);
+    assert_eq!(alice.events[0].busy_type, LegacyFreeBusyStatus::Busy);
+    assert_eq!(alice.events[0].subject.as_deref(), Some("Review"));
+    assert!(alice.events[0].is_meeting);
+
+    let nobody = &availability.attendees[1];
+    assert_eq!(nobody.error.as_deref(), Some("ErrorMailRecipientNotFound"));
+    assert!(nobody.merged_free_busy.is_none());
+
+    // Suggestions are sorted from the best to the worst
+    assert_eq!(availability.suggestions.len(), 2);
+    assert_eq!(availability.suggestions[0].quality, SuggestionQuality::Excellent);
+    assert_eq!(availability.suggestions[0].start, WINDOW_START);
+    assert_eq!(availability.suggestions[1].quality, SuggestionQuality::Good);
+}
+
+/// Test that invalid requests are rejected before any request
+#[tokio::test]
+async fn test_get_user_availability_rejects_invalid_parameters() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let attendees = [Attendee::new("alice@example.com", AttendeeType::Required)];
+
+    let result = client.get_user_availability(&[], window(), 30, None).await;
+    assert!(result.is_err());
+
+    let inverted = TimeWindow {
+        start: WINDOW_START,
+        end: WINDOW_START - 1,
+    };
+    let result = client.get_user_availability(&attendees, inverted, 30, None).await;
+    assert!(result.is_err());
+
+    let result = client.get_user_availability(&attendees, window(), 1, None).await;
+    assert!(result.is_err());
+}
+
+/// Test that a mismatched number of responses is reported as an error
+#[tokio::test]
+async fn test_get_user_availability_unexpected_response_count() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetUserAvailability", fixtures::get_user_availability_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let attendees = [Attendee::new("alice@example.com", AttendeeType::Required)];
+    let result = client.get_user_availability(&attendees, window(), 30, None).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,339 @@
+//! Integration tests for calendar operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for calendar
+//! views and calendar item operations without requiring a real Exchange
+//! server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Attendee, AttendeeType, CalendarItemDraft, CalendarItemType, CalendarItemUpdate, Credentials, DayOfWeek, EwsClient,
+    LegacyFreeBusyStatus, RecurrencePattern, ResponseType, SendMeetingInvitations, SendMeetingUpdates, parse_icalendar,
+};
+
+/// 2024-01-15T10:30:00Z
+const START: i64 = 1_705_314_600;
+
+/// Test reading a calendar view with expanded occurrences
+#[tokio::test]
+async fn test_get_calendar_view_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("FindItem", fixtures::calendar_view_response(true))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .get_calendar_view(None, START, START + 14 * 24 * 3600, None)
+        .await;
+    assert!(result.is_ok(), "get_calendar_view failed: {:?}", result.err());
+
+    let view = result.unwrap();
+    assert!(view.includes_last_item);
+    assert_eq!(view.items.len(), 2);
+
+    let first = &view.items[0];
+    assert_eq!(first.item_id, "occurrence-1");
+    assert_eq!(first.subject.as_deref(), Some("Weekly sync"));
+    assert_eq!(first.start, Some(START));
+    assert_eq!(first.end, Some(START + 1800));
+    assert_eq!(first.location.as_deref(), Some("Room 1"));
+    assert_eq!(first.calendar_item_type, Some(CalendarItemType::Occurrence));
+    assert_eq!(first.legacy_free_busy_status, Some(LegacyFreeBusyStatus::Busy));
+    assert!(first.is_meeting);
+
+    assert_eq!(view.items[1].calendar_item_type, Some(CalendarItemType::Exception));
+}
+
+/// Test that a truncated calendar view is reported
+#[tokio::test]
+async fn test_get_calendar_view_truncated() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("FindItem", fixtures::calendar_view_response(false))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let view = client
+        .get_calendar_view(Some("calendar"), START, START + 3600, Some(2))
+        .await
+        .unwrap();
+    assert!(!view.includes_last_item);
+}
+
+/// Test that an inverted date range is rejected before any request
+#[tokio::test]
+async fn test_get_calendar_view_rejects_inverted_range() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_calendar_view(None, START, START - 1, None).await;
+    assert!(result.is_err());
+}
+
+/// Test reading a meeting with its attendees
+#[tokio::test]
+async fn test_get_calendar_items_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::get_calendar_item_response("meeting-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_calendar_items(&["meeting-1"]).await;
+    assert!(result.is_ok(), "get_calendar_items failed: {:?}", result.err());
+
+    let items = result.unwrap();
+    assert_eq!(items.len(), 1);
+
+    let item = &items[0];
+    assert_eq!(item.item_id, "meeting-1");
+    assert_eq!(item.change_key.as_deref(), Some("DwAAAA=="));
+    assert_eq!(item.body.as_deref(), Some("Agenda & notes"));
+    assert_eq!(item.start_time_zone.as_deref(), Some("W. Europe Standard Time"));
+    assert_eq!(item.my_response_type, Some(ResponseType::Organizer));
+    assert_eq!(item.uid.as_deref(), Some("040000008200E00074C5B7101A82E008"));
+
+    let organizer = item.organizer.as_ref().unwrap();
+    assert_eq!(organizer.email, "organizer@example.com");
+
+    assert_eq!(item.attendees.len(), 2);
+    assert_eq!(item.attendees[0].email, "alice@example.com");
+    assert_eq!(item.attendees[0].name.as_deref(), Some("Alice"));
+    assert_eq!(item.attendees[0].attendee_type, AttendeeType::Required);
+    assert_eq!(item.attendees[0].response_type, Some(ResponseType::Accept));
+    assert_eq!(item.attendees[1].attendee_type, AttendeeType::Optional);
+    assert_eq!(item.attendees[1].response_type, Some(ResponseType::NoResponseReceived));
+}
+
+/// Test creating a meeting with mock server
+#[tokio::test]
+async fn test_create_calendar_item_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::create_calendar_item_response("meeting-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let draft = CalendarItemDraft {
+        subject: "Planning".to_string(),
+        start: START,
+        end: START + 3600,
+        location: Some("Room 1".to_string()),
+        time_zone: Some("W. Europe Standard Time".to_string()),
+        attendees: vec![
+            Attendee::new("alice@example.com", AttendeeType::Required),
+            Attendee::new("room@example.com", AttendeeType::Resource),
+        ],
+        reminder_minutes: Some(15),
+        ..Default::default()
+    };
+
+    let result = client
+        .create_calendar_item(None, &draft, SendMeetingInvitations::SendToAllAndSaveCopy)
+        .await;
+    assert!(result.is_ok(), "create_calendar_item failed: {:?}", result.err());
+    assert_eq!(result.unwrap().item_id, "meeting-1");
+}
+
+/// Test updating a meeting with mock server
+#[tokio::test]
+async fn test_update_calendar_item_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "UpdateItem",
+        fixtures::update_calendar_item_response("meeting-1", "EAAAAA=="),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let update = CalendarItemUpdate {
+        start: Some(START + 3600),
+        end: Some(START + 7200),
+        attendees: Some(vec![Attendee::new("alice@example.com", AttendeeType::Required)]),
+        ..Default::default()
+    };
+
+    let result = client
+        .update_calendar_item(
+            "meeting-1",
+            Some("DwAAAA=="),
+            &update,
+            SendMeetingUpdates::SendToChangedAndSaveCopy,
+        )
+        .await;
+    assert!(result.is_ok(), "update_calendar_item failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), "EAAAAA==");
+
+    // The change key makes the update fail if the item changed since it was
+    // read, rather than merge over the other changes
+    let requests = mock.received_ews_requests().await;
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains(r#"ConflictResolution="NeverOverwrite""#), "{body}");
+    assert!(
+        body.contains(r#"<t:ItemId Id="meeting-1" ChangeKey="DwAAAA=="/>"#),
+        "{body}"
+    );
+}
+
+/// Test updating a meeting without a change key with mock server
+#[tokio::test]
+async fn test_update_calendar_item_without_change_key_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "UpdateItem",
+        fixtures::update_calendar_item_response("meeting-1", "EAAAAA=="),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let update = CalendarItemUpdate {
+        location: Some("Room 2".to_string()),
+        ..Default::default()
+    };
+
+    let result = client
+        .update_calendar_item("meeting-1", None, &update, SendMeetingUpdates::SendToNone)
+        .await;
+    assert!(result.is_ok(), "update_calendar_item failed: {:?}", result.err());
+
+    let requests = mock.received_ews_requests().await;
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains(r#"ConflictResolution="AutoResolve""#), "{body}");
+}
+
+/// Test that an empty update is rejected before any request
+#[tokio::test]
+async fn test_update_calendar_item_rejects_empty_update() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .update_calendar_item(
+            "meeting-1",
+            None,
+            &CalendarItemUpdate::default(),
+            SendMeetingUpdates::SendToNone,
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test deleting a meeting with mock server
+#[tokio::test]
+async fn test_delete_calendar_item_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.mock_delete_item().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .delete_calendar_item("meeting-1", SendMeetingInvitations::SendToAllAndSaveCopy)
+        .await;
+    assert!(result.is_ok(), "delete_calendar_item failed: {:?}", result.err());
+}
+
+/// Test that a missing item is reported as an error
+#[tokio::test]
+async fn test_delete_calendar_item_not_found() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("DeleteItem", fixtures::error_item_not_found())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .delete_calendar_item("missing", SendMeetingInvitations::SendToNone)
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test exporting a recurring meeting with its exceptions to iCalendar and
+/// reading it back
+#[tokio::test]
+async fn test_recurring_calendar_item_icalendar_round_trip() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::get_recurring_calendar_item_response("master-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let items = client.get_calendar_items(&["master-1"]).await.unwrap();
+    let master = &items[0];
+    assert_eq!(master.calendar_item_type, Some(CalendarItemType::RecurringMaster));
+    assert_eq!(
+        master.recurrence.as_ref().unwrap().pattern,
+        RecurrencePattern::Weekly {
+            interval: 1,
+            days_of_week: vec![DayOfWeek::Monday],
+        }
+    );
+    assert_eq!(master.deleted_occurrences, vec![START + 7 * 24 * 3600]);
+    assert_eq!(master.modified_occurrences.len(), 1);
+    assert_eq!(master.modified_occurrences[0].item_id, "exception-1");
+    assert_eq!(master.modified_occurrences[0].original_start, START + 14 * 24 * 3600);
+
+    let time_zone = master.start_time_zone_definition.as_ref().unwrap();
+    assert_eq!(time_zone.standard_offset, 60);
+    assert_eq!(time_zone.daylight.unwrap().offset, 120);
+
+    let text = master.to_icalendar().unwrap();
+    assert!(text.contains("BEGIN:VTIMEZONE\r\nTZID:W. Europe Standard Time\r\n"));
+    assert!(text.contains("DTSTART;TZID=W. Europe Standard Time:20240115T113000\r\n"));
+    assert!(text.contains("RRULE:FREQ=WEEKLY;INTERVAL=1;BYDAY=MO;UNTIL=20240325T225959Z\r\n"));
+    assert!(text.contains("EXDATE;TZID=W. Europe Standard Time:20240122T113000\r\n"));
+    assert!(text.contains("RECURRENCE-ID;TZID=W. Europe Standard Time:20240129T113000\r\n"));
+
+    let imported = parse_icalendar(&text).unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0].recurrence, master.recurrence);
+    assert_eq!(imported[0].deleted_occurrences, master.deleted_occurrences);
+    assert_eq!(
+        imported[0].modified_occurrences[0].start,
+        master.modified_occurrences[0].start
+    );
+    assert_eq!(imported[1].calendar_item_type, Some(CalendarItemType::Exception));
+}
+
+/// Test creating a meeting from an `.ics` invite, which is sent with its time
+/// zone definition once the server is known to run Exchange 2010 or later
+#[tokio::test]
+async fn test_create_calendar_item_from_icalendar_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "GetItem",
+        fixtures::with_server_version(
+            &fixtures::get_recurring_calendar_item_response("master-1"),
+            "Exchange2013_SP1",
+        ),
+    )
+    .await;
+    mock.register_operation("CreateItem", fixtures::create_calendar_item_response("meeting-new"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+    let master = client.get_calendar_items(&["master-1"]).await.unwrap().remove(0);
+
+    let imported = parse_icalendar(&master.to_icalendar().unwrap()).unwrap();
+    let draft = CalendarItemDraft::from(&imported[0]);
+    assert_eq!(draft.subject, "Weekly sync");
+    assert_eq!(draft.start, START);
+    assert_eq!(draft.uid.as_deref(), Some("040000008200E00074C5B7101A82E008"));
+    assert_eq!(draft.recurrence, master.recurrence);
+    assert!(draft.time_zone_definition.is_some());
+
+    let result = client
+        .create_calendar_item(None, &draft, SendMeetingInvitations::SendToNone)
+        .await;
+    assert!(result.is_ok(), "create_calendar_item failed: {:?}", result.err());
+    assert_eq!(result.unwrap().item_id, "meeting-new");
+}
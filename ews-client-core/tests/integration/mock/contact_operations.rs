@@ -0,0 +1,216 @@
+//! Integration tests for contact operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for listing,
+//! reading, creating, updating and deleting contacts without requiring a real
+//! Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Contact, ContactEmailAddress, ContactUpdate, Credentials, EmailAddressKey, EwsClient, PhoneNumberKey,
+    PhysicalAddressKey,
+};
+
+/// Test listing a page of contacts with mock server
+#[tokio::test]
+async fn test_find_contacts_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("FindItem", fixtures::find_contacts_response(false))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.find_contacts(None, 0, 2).await;
+    assert!(result.is_ok(), "find_contacts failed: {:?}", result.err());
+
+    let page = result.unwrap();
+    assert_eq!(page.contacts.len(), 2);
+    assert_eq!(page.total_items, Some(3));
+    assert!(!page.includes_last_item);
+    assert_eq!(page.next_offset, Some(2));
+
+    let alice = &page.contacts[0];
+    assert_eq!(alice.item_id.as_deref(), Some("contact-1"));
+    assert_eq!(alice.display_name.as_deref(), Some("Alice Smith"));
+    assert_eq!(alice.email_addresses[0].address, "alice@example.com");
+}
+
+/// Test that the last page of contacts has no next offset
+#[tokio::test]
+async fn test_find_contacts_last_page() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("FindItem", fixtures::find_contacts_response(true))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let page = client.find_contacts(Some("contacts-folder"), 2, 2).await.unwrap();
+    assert!(page.includes_last_item);
+    assert_eq!(page.next_offset, None);
+}
+
+/// Test reading a contact with typed email addresses, phone numbers and
+/// physical addresses
+#[tokio::test]
+async fn test_get_contacts_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::get_contact_response("contact-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_contacts(&["contact-1"]).await;
+    assert!(result.is_ok(), "get_contacts failed: {:?}", result.err());
+
+    let contacts = result.unwrap();
+    assert_eq!(contacts.len(), 1);
+
+    let contact = &contacts[0];
+    assert_eq!(contact.item_id.as_deref(), Some("contact-1"));
+    assert_eq!(contact.change_key.as_deref(), Some("EQAAAA=="));
+    assert_eq!(contact.company_name.as_deref(), Some("Contoso"));
+    assert_eq!(contact.job_title.as_deref(), Some("Engineer"));
+    assert_eq!(contact.notes.as_deref(), Some("Met at the conference"));
+    assert_eq!(contact.birthday, Some(633_830_400));
+
+    // The routing prefix is removed from email addresses
+    assert_eq!(contact.email_addresses.len(), 2);
+    assert_eq!(contact.email_addresses[0].key, EmailAddressKey::EmailAddress1);
+    assert_eq!(contact.email_addresses[0].address, "alice@example.com");
+
+    // Empty entries are skipped
+    assert_eq!(contact.phone_numbers.len(), 2);
+    assert_eq!(contact.phone_numbers[0].key, PhoneNumberKey::BusinessPhone);
+    assert_eq!(contact.phone_numbers[1].key, PhoneNumberKey::MobilePhone);
+    assert_eq!(contact.phone_numbers[1].number, "+1 555 0199");
+
+    assert_eq!(contact.physical_addresses.len(), 1);
+    let address = &contact.physical_addresses[0];
+    assert_eq!(address.key, PhysicalAddressKey::Business);
+    assert_eq!(address.city.as_deref(), Some("Springfield"));
+    assert_eq!(address.state, None);
+}
+
+/// Test creating a contact with mock server
+#[tokio::test]
+async fn test_create_contact_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::create_contact_response("contact-new"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let contact = Contact {
+        given_name: Some("Carol".to_string()),
+        surname: Some("White".to_string()),
+        email_addresses: vec![ContactEmailAddress {
+            key: EmailAddressKey::EmailAddress1,
+            address: "carol@example.com".to_string(),
+        }],
+        birthday: Some(633_830_400),
+        ..Default::default()
+    };
+
+    let result = client.create_contact(None, &contact).await;
+    assert!(result.is_ok(), "create_contact failed: {:?}", result.err());
+
+    let created = result.unwrap();
+    assert_eq!(created.item_id, "contact-new");
+    assert_eq!(created.change_key.as_deref(), Some("CQAAAA=="));
+}
+
+/// Test updating a contact with mock server
+#[tokio::test]
+async fn test_update_contact_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("UpdateItem", fixtures::update_contact_response("contact-1", "EgAAAA=="))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let update = ContactUpdate {
+        job_title: Some("Manager".to_string()),
+        nickname: Some(String::new()),
+        email_addresses: Some(vec![ContactEmailAddress {
+            key: EmailAddressKey::EmailAddress2,
+            address: "alice@new.example".to_string(),
+        }]),
+        ..Default::default()
+    };
+
+    let result = client.update_contact("contact-1", Some("EQAAAA=="), &update).await;
+    assert!(result.is_ok(), "update_contact failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), "EgAAAA==");
+
+    let requests = mock.received_ews_requests().await;
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains(r#"ConflictResolution="NeverOverwrite""#), "{body}");
+}
+
+/// Test that an update without any change is rejected before it is sent
+#[tokio::test]
+async fn test_update_contact_without_changes() {
+    let mock = MockEwsServer::new().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .update_contact("contact-1", None, &ContactUpdate::default())
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test deleting a contact with mock server
+#[tokio::test]
+async fn test_delete_contact_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.mock_delete_item().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.delete_contact("contact-1").await;
+    assert!(result.is_ok(), "delete_contact failed: {:?}", result.err());
+}
+
+/// Test that reading a missing contact is reported as an error
+#[tokio::test]
+async fn test_get_contacts_not_found() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::error_item_not_found())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_contacts(&["missing"]).await;
+    assert!(result.is_err());
+}
+
+/// Test exporting a contact read from the server as a vCard and importing it
+/// back
+#[tokio::test]
+async fn test_contact_vcard_round_trip_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::get_contact_response("contact-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let contact = client.get_contacts(&["contact-1"]).await.unwrap().remove(0);
+    let vcard = contact.to_vcard();
+    assert!(vcard.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+    assert!(vcard.contains("BDAY:19900201"));
+
+    let imported = Contact::from_vcard(&vcard).unwrap();
+    assert_eq!(imported.item_id, None);
+    assert_eq!(imported.display_name, contact.display_name);
+    assert_eq!(imported.email_addresses, contact.email_addresses);
+    assert_eq!(imported.phone_numbers, contact.phone_numbers);
+    assert_eq!(imported.birthday, contact.birthday);
+}
@@ -0,0 +1,150 @@
+//! Integration tests for delegate operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for listing,
+//! adding, changing and removing delegates without requiring a real Exchange
+//! server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Credentials, DelegateFolderPermissionLevel, DelegatePermissions, DelegateUser, DeliverMeetingRequests, EwsClient,
+    EwsError,
+};
+
+/// Test listing the delegates of a mailbox with mock server
+#[tokio::test]
+async fn test_get_delegates_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetDelegate", fixtures::get_delegate_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_delegates("boss@example.com", true).await;
+    assert!(result.is_ok(), "get_delegates failed: {:?}", result.err());
+
+    let settings = result.unwrap();
+    assert_eq!(
+        settings.deliver_meeting_requests,
+        Some(DeliverMeetingRequests::DelegatesAndMe)
+    );
+    assert_eq!(settings.delegates.len(), 1);
+
+    let delegate = &settings.delegates[0];
+    assert_eq!(delegate.primary_smtp_address, "assistant@example.com");
+    assert_eq!(delegate.display_name.as_deref(), Some("Alex Assistant"));
+    assert!(delegate.sid.is_some());
+    assert_eq!(
+        delegate.permissions.calendar,
+        Some(DelegateFolderPermissionLevel::Editor)
+    );
+    assert_eq!(
+        delegate.permissions.inbox,
+        Some(DelegateFolderPermissionLevel::Reviewer)
+    );
+    assert_eq!(
+        delegate.permissions.journal,
+        Some(DelegateFolderPermissionLevel::Custom)
+    );
+    assert!(delegate.receive_copies_of_meeting_messages);
+    assert!(!delegate.view_private_items);
+}
+
+/// Test adding a delegate with mock server
+#[tokio::test]
+async fn test_add_delegates_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("AddDelegate", fixtures::add_delegate_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let delegate = DelegateUser {
+        permissions: DelegatePermissions {
+            calendar: Some(DelegateFolderPermissionLevel::Editor),
+            inbox: Some(DelegateFolderPermissionLevel::Reviewer),
+            ..DelegatePermissions::default()
+        },
+        receive_copies_of_meeting_messages: true,
+        ..DelegateUser::new("assistant@example.com")
+    };
+
+    let result = client
+        .add_delegates(
+            "boss@example.com",
+            &[delegate],
+            Some(DeliverMeetingRequests::DelegatesOnly),
+        )
+        .await;
+    assert!(result.is_ok(), "add_delegates failed: {:?}", result.err());
+
+    let settings = result.unwrap();
+    assert_eq!(settings.delegates[0].primary_smtp_address, "assistant@example.com");
+    assert_eq!(
+        settings.deliver_meeting_requests,
+        Some(DeliverMeetingRequests::DelegatesOnly)
+    );
+}
+
+/// Test that a delegate which cannot be added is reported as an error
+#[tokio::test]
+async fn test_add_delegates_already_exists() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("AddDelegate", fixtures::add_delegate_already_exists_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .add_delegates("boss@example.com", &[DelegateUser::new("assistant@example.com")], None)
+        .await;
+    assert!(
+        matches!(
+            &result,
+            Err(EwsError::RawResponseError { response_code, .. }) if response_code == "ErrorDelegateAlreadyExists"
+        ),
+        "unexpected result: {result:?}"
+    );
+}
+
+/// Test changing delegates and removing them with mock server
+#[tokio::test]
+async fn test_update_and_remove_delegates_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("UpdateDelegate", fixtures::get_delegate_response())
+        .await;
+    mock.register_operation("RemoveDelegate", fixtures::remove_delegate_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    // Only the delivery of meeting requests changes
+    let result = client
+        .update_delegates("boss@example.com", &[], Some(DeliverMeetingRequests::DelegatesAndMe))
+        .await;
+    assert!(result.is_ok(), "update_delegates failed: {:?}", result.err());
+
+    let result = client
+        .remove_delegates("boss@example.com", &["assistant@example.com"])
+        .await;
+    assert!(result.is_ok(), "remove_delegates failed: {:?}", result.err());
+}
+
+/// Test that invalid requests are rejected before any request
+#[tokio::test]
+async fn test_delegates_invalid() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    assert!(client.add_delegates("boss@example.com", &[], None).await.is_err());
+    assert!(client.update_delegates("boss@example.com", &[], None).await.is_err());
+    assert!(client.remove_delegates("boss@example.com", &[]).await.is_err());
+    assert!(client.get_delegates(" ", false).await.is_err());
+}
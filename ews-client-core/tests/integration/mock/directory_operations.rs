@@ -0,0 +1,205 @@
+//! Integration tests for directory operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for name
+//! resolution and distribution list expansion without requiring a real
+//! Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Credentials, EwsClient, EwsError, MailboxType, OwnedMailbox, Paging, ResolveNamesSearchScope,
+};
+
+/// Test resolving a partial name to several matches with contact details
+#[tokio::test]
+async fn test_resolve_names_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("ResolveNames", fixtures::resolve_names_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .resolve_names("al", ResolveNamesSearchScope::ActiveDirectoryContacts, true)
+        .await;
+    assert!(result.is_ok(), "resolve_names failed: {:?}", result.err());
+
+    let names = result.unwrap();
+    assert_eq!(names.len(), 2);
+
+    let alice = &names[0];
+    assert_eq!(alice.mailbox.to_string(), "\"Alice Smith\" <alice@example.com>");
+    assert_eq!(alice.routing_type.as_deref(), Some("SMTP"));
+    assert_eq!(alice.mailbox_type, Some(MailboxType::Mailbox));
+
+    let contact = alice.contact.as_ref().unwrap();
+    assert_eq!(contact.item_id, None);
+    assert_eq!(contact.job_title.as_deref(), Some("Engineer"));
+    assert_eq!(contact.email_addresses[0].address, "alice@example.com");
+    assert_eq!(contact.phone_numbers[0].number, "+1 555 0100");
+
+    let list = &names[1];
+    assert_eq!(list.mailbox.email_address.as_deref(), Some("engineering@example.com"));
+    assert!(list.mailbox_type.unwrap().is_distribution_list());
+    assert!(list.contact.is_none());
+}
+
+/// Test that a name without any match resolves to an empty list
+#[tokio::test]
+async fn test_resolve_names_no_results() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("ResolveNames", fixtures::resolve_names_no_results_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .resolve_names("nobody", ResolveNamesSearchScope::ActiveDirectory, false)
+        .await;
+    assert!(result.is_ok(), "resolve_names failed: {:?}", result.err());
+    assert!(result.unwrap().is_empty());
+}
+
+/// Test that an empty query is rejected before any request
+#[tokio::test]
+async fn test_resolve_names_rejects_empty_query() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .resolve_names("  ", ResolveNamesSearchScope::default(), false)
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test listing the members of a distribution list with mock server
+#[tokio::test]
+async fn test_expand_distribution_list_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("ExpandDL", fixtures::expand_dl_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.expand_distribution_list("engineering@example.com").await;
+    assert!(result.is_ok(), "expand_distribution_list failed: {:?}", result.err());
+
+    let members = result.unwrap();
+    assert_eq!(
+        members,
+        vec![
+            OwnedMailbox {
+                name: Some("Alice Smith".to_string()),
+                email_address: Some("alice@example.com".to_string()),
+            },
+            OwnedMailbox {
+                name: Some("Bob Jones".to_string()),
+                email_address: Some("bob@example.com".to_string()),
+            },
+        ]
+    );
+}
+
+/// Test searching for people in the user's contacts and the directory, which
+/// requires the client to know the server runs Exchange 2013 or later
+#[tokio::test]
+async fn test_find_people_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "ResolveNames",
+        fixtures::with_server_version(&fixtures::resolve_names_response(), "Exchange2013_SP1"),
+    )
+    .await;
+    mock.register_operation_containing("FindPeople", r#"Id="directory""#, fixtures::find_people_response())
+        .await;
+    mock.register_operation_containing(
+        "FindPeople",
+        r#"Id="mycontacts""#,
+        fixtures::find_people_contacts_response(),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    // The server version is not known before the first response
+    let result = client.find_people("alice", Paging::default()).await;
+    assert!(
+        matches!(&result, Err(EwsError::Unsupported { feature, .. }) if feature == "FindPeople"),
+        "{result:?}"
+    );
+
+    client
+        .resolve_names("alice", ResolveNamesSearchScope::default(), false)
+        .await
+        .unwrap();
+
+    let paging = Paging {
+        offset: 0,
+        max_entries: 1,
+    };
+    let result = client.find_people("alice", paging).await;
+    assert!(result.is_ok(), "find_people failed: {:?}", result.err());
+
+    // Alice is found in both sources and counted once
+    let page = result.unwrap();
+    assert_eq!(page.total_items, Some(4));
+    assert_eq!(page.next_offset, Some(1));
+    assert_eq!(page.people.len(), 1);
+
+    // The directory entry of Alice is the more relevant one
+    let person = &page.people[0];
+    assert_eq!(person.persona_id, "AAUQAA==");
+    assert_eq!(person.display_name.as_deref(), Some("Alice Smith"));
+    assert_eq!(
+        person.email_address.as_ref().unwrap().email_address.as_deref(),
+        Some("alice@example.com")
+    );
+
+    // Both the user's contacts and the global address list are searched
+    let requests = mock.received_ews_requests().await;
+    let bodies: Vec<String> = requests
+        .iter()
+        .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+        .filter(|body| body.contains("<m:FindPeople>"))
+        .collect();
+    assert_eq!(bodies.len(), 2, "{bodies:?}");
+    for folder in ["directory", "mycontacts"] {
+        let parent = format!(r#"<m:ParentFolderId><t:DistinguishedFolderId Id="{folder}"/></m:ParentFolderId>"#);
+        assert!(bodies.iter().any(|body| body.contains(&parent)), "{bodies:?}");
+    }
+    assert!(
+        bodies
+            .iter()
+            .all(|body| body.contains(r#"MaxEntriesReturned="1" Offset="0""#)),
+        "{bodies:?}"
+    );
+
+    // The next page holds Carol from the user's contacts, and the directory
+    // has more people to page through
+    let paging = Paging {
+        offset: 1,
+        max_entries: 1,
+    };
+    let page = client.find_people("alice", paging).await.unwrap();
+    assert_eq!(page.people.len(), 1);
+    assert_eq!(page.people[0].persona_id, "AAUQAC==");
+    assert_eq!(page.next_offset, Some(2));
+
+    let requests = mock.received_ews_requests().await;
+    let body = String::from_utf8_lossy(&requests.last().unwrap().body);
+    assert!(body.contains(r#"MaxEntriesReturned="2" Offset="0""#), "{body}");
+
+    // Pages must hold between 1 and 1000 people
+    let paging = Paging {
+        offset: 0,
+        max_entries: 0,
+    };
+    assert!(client.find_people("alice", paging).await.is_err());
+}
@@ -14,7 +14,11 @@
 )]
 
 use crate::common::{MockEwsServer, fixtures, test_utils::*};
-use ews_client_core::client::{Credentials, EwsClient};
+use ews_client_core::client::{
+    ContainmentMode, Credentials, EwsClient, EwsError, ExtendedPropertyKey, FolderExtendedProperty, FolderKind,
+    FolderPermission, FolderUpdate, ItemAccess, MapiPropertyType, OwnedMailbox, PermissionLevel, PermissionSet,
+    PermissionUser, ReadAccess, SearchExpression, SearchFolderDraft, SearchFolderTraversal, SearchProperty,
+};
 
 /// Helper function to create a SOAP request body for testing
 fn create_soap_request(operation: &str, body_content: &str) -> String {
@@ -71,6 +75,92 @@ async fn test_create_folder_with_mock() {
     assert_eq!(created_id, folder_id);
 }
 
+/// Test creating a calendar folder with mock server
+#[tokio::test]
+async fn test_create_folder_of_kind_with_mock() {
+    let mock = MockEwsServer::new().await;
+    let folder_id = "folder-test-calendar";
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_typed_folder_response("CalendarFolder", folder_id),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .create_folder_of_kind("calendar", "Team Events", FolderKind::Calendar, None)
+        .await;
+    assert!(result.is_ok(), "create_folder_of_kind failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), folder_id);
+}
+
+/// Test creating a search folder with mock server
+#[tokio::test]
+async fn test_create_search_folder_with_mock() {
+    let mock = MockEwsServer::new().await;
+    let folder_id = "folder-test-search";
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_typed_folder_response("SearchFolder", folder_id),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let mut draft = SearchFolderDraft {
+        display_name: "Unread from VIPs".to_string(),
+        folder_class: Some("IPF.Note".to_string()),
+        restriction: SearchExpression::And(vec![
+            SearchExpression::IsEqualTo(SearchProperty::Field("message:IsRead".to_string()), "false".to_string()),
+            SearchExpression::Contains {
+                property: SearchProperty::Field("message:From".to_string()),
+                value: "ceo@example.com".to_string(),
+                mode: ContainmentMode::Substring,
+                ignore_case: true,
+            },
+        ]),
+        base_folder_ids: vec!["inbox".to_string()],
+        traversal: SearchFolderTraversal::Deep,
+    };
+
+    let result = client.create_search_folder("searchfolders", &draft).await;
+    assert!(result.is_ok(), "create_search_folder failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), folder_id);
+
+    // A search folder without base folders is rejected before any request
+    draft.base_folder_ids.clear();
+    let result = client.create_search_folder("searchfolders", &draft).await;
+    assert!(matches!(result, Err(EwsError::Processing { .. })), "{result:?}");
+}
+
+/// Test a search folder restriction rejected by the server with mock server
+#[tokio::test]
+async fn test_create_search_folder_invalid_restriction_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_search_folder_invalid_restriction_response(),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let draft = SearchFolderDraft {
+        display_name: "Broken".to_string(),
+        folder_class: None,
+        restriction: SearchExpression::Exists(SearchProperty::Field("item:Unknown".to_string())),
+        base_folder_ids: vec!["inbox".to_string()],
+        traversal: SearchFolderTraversal::Shallow,
+    };
+
+    let result = client.create_search_folder("searchfolders", &draft).await;
+    assert!(
+        matches!(&result, Err(EwsError::RawResponseError { response_code, .. }) if response_code == "ErrorInvalidRestriction"),
+        "{result:?}"
+    );
+}
+
 /// Test getting a folder with mock server
 #[tokio::test]
 async fn test_get_folder_with_mock() {
@@ -135,10 +225,52 @@ async fn test_update_folder_with_mock() {
     let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
 
     // Use EwsClient to update folder
-    let result = client.update_folder(folder_id, "New Name").await;
+    let update = FolderUpdate {
+        display_name: Some("New Name".to_string()),
+        ..FolderUpdate::default()
+    };
+    let result = client.update_folder(folder_id, None, &update).await;
 
     // Verify response
     assert!(result.is_ok(), "update_folder failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), "AQAAAA==");
+}
+
+/// Test changing the class and extended properties of a folder with mock
+/// server
+#[tokio::test]
+async fn test_update_folder_properties_with_mock() {
+    let mock = MockEwsServer::new().await;
+    let folder_id = "folder-test-update-properties";
+    mock.register_operation("UpdateFolder", fixtures::update_folder_response(folder_id))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let update = FolderUpdate {
+        folder_class: Some("IPF.Note.Archive".to_string()),
+        extended_properties: vec![
+            // PR_ATTR_HIDDEN
+            FolderExtendedProperty {
+                key: ExtendedPropertyKey::Tag(0x10F4),
+                property_type: MapiPropertyType::Boolean,
+                value: Some("true".to_string()),
+            },
+            // PR_RETENTION_PERIOD
+            FolderExtendedProperty {
+                key: ExtendedPropertyKey::Tag(0x301A),
+                property_type: MapiPropertyType::Integer,
+                value: None,
+            },
+        ],
+        ..FolderUpdate::default()
+    };
+    let result = client.update_folder(folder_id, Some("AQAAAA=="), &update).await;
+    assert!(result.is_ok(), "update_folder failed: {:?}", result.err());
+
+    // An update without changes is rejected before any request
+    let result = client.update_folder(folder_id, None, &FolderUpdate::default()).await;
+    assert!(result.is_err());
 }
 
 /// Test finding folders with mock server
@@ -497,6 +629,105 @@ async fn test_sync_folder_hierarchy_with_changes_mock() {
     assert!(!sync_result.deleted_folder_ids.is_empty());
 }
 
+/// Test syncing a folder hierarchy which contains a contacts folder with mock
+/// server
+#[tokio::test]
+async fn test_sync_folder_hierarchy_contacts_folder_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "SyncFolderHierarchy",
+        fixtures::sync_folder_hierarchy_typed_folder_response(
+            "contacts-state",
+            "ContactsFolder",
+            "contacts-id",
+            "IPF.Contact",
+        ),
+    )
+    .await;
+    mock.register_operation(
+        "GetFolder",
+        fixtures::get_typed_folder_response("ContactsFolder", "contacts-id", "Contacts", "IPF.Contact"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.sync_folder_hierarchy(Some("old-state".to_string())).await;
+    assert!(result.is_ok(), "sync_folder_hierarchy failed: {:?}", result.err());
+
+    let sync_result = result.unwrap();
+    assert_eq!(sync_result.created_folders.len(), 1);
+    let folder = &sync_result.created_folders[0];
+    assert_eq!(folder.folder_id, "contacts-id");
+    assert_eq!(folder.display_name, "Contacts");
+    assert_eq!(folder.folder_class.as_deref(), Some("IPF.Contact"));
+    assert_eq!(folder.unread_count, None);
+}
+
+/// Test syncing a folder hierarchy which contains a tasks folder with mock
+/// server
+#[tokio::test]
+async fn test_sync_folder_hierarchy_tasks_folder_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "SyncFolderHierarchy",
+        fixtures::sync_folder_hierarchy_typed_folder_response("tasks-state", "TasksFolder", "tasks-id", "IPF.Task"),
+    )
+    .await;
+    mock.register_operation(
+        "GetFolder",
+        fixtures::get_typed_folder_response("TasksFolder", "tasks-id", "Tasks", "IPF.Task"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.sync_folder_hierarchy(Some("old-state".to_string())).await;
+    assert!(result.is_ok(), "sync_folder_hierarchy failed: {:?}", result.err());
+
+    let sync_result = result.unwrap();
+    assert_eq!(sync_result.created_folders.len(), 1);
+    let folder = &sync_result.created_folders[0];
+    assert_eq!(folder.folder_id, "tasks-id");
+    assert_eq!(folder.display_name, "Tasks");
+    assert_eq!(folder.folder_class.as_deref(), Some("IPF.Task"));
+}
+
+/// Test syncing a folder hierarchy which contains a calendar folder with mock
+/// server
+#[tokio::test]
+async fn test_sync_folder_hierarchy_calendar_folder_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "SyncFolderHierarchy",
+        fixtures::sync_folder_hierarchy_typed_folder_response(
+            "calendar-state",
+            "CalendarFolder",
+            "calendar-id",
+            "IPF.Appointment",
+        ),
+    )
+    .await;
+    mock.register_operation(
+        "GetFolder",
+        fixtures::get_typed_folder_response("CalendarFolder", "calendar-id", "Calendar", "IPF.Appointment"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.sync_folder_hierarchy(Some("old-state".to_string())).await;
+    assert!(result.is_ok(), "sync_folder_hierarchy failed: {:?}", result.err());
+
+    let sync_result = result.unwrap();
+    assert_eq!(sync_result.created_folders.len(), 1);
+    let folder = &sync_result.created_folders[0];
+    assert_eq!(folder.folder_id, "calendar-id");
+    assert_eq!(folder.display_name, "Calendar");
+    assert_eq!(folder.folder_class.as_deref(), Some("IPF.Appointment"));
+    assert_eq!(folder.unread_count, None);
+}
+
 /// Test batch delete folders with mixed results
 #[tokio::test]
 async fn test_batch_delete_folders_mixed_with_mock() {
@@ -518,3 +749,81 @@ async fn test_batch_delete_folders_mixed_with_mock() {
     // Verify response
     assert!(result.is_ok(), "delete_folder failed: {:?}", result.err());
 }
+
+/// Test reading the permissions of a calendar folder with mock server
+#[tokio::test]
+async fn test_get_folder_permissions_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "GetFolder",
+        fixtures::get_calendar_folder_permissions_response("calendar-folder-id"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_folder_permissions("calendar").await;
+    assert!(result.is_ok(), "get_folder_permissions failed: {:?}", result.err());
+
+    let permission_set = result.unwrap();
+    assert!(permission_set.is_calendar);
+    assert_eq!(permission_set.permissions.len(), 2);
+    assert_eq!(permission_set.unknown_entries.len(), 1);
+
+    let default = &permission_set.permissions[0];
+    assert_eq!(default.user, PermissionUser::Default);
+    assert_eq!(default.level, PermissionLevel::FreeBusyTimeOnly);
+    assert_eq!(default.read_items, ReadAccess::TimeOnly);
+
+    let colleague = &permission_set.permissions[1];
+    assert_eq!(
+        colleague.user,
+        PermissionUser::User(OwnedMailbox {
+            name: Some("Casey Colleague".to_string()),
+            email_address: Some("colleague@example.com".to_string()),
+        })
+    );
+    assert_eq!(colleague.level, PermissionLevel::Editor);
+    assert!(colleague.can_create_items);
+    assert_eq!(colleague.edit_items, ItemAccess::All);
+}
+
+/// Test replacing the permissions of a folder with mock server
+#[tokio::test]
+async fn test_set_folder_permissions_with_mock() {
+    let mock = MockEwsServer::new().await;
+    let folder_id = "folder-test-permissions";
+    mock.register_operation("UpdateFolder", fixtures::update_folder_response(folder_id))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let permission_set = PermissionSet {
+        is_calendar: false,
+        permissions: vec![
+            FolderPermission::new(PermissionUser::Default, PermissionLevel::None),
+            FolderPermission::new(
+                PermissionUser::User(OwnedMailbox {
+                    name: None,
+                    email_address: Some("team@example.com".to_string()),
+                }),
+                PermissionLevel::Author,
+            ),
+        ],
+        unknown_entries: Vec::new(),
+    };
+
+    let result = client.set_folder_permissions(folder_id, &permission_set).await;
+    assert!(result.is_ok(), "set_folder_permissions failed: {:?}", result.err());
+
+    // Free/busy levels only exist on calendar folders
+    let free_busy = PermissionSet {
+        permissions: vec![FolderPermission::new(
+            PermissionUser::Default,
+            PermissionLevel::FreeBusyTimeAndSubjectAndLocation,
+        )],
+        ..permission_set
+    };
+    let result = client.set_folder_permissions(folder_id, &free_busy).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,179 @@
+//! Integration tests for Inbox rule operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for reading
+//! and changing server-side rules without requiring a real Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Credentials, EwsClient, EwsError, Importance, InboxRule, OwnedMailbox, RuleActions, RuleOperation, RulePredicates,
+};
+
+/// Creates a client which has learned from a first response that the server
+/// runs Exchange 2013, since the rule operations require Exchange 2010 or
+/// later.
+async fn client_for_exchange_2013(mock: &MockEwsServer) -> EwsClient {
+    mock.register_operation(
+        "GetUserOofSettingsRequest",
+        fixtures::with_server_version(&fixtures::get_user_oof_settings_response(), "Exchange2013_SP1"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    // The server version is not known before the first response
+    assert!(client.get_inbox_rules(None).await.is_err());
+
+    client.get_user_oof_settings("user@example.com").await.unwrap();
+    client
+}
+
+/// Test reading the rules of a mailbox with mock server
+#[tokio::test]
+async fn test_get_inbox_rules_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetInboxRules", fixtures::get_inbox_rules_response())
+        .await;
+    let client = client_for_exchange_2013(&mock).await;
+
+    let result = client.get_inbox_rules(Some("user@example.com")).await;
+    assert!(result.is_ok(), "get_inbox_rules failed: {:?}", result.err());
+
+    let result = result.unwrap();
+    assert!(result.outlook_rule_blob_exists);
+    assert_eq!(result.rules.len(), 2);
+
+    let newsletters = &result.rules[0];
+    assert_eq!(newsletters.rule_id.as_deref(), Some("AQAAAJRHm8s="));
+    assert_eq!(newsletters.display_name, "Newsletters");
+    assert_eq!(newsletters.priority, 1);
+    assert!(newsletters.is_enabled);
+    assert_eq!(
+        newsletters.conditions.contains_subject_strings,
+        vec!["Newsletter".to_string()]
+    );
+    assert_eq!(newsletters.exceptions.importance, Some(Importance::High));
+    assert_eq!(newsletters.actions.move_to_folder.as_deref(), Some("AAMkAGNewsletters"));
+    assert!(newsletters.actions.mark_as_read);
+    assert!(newsletters.actions.stop_processing_rules);
+
+    let forward = &result.rules[1];
+    assert!(!forward.is_enabled);
+    assert!(forward.is_in_error);
+    assert!(forward.conditions.sent_to_me);
+    assert_eq!(
+        forward.conditions.from_addresses,
+        vec![OwnedMailbox {
+            name: Some("The Boss".to_string()),
+            email_address: Some("boss@example.com".to_string()),
+        }]
+    );
+    assert_eq!(
+        forward.actions.forward_to_recipients[0].email_address.as_deref(),
+        Some("assistant@example.com")
+    );
+}
+
+/// Test creating, changing and deleting rules with mock server
+#[tokio::test]
+async fn test_update_inbox_rules_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("UpdateInboxRules", fixtures::update_inbox_rules_response())
+        .await;
+    let client = client_for_exchange_2013(&mock).await;
+
+    let rule = InboxRule {
+        display_name: "Invoices".to_string(),
+        priority: 1,
+        is_enabled: true,
+        conditions: RulePredicates {
+            contains_subject_strings: vec!["Invoice".to_string()],
+            has_attachments: true,
+            ..RulePredicates::default()
+        },
+        actions: RuleActions {
+            move_to_folder: Some("AAMkAGInvoices".to_string()),
+            mark_as_read: true,
+            ..RuleActions::default()
+        },
+        ..InboxRule::default()
+    };
+    let changed = InboxRule {
+        rule_id: Some("AQAAAJRHm8s=".to_string()),
+        is_enabled: false,
+        ..rule.clone()
+    };
+
+    let result = client
+        .update_inbox_rules(
+            None,
+            &[
+                RuleOperation::Create(rule),
+                RuleOperation::Set(changed),
+                RuleOperation::Delete("AQAAAJRHm8t=".to_string()),
+            ],
+            false,
+        )
+        .await;
+    assert!(result.is_ok(), "update_inbox_rules failed: {:?}", result.err());
+}
+
+/// Test that invalid operations are rejected before any request
+#[tokio::test]
+async fn test_update_inbox_rules_invalid() {
+    let mock = MockEwsServer::new().await;
+    let client = client_for_exchange_2013(&mock).await;
+
+    let result = client
+        .update_inbox_rules(None, &[RuleOperation::Set(InboxRule::default())], false)
+        .await;
+    assert!(matches!(result, Err(EwsError::Processing { .. })));
+
+    let result = client
+        .update_inbox_rules(None, &[RuleOperation::Delete(String::new())], false)
+        .await;
+    assert!(matches!(result, Err(EwsError::Processing { .. })));
+}
+
+/// Test that the validation errors of a rejected update are reported
+#[tokio::test]
+async fn test_update_inbox_rules_validation_error() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "UpdateInboxRules",
+        fixtures::update_inbox_rules_validation_error_response(),
+    )
+    .await;
+    let client = client_for_exchange_2013(&mock).await;
+
+    let rule = InboxRule {
+        display_name: "Archive".to_string(),
+        priority: 1,
+        is_enabled: true,
+        actions: RuleActions {
+            move_to_folder: Some("AAMkAGMissing".to_string()),
+            ..RuleActions::default()
+        },
+        ..InboxRule::default()
+    };
+
+    let result = client
+        .update_inbox_rules(None, &[RuleOperation::Create(rule)], false)
+        .await;
+    assert!(
+        matches!(
+            &result,
+            Err(EwsError::RawResponseError { response_code, message_text })
+                if response_code == "ErrorInboxRulesValidationError"
+                    && message_text.contains("ActionsMoveToFolder: FolderDoesNotExist")
+        ),
+        "unexpected result: {result:?}"
+    );
+}
@@ -14,7 +14,9 @@
 )]
 
 use crate::common::{MockEwsServer, fixtures, test_utils::*};
-use ews_client_core::client::{Credentials, EwsClient};
+use ews_client_core::client::{
+    BodyType, Credentials, EwsClient, ExtendedPropertyKey, ItemBaseShape, ItemShape, MapiPropertyType,
+};
 
 /// Helper function to create a SOAP request body for testing
 fn create_soap_request(operation: &str, body_content: &str) -> String {
@@ -374,6 +376,68 @@ async fn test_batch_get_items_with_mock() {
     assert_contains_subject(&body, subjects[1]);
 }
 
+/// Test getting items with a typed property shape with mock server
+#[tokio::test]
+async fn test_get_items_with_shape_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "GetItem",
+        fixtures::get_item_with_properties_response("item-props", "Plain text body"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let shape = ItemShape::new(ItemBaseShape::IdOnly)
+        .with_field("item:Body")
+        .with_field("item:Categories")
+        .with_field("message:InternetMessageHeaders")
+        .with_extended_property(ExtendedPropertyKey::Tag(0x1081), MapiPropertyType::Integer)
+        .with_body_type(BodyType::Text)
+        .with_filter_html_content(true);
+    let result = client.get_items(&["item-props"], &shape).await;
+    assert!(result.is_ok(), "get_items failed: {:?}", result.err());
+
+    let messages = result.unwrap();
+    assert_eq!(messages.len(), 1);
+    let message = &messages[0];
+    assert_eq!(message.item_id, "item-props");
+    let body = message.body.as_ref().unwrap();
+    assert_eq!(body.body_type, BodyType::Text);
+    assert_eq!(body.content, "Plain text body");
+    assert_eq!(message.categories, vec!["Project"]);
+    assert_eq!(message.internet_message_headers.len(), 2);
+    assert_eq!(
+        message.internet_message_headers[0],
+        ("List-Id".to_string(), "team.example.com".to_string())
+    );
+    assert_eq!(message.extended_properties[0].key, ExtendedPropertyKey::Tag(0x1081));
+    assert_eq!(message.extended_properties[0].value, "261");
+
+    // The shape is sent as requested
+    let requests = mock.received_ews_requests().await;
+    let request = String::from_utf8_lossy(&requests[0].body);
+    assert!(request.contains("<t:BodyType>Text</t:BodyType>"), "{request}");
+    assert!(
+        request.contains(r#"<t:ExtendedFieldURI PropertyTag="0x1081" PropertyType="Integer"/>"#),
+        "{request}"
+    );
+    assert!(request.contains(r#"<t:ItemId Id="item-props"/>"#), "{request}");
+}
+
+/// Test that `get_items` fails when an item cannot be found with mock server
+#[tokio::test]
+async fn test_get_items_not_found_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::error_item_not_found())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_items(&["missing"], &ItemShape::default()).await;
+    assert!(result.is_err(), "{result:?}");
+}
+
 /// Test batch delete items with mock server
 #[tokio::test]
 async fn test_batch_delete_items_with_mock() {
@@ -0,0 +1,250 @@
+//! Integration tests for impersonation and shared mailbox access using mock
+//! EWS server
+//!
+//! These tests inspect the requests received by the `MockEwsServer` to check
+//! the `ExchangeImpersonation` header, the `X-AnchorMailbox` header and the
+//! mailbox of distinguished folder IDs.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{Credentials, EwsClient, EwsClientPool, FolderKind, ImpersonatedUserId};
+
+/// Reads the body and `X-AnchorMailbox` header of a received request.
+fn body_and_anchor(request: &wiremock::Request) -> (String, Option<String>) {
+    let body = String::from_utf8_lossy(&request.body).into_owned();
+    let anchor = request
+        .headers
+        .get("X-AnchorMailbox")
+        .map(|value| value.to_str().unwrap().to_string());
+    (body, anchor)
+}
+
+/// Test a client impersonating a user for all its requests with mock server
+#[tokio::test]
+async fn test_client_impersonation_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CopyFolder", fixtures::copy_folder_response("folder-copy"))
+        .await;
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_typed_folder_response("TasksFolder", "folder-tasks"),
+    )
+    .await;
+
+    let client = EwsClient::new(
+        mock.ews_endpoint().parse().unwrap(),
+        Credentials::basic("service", "pass"),
+    )
+    .unwrap()
+    .with_impersonation(ImpersonatedUserId::SmtpAddress("user@example.com".to_string()));
+
+    // Operations from the `ews` crate and raw operations both carry the header
+    let result = client.copy_folders("inbox", &["folder-source"]).await;
+    assert!(result.is_ok(), "copy_folders failed: {:?}", result.err());
+    let result = client
+        .create_folder_of_kind("tasks", "Projects", FolderKind::Tasks, None)
+        .await;
+    assert!(result.is_ok(), "create_folder_of_kind failed: {:?}", result.err());
+
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        let (body, anchor) = body_and_anchor(request);
+        assert!(
+            body.contains(
+                "<t:ExchangeImpersonation><t:ConnectingSID><t:PrimarySmtpAddress>user@example.com</t:PrimarySmtpAddress></t:ConnectingSID></t:ExchangeImpersonation></soap:Header>"
+            ),
+            "{body}"
+        );
+        assert_eq!(anchor.as_deref(), Some("user@example.com"));
+    }
+}
+
+/// Test impersonating a user for a single request with mock server
+#[tokio::test]
+async fn test_request_impersonation_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateFolder", fixtures::create_folder_response("folder-created"))
+        .await;
+
+    let client = EwsClient::new(
+        mock.ews_endpoint().parse().unwrap(),
+        Credentials::basic("service", "pass"),
+    )
+    .unwrap()
+    .with_anchor_mailbox("service@example.com");
+
+    let impersonated = client.impersonating(ImpersonatedUserId::Sid("S-1-5-21-1117".to_string()));
+    let result = impersonated.create_folder("folder-parent", "Impersonated").await;
+    assert!(result.is_ok(), "create_folder failed: {:?}", result.err());
+
+    let result = client.create_folder("folder-parent", "Not impersonated").await;
+    assert!(result.is_ok(), "create_folder failed: {:?}", result.err());
+
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 2);
+
+    let (body, anchor) = body_and_anchor(&requests[0]);
+    assert!(body.contains("<t:SID>S-1-5-21-1117</t:SID>"), "{body}");
+    assert_eq!(anchor.as_deref(), Some("service@example.com"));
+
+    let (body, anchor) = body_and_anchor(&requests[1]);
+    assert!(!body.contains("ExchangeImpersonation"), "{body}");
+    assert_eq!(anchor.as_deref(), Some("service@example.com"));
+}
+
+/// Test targeting the distinguished folders of a shared mailbox with mock
+/// server
+#[tokio::test]
+async fn test_shared_mailbox_folders_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CopyFolder", fixtures::copy_folder_response("folder-copy"))
+        .await;
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_typed_folder_response("CalendarFolder", "folder-calendar"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+    let shared = client.for_mailbox("shared@example.com");
+
+    // Distinguished folders are named with their mailbox, without looking up
+    // their ID first
+    let result = shared.copy_folders("inbox", &["folder-source"]).await;
+    assert!(result.is_ok(), "copy_folders failed: {:?}", result.err());
+    let result = shared
+        .create_folder_of_kind("calendar", "Rota", FolderKind::Calendar, None)
+        .await;
+    assert!(result.is_ok(), "create_folder_of_kind failed: {:?}", result.err());
+
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 2);
+    let mailbox = "<t:Mailbox><t:EmailAddress>shared@example.com</t:EmailAddress></t:Mailbox>";
+
+    let (body, anchor) = body_and_anchor(&requests[0]);
+    assert!(body.contains("<m:CopyFolder"), "{body}");
+    assert!(
+        body.contains(&format!(
+            r#"<t:DistinguishedFolderId Id="inbox">{mailbox}</t:DistinguishedFolderId>"#
+        )),
+        "{body}"
+    );
+    assert!(body.contains(r#"<t:FolderId Id="folder-source"/>"#), "{body}");
+    assert_eq!(anchor.as_deref(), Some("shared@example.com"));
+
+    let (body, anchor) = body_and_anchor(&requests[1]);
+    assert!(
+        body.contains(&format!(
+            r#"<t:DistinguishedFolderId Id="calendar">{mailbox}</t:DistinguishedFolderId>"#
+        )),
+        "{body}"
+    );
+    assert!(!body.contains("ExchangeImpersonation"), "{body}");
+    assert_eq!(anchor.as_deref(), Some("shared@example.com"));
+}
+
+/// Test syncing the folder hierarchy of a shared mailbox with mock server
+#[tokio::test]
+async fn test_shared_mailbox_sync_folder_hierarchy_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "SyncFolderHierarchy",
+        fixtures::sync_folder_hierarchy_with_changes_response(
+            "shared-state",
+            "folder-created",
+            "folder-updated",
+            "folder-deleted",
+        ),
+    )
+    .await;
+    for (folder_id, display_name) in [("folder-created", "NewFolder"), ("folder-updated", "UpdatedFolder")] {
+        mock.register_operation_containing(
+            "GetFolder",
+            folder_id,
+            fixtures::get_folder_full_response(folder_id, "root-folder-id", display_name, "IPF.Note"),
+        )
+        .await;
+    }
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+    let shared = client.for_mailbox("shared@example.com");
+
+    let result = shared
+        .sync_folder_hierarchy(Some("old-state".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(result.sync_state, "shared-state");
+    assert_eq!(result.created_folders.len(), 1);
+    assert_eq!(result.created_folders[0].folder_id, "folder-created");
+    assert_eq!(result.updated_folders.len(), 1);
+    assert_eq!(result.updated_folders[0].folder_id, "folder-updated");
+    assert_eq!(result.deleted_folder_ids, vec!["folder-deleted".to_string()]);
+
+    // The root folder is named with its mailbox, without looking up its ID
+    // first
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 3);
+    let (body, anchor) = body_and_anchor(&requests[0]);
+    assert!(body.contains("<m:SyncFolderHierarchy>"), "{body}");
+    assert!(
+        body.contains(
+            r#"<t:DistinguishedFolderId Id="msgfolderroot"><t:Mailbox><t:EmailAddress>shared@example.com</t:EmailAddress></t:Mailbox></t:DistinguishedFolderId>"#
+        ),
+        "{body}"
+    );
+    assert!(body.contains("<m:SyncState>old-state</m:SyncState>"), "{body}");
+    assert_eq!(anchor.as_deref(), Some("shared@example.com"));
+}
+
+/// Test clients of a pool acting on different mailboxes with mock server
+#[tokio::test]
+async fn test_client_pool_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateFolder", fixtures::create_folder_response("folder-created"))
+        .await;
+
+    let pool = EwsClientPool::new(
+        mock.ews_endpoint().parse().unwrap(),
+        Credentials::basic("service", "pass"),
+    )
+    .unwrap();
+
+    let alice = pool.impersonated_client("alice@example.com");
+    let bob = pool
+        .client("bob@example.com")
+        .with_credentials(Credentials::basic("bob", "secret"));
+
+    let (alice_result, bob_result) = tokio::join!(
+        alice.create_folder("folder-parent", "Alice"),
+        bob.create_folder("folder-parent", "Bob")
+    );
+    assert!(alice_result.is_ok(), "create_folder failed: {:?}", alice_result.err());
+    assert!(bob_result.is_ok(), "create_folder failed: {:?}", bob_result.err());
+
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        let (body, anchor) = body_and_anchor(request);
+        let authorization = request.headers.get("Authorization").unwrap().to_str().unwrap();
+        if body.contains("<t:DisplayName>Alice</t:DisplayName>") {
+            assert!(
+                body.contains("<t:PrimarySmtpAddress>alice@example.com</t:PrimarySmtpAddress>"),
+                "{body}"
+            );
+            assert_eq!(anchor.as_deref(), Some("alice@example.com"));
+            assert_eq!(authorization, Credentials::basic("service", "pass").to_auth_header());
+        } else {
+            assert!(!body.contains("ExchangeImpersonation"), "{body}");
+            assert_eq!(anchor.as_deref(), Some("bob@example.com"));
+            assert_eq!(authorization, Credentials::basic("bob", "secret").to_auth_header());
+        }
+    }
+}
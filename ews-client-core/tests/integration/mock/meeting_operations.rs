@@ -0,0 +1,134 @@
+//! Integration tests for meeting workflow operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for meeting
+//! responses, cancellations and the meeting properties of items without requiring a real
+//! Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Credentials, EwsClient, ItemBaseShape, ItemShape, MeetingMessageKind, MeetingResponse, ResponseType,
+};
+
+/// Test accepting a meeting request with mock server
+#[tokio::test]
+async fn test_respond_to_meeting_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::create_calendar_item_response("meeting-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    for response in [
+        MeetingResponse::Accept,
+        MeetingResponse::Tentative,
+        MeetingResponse::Decline,
+    ] {
+        let result = client
+            .respond_to_meeting("request-1", response, Some("See you there"), true)
+            .await;
+        assert!(result.is_ok(), "respond_to_meeting failed: {:?}", result.err());
+        assert_eq!(result.unwrap().as_deref(), Some("meeting-1"));
+    }
+}
+
+/// Test that a response without a returned calendar item still succeeds
+#[tokio::test]
+async fn test_respond_to_meeting_without_calendar_item() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::create_item_send_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .respond_to_meeting("request-1", MeetingResponse::Decline, None, false)
+        .await;
+    assert!(result.is_ok(), "respond_to_meeting failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), None);
+}
+
+/// Test cancelling a meeting with mock server
+#[tokio::test]
+async fn test_cancel_meeting_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::create_item_send_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.cancel_meeting("meeting-1", Some("Cancelled, sorry")).await;
+    assert!(result.is_ok(), "cancel_meeting failed: {:?}", result.err());
+}
+
+/// Test that cancelling a missing meeting is reported as an error
+#[tokio::test]
+async fn test_cancel_meeting_not_found() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::error_item_not_found())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.cancel_meeting("missing", None).await;
+    assert!(result.is_err());
+}
+
+/// Test reading the meeting properties of a meeting request with `get_items`
+#[tokio::test]
+async fn test_get_items_meeting_request_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "GetItem",
+        fixtures::get_meeting_request_response("request-1", "meeting-1"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .get_items(&["request-1"], &ItemShape::new(ItemBaseShape::AllProperties))
+        .await;
+    assert!(result.is_ok(), "get_items failed: {:?}", result.err());
+
+    let messages = result.unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].item_id, "request-1");
+    assert_eq!(messages[0].subject.as_deref(), Some("Planning"));
+
+    let info = messages[0].meeting.as_ref().unwrap();
+    assert_eq!(info.kind, MeetingMessageKind::Request);
+    assert_eq!(info.associated_calendar_item_id.as_deref(), Some("meeting-1"));
+    assert_eq!(info.start, Some(1_705_314_600));
+    assert_eq!(info.end, Some(1_705_318_200));
+    assert_eq!(info.location.as_deref(), Some("Room 1"));
+    assert_eq!(info.response_type, Some(ResponseType::NoResponseReceived));
+    assert!(info.has_been_processed);
+    assert!(!info.is_out_of_date);
+
+    // The meeting properties come with the item, without another request
+    assert_eq!(mock.received_ews_requests().await.len(), 1);
+}
+
+/// Test that items which are not meeting messages have no meeting properties
+#[tokio::test]
+async fn test_get_items_plain_message_has_no_meeting() {
+    let mock = MockEwsServer::new().await;
+    mock.mock_get_item("message-1", "Hello").await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let messages = client
+        .get_items(&["message-1"], &ItemShape::new(ItemBaseShape::Default))
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].meeting.is_none());
+}
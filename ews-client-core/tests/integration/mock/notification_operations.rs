@@ -0,0 +1,279 @@
+//! Integration tests for notification operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for pull
+//! subscriptions without requiring a real Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{Credentials, EwsClient, NotificationEventType, PullSubscriptionState};
+
+/// Test creating a pull subscription with mock server
+#[tokio::test]
+async fn test_subscribe_pull_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("Subscribe", fixtures::subscribe_response("sub-1", "wm-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .subscribe_pull(&["inbox"], &[NotificationEventType::NewMail], 30)
+        .await;
+    assert!(result.is_ok(), "subscribe_pull failed: {:?}", result.err());
+
+    let state = result.unwrap();
+    assert_eq!(state.subscription_id, "sub-1");
+    assert_eq!(state.watermark, "wm-1");
+    assert_eq!(state.folder_ids, vec!["inbox".to_string()]);
+    assert_eq!(state.timeout_minutes, 30);
+
+    // The state should survive a round trip through its persisted form
+    let restored = PullSubscriptionState::from_json(&state.to_json().unwrap()).unwrap();
+    assert_eq!(restored, state);
+}
+
+/// Test that invalid subscription parameters are rejected before any request
+#[tokio::test]
+async fn test_subscribe_pull_rejects_invalid_parameters() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .subscribe_pull(&["inbox"], &[NotificationEventType::NewMail], 0)
+        .await;
+    assert!(result.is_err());
+
+    let result = client
+        .subscribe_pull(&["inbox"], &[NotificationEventType::Status], 30)
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test fetching events with mock server
+#[tokio::test]
+async fn test_get_events_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "GetEvents",
+        fixtures::get_events_response("sub-1", "item-1", "wm-2", false),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_events("sub-1", "wm-1").await;
+    assert!(result.is_ok(), "get_events failed: {:?}", result.err());
+
+    let notification = result.unwrap();
+    assert_eq!(notification.subscription_id, "sub-1");
+    assert!(!notification.more_events);
+    assert_eq!(notification.events.len(), 1);
+    assert_eq!(notification.events[0].event_type, NotificationEventType::NewMail);
+    assert_eq!(notification.events[0].item_id.as_deref(), Some("item-1"));
+    assert_eq!(
+        notification.events[0].parent_folder_id.as_deref(),
+        Some("inbox-folder-id")
+    );
+    assert_eq!(notification.last_watermark(), Some("wm-2"));
+}
+
+/// Test that polling advances the watermark and drops status events
+#[tokio::test]
+async fn test_poll_pull_subscription_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetEvents", fixtures::get_events_status_response("sub-1", "wm-5"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let mut state = PullSubscriptionState {
+        subscription_id: "sub-1".to_string(),
+        watermark: "wm-1".to_string(),
+        folder_ids: vec!["inbox".to_string()],
+        event_types: vec![NotificationEventType::NewMail],
+        timeout_minutes: 30,
+    };
+
+    let result = client.poll_pull_subscription(&mut state).await.unwrap();
+    assert!(result.events.is_empty());
+    assert!(!result.requires_resync);
+    assert_eq!(state.watermark, "wm-5");
+}
+
+/// Test that the watermark is left unchanged when a later page of events
+/// fails, so that the next poll reads the same events again
+#[tokio::test]
+async fn test_poll_pull_subscription_keeps_watermark_on_failure() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation_once(
+        "GetEvents",
+        fixtures::get_events_response("sub-1", "item-1", "wm-2", true),
+    )
+    .await;
+    mock.register_server_error().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let mut state = PullSubscriptionState {
+        subscription_id: "sub-1".to_string(),
+        watermark: "wm-1".to_string(),
+        folder_ids: vec!["inbox".to_string()],
+        event_types: vec![NotificationEventType::NewMail],
+        timeout_minutes: 30,
+    };
+
+    let result = client.poll_pull_subscription(&mut state).await;
+    assert!(result.is_err());
+    assert_eq!(state.watermark, "wm-1");
+
+    // The second page was requested from the watermark of the first one
+    let requests = mock.received_ews_requests().await;
+    let body = String::from_utf8_lossy(&requests.last().unwrap().body);
+    assert!(body.contains("<m:Watermark>wm-2</m:Watermark>"), "{body}");
+}
+
+/// Test that an expired subscription is re-created from its last watermark,
+/// and the events of the new subscription are read
+#[tokio::test]
+async fn test_poll_pull_subscription_recreates_expired_subscription() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation_once("GetEvents", fixtures::error_expired_subscription())
+        .await;
+    mock.register_operation(
+        "GetEvents",
+        fixtures::get_events_response("sub-2", "item-2", "wm-3", false),
+    )
+    .await;
+    mock.register_operation("Subscribe", fixtures::subscribe_response("sub-2", "wm-2"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let mut state = PullSubscriptionState {
+        subscription_id: "sub-1".to_string(),
+        watermark: "wm-1".to_string(),
+        folder_ids: vec!["inbox".to_string()],
+        event_types: vec![NotificationEventType::NewMail],
+        timeout_minutes: 30,
+    };
+
+    let result = client.poll_pull_subscription(&mut state).await;
+    assert!(result.is_ok(), "poll_pull_subscription failed: {:?}", result.err());
+
+    let result = result.unwrap();
+    assert!(!result.requires_resync);
+    assert_eq!(result.events.len(), 1);
+    assert_eq!(result.events[0].item_id.as_deref(), Some("item-2"));
+    assert_eq!(state.subscription_id, "sub-2");
+    assert_eq!(state.watermark, "wm-3");
+
+    // The new subscription resumed from the last watermark and was polled
+    let requests = mock.received_ews_requests().await;
+    let bodies: Vec<String> = requests
+        .iter()
+        .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+        .collect();
+    assert!(
+        bodies
+            .iter()
+            .any(|body| body.contains("<m:Subscribe>") && body.contains("<t:Watermark>wm-1</t:Watermark>")),
+        "{bodies:?}"
+    );
+    let last = bodies.last().unwrap();
+    assert!(last.contains("<m:SubscriptionId>sub-2</m:SubscriptionId>"), "{last}");
+}
+
+/// Test that an expired subscription is re-created without a watermark when
+/// the old one can no longer be used
+#[tokio::test]
+async fn test_poll_pull_subscription_requires_resync_on_invalid_watermark() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation_once("GetEvents", fixtures::error_expired_subscription())
+        .await;
+    mock.register_operation("GetEvents", fixtures::get_events_status_response("sub-2", "wm-3"))
+        .await;
+    // Resuming from the old watermark fails, the fresh subscription succeeds
+    mock.register_operation_once("Subscribe", fixtures::error_invalid_watermark())
+        .await;
+    mock.register_operation("Subscribe", fixtures::subscribe_response("sub-2", "wm-2"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let mut state = PullSubscriptionState {
+        subscription_id: "sub-1".to_string(),
+        watermark: "wm-1".to_string(),
+        folder_ids: vec![],
+        event_types: vec![NotificationEventType::Created],
+        timeout_minutes: 30,
+    };
+
+    let result = client.poll_pull_subscription(&mut state).await;
+    assert!(result.is_ok(), "poll_pull_subscription failed: {:?}", result.err());
+
+    let result = result.unwrap();
+    assert!(result.requires_resync);
+    assert!(result.events.is_empty());
+    assert_eq!(state.subscription_id, "sub-2");
+    assert_eq!(state.watermark, "wm-3");
+
+    // The fallback subscription starts without a watermark
+    let requests = mock.received_ews_requests().await;
+    let subscribe_bodies: Vec<String> = requests
+        .iter()
+        .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+        .filter(|body| body.contains("<m:Subscribe>"))
+        .collect();
+    assert_eq!(subscribe_bodies.len(), 2, "{subscribe_bodies:?}");
+    assert!(
+        !subscribe_bodies[1].contains("<t:Watermark>"),
+        "{}",
+        subscribe_bodies[1]
+    );
+}
+
+/// Test that the state is left untouched when no subscription can be
+/// re-created
+#[tokio::test]
+async fn test_poll_pull_subscription_keeps_state_when_resubscribing_fails() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetEvents", fixtures::error_expired_subscription())
+        .await;
+    // Every `Subscribe` request fails, so the fallback subscription fails too
+    mock.register_operation("Subscribe", fixtures::error_invalid_watermark())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let mut state = PullSubscriptionState {
+        subscription_id: "sub-1".to_string(),
+        watermark: "wm-1".to_string(),
+        folder_ids: vec![],
+        event_types: vec![NotificationEventType::Created],
+        timeout_minutes: 30,
+    };
+
+    let result = client.poll_pull_subscription(&mut state).await;
+    assert!(result.is_err());
+    assert_eq!(state.subscription_id, "sub-1");
+}
+
+/// Test ending a subscription with mock server
+#[tokio::test]
+async fn test_unsubscribe_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("Unsubscribe", fixtures::unsubscribe_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.unsubscribe("sub-1").await;
+    assert!(result.is_ok(), "unsubscribe failed: {:?}", result.err());
+}
@@ -0,0 +1,112 @@
+//! Integration tests for out-of-office operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for reading
+//! and changing automatic replies without requiring a real Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{Credentials, EwsClient, ExternalAudience, OofState};
+
+/// 2024-01-15T00:00:00Z
+const START: i64 = 1_705_276_800;
+
+/// Test reading scheduled automatic replies with mock server
+#[tokio::test]
+async fn test_get_user_oof_settings_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetUserOofSettingsRequest", fixtures::get_user_oof_settings_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_user_oof_settings("user@example.com").await;
+    assert!(result.is_ok(), "get_user_oof_settings failed: {:?}", result.err());
+
+    let settings = result.unwrap();
+    assert_eq!(
+        settings.state,
+        OofState::Scheduled {
+            start: START,
+            end: START + 7 * 24 * 3600
+        }
+    );
+    assert_eq!(settings.external_audience, ExternalAudience::All);
+    assert_eq!(settings.internal_reply.as_deref(), Some("<p>Back on Monday</p>"));
+    assert_eq!(settings.external_reply.as_deref(), Some("I am out of the office."));
+    assert_eq!(settings.allow_external_oof, Some(ExternalAudience::All));
+}
+
+/// Test enabling automatic replies with mock server
+#[tokio::test]
+async fn test_set_user_oof_settings_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("SetUserOofSettingsRequest", fixtures::set_user_oof_settings_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .set_user_oof_settings(
+            "user@example.com",
+            OofState::Scheduled {
+                start: START,
+                end: START + 3600,
+            },
+            "<p>Back soon</p>",
+            "Out of the office",
+            ExternalAudience::Known,
+        )
+        .await;
+    assert!(result.is_ok(), "set_user_oof_settings failed: {:?}", result.err());
+}
+
+/// Test that invalid settings are rejected before any request
+#[tokio::test]
+async fn test_set_user_oof_settings_invalid() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .set_user_oof_settings(
+            "user@example.com",
+            OofState::Scheduled {
+                start: START,
+                end: START,
+            },
+            "",
+            "",
+            ExternalAudience::None,
+        )
+        .await;
+    assert!(result.is_err());
+
+    let result = client
+        .set_user_oof_settings("", OofState::Disabled, "", "", ExternalAudience::None)
+        .await;
+    assert!(result.is_err());
+}
+
+/// Test that a refused change is reported as an error
+#[tokio::test]
+async fn test_set_user_oof_settings_access_denied() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "SetUserOofSettingsRequest",
+        fixtures::set_user_oof_settings_access_denied_response(),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client
+        .set_user_oof_settings("boss@example.com", OofState::Enabled, "", "", ExternalAudience::All)
+        .await;
+    assert!(result.is_err());
+}
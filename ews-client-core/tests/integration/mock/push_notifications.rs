@@ -0,0 +1,174 @@
+//! Integration tests for push notifications using mock EWS server
+//!
+//! These tests cover creating push subscriptions against the `MockEwsServer`,
+//! and play the part of the Exchange server to deliver notifications to a
+//! local `PushNotificationListener`.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Credentials, EwsClient, NotificationEventType, PushNotificationListener, PushNotificationResult,
+};
+use tokio::sync::mpsc;
+
+/// Test creating a push subscription with mock server
+#[tokio::test]
+async fn test_subscribe_push_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("Subscribe", fixtures::subscribe_response("push-sub-1", "wm-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+    let callback_url = "http://127.0.0.1:8080/notifications".parse().unwrap();
+
+    let result = client
+        .subscribe_push(&["inbox"], &[NotificationEventType::NewMail], &callback_url, 5, None)
+        .await;
+    assert!(result.is_ok(), "subscribe_push failed: {:?}", result.err());
+
+    let subscription = result.unwrap();
+    assert_eq!(subscription.subscription_id, "push-sub-1");
+    assert_eq!(subscription.watermark.as_deref(), Some("wm-1"));
+}
+
+/// Test that an invalid status frequency is rejected before any request
+#[tokio::test]
+async fn test_subscribe_push_rejects_invalid_status_frequency() {
+    let mock = MockEwsServer::new().await;
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+    let callback_url = "http://127.0.0.1:8080/notifications".parse().unwrap();
+
+    let result = client
+        .subscribe_push(&["inbox"], &[NotificationEventType::NewMail], &callback_url, 0, None)
+        .await;
+    assert!(result.is_err());
+}
+
+/// Starts a listener on a random port, forwarding notifications to a channel
+/// and answering with the given result. Returns the listener's URL.
+async fn start_listener(
+    result: PushNotificationResult,
+) -> (String, mpsc::UnboundedReceiver<ews_client_core::client::Notification>) {
+    let callback_url = "http://127.0.0.1/notifications".parse().unwrap();
+    let listener = PushNotificationListener::bind("127.0.0.1:0", &callback_url)
+        .await
+        .unwrap();
+    let url = format!("http://{}/notifications", listener.local_addr().unwrap());
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(listener.run(move |notification| {
+        let sender = sender.clone();
+        async move {
+            sender.send(notification).unwrap();
+            result
+        }
+    }));
+
+    (url, receiver)
+}
+
+/// Test that the listener parses notifications and keeps the subscription
+#[tokio::test]
+async fn test_listener_receives_notification() {
+    let (url, mut receiver) = start_listener(PushNotificationResult::Ok).await;
+
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header("Content-Type", "text/xml; charset=utf-8")
+        .body(fixtures::send_notification_request("push-sub-1", "item-1"))
+        .send()
+        .await
+        .unwrap();
+
+    assert!(response.status().is_success());
+    let body = response.text().await.unwrap();
+    assert!(body.contains("<m:SubscriptionStatus>OK</m:SubscriptionStatus>"));
+
+    let notification = receiver.recv().await.unwrap();
+    assert_eq!(notification.subscription_id, "push-sub-1");
+    assert_eq!(notification.events.len(), 1);
+    assert_eq!(notification.events[0].event_type, NotificationEventType::NewMail);
+    assert_eq!(notification.events[0].item_id.as_deref(), Some("item-1"));
+}
+
+/// Test that the listener relays the handler's request to unsubscribe
+#[tokio::test]
+async fn test_listener_answers_unsubscribe() {
+    let (url, _receiver) = start_listener(PushNotificationResult::Unsubscribe).await;
+
+    let response = reqwest::Client::new()
+        .post(&url)
+        .body(fixtures::send_notification_request("push-sub-1", "item-1"))
+        .send()
+        .await
+        .unwrap();
+
+    let body = response.text().await.unwrap();
+    assert!(body.contains("<m:SubscriptionStatus>Unsubscribe</m:SubscriptionStatus>"));
+}
+
+/// Test that the listener rejects requests which aren't notifications
+#[tokio::test]
+async fn test_listener_rejects_invalid_request() {
+    let (url, _receiver) = start_listener(PushNotificationResult::Ok).await;
+
+    let response = reqwest::Client::new()
+        .post(&url)
+        .body("not a notification")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let response = reqwest::Client::new().get(&url).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::METHOD_NOT_ALLOWED);
+}
+
+/// Test that the listener only serves the path of the callback URL
+#[tokio::test]
+async fn test_listener_rejects_other_paths() {
+    let (url, _receiver) = start_listener(PushNotificationResult::Ok).await;
+    let other_url = url.replace("/notifications", "/other");
+
+    let response = reqwest::Client::new()
+        .post(&other_url)
+        .body(fixtures::send_notification_request("push-sub-1", "item-1"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+    // The query string is not part of the path
+    let response = reqwest::Client::new()
+        .post(format!("{url}?id=1"))
+        .body(fixtures::send_notification_request("push-sub-1", "item-1"))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+}
+
+/// Test that the listener rejects bodies larger than a notification can be
+#[tokio::test]
+async fn test_listener_rejects_large_body() {
+    let (url, _receiver) = start_listener(PushNotificationResult::Ok).await;
+
+    let response = reqwest::Client::new()
+        .post(&url)
+        .body(vec![b'a'; 2 * 1024 * 1024])
+        .send()
+        .await;
+
+    // The listener answers before reading the body, so the client may fail
+    // to send it instead of reading the answer
+    if let Ok(response) = response {
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+}
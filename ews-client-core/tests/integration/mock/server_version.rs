@@ -0,0 +1,175 @@
+//! Integration tests for server version detection and storage using mock EWS
+//! server
+//!
+//! These tests check that clients detect the server version on demand, that
+//! a `VersionStore` lets new clients use it from their first request, that
+//! operations check the server's capabilities, and that a pinned version
+//! replaces detection.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use std::sync::Arc;
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::{
+    client::{
+        Credentials, EwsClient, EwsClientPool, EwsError, InMemoryVersionStore, JsonFileVersionStore, VersionStore,
+    },
+    ews::server_version::ExchangeServerVersion,
+};
+
+/// Registers a root folder response reporting Exchange 2013 SP1, and a
+/// `MarkAsJunk` response.
+async fn register_exchange_2013_responses(mock: &MockEwsServer) {
+    mock.register_operation(
+        "GetFolder",
+        fixtures::with_server_version(
+            &fixtures::get_folder_distinguished_response("msgfolderroot", "root-id"),
+            "Exchange2013_SP1",
+        ),
+    )
+    .await;
+    mock.register_operation("MarkAsJunk", fixtures::mark_as_junk_response(Some("junk-moved")))
+        .await;
+}
+
+/// Test detecting the server version before a version-dependent operation
+/// with mock server
+#[tokio::test]
+async fn test_detect_server_version_with_mock() {
+    let mock = MockEwsServer::new().await;
+    register_exchange_2013_responses(&mock).await;
+
+    let store = Arc::new(InMemoryVersionStore::new());
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_version_store(store.clone());
+
+    let version = client.detect_server_version().await.unwrap();
+    assert_eq!(version, ExchangeServerVersion::Exchange2013_SP1);
+    assert_eq!(
+        store.load(client.endpoint()),
+        Some(ExchangeServerVersion::Exchange2013_SP1)
+    );
+
+    // The first call uses `MarkAsJunk` rather than moving the items
+    let result = client.mark_as_junk(&["junk-item"], true, "").await;
+    assert!(result.is_ok(), "mark_as_junk failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), vec!["junk-moved"]);
+}
+
+/// Test new clients reading the version stored in a JSON file with mock
+/// server
+#[tokio::test]
+async fn test_json_file_version_store_with_mock() {
+    let mock = MockEwsServer::new().await;
+    register_exchange_2013_responses(&mock).await;
+
+    let path = std::env::temp_dir().join(format!("ews-mock-versions-{}.json", std::process::id()));
+    let endpoint = mock.ews_endpoint().parse().unwrap();
+
+    let client = EwsClient::new(endpoint, Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_version_store(Arc::new(JsonFileVersionStore::open(&path).unwrap()));
+    client.detect_server_version().await.unwrap();
+    drop(client);
+
+    // A client of another process would read the version from the file
+    let endpoint = mock.ews_endpoint().parse().unwrap();
+    let client = EwsClient::new(endpoint, Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_version_store(Arc::new(JsonFileVersionStore::open(&path).unwrap()));
+    let result = client.mark_as_junk(&["junk-item"], true, "").await;
+    assert!(result.is_ok(), "mark_as_junk failed: {:?}", result.err());
+
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 2);
+    let body = String::from_utf8_lossy(&requests[1].body);
+    assert!(body.contains(r#"Version="Exchange2013_SP1""#), "{body}");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Test operations checking the capabilities of the detected version with
+/// mock server
+#[tokio::test]
+async fn test_server_capabilities_with_mock() {
+    let mock = MockEwsServer::new().await;
+    register_exchange_2013_responses(&mock).await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_version_store(Arc::new(InMemoryVersionStore::new()));
+
+    // Before detection, the client assumes Exchange 2007 SP1
+    assert!(!client.capabilities().mark_as_junk);
+    let result = client.mark_as_junk(&["junk-item"], true, "").await;
+    assert!(
+        matches!(
+            &result,
+            Err(EwsError::Unsupported { feature, server_version: ExchangeServerVersion::Exchange2007_SP1 })
+                if feature == "MarkAsJunk"
+        ),
+        "{result:?}"
+    );
+    assert!(mock.received_ews_requests().await.is_empty());
+
+    client.detect_server_version().await.unwrap();
+    let capabilities = client.capabilities();
+    assert_eq!(capabilities.version, ExchangeServerVersion::Exchange2013_SP1);
+    assert!(capabilities.mark_as_junk);
+    assert!(capabilities.find_people);
+}
+
+/// Test pinning the version of a client whose server reports another one
+/// with mock server
+#[tokio::test]
+async fn test_pinned_server_version_with_mock() {
+    let mock = MockEwsServer::new().await;
+    register_exchange_2013_responses(&mock).await;
+    mock.register_operation("MoveItem", fixtures::move_item_response("junk-moved"))
+        .await;
+
+    let store = Arc::new(InMemoryVersionStore::new());
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_server_version(ExchangeServerVersion::Exchange2010)
+        .with_version_store(store.clone());
+    assert!(client.is_server_version_pinned());
+
+    // Detection returns the pinned version without sending a request
+    let version = client.detect_server_version().await.unwrap();
+    assert_eq!(version, ExchangeServerVersion::Exchange2010);
+    assert!(mock.received_ews_requests().await.is_empty());
+
+    // The version reported by the server is neither used nor stored
+    client.check_connectivity().await.unwrap();
+    assert_eq!(client.server_version(), ExchangeServerVersion::Exchange2010);
+    assert_eq!(store.load(client.endpoint()), None);
+
+    // Operations follow the pinned version
+    let result = client.mark_as_junk(&["junk-item"], true, "junk-folder-id").await;
+    assert!(result.is_ok(), "mark_as_junk failed: {:?}", result.err());
+
+    let requests = mock.received_ews_requests().await;
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        let body = String::from_utf8_lossy(&request.body);
+        assert!(body.contains(r#"Version="Exchange2010""#), "{body}");
+    }
+    assert!(String::from_utf8_lossy(&requests[1].body).contains("MoveItem"));
+
+    // Clients of a pool with a pinned version are pinned too
+    let pool = EwsClientPool::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_server_version(ExchangeServerVersion::Exchange2007_SP1);
+    let client = pool.client("alice@example.com");
+    assert!(client.is_server_version_pinned());
+    assert_eq!(client.server_version(), ExchangeServerVersion::Exchange2007_SP1);
+}
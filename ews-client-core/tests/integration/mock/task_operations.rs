@@ -0,0 +1,224 @@
+//! Integration tests for task operations using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate EWS responses for listing,
+//! reading, creating, updating and deleting tasks, and for flagging messages,
+//! without requiring a real Exchange server.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{
+    Credentials, DayOfWeek, DayOfWeekIndex, EwsClient, Recurrence, RecurrencePattern, RecurrenceRange,
+    RegenerationFrequency, TaskDraft, TaskStatus, TaskUpdate,
+};
+
+/// Test listing a page of tasks with mock server
+#[tokio::test]
+async fn test_find_tasks_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("FindItem", fixtures::find_tasks_response(true))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.find_tasks(None, 0, 50).await;
+    assert!(result.is_ok(), "find_tasks failed: {:?}", result.err());
+
+    // The flagged message in the folder is not returned as a task
+    let page = result.unwrap();
+    assert_eq!(page.tasks.len(), 1);
+    assert!(page.includes_last_item);
+    assert_eq!(page.next_offset, None);
+
+    let task = &page.tasks[0];
+    assert_eq!(task.item_id, "task-1");
+    assert_eq!(task.status, TaskStatus::InProgress);
+    assert!((task.percent_complete - 50.0).abs() < f64::EPSILON);
+    assert_eq!(task.due_date, Some(1_706_659_200));
+    assert_eq!(task.reminder, None);
+}
+
+/// Test reading a recurring task with a reminder
+#[tokio::test]
+async fn test_get_tasks_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("GetItem", fixtures::get_task_response("task-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.get_tasks(&["task-1"]).await;
+    assert!(result.is_ok(), "get_tasks failed: {:?}", result.err());
+
+    let tasks = result.unwrap();
+    assert_eq!(tasks.len(), 1);
+
+    let task = &tasks[0];
+    assert_eq!(task.subject.as_deref(), Some("Send the monthly report"));
+    assert_eq!(task.body.as_deref(), Some("To the whole team"));
+    assert_eq!(task.start_date, Some(1_705_276_800));
+    assert_eq!(task.reminder, Some(1_706_605_200));
+    assert_eq!(task.status, TaskStatus::NotStarted);
+    assert!(task.is_recurring);
+    assert_eq!(
+        task.recurrence,
+        Some(Recurrence {
+            pattern: RecurrencePattern::RelativeMonthly {
+                interval: 1,
+                day_of_week: DayOfWeek::Weekday,
+                day_of_week_index: DayOfWeekIndex::Last,
+            },
+            range: RecurrenceRange::Numbered {
+                start_date: 1_706_659_200,
+                occurrences: 12,
+            },
+        })
+    );
+}
+
+/// Test creating a recurring task with mock server
+#[tokio::test]
+async fn test_create_task_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateItem", fixtures::create_task_response("task-new"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let draft = TaskDraft {
+        subject: "Water the plants".to_string(),
+        start_date: Some(1_705_276_800),
+        due_date: Some(1_705_276_800),
+        recurrence: Some(Recurrence {
+            pattern: RecurrencePattern::Regenerating {
+                frequency: RegenerationFrequency::Weekly,
+                interval: 1,
+            },
+            range: RecurrenceRange::NoEnd {
+                start_date: 1_705_276_800,
+            },
+        }),
+        reminder: Some(1_705_309_200),
+        ..Default::default()
+    };
+
+    let result = client.create_task(None, &draft).await;
+    assert!(result.is_ok(), "create_task failed: {:?}", result.err());
+    assert_eq!(result.unwrap().item_id, "task-new");
+}
+
+/// Test that an invalid task is rejected before it is sent
+#[tokio::test]
+async fn test_create_task_invalid() {
+    let mock = MockEwsServer::new().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let draft = TaskDraft {
+        subject: "Too much".to_string(),
+        percent_complete: Some(150.0),
+        ..Default::default()
+    };
+    assert!(client.create_task(None, &draft).await.is_err());
+
+    let draft = TaskDraft {
+        subject: "Never".to_string(),
+        recurrence: Some(Recurrence {
+            pattern: RecurrencePattern::Daily { interval: 0 },
+            range: RecurrenceRange::NoEnd { start_date: 0 },
+        }),
+        ..Default::default()
+    };
+    assert!(client.create_task(None, &draft).await.is_err());
+}
+
+/// Test completing a task and removing its reminder with mock server
+#[tokio::test]
+async fn test_update_task_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("UpdateItem", fixtures::update_task_response("task-1", "FAAAAA=="))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let update = TaskUpdate {
+        status: Some(TaskStatus::Completed),
+        percent_complete: Some(100.0),
+        reminder: Some(None),
+        due_date: Some(None),
+        ..Default::default()
+    };
+
+    let result = client.update_task("task-1", Some("EwAAAA=="), &update).await;
+    assert!(result.is_ok(), "update_task failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), "FAAAAA==");
+
+    let requests = mock.received_ews_requests().await;
+    let body = String::from_utf8_lossy(&requests[0].body);
+    assert!(body.contains(r#"ConflictResolution="NeverOverwrite""#), "{body}");
+}
+
+/// Test that an update without any change is rejected before it is sent
+#[tokio::test]
+async fn test_update_task_without_changes() {
+    let mock = MockEwsServer::new().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.update_task("task-1", None, &TaskUpdate::default()).await;
+    assert!(result.is_err());
+}
+
+/// Test deleting a task with mock server
+#[tokio::test]
+async fn test_delete_task_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.mock_delete_item().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.delete_task("task-1").await;
+    assert!(result.is_ok(), "delete_task failed: {:?}", result.err());
+}
+
+/// Test flagging a message as a task, which requires the client to know the
+/// server runs Exchange 2013 or later
+#[tokio::test]
+async fn test_mark_message_as_task_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation(
+        "GetItem",
+        fixtures::with_server_version(&fixtures::get_task_response("task-1"), "Exchange2013_SP1"),
+    )
+    .await;
+    mock.register_operation("UpdateItem", fixtures::update_item_response("message-1"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    // The server version is not known before the first response
+    let result = client
+        .mark_message_as_task("message-1", None, Some(1_705_276_800), Some(1_706_659_200))
+        .await;
+    assert!(result.is_err());
+
+    client.get_tasks(&["task-1"]).await.unwrap();
+
+    let result = client
+        .mark_message_as_task("message-1", None, Some(1_705_276_800), Some(1_706_659_200))
+        .await;
+    assert!(result.is_ok(), "mark_message_as_task failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), "CQAAAA==");
+
+    // The start and due dates go together
+    let result = client
+        .mark_message_as_task("message-1", None, None, Some(1_706_659_200))
+        .await;
+    assert!(result.is_err());
+}
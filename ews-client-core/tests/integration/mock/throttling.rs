@@ -0,0 +1,128 @@
+//! Integration tests for client-side throttling using mock EWS server
+//!
+//! These tests use the `MockEwsServer` to simulate a server asking the client
+//! to back off, and check that throttled clients wait and retry.
+
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::ignored_unit_patterns,
+    clippy::indexing_slicing,
+    clippy::print_stdout
+)]
+
+use std::time::{Duration, Instant};
+
+use crate::common::{MockEwsServer, fixtures};
+use ews_client_core::client::{Credentials, EwsClient, FolderKind, ThrottlingConfig};
+
+/// Test a throttled client backing off when the server is busy with mock
+/// server, which Exchange reports with a SOAP fault and a 500 status
+#[tokio::test]
+async fn test_throttled_client_backs_off_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation_once_with_status("CreateFolder", 500, fixtures::server_busy_fault(100))
+        .await;
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_typed_folder_response("Folder", "folder-created"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_throttling(ThrottlingConfig {
+            requests_per_second: 1000.0,
+            ..ThrottlingConfig::default()
+        });
+
+    let start = Instant::now();
+    let result = client
+        .create_folder_of_kind("inbox", "Retried", FolderKind::Mail, None)
+        .await;
+    assert!(result.is_ok(), "create_folder_of_kind failed: {:?}", result.err());
+    assert_eq!(result.unwrap(), "folder-created");
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    assert_eq!(mock.received_ews_requests().await.len(), 2);
+}
+
+/// Test a throttled client backing off when the first response message of
+/// an operation reports the server is busy with mock server
+#[tokio::test]
+async fn test_throttled_client_backs_off_on_busy_response_message_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation_once("CreateFolder", fixtures::server_busy_response("CreateFolder", 100))
+        .await;
+    mock.register_operation(
+        "CreateFolder",
+        fixtures::create_typed_folder_response("Folder", "folder-created"),
+    )
+    .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let start = Instant::now();
+    let result = client
+        .create_folder_of_kind("inbox", "Retried", FolderKind::Mail, None)
+        .await;
+    assert!(result.is_ok(), "create_folder_of_kind failed: {:?}", result.err());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(mock.received_ews_requests().await.len(), 2);
+}
+
+/// Test an operation the `ews` crate does not model backing off on a SOAP
+/// fault sent with a 500 status with mock server
+#[tokio::test]
+async fn test_raw_operation_backs_off_on_fault_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation_once_with_status("Unsubscribe", 500, fixtures::server_busy_fault(100))
+        .await;
+    mock.register_operation("Unsubscribe", fixtures::unsubscribe_response())
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let start = Instant::now();
+    let result = client.unsubscribe("sub-1").await;
+    assert!(result.is_ok(), "unsubscribe failed: {:?}", result.err());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(mock.received_ews_requests().await.len(), 2);
+}
+
+/// Test that other server errors are not retried with mock server
+#[tokio::test]
+async fn test_server_error_is_not_retried_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_server_error().await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass")).unwrap();
+
+    let result = client.unsubscribe("sub-1").await;
+    assert!(result.is_err());
+    assert_eq!(mock.received_ews_requests().await.len(), 1);
+}
+
+/// Test the request rate of a throttled client with mock server
+#[tokio::test]
+async fn test_throttled_client_rate_with_mock() {
+    let mock = MockEwsServer::new().await;
+    mock.register_operation("CreateFolder", fixtures::create_folder_response("folder-created"))
+        .await;
+
+    let client = EwsClient::new(mock.ews_endpoint().parse().unwrap(), Credentials::basic("user", "pass"))
+        .unwrap()
+        .with_throttling(ThrottlingConfig {
+            requests_per_second: 20.0,
+            burst: 1,
+            ..ThrottlingConfig::default()
+        });
+
+    // After the first request, each one waits for a new token
+    let start = Instant::now();
+    for name in ["First", "Second", "Third"] {
+        let result = client.create_folder("folder-parent", name).await;
+        assert!(result.is_ok(), "create_folder failed: {:?}", result.err());
+    }
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
@@ -11,7 +11,7 @@
     clippy::print_stdout
 )]
 
-use ews_client_core::client::{Credentials, EwsClient};
+use ews_client_core::client::{Credentials, EwsClient, FolderUpdate};
 
 // Helper function to create a test client
 // In real tests, you would read credentials from environment variables
@@ -78,7 +78,14 @@ async fn test_update_folder() {
     // Update the folder name
     let new_name = format!("{original_name} - Updated");
     client
-        .update_folder(&folder_id, &new_name)
+        .update_folder(
+            &folder_id,
+            None,
+            &FolderUpdate {
+                display_name: Some(new_name),
+                ..FolderUpdate::default()
+            },
+        )
         .await
         .expect("Failed to update folder");
 
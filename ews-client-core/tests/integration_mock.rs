@@ -19,8 +19,54 @@ mod common;
 #[path = "integration/mock/infrastructure.rs"]
 mod infrastructure;
 
+#[path = "integration/mock/autodiscover.rs"]
+mod autodiscover;
+
+#[path = "integration/mock/availability_operations.rs"]
+mod availability_operations;
+
+#[path = "integration/mock/calendar_operations.rs"]
+mod calendar_operations;
+
+#[path = "integration/mock/contact_operations.rs"]
+mod contact_operations;
+
+#[path = "integration/mock/delegate_operations.rs"]
+mod delegate_operations;
+
+#[path = "integration/mock/directory_operations.rs"]
+mod directory_operations;
+
 #[path = "integration/mock/folder_operations.rs"]
 mod folder_operations;
 
+#[path = "integration/mock/inbox_rule_operations.rs"]
+mod inbox_rule_operations;
+
 #[path = "integration/mock/item_operations.rs"]
 mod item_operations;
+
+#[path = "integration/mock/mailbox_access.rs"]
+mod mailbox_access;
+
+#[path = "integration/mock/meeting_operations.rs"]
+mod meeting_operations;
+
+#[path = "integration/mock/notification_operations.rs"]
+mod notification_operations;
+
+#[path = "integration/mock/out_of_office_operations.rs"]
+mod out_of_office_operations;
+
+#[cfg(feature = "push-notifications")]
+#[path = "integration/mock/push_notifications.rs"]
+mod push_notifications;
+
+#[path = "integration/mock/server_version.rs"]
+mod server_version;
+
+#[path = "integration/mock/task_operations.rs"]
+mod task_operations;
+
+#[path = "integration/mock/throttling.rs"]
+mod throttling;
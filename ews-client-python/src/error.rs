@@ -87,9 +87,9 @@ pub fn ews_error_to_py_err(err: &EwsError) -> PyErr {
 
     match err {
         EwsError::Authentication => EWSAuthenticationError::new_err(msg),
-        EwsError::Http(_) => EWSHTTPError::new_err(msg),
+        EwsError::Http(_) | EwsError::Io(_) => EWSHTTPError::new_err(msg),
         EwsError::Protocol(_) => EWSProtocolError::new_err(msg),
-        EwsError::ResponseError(_) => EWSResponseError::new_err(msg),
+        EwsError::ResponseError(_) | EwsError::RawResponseError { .. } => EWSResponseError::new_err(msg),
         EwsError::Processing { .. } => EWSProcessingError::new_err(msg),
         EwsError::MissingIdInResponse => EWSMissingIdError::new_err(msg),
         EwsError::Serialization(_) => EWSSerializationError::new_err(msg),
@@ -101,5 +101,9 @@ pub fn ews_error_to_py_err(err: &EwsError) -> PyErr {
             // For response count errors, use EWSProcessingError
             EWSProcessingError::new_err(msg)
         }
+        EwsError::Unsupported { .. } => {
+            // Version checks used to report processing errors, keep raising the same exception
+            EWSProcessingError::new_err(msg)
+        }
     }
 }